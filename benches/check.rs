@@ -0,0 +1,53 @@
+//! Benchmarks of checking synthetic sources and crates.
+//!
+//! Run with `cargo bench`. For end-to-end numbers on a larger crate, including the
+//! cache, use `pep257 bench --synthetic N`.
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use pep257::{
+    analyzer::RustDocAnalyzer,
+    bench::{synthetic_source, write_synthetic_crate},
+};
+
+/// Benchmark parsing and checking a single source file.
+fn bench_source(c: &mut Criterion) {
+    let mut analyzer = RustDocAnalyzer::new().unwrap();
+    let source = synthetic_source(0);
+
+    let mut group = c.benchmark_group("source");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.bench_function("analyze_items", |b| {
+        b.iter(|| analyzer.analyze_items(black_box(&source), None).unwrap());
+    });
+    group.finish();
+}
+
+/// Benchmark checking the files of synthetic crates in parallel, with and without
+/// the cache.
+fn bench_crate(c: &mut Criterion) {
+    let mut analyzer = RustDocAnalyzer::new().unwrap();
+    let mut group = c.benchmark_group("crate");
+    group.sample_size(10);
+
+    for modules in [10, 100] {
+        let dir = tempfile::tempdir().unwrap();
+        let files = write_synthetic_crate(dir.path(), modules).unwrap();
+        group.throughput(Throughput::Elements(files.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("uncached", modules), &files, |b, files| {
+            b.iter(|| analyzer.analyze_files(files));
+        });
+
+        let mut cache = analyzer.load_cache(&dir.path().join("cache"));
+        analyzer.analyze_files_cached(&files, &mut cache);
+        group.bench_with_input(BenchmarkId::new("cached", modules), &files, |b, files| {
+            b.iter(|| analyzer.analyze_files_cached(files, &mut cache));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_source, bench_crate);
+criterion_main!(benches);
@@ -0,0 +1,18 @@
+//! Fuzz the lenient entry point with arbitrary source text.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pep257::analyzer::{Diagnostic, check_source_lossy};
+
+fuzz_target!(|data: &[u8]| {
+    let source = String::from_utf8_lossy(data);
+    let result = check_source_lossy(&source);
+
+    // Panics are caught and reported as diagnostics, which are checker bugs
+    if let Some(Diagnostic::InternalError { line, message }) =
+        result.diagnostics.iter().find(|d| matches!(d, Diagnostic::InternalError { .. }))
+    {
+        panic!("check panicked at line {line}: {message}");
+    }
+});
@@ -0,0 +1,201 @@
+//! Editor-agnostic actions for violations.
+//!
+//! Simple editor plugins can offer a menu of actions for each violation without any
+//! knowledge of the rules. `--format actions-json` prints one object per line for
+//! each violation, listing its actions with the concrete edits they make:
+//!
+//! ```json
+//! {
+//!   "file": "src/lib.rs",
+//!   "rule": "D400",
+//!   "message": "First line should end with a period",
+//!   "severity": "error",
+//!   "line": 3,
+//!   "column": 1,
+//!   "actions": [
+//!     {
+//!       "kind": "fix",
+//!       "title": "Fix D400: First line should end with a period",
+//!       "edits": [
+//!         {
+//!           "start": { "line": 3, "column": 13, "offset": 24 },
+//!           "end": { "line": 3, "column": 13, "offset": 24 },
+//!           "new_text": "."
+//!         }
+//!       ]
+//!     },
+//!     {
+//!       "kind": "suppress",
+//!       "title": "Suppress D400 for this item",
+//!       "edits": [
+//!         {
+//!           "start": { "line": 3, "column": 1, "offset": 12 },
+//!           "end": { "line": 3, "column": 1, "offset": 12 },
+//!           "new_text": "// pep257: ignore[D400]\n"
+//!         }
+//!       ]
+//!     },
+//!     {
+//!       "kind": "docs",
+//!       "title": "Open the documentation of D400",
+//!       "url": "https://github.com/jayvdb/pep257-rs/blob/main/CHECKS.md#d400-first-line-should-end-with-a-period"
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! An edit replaces the text from `start` to `end` with `new_text`. The edits of an
+//! action don't overlap, and their positions are in the original source, so they're
+//! applied together. Lines and columns count from 1, with columns in the check's
+//! column unit, and offsets count UTF-8 bytes from the start of the file.
+
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+use crate::{
+    analyzer::RustDocAnalyzer, parser::ParseError, pep257::Violation, position::LineIndex,
+    rules::docs_url, suppression::format_comment,
+};
+
+/// Return the violations reported for a file with their actions, as JSON objects.
+///
+/// `path` is the file as reported and `file` the file to read settings for, such as
+/// the rustfmt widths that fixes stay within. The violations' columns are in the
+/// analyzer's column unit. A violation gets a fix action if `pep257 fix` would fix
+/// it, and a suppress action unless it's a crate-level violation in the file header.
+pub fn violation_actions(
+    analyzer: &mut RustDocAnalyzer,
+    path: &str,
+    file: &Path,
+    source: &str,
+    violations: &[Violation],
+) -> Result<Vec<Value>, ParseError> {
+    let docstring_fixes = analyzer.docstring_fixes(source, Some(file))?;
+    let items = analyzer.analyze_items(source, Some(file))?;
+    let column_unit = analyzer.options().column_unit;
+    let index = LineIndex::new(source);
+    let newline = if source.contains("\r\n") { "\r\n" } else { "\n" };
+
+    let position = |offset: usize| {
+        let line = index.line_at(offset);
+        let column = offset - index.line_range(line).start + 1;
+        json!({
+            "line": line,
+            "column": index.convert_column(line, column, column_unit),
+            "offset": offset,
+        })
+    };
+    let edit = |start: usize, end: usize, new_text: &str| json!({ "start": position(start), "end": position(end), "new_text": new_text });
+
+    let mut objects = Vec::new();
+    for violation in violations {
+        let rule = violation.rule.as_str();
+        let mut actions = Vec::new();
+
+        // Fixes are found per docstring, with violations in UTF-8 columns
+        let fixes: Vec<Value> = docstring_fixes
+            .iter()
+            .filter(|(violations, _)| {
+                violations.iter().any(|v| v.rule == rule && v.line == violation.line)
+            })
+            .flat_map(|(_, fixes)| fixes.iter().filter(|fix| fix.rule == rule))
+            .map(|fix| edit(fix.range.start, fix.range.end, &fix.replacement))
+            .collect();
+        if !fixes.is_empty() {
+            actions.push(json!({
+                "kind": "fix",
+                "title": format!("Fix {rule}: {}", violation.message),
+                "edits": fixes,
+            }));
+        }
+
+        let item_line = items.iter().find_map(|(line, violations)| {
+            violations
+                .iter()
+                .any(|v| v.rule == rule && v.line == violation.line && v.column == violation.column)
+                .then_some(*line)
+        });
+        if let Some(line) = item_line {
+            let text = index.line_text(line);
+            let indent = &text[..text.len() - text.trim_start_matches([' ', '\t']).len()];
+            let start = index.line_range(line).start;
+            let comment = format!("{indent}{}{newline}", format_comment(&[rule.to_string()]));
+            actions.push(json!({
+                "kind": "suppress",
+                "title": format!("Suppress {rule} for this item"),
+                "edits": [edit(start, start, &comment)],
+            }));
+        }
+
+        actions.push(json!({
+            "kind": "docs",
+            "title": format!("Open the documentation of {rule}"),
+            "url": docs_url(rule),
+        }));
+
+        objects.push(json!({
+            "file": path,
+            "rule": rule,
+            "message": violation.message,
+            "severity": violation.severity.name(),
+            "line": violation.line,
+            "column": violation.column,
+            "actions": actions,
+        }));
+    }
+    Ok(objects)
+}
+
+/// Unit tests for violation actions.
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /// Test the actions of fixable and unfixable violations.
+    #[test]
+    fn test_violation_actions() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        let source =
+            "mod m {\n    /// Parse it\n    fn f() {}\n\n    /// Parse it:\n    fn g() {}\n}\n";
+        fs::write(&file, source).unwrap();
+
+        let mut analyzer = RustDocAnalyzer::new().unwrap();
+        let violations = analyzer.analyze_source(source).unwrap();
+        let objects =
+            violation_actions(&mut analyzer, "lib.rs", &file, source, &violations).unwrap();
+        assert_eq!(objects.len(), 2);
+
+        let fixable = &objects[0];
+        assert_eq!((fixable["rule"].as_str(), fixable["line"].as_u64()), (Some("D400"), Some(2)));
+        let kinds: Vec<&str> = fixable["actions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|a| a["kind"].as_str())
+            .collect();
+        assert_eq!(kinds, vec!["fix", "suppress", "docs"]);
+        assert_eq!(
+            fixable["actions"][0]["edits"],
+            json!([{
+                "start": { "line": 2, "column": 17, "offset": 24 },
+                "end": { "line": 2, "column": 17, "offset": 24 },
+                "new_text": ".",
+            }])
+        );
+        assert_eq!(fixable["actions"][1]["edits"][0]["new_text"], "    // pep257: ignore[D400]\n");
+        assert_eq!(fixable["actions"][1]["edits"][0]["start"]["offset"], 8);
+
+        // A summary ending with a colon has no mechanical fix
+        let kinds: Vec<&str> = objects[1]["actions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|a| a["kind"].as_str())
+            .collect();
+        assert_eq!(kinds, vec!["suppress", "docs"]);
+    }
+}
@@ -1,56 +1,1531 @@
-use std::path::Path;
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet},
+    fs,
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    str::Utf8Error,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
-use log::info;
+use clap::ValueEnum;
+use log::{info, warn};
 
 use crate::{
-    parser::{ParseError, RustParser},
-    pep257::{Pep257Checker, Violation},
+    backend::{LanguageBackend, ParsedFile, create_backend},
+    baseline::Target,
+    cache::{self, Cache, CachedResult},
+    context::{AnalysisContext, CrateInfo, Edition},
+    fixer::{self, Fix, FixedSource, RustfmtSettings},
+    parser::ParseError,
+    pep257::{CheckerOptions, Docstring, DocstringTarget, Pep257Checker, Severity, Violation},
+    position::{ColumnUnit, LineIndex, SourcePosition},
+    rules::{PerFileIgnores, RuleSelection, find_rule},
+    suppression::Suppressions,
 };
 
+/// Options controlling how the analyzer interprets source code.
+#[derive(Debug, Clone, Default)]
+pub struct AnalyzerOptions {
+    /// Decide visibility from the item's own `pub` keyword only.
+    ///
+    /// The visibility of enclosing modules and types is ignored.
+    pub syntactic_visibility: bool,
+    /// How items behind `#[cfg(...)]` gates are treated.
+    pub cfg_gated: CfgPolicy,
+    /// Options for the docstring checks.
+    pub checker: CheckerOptions,
+    /// Unit used to count columns in reported positions.
+    pub column_unit: ColumnUnit,
+    /// Shared library of a tree-sitter Rust grammar to use instead of the bundled one.
+    pub grammar: Option<PathBuf>,
+    /// Severities to report violations of a rule with, overriding the rule's own.
+    pub severities: Vec<(String, Severity)>,
+    /// Version of the rule set to pin.
+    ///
+    /// Violations of rules added after it, and on kinds of items it didn't check, get info
+    /// severity.
+    pub rules_version: Option<u32>,
+    /// Edition to parse source files as, instead of the one of their crate.
+    pub edition: Option<Edition>,
+    /// Rules whose violations are reported.
+    pub rules: RuleSelection,
+    /// Rules whose violations aren't reported in some files.
+    pub per_file_ignores: PerFileIgnores,
+    /// How undocumented `pub use` re-exports are reported.
+    pub reexports: ReexportPolicy,
+}
+
+/// Policy for `pub use` re-exports without docs of their own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReexportPolicy {
+    /// Report every undocumented re-export.
+    #[default]
+    RequireDocs,
+    /// Report an undocumented re-export only if an item it re-exports isn't
+    /// documented in the checked files, as rustdoc then shows the item's own docs.
+    DocumentedSource,
+}
+
+/// Policy for items gated behind `#[cfg(...)]` attributes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum CfgPolicy {
+    /// Check every item regardless of cfg gates.
+    #[default]
+    CheckAll,
+    /// Skip every item behind a cfg gate, including items in gated modules.
+    SkipAll,
+    /// Check gated items only when each gate matches one of these predicates,
+    /// such as `doc`, `unix` or `feature = "serde"`.
+    CheckOnly(Vec<String>),
+}
+
+/// Result of checking source code that may be malformed.
+#[derive(Debug, Default)]
+pub struct LossyCheck {
+    /// Violations found in the parts of the source that could be checked.
+    pub violations: Vec<Violation>,
+    /// Problems that may have left violations out of the results.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A problem encountered while checking source code leniently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// The source has a syntax error here, so nearby items may be missing or misread.
+    SyntaxError { line: usize, column: usize },
+    /// The source uses syntax that its edition of Rust doesn't accept.
+    EditionError { line: usize, column: usize, message: String },
+    /// The source could not be parsed at all.
+    ParseFailed(String),
+    /// A check panicked, so its violations are missing from the results.
+    ///
+    /// This is always a bug in the checker.
+    InternalError { line: usize, message: String },
+}
+
+/// Check Rust source code without failing or panicking, whatever the input.
+///
+/// This suits untrusted input such as unsaved editor buffers: malformed source gives
+/// partial results with diagnostics explaining what may be missing.
+#[must_use]
+pub fn check_source_lossy(source: &str) -> LossyCheck {
+    match RustDocAnalyzer::new() {
+        Ok(mut analyzer) => analyzer.analyze_source_lossy(source),
+        Err(e) => LossyCheck {
+            violations: Vec::new(),
+            diagnostics: vec![Diagnostic::ParseFailed(e.to_string())],
+        },
+    }
+}
+
+/// Time spent in each phase of analyzing a file.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileTimings {
+    /// Time spent reading the file.
+    pub read: Duration,
+    /// Time spent parsing the source and extracting docstrings.
+    pub parse: Duration,
+    /// Time spent running the checks.
+    pub check: Duration,
+}
+
+/// Implementation of file timing methods.
+impl FileTimings {
+    /// Return the total time spent on the file.
+    #[must_use]
+    pub fn total(&self) -> Duration {
+        self.read + self.parse + self.check
+    }
+}
+
+/// Number of documented public items out of all public items.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Coverage {
+    /// Public items that have docs.
+    pub documented: usize,
+    /// All public items.
+    pub total: usize,
+}
+
+/// Implementation of coverage methods.
+impl Coverage {
+    /// Add the counts of another coverage to this one.
+    pub fn add(&mut self, other: Self) {
+        self.documented += other.documented;
+        self.total += other.total;
+    }
+
+    /// Return the percentage of public items that are documented, 100 if there are none.
+    #[must_use]
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            return 100.0;
+        }
+        // Item counts are far below the precision limit of f64
+        #[allow(clippy::cast_precision_loss)]
+        let percent = self.documented as f64 * 100.0 / self.total as f64;
+        percent
+    }
+
+    /// Count the public items of a file and the ones that are documented.
+    fn of_docstrings(docstrings: &[Docstring]) -> Self {
+        let public = docstrings.iter().filter(|docstring| docstring.is_public);
+        let (documented, total) = public.fold((0, 0), |(documented, total), docstring| {
+            (documented + usize::from(!docstring.content.trim().is_empty()), total + 1)
+        });
+        Self { documented, total }
+    }
+}
+
+/// Documented public items of a file and its undocumented re-exports.
+///
+/// Used to check re-exports against the items they re-export in other files. Only collected
+/// with [`ReexportPolicy::DocumentedSource`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Exports {
+    /// Paths of the documented public items, such as `crate::parser::Parser`.
+    pub documented: Vec<String>,
+    /// Undocumented re-exports by item path, with the paths of the items they re-export.
+    ///
+    /// A re-exported path outside the crate is `None`.
+    pub reexports: Vec<(String, Vec<Option<String>>)>,
+}
+
+/// Implementation of export methods.
+impl Exports {
+    /// Collect the documented public items and undocumented re-exports of a file.
+    fn of_docstrings(docstrings: &[Docstring], context: &AnalysisContext) -> Self {
+        let mut exports = Self::default();
+        for docstring in docstrings.iter().filter(|docstring| docstring.is_public) {
+            let path = qualified_path(&context.module_path, &docstring.item_path);
+            if !docstring.content.trim().is_empty() {
+                exports.documented.push(path);
+            } else if docstring.target_type == DocstringTarget::Reexport {
+                // Paths in a `use` are relative to the module the declaration is in
+                let scope = match docstring.item_path.rsplit_once("::") {
+                    Some((parent, _)) => format!("{}::{parent}", context.module_path),
+                    None => context.module_path.clone(),
+                };
+                let targets = docstring
+                    .attributes
+                    .reexports
+                    .iter()
+                    .map(|target| resolve_use_path(target, &scope))
+                    .collect();
+                exports.reexports.push((path, targets));
+            }
+        }
+        exports
+    }
+
+    /// Remove the re-exports whose items are all documented, returning their item paths.
+    fn take_resolved(&mut self, documented: &HashSet<&str>) -> HashSet<String> {
+        let (resolved, unresolved): (Vec<_>, Vec<_>) =
+            self.reexports.drain(..).partition(|(_, targets)| {
+                targets.iter().all(|target| {
+                    target.as_deref().is_some_and(|target| documented.contains(target))
+                })
+            });
+        self.reexports = unresolved;
+        resolved.into_iter().map(|(path, _)| path).collect()
+    }
+}
+
+/// Result of analyzing one file of a batch.
+#[derive(Debug)]
+pub struct FileReport {
+    /// Path of the analyzed file.
+    pub path: PathBuf,
+    /// Violations found in the file, or the error that stopped its analysis.
+    pub result: Result<Vec<Violation>, ParseError>,
+    /// Time spent in each phase of the analysis.
+    pub timings: FileTimings,
+    /// Documentation coverage of the file's public items.
+    pub coverage: Coverage,
+    /// Documented items and undocumented re-exports of the file.
+    pub exports: Exports,
+}
+
 /// Main analyzer that combines parsing and checking.
 pub struct RustDocAnalyzer {
-    parser: RustParser,
+    backend: Box<dyn LanguageBackend>,
+    options: AnalyzerOptions,
+    /// Crate metadata of the directories analyzed so far.
+    crates: HashMap<PathBuf, Option<CrateInfo>>,
+    /// Rustfmt settings of the directories fixed so far.
+    rustfmt: HashMap<PathBuf, RustfmtSettings>,
+    /// Maximum number of worker threads analyzing a batch of files.
+    ///
+    /// The available parallelism is used when `None`.
+    jobs: Option<NonZeroUsize>,
+    /// Flag that stops batches of files from starting on further files once set.
+    interrupt: Option<Arc<AtomicBool>>,
+    /// Whether fixes insert doc templates for undocumented public items.
+    doc_templates: bool,
 }
 
 /// Implementation of analyzer methods.
 impl RustDocAnalyzer {
     /// Create a new analyzer instance.
     pub fn new() -> Result<Self, ParseError> {
-        Ok(Self { parser: RustParser::new()? })
+        Self::with_options(AnalyzerOptions::default())
+    }
+
+    /// Create a new analyzer instance with the given options.
+    pub fn with_options(options: AnalyzerOptions) -> Result<Self, ParseError> {
+        Ok(Self {
+            backend: create_backend(&options)?,
+            options,
+            crates: HashMap::new(),
+            rustfmt: HashMap::new(),
+            jobs: None,
+            interrupt: None,
+            doc_templates: false,
+        })
+    }
+
+    /// Limit batches of files to the given number of worker threads, this analyzer included.
+    ///
+    /// The limit isn't one of the [`AnalyzerOptions`], as it doesn't change the results,
+    /// so cached results stay valid whatever the limit.
+    #[must_use]
+    pub const fn with_jobs(mut self, jobs: NonZeroUsize) -> Self {
+        self.jobs = Some(jobs);
+        self
+    }
+
+    /// Stop batches of files from starting on further files once `interrupt` is set.
+    ///
+    /// It's set when the user presses Ctrl-C, for example. The files already started are
+    /// finished, so an interrupted batch reports a prefix of its files.
+    #[must_use]
+    pub fn with_interrupt(mut self, interrupt: Arc<AtomicBool>) -> Self {
+        self.interrupt = Some(interrupt);
+        self
+    }
+
+    /// Make fixes insert a template of the docs of each undocumented public item.
+    ///
+    /// The template holds a placeholder summary and the sections the signature calls for.
+    #[must_use]
+    pub fn with_doc_templates(mut self, doc_templates: bool) -> Self {
+        self.doc_templates = doc_templates;
+        self
+    }
+
+    /// Return the options the analyzer was created with.
+    #[must_use]
+    pub const fn options(&self) -> &AnalyzerOptions {
+        &self.options
+    }
+
+    /// Return the file extensions the analyzer can check, without the leading dot.
+    #[must_use]
+    pub fn extensions(&self) -> &'static [&'static str] {
+        self.backend.extensions()
     }
 
     /// Analyze a Rust file and return all PEP 257 violations.
     pub fn analyze_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<Violation>, ParseError> {
-        info!("Processing file: {}", path.as_ref().display());
-        let docstrings = self.parser.parse_file(&path)?;
-        let mut violations = Vec::new();
+        self.analyze_file_timed(path).map(|(violations, _)| violations)
+    }
+
+    /// Analyze a Rust file, also returning the time spent in each phase.
+    pub fn analyze_file_timed<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<(Vec<Violation>, FileTimings), ParseError> {
+        let report = self.analyze_file_report(path.as_ref());
+        report.result.map(|violations| (violations, report.timings))
+    }
+
+    /// Analyze a Rust file, reporting its violations, timings, documentation coverage and exports.
+    ///
+    /// Re-exports of items in other files are left for the batch to resolve.
+    fn analyze_file_report(&mut self, path: &Path) -> FileReport {
+        info!("Processing {} file: {}", self.backend.name(), path.display());
+        let mut report = FileReport {
+            path: path.to_path_buf(),
+            result: Ok(Vec::new()),
+            timings: FileTimings::default(),
+            coverage: Coverage::default(),
+            exports: Exports::default(),
+        };
 
-        for docstring in docstrings {
-            violations.extend(Pep257Checker::check_docstring(&docstring));
+        let start = Instant::now();
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                report.result = Err(e.into());
+                return report;
+            }
+        };
+        let source = match String::from_utf8(bytes) {
+            Ok(source) => source,
+            Err(e) => {
+                report.result = Ok(self.invalid_utf8(path, e.as_bytes(), e.utf8_error()));
+                report.timings.read = start.elapsed();
+                return report;
+            }
+        };
+        report.timings.read = start.elapsed();
+
+        let items = self.analyze_items_timed(
+            &source,
+            Some(path),
+            &mut report.timings,
+            &mut report.coverage,
+            &mut report.exports,
+        );
+        report.result =
+            items.map(|items| items.into_iter().flat_map(|(_, violations)| violations).collect());
+        if report.result.is_err() {
+            report.timings = FileTimings::default();
+            report.coverage = Coverage::default();
+            report.exports = Exports::default();
         }
+        report
+    }
 
-        Ok(violations)
+    /// Report a file that isn't valid UTF-8 with an R001 violation, instead of checking it.
+    ///
+    /// The violation is at the first invalid byte, and quotes its line with invalid bytes
+    /// replaced by `U+FFFD`.
+    fn invalid_utf8(&self, path: &Path, bytes: &[u8], error: Utf8Error) -> Vec<Violation> {
+        if !self.allows(Some(path), "R001") {
+            return Vec::new();
+        }
+        let start = error.valid_up_to();
+        let end = start + error.error_len().unwrap_or(bytes.len() - start);
+        let prefix = String::from_utf8_lossy(&bytes[..start]);
+        let line = prefix.matches('\n').count() + 1;
+        let column = start - prefix.rfind('\n').map_or(0, |newline| newline + 1) + 1;
+
+        let message = format!(
+            "File is not valid UTF-8 (invalid byte `0x{:02X}`) and wasn't checked",
+            bytes[start]
+        );
+        let violation =
+            Violation::new("R001", message, SourcePosition { line, column }, Severity::Error)
+                .with_byte_range(start..end)
+                .ending_at(SourcePosition { line, column: column + end - start });
+        let mut violations = with_item_path(vec![violation], &module_path(path), "");
+        self.override_severities(&mut violations);
+        self.locate(&String::from_utf8_lossy(bytes), &mut violations);
+        violations
+    }
+
+    /// Analyze a batch of files in parallel, returning a report per file in input order.
+    ///
+    /// Each worker thread keeps its own parser for the whole batch and takes the next
+    /// unclaimed file when it finishes one, so large and small files balance out. This
+    /// analyzer is one of the workers, and the others are created with its options.
+    /// There is a worker per available CPU, unless limited with [`Self::with_jobs`].
+    /// An interrupted batch, see [`Self::with_interrupt`], reports fewer files.
+    pub fn analyze_files(&mut self, paths: &[PathBuf]) -> Vec<FileReport> {
+        let mut reports = self.analyze_batch(paths);
+        resolve_reexports(&mut reports);
+        reports
+    }
+
+    /// Analyze a batch of files in parallel, leaving re-exports of items in other files unresolved.
+    fn analyze_batch(&mut self, paths: &[PathBuf]) -> Vec<FileReport> {
+        let workers = self
+            .jobs
+            .or_else(|| thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get)
+            .min(paths.len());
+        let next = AtomicUsize::new(0);
+        let interrupt = self.interrupt.clone();
+
+        // Claim files until the batch runs out or is interrupted, returning each report
+        // with its index
+        let work = |analyzer: &mut Self| {
+            let mut reports = Vec::new();
+            loop {
+                if interrupt.as_ref().is_some_and(|interrupt| interrupt.load(Ordering::Relaxed)) {
+                    return reports;
+                }
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = paths.get(index) else {
+                    return reports;
+                };
+                reports.push((index, analyzer.analyze_file_report(path)));
+            }
+        };
+
+        let mut reports = thread::scope(|scope| {
+            let handles: Vec<_> = (1..workers)
+                .map(|_| {
+                    let options = self.options.clone();
+                    scope.spawn(move || match Self::with_options(options) {
+                        Ok(mut analyzer) => work(&mut analyzer),
+                        // The other workers, including this analyzer, take its share
+                        Err(_) => Vec::new(),
+                    })
+                })
+                .collect();
+
+            let mut reports = work(self);
+            for handle in handles {
+                reports
+                    .extend(handle.join().unwrap_or_else(|payload| panic::resume_unwind(payload)));
+            }
+            reports
+        });
+
+        reports.sort_by_key(|&(index, _)| index);
+        reports.into_iter().map(|(_, report)| report).collect()
+    }
+
+    /// Load the result cache from a file.
+    ///
+    /// Its results are kept only if they were found with this analyzer's options.
+    #[must_use]
+    pub fn load_cache(&self, path: &Path) -> Cache {
+        Cache::load(path, &format!("{:?}", self.options))
+    }
+
+    /// Analyze a batch of files like [`Self::analyze_files`], reusing the cached results.
+    ///
+    /// The results of the other files are cached. A file is unchanged if its content and the
+    /// metadata of its crate are. Re-exports are resolved across the whole batch, cached files
+    /// included.
+    pub fn analyze_files_cached(
+        &mut self,
+        paths: &[PathBuf],
+        cache: &mut Cache,
+    ) -> Vec<FileReport> {
+        let keys: Vec<Option<u64>> = paths
+            .iter()
+            .map(|path| {
+                let source = fs::read(path).ok()?;
+                let crate_info = format!("{:?}", self.crate_info(path));
+                // Files included as docs are part of the file's docs
+                let included: Vec<Vec<u8>> = included_files(&String::from_utf8_lossy(&source))
+                    .filter_map(|file| fs::read(path.parent()?.join(file)).ok())
+                    .collect();
+                let mut parts = vec![source.as_slice(), crate_info.as_bytes()];
+                parts.extend(included.iter().map(Vec::as_slice));
+                Some(cache::hash(&parts))
+            })
+            .collect();
+        let cached = |(path, key): (&PathBuf, &Option<u64>)| {
+            key.and_then(|key| cache.get(path, key)).is_some()
+        };
+        let changed: Vec<PathBuf> = paths
+            .iter()
+            .zip(&keys)
+            .filter(|&entry| !cached(entry))
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        let mut fresh = self.analyze_batch(&changed).into_iter();
+        let mut reports = Vec::with_capacity(paths.len());
+        for (path, key) in paths.iter().zip(keys) {
+            if let Some(result) = key.and_then(|key| cache.get(path, key)) {
+                reports.push(FileReport {
+                    path: path.clone(),
+                    result: Ok(result.violations.clone()),
+                    timings: FileTimings::default(),
+                    coverage: result.coverage,
+                    exports: result.exports.clone(),
+                });
+                continue;
+            }
+            let Some(report) = fresh.next() else {
+                break;
+            };
+            if let (Some(key), Ok(violations)) = (key, &report.result) {
+                let result = CachedResult {
+                    violations: violations.clone(),
+                    coverage: report.coverage,
+                    exports: report.exports.clone(),
+                };
+                cache.insert(path, key, result);
+            }
+            reports.push(report);
+        }
+        resolve_reexports(&mut reports);
+        reports
     }
 
     /// Analyze Rust source code and return all PEP 257 violations.
-    #[allow(dead_code)]
     pub(crate) fn analyze_source(&mut self, source: &str) -> Result<Vec<Violation>, ParseError> {
-        let docstrings = self.parser.parse_source(source)?;
-        let mut violations = Vec::new();
+        self.analyze_source_at(source, None)
+    }
+
+    /// Analyze Rust source code read from an optional path.
+    ///
+    /// The path decides whether file-level rules such as the crate root checks apply.
+    fn analyze_source_at(
+        &mut self,
+        source: &str,
+        path: Option<&Path>,
+    ) -> Result<Vec<Violation>, ParseError> {
+        let items = self.analyze_items(source, path)?;
+        Ok(items.into_iter().flat_map(|(_, violations)| violations).collect())
+    }
+
+    /// Analyze Rust source code, grouping the violations by item.
+    ///
+    /// Each group comes with the 1-based line a suppression comment for the item
+    /// goes above. Violations that are already suppressed are left out.
+    pub fn analyze_items(
+        &mut self,
+        source: &str,
+        path: Option<&Path>,
+    ) -> Result<Vec<(usize, Vec<Violation>)>, ParseError> {
+        self.analyze_items_timed(
+            source,
+            path,
+            &mut FileTimings::default(),
+            &mut Coverage::default(),
+            &mut Exports::default(),
+        )
+    }
+
+    /// Analyze Rust source code by item, recording timings, coverage and exports.
+    fn analyze_items_timed(
+        &mut self,
+        source: &str,
+        path: Option<&Path>,
+        timings: &mut FileTimings,
+        coverage: &mut Coverage,
+        exports: &mut Exports,
+    ) -> Result<Vec<(usize, Vec<Violation>)>, ParseError> {
+        let start = Instant::now();
+        let crate_info = path.and_then(|path| self.crate_info(path));
+        let edition = self.options.edition.or(crate_info.as_ref().map(|info| info.edition));
+        let mut parsed = self.backend.parse(source, edition)?;
+        load_included_docs(&mut parsed.docstrings, path);
+        timings.parse = start.elapsed();
+        for (position, message) in &parsed.edition_errors {
+            let file = path.unwrap_or_else(|| Path::new("<source>")).display();
+            warn!("{file}:{}:{}: {message}", position.line, position.column);
+        }
+
+        let start = Instant::now();
+        let mut items = Vec::new();
+        let module_path = path.map_or_else(|| "crate".to_string(), module_path);
+        let suppressions = Suppressions::parse(source);
+
+        if let Some(position) = parsed.syntax_errors.first() {
+            let mut error_violations = Pep257Checker::check_syntax_errors(*position);
+            error_violations.retain(|v| !suppressions.suppresses(position.line, &v.rule));
+            items.push((position.line, with_item_path(error_violations, &module_path, "")));
+        }
+
+        if path.is_some_and(Self::is_crate_root) {
+            let mut crate_violations = Pep257Checker::check_crate_attributes(
+                &parsed.inner_attributes,
+                &self.options.checker,
+            );
+            crate_violations.retain(|v| !suppressions.header_suppresses(&v.rule));
+            items.push((1, with_item_path(crate_violations, &module_path, "")));
+        }
+
+        let checker_options = self.checker_options(&parsed);
+        let mut context = Self::context(&parsed, &module_path, crate_info.as_ref());
+        context.edition = edition;
+        *coverage = Coverage::of_docstrings(&parsed.docstrings);
+        if self.options.reexports == ReexportPolicy::DocumentedSource {
+            *exports = Exports::of_docstrings(&parsed.docstrings, &context);
+        }
+        for docstring in parsed.docstrings {
+            let mut docstring_violations =
+                Pep257Checker::check_docstring_in(&docstring, &checker_options, &context);
+            let anchor = remove_suppressed(&suppressions, &docstring, &mut docstring_violations);
+            self.pin_item_kinds(&docstring, &mut docstring_violations);
+            items.push((
+                anchor,
+                with_item_path(docstring_violations, &module_path, &docstring.item_path),
+            ));
+        }
+
+        for comment in &parsed.trailing_comments {
+            let mut comment_violations = Pep257Checker::check_trailing_comment(comment);
+            comment_violations.retain(|v| !suppressions.suppresses(comment.member_line, &v.rule));
+            items.push((
+                comment.member_line,
+                with_item_path(comment_violations, &module_path, &comment.item_path),
+            ));
+        }
+
+        for doc in &parsed.dangling_docs {
+            let mut doc_violations = Pep257Checker::check_dangling_doc(doc);
+            doc_violations.retain(|v| !suppressions.suppresses(doc.position.line, &v.rule));
+            items.push((
+                doc.position.line,
+                with_item_path(doc_violations, &module_path, &doc.item_path),
+            ));
+        }
+
+        for block in &parsed.unsafe_blocks {
+            let mut block_violations = Pep257Checker::check_unsafe_block(block, &checker_options);
+            block_violations.retain(|v| !suppressions.suppresses(block.position.line, &v.rule));
+            items.push((
+                block.position.line,
+                with_item_path(block_violations, &module_path, &block.item_path),
+            ));
+        }
+
+        for (_, violations) in &mut items {
+            violations.retain(|v| self.allows(path, &v.rule));
+        }
+        let documented = exports.documented.clone();
+        let resolved = exports.take_resolved(&documented.iter().map(String::as_str).collect());
+        for (_, violations) in &mut items {
+            remove_resolved(&resolved, violations);
+        }
+        items.retain(|(_, violations)| !violations.is_empty());
+        for (_, violations) in &mut items {
+            self.override_severities(violations);
+            self.locate(source, violations);
+        }
+        timings.check = start.elapsed();
+        Ok(items)
+    }
+
+    /// Fix the mechanically fixable violations of Rust source code.
+    ///
+    /// Suppressed violations and violations of rules that aren't selected are left
+    /// alone, like they would be left out of a check. With a path, fixes stay within
+    /// the line widths of the nearest `rustfmt.toml`.
+    pub fn fix_source(
+        &mut self,
+        source: &str,
+        path: Option<&Path>,
+    ) -> Result<FixedSource, ParseError> {
+        let fixes = self.docstring_fixes(source, path)?.into_iter().flat_map(|(_, fixes)| fixes);
+        Ok(fixer::apply_fixes(source, fixes.collect()))
+    }
+
+    /// Return the fixes of each docstring and trailing comment of Rust source code.
+    ///
+    /// Each fix comes with the violations it resolves, in UTF-8 columns whatever the configured
+    /// column unit.
+    pub(crate) fn docstring_fixes(
+        &mut self,
+        source: &str,
+        path: Option<&Path>,
+    ) -> Result<Vec<(Vec<Violation>, Vec<Fix>)>, ParseError> {
+        let crate_info = path.and_then(|path| self.crate_info(path));
+        let edition = self.options.edition.or(crate_info.as_ref().map(|info| info.edition));
+        let mut parsed = self.backend.parse(source, edition)?;
+        // Docs included from another file can't be fixed in this one
+        parsed.docstrings.retain(|docstring| docstring.attributes.doc_include.is_none());
+
+        let module_path = path.map_or_else(|| "crate".to_string(), module_path);
+        let suppressions = Suppressions::parse(source);
+        let checker_options = self.checker_options(&parsed);
+        let mut context = Self::context(&parsed, &module_path, crate_info.as_ref());
+        context.edition = edition;
+        let index = LineIndex::new(source);
+
+        let width =
+            path.map_or_else(RustfmtSettings::default, |path| self.rustfmt(path)).doc_width();
+
+        let mut docstring_fixes = Vec::new();
+        for docstring in &parsed.docstrings {
+            let mut violations =
+                Pep257Checker::check_docstring_in(docstring, &checker_options, &context);
+            remove_suppressed(&suppressions, docstring, &mut violations);
+            violations.retain(|v| self.allows(path, &v.rule));
+            let mut fixes =
+                fixer::docstring_fixes(docstring, &violations, &index, &checker_options);
+            if self.doc_templates {
+                fixes.extend(fixer::doc_template_fix(docstring, &violations, &index));
+            }
+            fixes.retain(|fix| fixer::fits_width(fix, &index, width));
+            if !fixes.is_empty() {
+                docstring_fixes.push((violations, fixes));
+            }
+        }
+        for comment in &parsed.trailing_comments {
+            let mut violations = Pep257Checker::check_trailing_comment(comment);
+            violations.retain(|v| {
+                !suppressions.suppresses(comment.member_line, &v.rule) && self.allows(path, &v.rule)
+            });
+            if violations.is_empty() {
+                continue;
+            }
+            let fixes: Vec<Fix> = fixer::trailing_comment_fix(comment, &index)
+                .filter(|fix| fixer::fits_width(fix, &index, width))
+                .into_iter()
+                .collect();
+            if !fixes.is_empty() {
+                docstring_fixes.push((violations, fixes));
+            }
+        }
+        Ok(docstring_fixes)
+    }
+
+    /// Fix the mechanically fixable violations of a Rust file, returning the fixes made.
+    ///
+    /// The file is only written if something was fixed, and never when `dry_run` is set.
+    pub fn fix_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        dry_run: bool,
+    ) -> Result<FixedSource, ParseError> {
+        let path = path.as_ref();
+        info!("Fixing {} file: {}", self.backend.name(), path.display());
+        let source = fs::read_to_string(path)?;
+        let fixed = self.fix_source(&source, Some(path))?;
+        if !dry_run && fixed.source != source {
+            fs::write(path, &fixed.source)?;
+        }
+        Ok(fixed)
+    }
+
+    /// Analyze Rust source code without failing or panicking on malformed input.
+    ///
+    /// Each docstring is checked on its own, so a panicking check only loses the
+    /// violations of that docstring.
+    pub fn analyze_source_lossy(&mut self, source: &str) -> LossyCheck {
+        let mut result = LossyCheck::default();
+
+        let edition = self.options.edition;
+        let mut parsed = match panic::catch_unwind(AssertUnwindSafe(|| {
+            self.backend.parse(source, edition)
+        })) {
+            Ok(Ok(parsed)) => parsed,
+            Ok(Err(e)) => {
+                result.diagnostics.push(Diagnostic::ParseFailed(e.to_string()));
+                return result;
+            }
+            Err(payload) => {
+                result
+                    .diagnostics
+                    .push(Diagnostic::InternalError { line: 1, message: panic_message(&*payload) });
+                return result;
+            }
+        };
+
+        result.diagnostics.extend(parsed.syntax_errors.iter().map(|position| {
+            Diagnostic::SyntaxError { line: position.line, column: position.column }
+        }));
+        result.diagnostics.extend(parsed.edition_errors.iter().map(|(position, message)| {
+            Diagnostic::EditionError {
+                line: position.line,
+                column: position.column,
+                message: message.clone(),
+            }
+        }));
+
+        load_included_docs(&mut parsed.docstrings, None);
+        let checker_options = self.checker_options(&parsed);
+        let context = Self::context(&parsed, "crate", None);
+        let suppressions = Suppressions::parse(source);
+        for docstring in &parsed.docstrings {
+            match panic::catch_unwind(|| {
+                let mut violations =
+                    Pep257Checker::check_docstring_in(docstring, &checker_options, &context);
+                remove_suppressed(&suppressions, docstring, &mut violations);
+                violations
+            }) {
+                Ok(violations) => result.violations.extend(with_item_path(
+                    violations,
+                    "crate",
+                    &docstring.item_path,
+                )),
+                Err(payload) => result.diagnostics.push(Diagnostic::InternalError {
+                    line: docstring.line,
+                    message: panic_message(&*payload),
+                }),
+            }
+        }
+
+        result.violations.retain(|v| self.options.rules.allows(&v.rule));
+        self.override_severities(&mut result.violations);
+        self.locate(source, &mut result.violations);
+        result
+    }
+
+    /// Check whether violations of a rule are reported, in the file at `path` if any.
+    fn allows(&self, path: Option<&Path>, rule: &str) -> bool {
+        self.options.rules.allows(rule)
+            && path.is_none_or(|path| self.options.per_file_ignores.allows(path, rule))
+    }
+
+    /// Return the checker options to use for a parsed file.
+    fn checker_options(&self, parsed: &ParsedFile) -> CheckerOptions {
+        // Crates using rustdoc's doc_cfg feature should mark gated items with #[doc(cfg)]
+        let mut checker_options = self.options.checker.clone();
+        checker_options.require_doc_cfg |=
+            parsed.inner_attributes.iter().any(|attr| attr.contains("doc_cfg"));
+        checker_options
+    }
+
+    /// Return the edition a source file is parsed as, if known.
+    ///
+    /// This is the configured edition, or else the edition of the file's crate.
+    pub fn edition(&mut self, path: &Path) -> Option<Edition> {
+        self.options.edition.or_else(|| self.crate_info(path).map(|info| info.edition))
+    }
+
+    /// Return the top-level module a source file belongs to.
+    ///
+    /// For example `my_crate::parser` for `src/parser/lexer.rs`. The crate is named `crate` if
+    /// its manifest can't be found.
+    pub fn top_level_module(&mut self, path: &Path) -> String {
+        let crate_name =
+            self.crate_info(path).map_or_else(|| "crate".to_string(), |info| info.name);
+        let module_path = module_path(path);
+        match module_path.split("::").nth(1) {
+            Some(module) => format!("{crate_name}::{module}"),
+            None => crate_name,
+        }
+    }
+
+    /// Return the crate target a source file belongs to.
+    ///
+    /// For example `my_crate` and `src/lib.rs` for `src/parser/lexer.rs`. The crate is named
+    /// `crate` if its manifest can't be found.
+    pub fn target(&mut self, path: &Path) -> Target {
+        let crate_name =
+            self.crate_info(path).map_or_else(|| "crate".to_string(), |info| info.name);
+        Target { crate_name, root: target_root(path) }
+    }
+
+    /// Return the crate metadata of a source file, reading each directory's only once.
+    fn crate_info(&mut self, path: &Path) -> Option<CrateInfo> {
+        let dir = path.parent().unwrap_or(path).to_path_buf();
+        self.crates.entry(dir).or_insert_with(|| CrateInfo::for_file(path)).clone()
+    }
+
+    /// Return the rustfmt settings of a file, reading them once per directory.
+    fn rustfmt(&mut self, path: &Path) -> RustfmtSettings {
+        let dir = path.parent().unwrap_or(path).to_path_buf();
+        *self.rustfmt.entry(dir).or_insert_with(|| RustfmtSettings::for_file(path))
+    }
+
+    /// Build the analysis context of a parsed file.
+    fn context(
+        parsed: &ParsedFile,
+        module_path: &str,
+        crate_info: Option<&CrateInfo>,
+    ) -> AnalysisContext {
+        AnalysisContext {
+            items: parsed.items.clone(),
+            ..AnalysisContext::new(module_path, crate_info)
+        }
+    }
+
+    /// Lower the severity of violations of rules newer than the pinned rule set.
+    ///
+    /// The configured severity overrides are applied afterwards.
+    fn override_severities(&self, violations: &mut [Violation]) {
+        for violation in violations {
+            if let Some(version) = self.options.rules_version
+                && find_rule(&violation.rule).is_some_and(|rule| rule.since > version)
+            {
+                violation.severity = Severity::Info;
+            }
+            if let Some((_, severity)) =
+                self.options.severities.iter().rev().find(|(rule, _)| *rule == violation.rule)
+            {
+                violation.severity = *severity;
+            }
+        }
+    }
+
+    /// Lower the severity of violations on kinds of items the pinned rule set didn't check.
+    ///
+    /// Fields, for example, weren't checked by rule set 1.
+    fn pin_item_kinds(&self, docstring: &Docstring, violations: &mut [Violation]) {
+        let Some(version) = self.options.rules_version else {
+            return;
+        };
+        for violation in violations {
+            if docstring.checked_since(&violation.rule) > version {
+                violation.severity = Severity::Info;
+            }
+        }
+    }
+
+    /// Attach the source line, byte range and end position of each violation.
+    ///
+    /// Columns are converted to the configured unit. Violations with only an end position get
+    /// the byte range up to it, and violations without a known region cover the rest of their
+    /// line.
+    fn locate(&self, source: &str, violations: &mut [Violation]) {
+        let index = LineIndex::new(source);
+        for violation in violations {
+            let text = index.line_text(violation.line);
+            violation.source_line = Some(text.to_string());
+            let start = *violation.start_byte.get_or_insert_with(|| {
+                index.offset(SourcePosition { line: violation.line, column: violation.column })
+            });
+            let end = *violation.end_byte.get_or_insert_with(|| {
+                match (violation.end_line, violation.end_column) {
+                    (Some(line), Some(column)) => index.offset(SourcePosition { line, column }),
+                    _ => index.line_range(violation.line).start + text.len(),
+                }
+                .max(start)
+            });
+            let end_line = *violation.end_line.get_or_insert_with(|| index.line_at(end));
+            let end_column = *violation
+                .end_column
+                .get_or_insert_with(|| end - index.line_range(end_line).start + 1);
+            if self.options.column_unit != ColumnUnit::Utf8 {
+                violation.column = index.convert_column(
+                    violation.line,
+                    violation.column,
+                    self.options.column_unit,
+                );
+                violation.end_column =
+                    Some(index.convert_column(end_line, end_column, self.options.column_unit));
+            }
+        }
+    }
+
+    /// Check whether a path is a library crate root.
+    fn is_crate_root(path: &Path) -> bool {
+        path.file_name().is_some_and(|name| name == "lib.rs")
+    }
+}
+
+/// Return the module path of a source file, such as `crate::parser` for `src/parser.rs`.
+///
+/// The path is taken relative to the last `src` directory. Crate roots such as
+/// `lib.rs`, `main.rs` and the files of `src/bin`, `tests`, `examples` and `benches`
+/// are `crate` itself.
+fn module_path(path: &Path) -> String {
+    let components: Vec<&str> =
+        path.components().filter_map(|component| component.as_os_str().to_str()).collect();
+    let Some(root) = components
+        .iter()
+        .rposition(|&name| matches!(name, "src" | "tests" | "examples" | "benches"))
+    else {
+        let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+        return format!("crate::{stem}");
+    };
+
+    let base = components[root];
+    let mut relative = &components[root + 1..];
+
+    // Files directly in a targets directory such as `tests` or `src/bin` are crate
+    // roots, and subdirectories of targets directories are targets of their own
+    let is_targets_dir = base != "src" || relative.first() == Some(&"bin");
+    if base == "src" && is_targets_dir {
+        relative = &relative[1..];
+    }
+    if is_targets_dir {
+        match relative {
+            [_] => return "crate".to_string(),
+            [_, rest @ ..] => relative = rest,
+            [] => {}
+        }
+    }
+
+    let mut modules = vec!["crate"];
+    if let [directories @ .., file] = relative {
+        modules.extend(directories);
+        let stem = file.strip_suffix(".rs").unwrap_or(file);
+        if !matches!(stem, "lib" | "main" | "mod") {
+            modules.push(stem);
+        }
+    }
+    modules.join("::")
+}
 
-        for docstring in docstrings {
-            violations.extend(Pep257Checker::check_docstring(&docstring));
+/// Return the root file of the crate target a source file belongs to, relative to its package.
+///
+/// For example `src/lib.rs` for `src/parser.rs`. Files under `src` outside `src/bin` belong
+/// to the library if the package has one, and to `src/main.rs` otherwise. Files outside the
+/// targets directories are roots of their own.
+fn target_root(path: &Path) -> String {
+    let components: Vec<&str> =
+        path.components().filter_map(|component| component.as_os_str().to_str()).collect();
+    let Some(root) = components
+        .iter()
+        .rposition(|&name| matches!(name, "src" | "tests" | "examples" | "benches"))
+    else {
+        return path.file_name().and_then(|name| name.to_str()).unwrap_or_default().to_string();
+    };
+
+    let base = components[root];
+    let (dir, relative) = match &components[root + 1..] {
+        ["bin", rest @ ..] if base == "src" => ("src/bin", rest),
+        [file @ ("lib.rs" | "main.rs")] if base == "src" => return format!("src/{file}"),
+        relative if base == "src" => {
+            let src = path.ancestors().nth(relative.len()).unwrap_or(path);
+            let has_lib = src.join("lib.rs").exists() || !src.join("main.rs").exists();
+            return format!("src/{}", if has_lib { "lib.rs" } else { "main.rs" });
+        }
+        relative => (base, relative),
+    };
+    match relative {
+        [file] => format!("{dir}/{file}"),
+        [target, ..] => format!("{dir}/{target}/main.rs"),
+        [] => dir.to_string(),
+    }
+}
+
+/// Remove the suppressed violations of a docstring, returning where its suppression goes.
+///
+/// Module docs of the file itself are suppressed from the file's comment header, and
+/// other items from the comments directly above their docs.
+fn remove_suppressed(
+    suppressions: &Suppressions,
+    docstring: &Docstring,
+    violations: &mut Vec<Violation>,
+) -> usize {
+    if docstring.target_type == DocstringTarget::Package {
+        violations.retain(|v| !suppressions.header_suppresses(&v.rule));
+        1
+    } else {
+        let line = docstring.first_line();
+        violations.retain(|v| !suppressions.suppresses(line, &v.rule));
+        line
+    }
+}
+
+/// Load the docs that files include from other files with `#![doc = include_str!("...")]`.
+///
+/// The included file is found relative to the source file. Docs that can't be loaded,
+/// such as those of a source without a path, aren't checked. Violations in them are
+/// reported on the attribute.
+fn load_included_docs(docstrings: &mut Vec<Docstring>, path: Option<&Path>) {
+    docstrings.retain_mut(|docstring| {
+        let Some(file) = &docstring.attributes.doc_include else {
+            return true;
+        };
+        let Some(file) = path.and_then(Path::parent).map(|dir| dir.join(file)) else {
+            return false;
+        };
+        let content = match fs::read_to_string(&file) {
+            Ok(content) => content.replace("\r\n", "\n"),
+            Err(e) => {
+                warn!("{}: {e}", file.display());
+                return false;
+            }
+        };
+
+        let content = content.strip_suffix('\n').unwrap_or(&content).to_string();
+        let position = SourcePosition { line: docstring.line, column: docstring.column };
+        docstring.line_positions = vec![position; content.split('\n').count()];
+        docstring.is_multiline = content.contains('\n');
+        docstring.raw_content.clone_from(&content);
+        docstring.content = content;
+        true
+    });
+}
+
+/// Return the files that source code includes with `include_str!("...")`, as written.
+fn included_files(source: &str) -> impl Iterator<Item = &str> {
+    source
+        .split("include_str!(\"")
+        .skip(1)
+        .filter_map(|rest| rest.split_once('"'))
+        .map(|(file, _)| file)
+}
+
+/// Attach the item path to violations, qualified with the module path of its file.
+fn with_item_path(
+    violations: Vec<Violation>,
+    module_path: &str,
+    item_path: &str,
+) -> Vec<Violation> {
+    let qualified = qualified_path(module_path, item_path);
+    violations.into_iter().map(|violation| violation.with_item_path(qualified.as_str())).collect()
+}
+
+/// Qualify the path of an item with the module path of its file.
+fn qualified_path(module_path: &str, item_path: &str) -> String {
+    if item_path.is_empty() {
+        module_path.to_string()
+    } else {
+        format!("{module_path}::{item_path}")
+    }
+}
+
+/// Resolve a path in a `use` declaration in the module `scope` into a path from `crate`.
+///
+/// Returns `None` if it can't be resolved. Paths without a `crate`, `self` or `super` prefix
+/// are taken to be relative to the scope, so paths into other crates resolve to items that
+/// are never documented.
+fn resolve_use_path(path: &str, scope: &str) -> Option<String> {
+    let mut segments = path.split("::").peekable();
+    let mut resolved: Vec<&str> = Vec::new();
+    match *segments.peek()? {
+        // Paths starting with `::` name other crates
+        "" => return None,
+        "crate" => {
+            segments.next();
         }
+        first => {
+            if first == "self" {
+                segments.next();
+            }
+            resolved.extend(scope.split("::").skip(1));
+        }
+    }
+    while segments.next_if_eq(&"super").is_some() {
+        resolved.pop()?;
+    }
+    resolved.extend(segments);
+    Some(std::iter::once("crate").chain(resolved).collect::<Vec<_>>().join("::"))
+}
 
-        Ok(violations)
+/// Remove the violations of re-exports whose items are documented in any file of a batch.
+fn resolve_reexports(reports: &mut [FileReport]) {
+    let documented: HashSet<String> =
+        reports.iter().flat_map(|report| report.exports.documented.iter().cloned()).collect();
+    let documented = documented.iter().map(String::as_str).collect();
+    for report in reports {
+        let resolved = report.exports.take_resolved(&documented);
+        if let Ok(violations) = &mut report.result {
+            remove_resolved(&resolved, violations);
+        }
     }
 }
 
+/// Remove the violations of re-exports whose items are documented.
+fn remove_resolved(resolved: &HashSet<String>, violations: &mut Vec<Violation>) {
+    violations.retain(|v| {
+        v.rule != "R105" || v.item_path.as_ref().is_none_or(|path| !resolved.contains(path))
+    });
+}
+
+/// Extract the message from a panic payload.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| (*message).to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
 /// Unit tests for the analyzer.
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Test deriving module paths from file paths.
+    #[test]
+    fn test_module_path() {
+        assert_eq!(module_path(Path::new("src/lib.rs")), "crate");
+        assert_eq!(module_path(Path::new("src/parser.rs")), "crate::parser");
+        assert_eq!(module_path(Path::new("./crates/a/src/check/mod.rs")), "crate::check");
+        assert_eq!(module_path(Path::new("src/check/rules.rs")), "crate::check::rules");
+        assert_eq!(module_path(Path::new("src/bin/tool.rs")), "crate");
+        assert_eq!(module_path(Path::new("src/bin/tool/args.rs")), "crate::args");
+        assert_eq!(module_path(Path::new("tests/fixtures.rs")), "crate");
+        assert_eq!(module_path(Path::new("examples/demo/main.rs")), "crate");
+        assert_eq!(module_path(Path::new("snippet.rs")), "crate::snippet");
+    }
+
+    /// Test finding the root file of the crate target of a source file.
+    #[test]
+    fn test_target_root() {
+        assert_eq!(target_root(Path::new("src/lib.rs")), "src/lib.rs");
+        assert_eq!(target_root(Path::new("src/main.rs")), "src/main.rs");
+        assert_eq!(target_root(Path::new("./crates/a/src/check/mod.rs")), "src/lib.rs");
+        assert_eq!(target_root(Path::new("src/bin/tool.rs")), "src/bin/tool.rs");
+        assert_eq!(target_root(Path::new("src/bin/tool/args.rs")), "src/bin/tool/main.rs");
+        assert_eq!(target_root(Path::new("tests/fixtures.rs")), "tests/fixtures.rs");
+        assert_eq!(target_root(Path::new("examples/demo/main.rs")), "examples/demo/main.rs");
+        assert_eq!(target_root(Path::new("snippet.rs")), "snippet.rs");
+
+        // Modules of a package without a library belong to its binary
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        assert_eq!(target_root(&dir.path().join("src/parser.rs")), "src/main.rs");
+    }
+
+    /// Test that violations carry the qualified path of their item.
+    #[test]
+    fn test_violation_item_paths() {
+        let mut analyzer = RustDocAnalyzer::new().unwrap();
+        let source = "//! Parsing.\n\n/// A parser.\npub struct Parser;\n\n/// Parser methods.\nimpl Parser {\n    pub fn parse() {}\n}\n";
+        let violations =
+            analyzer.analyze_source_at(source, Some(Path::new("src/parser.rs"))).unwrap();
+        let paths: Vec<_> = violations.iter().map(|v| v.item_path.as_deref()).collect();
+        assert_eq!(paths, vec![
+            Some("crate::parser::Parser::parse"),
+            Some("crate::parser::Parser")
+        ]);
+    }
+
+    /// Test that suppression comments remove violations and report their anchors.
+    #[test]
+    fn test_suppression_comments() {
+        let mut analyzer = RustDocAnalyzer::new().unwrap();
+        let source = "// pep257: ignore[D104]\n\n// pep257: ignore[D400]\n/// Calculate the sum\n///\n/// Details.\npub fn add() {}\n\npub fn sub() {}\n";
+        let items = analyzer.analyze_items(source, None).unwrap();
+        let rules: Vec<(usize, Vec<&str>)> = items
+            .iter()
+            .map(|(line, violations)| (*line, violations.iter().map(|v| v.rule.as_str()).collect()))
+            .collect();
+        assert_eq!(rules, vec![(9, vec!["D103"])]);
+    }
+
+    /// Test that batch analysis reports every file in input order.
+    #[test]
+    fn test_analyze_files() {
+        let mut paths: Vec<PathBuf> = fs::read_dir("test_files")
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+            .collect();
+        paths.sort();
+        paths.push(PathBuf::from("test_files/missing.rs"));
+
+        let mut analyzer = RustDocAnalyzer::new().unwrap();
+        let reports = analyzer.analyze_files(&paths);
+        assert_eq!(
+            reports.iter().map(|r| &r.path).collect::<Vec<_>>(),
+            paths.iter().collect::<Vec<_>>()
+        );
+
+        for report in &reports[..reports.len() - 1] {
+            let expected = analyzer.analyze_file(&report.path).unwrap();
+            let violations = report.result.as_ref().unwrap();
+            assert_eq!(violations.len(), expected.len(), "{}", report.path.display());
+        }
+        assert!(matches!(reports.last().unwrap().result, Err(ParseError::Io(_))));
+    }
+
+    /// Test that the first syntax error of a file is reported with R000.
+    #[test]
+    fn test_analyze_syntax_errors() {
+        let mut analyzer = RustDocAnalyzer::new().unwrap();
+        let source = "/// Parse the input.\npub fn parse() {}\n\nfn broken( {}\nfn worse) {}\n";
+        let errors: Vec<Violation> = analyzer
+            .analyze_source(source)
+            .unwrap()
+            .into_iter()
+            .filter(|v| v.rule == "R000")
+            .collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 4);
+
+        let source = "// pep257: ignore[R000]\nfn broken( {}\n";
+        assert!(!analyzer.analyze_source(source).unwrap().iter().any(|v| v.rule == "R000"));
+        assert!(
+            !analyzer.analyze_source("fn valid() {}\n").unwrap().iter().any(|v| v.rule == "R000")
+        );
+    }
+
+    /// Test that a file that isn't UTF-8 is reported with R001 instead of failing.
+    #[test]
+    fn test_analyze_invalid_utf8() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("latin1.rs");
+        fs::write(&file, b"/// Parse it.\npub fn f() {}\n// caf\xe9 au lait\n").unwrap();
+
+        let mut analyzer = RustDocAnalyzer::new().unwrap();
+        let reports = analyzer.analyze_files(std::slice::from_ref(&file));
+        let violations = reports[0].result.as_ref().unwrap();
+        assert_eq!(violations.len(), 1);
+        let violation = &violations[0];
+        assert_eq!(violation.rule, "R001");
+        assert!(violation.message.contains("`0xE9`"), "{}", violation.message);
+        assert_eq!((violation.line, violation.column), (3, 7));
+        assert_eq!((violation.start_byte, violation.end_byte), (Some(34), Some(35)));
+        assert_eq!(violation.source_line.as_deref(), Some("// caf\u{FFFD} au lait"));
+
+        // The rule is selected like any other
+        let options = AnalyzerOptions {
+            rules: RuleSelection { ignore: vec!["R001".to_string()], ..RuleSelection::default() },
+            ..AnalyzerOptions::default()
+        };
+        let mut analyzer = RustDocAnalyzer::with_options(options).unwrap();
+        assert!(analyzer.analyze_file(&file).unwrap().is_empty());
+    }
+
+    /// Test that limiting the worker threads doesn't change the reports.
+    #[test]
+    fn test_analyze_files_with_jobs() {
+        let paths = [PathBuf::from("test_files/good_example.rs"), PathBuf::from("src/lib.rs")];
+        let parallel = RustDocAnalyzer::new().unwrap().analyze_files(&paths);
+        let serial =
+            RustDocAnalyzer::new().unwrap().with_jobs(NonZeroUsize::MIN).analyze_files(&paths);
+
+        let violations = |reports: &[FileReport]| -> Vec<usize> {
+            reports.iter().map(|report| report.result.as_ref().unwrap().len()).collect()
+        };
+        assert_eq!(violations(&serial), violations(&parallel));
+    }
+
+    /// Test that an interrupted batch doesn't start on further files.
+    #[test]
+    fn test_analyze_files_interrupted() {
+        let paths = [PathBuf::from("test_files/good_example.rs"), PathBuf::from("src/lib.rs")];
+        let interrupt = Arc::new(AtomicBool::new(false));
+        let mut analyzer = RustDocAnalyzer::new().unwrap().with_interrupt(Arc::clone(&interrupt));
+        assert_eq!(analyzer.analyze_files(&paths).len(), 2);
+
+        interrupt.store(true, Ordering::Relaxed);
+        assert!(analyzer.analyze_files(&paths).is_empty());
+    }
+
+    /// Test counting documented public items and naming top-level modules.
+    #[test]
+    fn test_coverage() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/parser")).unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]\nname = \"my-crate\"\n").unwrap();
+        let file = root.join("src/parser/lexer.rs");
+        fs::write(
+            &file,
+            "//! Lexer.\n\n/// Lex.\npub fn lex() {}\n\npub fn peek() {}\n\nfn skip() {}\n",
+        )
+        .unwrap();
+
+        let mut analyzer = RustDocAnalyzer::new().unwrap();
+        let reports = analyzer.analyze_files(std::slice::from_ref(&file));
+        assert_eq!(reports[0].coverage, Coverage { documented: 2, total: 3 });
+        assert!((reports[0].coverage.percent() - 66.7).abs() < 0.1);
+        assert!((Coverage::default().percent() - 100.0).abs() < f64::EPSILON);
+        assert_eq!(analyzer.top_level_module(&file), "my_crate::parser");
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        assert_eq!(analyzer.top_level_module(&root.join("src/lib.rs")), "my_crate");
+    }
+
+    /// Test that unchanged files reuse their cached results and changed ones don't.
+    #[test]
+    fn test_cached_analysis() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("parse.rs");
+        let cache_file = dir.path().join(crate::cache::CACHE_FILENAME);
+        fs::write(
+            &file,
+            "//! Crate.
+
+/// Parse it
+pub fn parse() {}
+",
+        )
+        .unwrap();
+        let rules = |reports: &[FileReport]| -> Vec<String> {
+            reports[0].result.as_ref().unwrap().iter().map(|v| v.rule.clone()).collect()
+        };
+
+        let mut analyzer = RustDocAnalyzer::new().unwrap();
+        let mut cache = analyzer.load_cache(&cache_file);
+        let reports = analyzer.analyze_files_cached(std::slice::from_ref(&file), &mut cache);
+        assert_eq!(rules(&reports), vec!["D400"]);
+        assert!(reports[0].timings.total() > Duration::ZERO);
+        cache.save().unwrap();
+
+        let mut cache = analyzer.load_cache(&cache_file);
+        let reports = analyzer.analyze_files_cached(std::slice::from_ref(&file), &mut cache);
+        assert_eq!(rules(&reports), vec!["D400"]);
+        assert_eq!(reports[0].timings.total(), Duration::ZERO);
+        assert_eq!(reports[0].coverage, Coverage { documented: 2, total: 2 });
+
+        fs::write(
+            &file,
+            "//! Crate.
+
+/// parse it.
+pub fn parse() {}
+",
+        )
+        .unwrap();
+        let reports = analyzer.analyze_files_cached(std::slice::from_ref(&file), &mut cache);
+        assert_eq!(rules(&reports), vec!["D403"]);
+    }
+
+    /// Test checking crate docs included from another file, and caching them along with the file.
+    #[test]
+    fn test_included_crate_docs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("src/lib.rs");
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(
+            &file,
+            "#![warn(missing_docs)]\n#![doc = include_str!(\"../README.md\")]\n\npub fn f() {}\n",
+        )
+        .unwrap();
+        let readme = dir.path().join("README.md");
+        fs::write(&readme, "Parse things\n").unwrap();
+        let rules = |reports: &[FileReport]| -> Vec<(String, usize)> {
+            let violations = reports[0].result.as_ref().unwrap();
+            violations.iter().map(|v| (v.rule.clone(), v.line)).collect()
+        };
+
+        let mut analyzer = RustDocAnalyzer::new().unwrap();
+        let mut cache = analyzer.load_cache(&dir.path().join(crate::cache::CACHE_FILENAME));
+        let reports = analyzer.analyze_files_cached(std::slice::from_ref(&file), &mut cache);
+        assert_eq!(rules(&reports), vec![("D400".to_string(), 2), ("D103".to_string(), 4)]);
+
+        fs::write(&readme, "Parse things.\n").unwrap();
+        let reports = analyzer.analyze_files_cached(std::slice::from_ref(&file), &mut cache);
+        assert_eq!(rules(&reports), vec![("D103".to_string(), 4)]);
+
+        // Docs that can't be loaded aren't reported as missing
+        fs::remove_file(&readme).unwrap();
+        let reports = analyzer.analyze_files_cached(std::slice::from_ref(&file), &mut cache);
+        assert_eq!(rules(&reports), vec![("D103".to_string(), 4)]);
+    }
+
+    /// Test reporting re-exports whose items aren't documented in the checked files.
+    #[test]
+    fn test_documented_reexports() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(
+            src.join("lib.rs"),
+            "//! Crate.\n\npub mod lexer;\n\npub use lexer::Token;\npub use self::lexer::{Token as \
+             Tok, Untyped};\npub use ::std::fmt::Display;\n\npub mod inner {\n    /// Inner \
+             item.\n    pub struct Item;\n}\n\npub use inner::Item;\n",
+        )
+        .unwrap();
+        fs::write(
+            src.join("lexer.rs"),
+            "//! Lexer.\n\n/// A token.\npub struct Token;\n\npub struct Untyped;\n",
+        )
+        .unwrap();
+        let paths = vec![src.join("lib.rs"), src.join("lexer.rs")];
+
+        let reexports = |reexports| {
+            let mut analyzer = RustDocAnalyzer::with_options(AnalyzerOptions {
+                reexports,
+                ..AnalyzerOptions::default()
+            })
+            .unwrap();
+            let reports = analyzer.analyze_files(&paths);
+            let violations = reports[0].result.as_ref().unwrap();
+            violations
+                .iter()
+                .filter(|v| v.rule == "R105")
+                .map(|v| v.item_path.clone().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(reexports(ReexportPolicy::RequireDocs), vec![
+            "crate::Token",
+            "crate::Tok",
+            "crate::Display",
+            "crate::Item"
+        ]);
+        assert_eq!(reexports(ReexportPolicy::DocumentedSource), vec![
+            "crate::Tok",
+            "crate::Display"
+        ]);
+
+        assert_eq!(
+            resolve_use_path("super::super::a::B", "crate::x::y").as_deref(),
+            Some("crate::a::B")
+        );
+        assert_eq!(resolve_use_path("super::B", "crate"), None);
+        assert_eq!(resolve_use_path("crate::a", "crate::x").as_deref(), Some("crate::a"));
+    }
+
     /// Test analyzer with properly formatted code.
     #[test]
     fn test_analyze_good_code() {
@@ -98,4 +1573,224 @@ pub fn subtract(a: i32, b: i32) -> i32 {
         assert!(violations.iter().any(|v| v.rule == "D403")); // Not capitalized
         assert!(violations.iter().any(|v| v.rule == "D103")); // Missing function docstring
     }
+
+    /// Test that only library crate roots get the missing_docs lint suggestion.
+    #[test]
+    fn test_analyze_crate_root_lint_suggestion() {
+        let mut analyzer = RustDocAnalyzer::new().unwrap();
+        let source = "//! A library.\n";
+
+        let violations = analyzer.analyze_source_at(source, Some(Path::new("src/lib.rs"))).unwrap();
+        assert!(violations.iter().any(|v| v.rule == "R104"));
+
+        let violations =
+            analyzer.analyze_source_at(source, Some(Path::new("src/util.rs"))).unwrap();
+        assert!(!violations.iter().any(|v| v.rule == "R104"));
+
+        let source = "#![warn(missing_docs)]\n//! A library.\n";
+        let violations = analyzer.analyze_source_at(source, Some(Path::new("src/lib.rs"))).unwrap();
+        assert!(!violations.iter().any(|v| v.rule == "R104"));
+        assert!(!violations.iter().any(|v| v.rule == "D104"));
+    }
+
+    /// Test reporting columns in UTF-16 code units.
+    #[test]
+    fn test_analyze_utf16_columns() {
+        let source = "/* \u{1f600} */ pub fn f() {}\n";
+
+        let mut analyzer = RustDocAnalyzer::new().unwrap();
+        let violations = analyzer.analyze_source(source).unwrap();
+        let missing = violations.iter().find(|v| v.rule == "D103").unwrap();
+        assert_eq!(missing.column, 12);
+
+        let options =
+            AnalyzerOptions { column_unit: ColumnUnit::Utf16, ..AnalyzerOptions::default() };
+        let mut analyzer = RustDocAnalyzer::with_options(options).unwrap();
+        let violations = analyzer.analyze_source(source).unwrap();
+        let missing = violations.iter().find(|v| v.rule == "D103").unwrap();
+        assert_eq!(missing.column, 10);
+        assert_eq!(missing.end_column, Some(23));
+        assert_eq!(missing.source_line.as_deref(), Some("/* \u{1f600} */ pub fn f() {}"));
+    }
+
+    /// Test that missing docs span the whole item, and other violations the rest of their line.
+    #[test]
+    fn test_analyze_byte_ranges() {
+        let source = "//! Crate.\n\n/// Parse it\npub fn f() {}\n\npub fn g() {}\n";
+
+        let mut analyzer = RustDocAnalyzer::new().unwrap();
+        let violations = analyzer.analyze_source(source).unwrap();
+        let range = |rule: &str| {
+            let violation = violations.iter().find(|v| v.rule == rule).unwrap();
+            &source[violation.start_byte.unwrap()..violation.end_byte.unwrap()]
+        };
+        assert_eq!(range("D103"), "pub fn g() {}");
+        assert_eq!(range("D400"), "/// Parse it");
+
+        // Rules that know the offending text cover just that text
+        let source = "//! Crate.\n\n/// Return an [Option] of [SqlType::Custom].  \n///\n/// See \
+                      std::io::Error at https://example.com for ``.\n///\n/// ```\n/// ```\npub \
+                      fn h() {}\n";
+        let violations = analyzer.analyze_source(source).unwrap();
+        let ranges = |rule: &str| -> Vec<&str> {
+            violations
+                .iter()
+                .filter(|v| v.rule == rule)
+                .map(|v| &source[v.start_byte.unwrap()..v.end_byte.unwrap()])
+                .collect()
+        };
+        assert_eq!(ranges("D210"), vec!["  "]);
+        assert_eq!(ranges("R401"), vec!["[SqlType::Custom]"]);
+        assert_eq!(ranges("R402"), vec!["[Option]"]);
+        assert_eq!(ranges("R403"), vec!["std::io::Error"]);
+        assert_eq!(ranges("R405"), vec!["``", "```\n/// ```"]);
+        assert_eq!(ranges("R406"), vec!["https://example.com"]);
+    }
+
+    /// Test the end positions of violations, after the last character of their region.
+    #[test]
+    fn test_analyze_end_positions() {
+        let source = "//! Crate.\n\npub fn f() {\n    g();\n}\n";
+
+        let mut analyzer = RustDocAnalyzer::new().unwrap();
+        let violations = analyzer.analyze_source(source).unwrap();
+        let missing = violations.iter().find(|v| v.rule == "D103").unwrap();
+        assert_eq!((missing.line, missing.column), (3, 1));
+        assert_eq!((missing.end_line, missing.end_column), (Some(5), Some(2)));
+
+        // A wrapped summary is covered up to the end of its last line
+        let source = "//! Crate.\n\n/// Parse the value\n/// of the input.\nfn f() {}\n";
+        let violations = analyzer.analyze_source(source).unwrap();
+        let wrapped = violations.iter().find(|v| v.rule == "R204").unwrap();
+        assert_eq!((wrapped.line, wrapped.column), (3, 1));
+        assert_eq!((wrapped.end_line, wrapped.end_column), (Some(4), Some(18)));
+    }
+
+    /// Test that pinning the rule set reports newer rules with info severity.
+    #[test]
+    fn test_analyze_pinned_rules_version() {
+        let source =
+            "//! Crate.\n\n/// Parse the input\npub fn parse() {}\n\n/// Print the output.\n";
+        let severities = |options: AnalyzerOptions| -> Vec<(String, Severity)> {
+            let mut analyzer = RustDocAnalyzer::with_options(options).unwrap();
+            let violations = analyzer.analyze_source(source).unwrap();
+            violations.into_iter().map(|v| (v.rule, v.severity)).collect()
+        };
+
+        let pinned = AnalyzerOptions { rules_version: Some(1), ..AnalyzerOptions::default() };
+        assert_eq!(severities(pinned.clone()), vec![
+            ("D400".to_string(), Severity::Error),
+            ("R203".to_string(), Severity::Info),
+        ]);
+
+        // Explicit overrides take precedence over the pin
+        let overridden =
+            AnalyzerOptions { severities: vec![("R203".to_string(), Severity::Error)], ..pinned };
+        assert!(severities(overridden).iter().all(|(_, severity)| *severity == Severity::Error));
+    }
+
+    /// Test that pinning the rule set reports unchecked kinds of items with info severity.
+    #[test]
+    fn test_analyze_pinned_item_kinds() {
+        let source = r#"//! Crate.
+
+/// Point on a line.
+pub struct Point {
+    /// the x coordinate
+    pub x: i32,
+}
+
+/// Methods of points.
+impl Point {
+    pub fn norm(&self) -> i32 {
+        0
+    }
+}
+
+pub static ORIGIN: Point = Point { x: 0 };
+
+/// Bindings to the C library.
+extern "C" {
+    pub fn abs(x: i32) -> i32;
+}
+
+/// parse the input
+pub fn parse() {}
+"#;
+        let severities = |rules_version: Option<u32>| -> Vec<(String, usize, Severity)> {
+            let options = AnalyzerOptions { rules_version, ..AnalyzerOptions::default() };
+            let mut analyzer = RustDocAnalyzer::with_options(options).unwrap();
+            let mut violations: Vec<(String, usize, Severity)> = analyzer
+                .analyze_source(source)
+                .unwrap()
+                .into_iter()
+                .map(|v| (v.rule, v.line, v.severity))
+                .collect();
+            violations.sort_by_key(|(rule, line, _)| (*line, rule.clone()));
+            violations
+        };
+
+        let info = |rule: &str, line| (rule.to_string(), line, Severity::Info);
+        let error = |rule: &str, line| (rule.to_string(), line, Severity::Error);
+        assert_eq!(severities(Some(1)), vec![
+            info("D400", 5),
+            info("D403", 5),
+            info("D102", 11),
+            info("R102", 16),
+            info("D103", 20),
+            error("D400", 23),
+            error("D403", 23),
+        ]);
+        assert_eq!(severities(None), vec![
+            error("D400", 5),
+            error("D403", 5),
+            error("D102", 11),
+            error("R102", 16),
+            error("D103", 20),
+            error("D400", 23),
+            error("D403", 23),
+        ]);
+    }
+
+    /// Test that positions inside docstrings follow the source text.
+    #[test]
+    fn test_analyze_docstring_positions() {
+        let mut analyzer = RustDocAnalyzer::new().unwrap();
+
+        // Tabs, multi-byte characters and CRLF line endings
+        let source = "\t/// Café summary.\r\n\t///\r\n\t/// Returns [Option].\r\n\tfn f() {}\r\n";
+        let violations = analyzer.analyze_source(source).unwrap();
+        let r402 = violations.iter().find(|v| v.rule == "R402").unwrap();
+        assert_eq!((r402.line, r402.column), (3, 14));
+
+        // Continuation lines of block comments
+        let source = "/**\n * Create a value.\n *\n * See [SqlType::Custom].\n */\nfn g() {}\n";
+        let violations = analyzer.analyze_source(source).unwrap();
+        let r401 = violations.iter().find(|v| v.rule == "R401").unwrap();
+        assert_eq!((r401.line, r401.column), (4, 8));
+
+        // Doc attributes
+        let source = "#[doc = \"Résumé of [Vec].\"]\nfn h() {}\n";
+        let violations = analyzer.analyze_source(source).unwrap();
+        let r402 = violations.iter().find(|v| v.rule == "R402").unwrap();
+        assert_eq!((r402.line, r402.column), (1, 22));
+    }
+
+    /// Test that malformed source gives partial results with diagnostics.
+    #[test]
+    fn test_check_source_lossy() {
+        let source = "/// calculate the sum.\nfn add(a: i32 {\n}\n\n/// Represents a point.\nstruct Point;\n";
+        let result = check_source_lossy(source);
+        assert!(result.diagnostics.iter().any(|d| matches!(d, Diagnostic::SyntaxError { .. })));
+        assert!(!result.diagnostics.iter().any(|d| matches!(d, Diagnostic::InternalError { .. })));
+        assert!(result.violations.iter().any(|v| v.rule == "D403"));
+
+        for source in ["", "#[doc = ]", "/**", "#![cfg(all(all(all(", "\u{feff}\r\n///\r"] {
+            let result = check_source_lossy(source);
+            assert!(
+                !result.diagnostics.iter().any(|d| matches!(d, Diagnostic::InternalError { .. })),
+                "{result:?}"
+            );
+        }
+    }
 }
@@ -0,0 +1,73 @@
+//! Language backends that extract docstrings from source files.
+
+use crate::{
+    analyzer::AnalyzerOptions,
+    context::{Edition, FileItem},
+    parser::{ParseError, RustParser},
+    pep257::{DanglingDoc, Docstring, TrailingComment, UnsafeBlock},
+    position::SourcePosition,
+};
+
+/// Everything extracted from a single parsed source file.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ParsedFile {
+    /// Docstrings (or missing docstrings) for every checked item.
+    pub docstrings: Vec<Docstring>,
+    /// Index of the checked items in source order, for rules that look across items.
+    pub items: Vec<FileItem>,
+    /// Contents of the file-level inner attributes, such as `warn(missing_docs)`.
+    pub inner_attributes: Vec<String>,
+    /// Positions of syntax errors, where the parser had to skip or insert code.
+    pub syntax_errors: Vec<SourcePosition>,
+    /// Syntax the grammar accepts but the crate's edition doesn't, with a message.
+    pub edition_errors: Vec<(SourcePosition, String)>,
+    /// Comments written after fields and variants on the same line.
+    pub trailing_comments: Vec<TrailingComment>,
+    /// Doc comments at the end of a file or block, which document no item.
+    pub dangling_docs: Vec<DanglingDoc>,
+    /// `unsafe` blocks in function bodies.
+    pub unsafe_blocks: Vec<UnsafeBlock>,
+}
+
+/// A source language whose docstrings can be checked.
+///
+/// A backend finds the documented items of a source file and reports them as
+/// docstrings with their targets, so the checks in [`crate::pep257`] don't depend
+/// on the grammar that produced them. Additional tree-sitter grammars can be added
+/// as backends behind a cargo feature.
+pub(crate) trait LanguageBackend {
+    /// Return the name of the language, e.g. `rust`.
+    fn name(&self) -> &'static str;
+
+    /// Return the file extensions handled by this backend, without the leading dot.
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// Parse source code and extract its docstrings.
+    ///
+    /// When the edition of the source is known, syntax it doesn't accept is reported.
+    fn parse(&mut self, source: &str, edition: Option<Edition>) -> Result<ParsedFile, ParseError>;
+}
+
+/// Create the backend that parses source files for the analyzer.
+pub(crate) fn create_backend(
+    options: &AnalyzerOptions,
+) -> Result<Box<dyn LanguageBackend>, ParseError> {
+    Ok(Box::new(RustParser::with_options(options.clone())?))
+}
+
+/// Unit tests for language backends.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that the default backend parses Rust files.
+    #[test]
+    fn test_create_backend() {
+        let mut backend = create_backend(&AnalyzerOptions::default()).unwrap();
+        assert_eq!(backend.name(), "rust");
+        assert_eq!(backend.extensions(), ["rs"]);
+
+        let parsed = backend.parse("/// Add two numbers.\nfn add() {}\n", None).unwrap();
+        assert_eq!(parsed.docstrings.len(), 1);
+    }
+}
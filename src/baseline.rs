@@ -0,0 +1,249 @@
+//! Baselines of known violations.
+//!
+//! A baseline records the violations a project already has, so that checks only
+//! report new ones. Violations are keyed on their crate target, item path and rule
+//! rather than their file and line, so moving an item within its file, or moving the
+//! file while keeping its module path, doesn't resurrect baselined violations. The
+//! target tells apart items of the same path in different crates, or in the library
+//! and binaries of one package, whose paths all start with `crate`.
+//!
+//! The baseline is stored as JSON:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "violations": [
+//!     {
+//!       "crate": "pep257_rs",
+//!       "root": "src/lib.rs",
+//!       "item_path": "crate::parser::Parser::new",
+//!       "rule": "D400",
+//!       "count": 1
+//!     }
+//!   ]
+//! }
+//! ```
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::pep257::Violation;
+
+/// Version of the baseline format written by this crate.
+const BASELINE_VERSION: u64 = 1;
+
+/// Errors that can occur when reading or writing a baseline.
+#[derive(thiserror::Error, Debug)]
+pub enum BaselineError {
+    /// The baseline file couldn't be read or written.
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The baseline file isn't valid JSON.
+    #[error("Invalid baseline JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The baseline JSON doesn't have the expected structure.
+    #[error("Invalid baseline: {0}")]
+    Format(String),
+}
+
+/// Crate target of a source file, which qualifies the item paths of its violations.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Target {
+    /// Name of the crate, or `crate` if its manifest can't be found.
+    pub crate_name: String,
+    /// Root file of the target relative to its package, such as `src/lib.rs` or `tests/cli.rs`.
+    pub root: String,
+}
+
+/// Known violations, counted per target, item path and rule.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Baseline {
+    entries: BTreeMap<(Target, String, String), usize>,
+}
+
+/// Implementation of baseline methods.
+impl Baseline {
+    /// Build a baseline recording the given violations of a target.
+    #[must_use]
+    pub fn from_violations(target: &Target, violations: &[Violation]) -> Self {
+        let mut baseline = Self::default();
+        for violation in violations {
+            baseline.record(target, violation);
+        }
+        baseline
+    }
+
+    /// Record a violation of a target in the baseline.
+    pub fn record(&mut self, target: &Target, violation: &Violation) {
+        *self.entries.entry(Self::key(target, violation)).or_default() += 1;
+    }
+
+    /// Check whether a violation is known, using up one of its recorded occurrences.
+    ///
+    /// An item with more violations of a rule than were recorded reports the extra
+    /// ones as new.
+    pub fn matches(&mut self, target: &Target, violation: &Violation) -> bool {
+        match self.entries.get_mut(&Self::key(target, violation)) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Return the number of violations recorded in the baseline.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.values().sum()
+    }
+
+    /// Check whether the baseline records no violations.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Read a baseline from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BaselineError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Write the baseline to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), BaselineError> {
+        fs::write(path, self.to_json()? + "\n")?;
+        Ok(())
+    }
+
+    /// Parse a baseline from its JSON representation.
+    pub fn from_json(json: &str) -> Result<Self, BaselineError> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let version = value.get("version").and_then(serde_json::Value::as_u64);
+        if version != Some(BASELINE_VERSION) {
+            return Err(BaselineError::Format(format!(
+                "unsupported version {}",
+                value.get("version").unwrap_or(&serde_json::Value::Null)
+            )));
+        }
+        let violations = value
+            .get("violations")
+            .and_then(serde_json::Value::as_array)
+            .ok_or_else(|| BaselineError::Format("missing `violations` array".to_string()))?;
+
+        let mut baseline = Self::default();
+        for entry in violations {
+            let field = |name: &str| {
+                entry.get(name).and_then(serde_json::Value::as_str).ok_or_else(|| {
+                    BaselineError::Format(format!("violation entry without `{name}`: {entry}"))
+                })
+            };
+            let target = Target {
+                crate_name: field("crate")?.to_string(),
+                root: field("root")?.to_string(),
+            };
+            let key = (target, field("item_path")?.to_string(), field("rule")?.to_string());
+            let count =
+                entry.get("count").map_or(Some(1), serde_json::Value::as_u64).ok_or_else(|| {
+                    BaselineError::Format(format!("violation entry with invalid `count`: {entry}"))
+                })?;
+            *baseline.entries.entry(key).or_default() +=
+                usize::try_from(count).unwrap_or(usize::MAX);
+        }
+        Ok(baseline)
+    }
+
+    /// Serialize the baseline as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, BaselineError> {
+        let json = serde_json::json!({
+            "version": BASELINE_VERSION,
+            "violations": self.entries.iter().filter(|&(_, &count)| count > 0).map(|((target, item_path, rule), count)| {
+                serde_json::json!({
+                    "crate": target.crate_name,
+                    "root": target.root,
+                    "item_path": item_path,
+                    "rule": rule,
+                    "count": count,
+                })
+            }).collect::<Vec<_>>(),
+        });
+        Ok(serde_json::to_string_pretty(&json)?)
+    }
+
+    /// Return the key a violation of a target is recorded under.
+    fn key(target: &Target, violation: &Violation) -> (Target, String, String) {
+        (target.clone(), violation.item_path.clone().unwrap_or_default(), violation.rule.clone())
+    }
+}
+
+/// Unit tests for baselines.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pep257::Severity, position::SourcePosition};
+
+    /// Build a violation of a rule on an item.
+    fn violation(item_path: &str, rule: &str, line: usize) -> Violation {
+        Violation::new(rule, "", SourcePosition { line, column: 1 }, Severity::Error)
+            .with_item_path(item_path)
+    }
+
+    /// Build the target of a crate root.
+    fn target(root: &str) -> Target {
+        Target { crate_name: "demo".to_string(), root: root.to_string() }
+    }
+
+    /// Test that baselined violations match regardless of their line, up to their count.
+    #[test]
+    fn test_matches() {
+        let lib = target("src/lib.rs");
+        let mut baseline = Baseline::from_violations(&lib, &[
+            violation("crate::f", "D400", 3),
+            violation("crate::f", "D400", 3),
+            violation("crate::g", "D403", 8),
+        ]);
+        assert_eq!(baseline.len(), 3);
+
+        assert!(baseline.matches(&lib, &violation("crate::f", "D400", 20)));
+        assert!(baseline.matches(&lib, &violation("crate::f", "D400", 20)));
+        assert!(!baseline.matches(&lib, &violation("crate::f", "D400", 20)));
+        assert!(!baseline.matches(&lib, &violation("crate::f", "D403", 20)));
+        assert!(!baseline.matches(&lib, &violation("crate::h", "D403", 8)));
+    }
+
+    /// Test that items of the same path in different roots are baselined apart.
+    #[test]
+    fn test_matches_roots() {
+        let (main, tool) = (target("src/main.rs"), target("src/bin/tool.rs"));
+        let mut baseline = Baseline::from_violations(&main, &[violation("crate::main", "D103", 1)]);
+
+        assert!(!baseline.matches(&tool, &violation("crate::main", "D103", 1)));
+        let other = Target { crate_name: "other".to_string(), ..main.clone() };
+        assert!(!baseline.matches(&other, &violation("crate::main", "D103", 1)));
+        assert!(baseline.matches(&main, &violation("crate::main", "D103", 1)));
+    }
+
+    /// Test that baselines round-trip through JSON and reject other formats.
+    #[test]
+    fn test_json() {
+        let mut baseline = Baseline::from_violations(&target("src/lib.rs"), &[
+            violation("crate::f", "D400", 3),
+            violation("crate::f", "D400", 5),
+            violation("crate", "D100", 1),
+        ]);
+        baseline.record(&target("tests/cli.rs"), &violation("crate", "D100", 1));
+        let json = baseline.to_json().unwrap();
+        assert!(json.contains(r#""item_path": "crate::f""#));
+        assert!(json.contains(r#""root": "tests/cli.rs""#));
+        assert_eq!(Baseline::from_json(&json).unwrap(), baseline);
+
+        let single = r#"{"version": 1, "violations": [{"crate": "demo", "root": "src/lib.rs", "item_path": "crate", "rule": "D100"}]}"#;
+        assert_eq!(Baseline::from_json(single).unwrap().len(), 1);
+        assert!(matches!(
+            Baseline::from_json(r#"{"version": 2, "violations": []}"#),
+            Err(BaselineError::Format(_))
+        ));
+        assert!(matches!(
+            Baseline::from_json(r#"{"version": 1, "violations": [{"rule": "D100"}]}"#),
+            Err(BaselineError::Format(_))
+        ));
+    }
+}
@@ -0,0 +1,128 @@
+//! Synthetic workloads for measuring the analyzer's throughput.
+//!
+//! A synthetic crate has a `lib.rs` declaring one module per generated file. Each
+//! module mixes documented and undocumented items, and docs that pass and fail the
+//! checks, so a run exercises parsing, every kind of item and violation reporting.
+//! The files of a crate differ, so results cached for one file never match another.
+
+use std::{
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Return the source of the synthetic module with the given index.
+#[must_use]
+pub fn synthetic_source(index: usize) -> String {
+    let mut source = format!("//! Synthetic module {index} for benchmarks.\n\n");
+    for item in 0..8 {
+        let name = format!("item_{index}_{item}");
+        let _ = write!(
+            source,
+            r#"/// Configuration of {name}.
+///
+/// Built with [`Builder{index}{item}`] and passed to `run_{name}`.
+#[derive(Debug, Clone, Default)]
+pub struct Config{index}{item} {{
+    /// Number of retries before giving up.
+    pub retries: u32,
+    /// name of the target
+    pub target: String,
+    pub verbose: bool,
+}}
+
+/// Builder of configurations.
+pub struct Builder{index}{item};
+
+/// implementation of builder methods
+impl Builder{index}{item} {{
+    /// Create a builder with the default settings.
+    #[must_use]
+    pub fn new() -> Self {{
+        Self
+    }}
+
+    /// Returns the built configuration
+    pub fn build(&self) -> Config{index}{item} {{
+        Config{index}{item}::default()
+    }}
+}}
+
+/// Run the {name} step.
+///
+/// # Errors
+///
+/// Returns an error if the target is empty.
+pub fn run_{name}(config: &Config{index}{item}) -> Result<u32, String> {{
+    if config.target.is_empty() {{
+        return Err(String::from("empty target"));
+    }}
+    Ok(config.retries)
+}}
+
+pub fn helper_{name}(value: i32) -> i32 {{
+    value * 2
+}}
+
+/// Maximum size of {name}
+pub const LIMIT_{index}_{item}: usize = {item};
+
+"#
+        );
+    }
+    source
+}
+
+/// Write a synthetic crate with the given number of modules to a directory.
+///
+/// Returns the paths of the crate's source files, `src/lib.rs` first.
+pub fn write_synthetic_crate(dir: &Path, modules: usize) -> io::Result<Vec<PathBuf>> {
+    let src = dir.join("src");
+    fs::create_dir_all(&src)?;
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"synthetic\"\nversion = \"0.1.0\"\nedition = \"2024\"\n",
+    )?;
+
+    let mut lib = String::from("//! Synthetic crate for benchmarks.\n\n");
+    let mut paths = vec![src.join("lib.rs")];
+    for index in 0..modules {
+        let _ = writeln!(lib, "/// Synthetic module {index}.\npub mod module_{index};");
+        let path = src.join(format!("module_{index}.rs"));
+        fs::write(&path, synthetic_source(index))?;
+        paths.push(path);
+    }
+    fs::write(&paths[0], lib)?;
+    Ok(paths)
+}
+
+/// Unit tests for synthetic workloads.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::RustDocAnalyzer;
+
+    /// Test that synthetic sources parse and report a mix of violations.
+    #[test]
+    fn test_synthetic_source() {
+        let mut analyzer = RustDocAnalyzer::new().unwrap();
+        let violations = analyzer.analyze_source(&synthetic_source(3)).unwrap();
+
+        let mut rules: Vec<_> = violations.iter().map(|v| v.rule.as_str()).collect();
+        rules.sort_unstable();
+        rules.dedup();
+        assert_eq!(rules, vec!["D103", "D400", "D401", "D403", "R106"]);
+        assert_ne!(synthetic_source(3), synthetic_source(4));
+    }
+
+    /// Test writing a synthetic crate.
+    #[test]
+    fn test_write_synthetic_crate() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = write_synthetic_crate(dir.path(), 2).unwrap();
+
+        assert_eq!(paths.len(), 3);
+        assert!(paths.iter().all(|path| path.is_file()));
+        assert!(fs::read_to_string(&paths[0]).unwrap().contains("pub mod module_1;"));
+    }
+}
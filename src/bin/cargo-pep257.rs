@@ -0,0 +1,10 @@
+//! Cargo subcommand checking the docstrings of a workspace, run as `cargo pep257`.
+
+/// Command-line interface shared with the `pep257` binary.
+#[path = "../main.rs"]
+mod cli;
+
+/// Entry point for `cargo pep257`.
+fn main() {
+    cli::main();
+}
@@ -0,0 +1,355 @@
+//! On-disk cache of analysis results.
+//!
+//! The cache maps each analyzed file to a hash of its content and crate metadata,
+//! along with the violations and coverage found in it, so files that haven't
+//! changed skip parsing and checking on the next run. The whole cache is dropped
+//! when the options it was built with, or the version of the checker, change.
+//!
+//! By default the cache is written to a `.pep257-cache` directory in the project
+//! root, which holds a `.gitignore` so the cache stays out of version control.
+//!
+//! The cache is stored as JSON:
+//!
+//! ```json
+//! {
+//!   "version": 4,
+//!   "options": "5d41402abc4b2a76",
+//!   "files": {
+//!     "src/lib.rs": {
+//!       "hash": "9e107d9d372bb682",
+//!       "coverage": [3, 4],
+//!       "exports": {
+//!         "documented": ["crate::parse"],
+//!         "reexports": [{ "path": "crate::Token", "targets": ["crate::lexer::Token"] }]
+//!       },
+//!       "violations": [
+//!         { "rule": "D400", "message": "...", "line": 1, "column": 1, "severity": "error" }
+//!       ]
+//!     }
+//!   }
+//! }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use log::warn;
+use serde_json::{Value, json};
+
+use crate::{
+    analyzer::{Coverage, Exports},
+    pep257::{Severity, Violation},
+};
+
+/// Name of the directory the cache is written to when no other location is configured.
+pub const CACHE_DIRNAME: &str = ".pep257-cache";
+
+/// Name of the cache file within the cache directory.
+pub const CACHE_FILENAME: &str = "results.json";
+
+/// Version of the cache format written by this crate.
+const CACHE_VERSION: u64 = 4;
+
+/// Return the cache file in the default cache directory of a project root.
+#[must_use]
+pub fn default_path(root: &Path) -> PathBuf {
+    root.join(CACHE_DIRNAME).join(CACHE_FILENAME)
+}
+
+/// Results of analyzing a file, as stored in the cache.
+#[derive(Debug, Clone)]
+pub struct CachedResult {
+    /// Violations found in the file.
+    pub violations: Vec<Violation>,
+    /// Documentation coverage of the file's public items.
+    pub coverage: Coverage,
+    /// Documented items and undocumented re-exports of the file.
+    pub exports: Exports,
+}
+
+/// Cache entry of a file.
+#[derive(Debug, Clone)]
+struct Entry {
+    /// Hash of the file's content and crate metadata.
+    hash: u64,
+    /// Results of analyzing that content.
+    result: CachedResult,
+}
+
+/// Analysis results of files, keyed on their content.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    /// File the cache is read from and written to.
+    path: PathBuf,
+    /// Hash of the options the results were found with.
+    options: u64,
+    /// Entries by analyzed file.
+    files: BTreeMap<PathBuf, Entry>,
+}
+
+/// Implementation of cache methods.
+impl Cache {
+    /// Load the cache from a file, for results found with options described by `options`.
+    ///
+    /// A missing or unreadable cache file, or one written with other options or
+    /// another version of the checker, gives an empty cache.
+    #[must_use]
+    pub fn load(path: &Path, options: &str) -> Self {
+        let options = hash(&[env!("CARGO_PKG_VERSION").as_bytes(), options.as_bytes()]);
+        let mut cache = Self { path: path.to_path_buf(), options, files: BTreeMap::new() };
+
+        let json = match fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return cache,
+            Err(e) => {
+                warn!("Ignoring unreadable cache {}: {e}", path.display());
+                return cache;
+            }
+        };
+        match Self::parse_files(&json, options) {
+            Some(files) => cache.files = files,
+            None => warn!("Ignoring outdated or invalid cache {}", path.display()),
+        }
+        cache
+    }
+
+    /// Return the cached results of a file, if its content and crate are unchanged.
+    #[must_use]
+    pub fn get(&self, file: &Path, key: u64) -> Option<&CachedResult> {
+        self.files.get(file).filter(|entry| entry.hash == key).map(|entry| &entry.result)
+    }
+
+    /// Record the results of analyzing a file.
+    pub fn insert(&mut self, file: &Path, key: u64, result: CachedResult) {
+        self.files.insert(file.to_path_buf(), Entry { hash: key, result });
+    }
+
+    /// Write the cache to its file.
+    ///
+    /// A missing directory of the file is created with a `.gitignore` ignoring its
+    /// content.
+    pub fn save(&self) -> io::Result<()> {
+        let files: serde_json::Map<String, Value> = self
+            .files
+            .iter()
+            .map(|(file, entry)| {
+                let violations: Vec<Value> =
+                    entry.result.violations.iter().map(violation_to_json).collect();
+                let coverage = entry.result.coverage;
+                let value = json!({
+                    "hash": format!("{:016x}", entry.hash),
+                    "coverage": [coverage.documented, coverage.total],
+                    "exports": exports_to_json(&entry.result.exports),
+                    "violations": violations,
+                });
+                (file.display().to_string(), value)
+            })
+            .collect();
+        let json = json!({
+            "version": CACHE_VERSION,
+            "options": format!("{:016x}", self.options),
+            "files": files,
+        });
+        if let Some(dir) =
+            self.path.parent().filter(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+        {
+            fs::create_dir_all(dir)?;
+            fs::write(dir.join(".gitignore"), "*\n")?;
+        }
+        fs::write(&self.path, json.to_string())
+    }
+
+    /// Parse the entries of a cache file written with the given options.
+    fn parse_files(json: &str, options: u64) -> Option<BTreeMap<PathBuf, Entry>> {
+        let value: Value = serde_json::from_str(json).ok()?;
+        if value.get("version")?.as_u64()? != CACHE_VERSION
+            || parse_hash(value.get("options")?)? != options
+        {
+            return None;
+        }
+
+        let mut files = BTreeMap::new();
+        for (file, entry) in value.get("files")?.as_object()? {
+            let coverage = entry.get("coverage")?.as_array()?;
+            let count = |index: usize| usize::try_from(coverage.get(index)?.as_u64()?).ok();
+            let violations = entry.get("violations")?.as_array()?;
+            let result = CachedResult {
+                violations: violations.iter().map(violation_from_json).collect::<Option<_>>()?,
+                coverage: Coverage { documented: count(0)?, total: count(1)? },
+                exports: exports_from_json(entry.get("exports")?)?,
+            };
+            files.insert(PathBuf::from(file), Entry {
+                hash: parse_hash(entry.get("hash")?)?,
+                result,
+            });
+        }
+        Some(files)
+    }
+}
+
+/// Hash byte strings with 64-bit FNV-1a.
+///
+/// Unlike the standard library's hasher, it gives the same hash on every platform and Rust
+/// version.
+#[must_use]
+pub fn hash(parts: &[&[u8]]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        // Separate the parts, so moving bytes from one part to the next changes the hash
+        for &byte in part.iter().chain(&[0xff]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Parse a hash written as hexadecimal.
+fn parse_hash(value: &Value) -> Option<u64> {
+    u64::from_str_radix(value.as_str()?, 16).ok()
+}
+
+/// Convert the exports of a file into their cached JSON representation.
+fn exports_to_json(exports: &Exports) -> Value {
+    let reexports: Vec<Value> = exports
+        .reexports
+        .iter()
+        .map(|(path, targets)| json!({ "path": path, "targets": targets }))
+        .collect();
+    json!({ "documented": exports.documented, "reexports": reexports })
+}
+
+/// Read the exports of a file from their cached JSON representation.
+fn exports_from_json(value: &Value) -> Option<Exports> {
+    let strings = |value: &Value| -> Option<Vec<Option<String>>> {
+        value.as_array()?.iter().map(|v| Some(v.as_str().map(str::to_string))).collect()
+    };
+    let documented = strings(value.get("documented")?)?.into_iter().collect::<Option<_>>()?;
+    let reexports = value
+        .get("reexports")?
+        .as_array()?
+        .iter()
+        .map(|reexport| {
+            let path = reexport.get("path")?.as_str()?.to_string();
+            Some((path, strings(reexport.get("targets")?)?))
+        })
+        .collect::<Option<_>>()?;
+    Some(Exports { documented, reexports })
+}
+
+/// Convert a violation into its cached JSON representation.
+pub(crate) fn violation_to_json(violation: &Violation) -> Value {
+    json!({
+        "rule": violation.rule,
+        "message": violation.message,
+        "line": violation.line,
+        "column": violation.column,
+        "severity": match violation.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        },
+        "item_path": violation.item_path,
+        "source_line": violation.source_line,
+        "start_byte": violation.start_byte,
+        "end_byte": violation.end_byte,
+        "end_line": violation.end_line,
+        "end_column": violation.end_column,
+    })
+}
+
+/// Read a violation from its cached JSON representation.
+pub(crate) fn violation_from_json(value: &Value) -> Option<Violation> {
+    let string = |name: &str| value.get(name)?.as_str().map(str::to_string);
+    let number = |name: &str| usize::try_from(value.get(name)?.as_u64()?).ok();
+    Some(Violation {
+        rule: string("rule")?,
+        message: string("message")?,
+        line: number("line")?,
+        column: number("column")?,
+        severity: match value.get("severity")?.as_str()? {
+            "error" => Severity::Error,
+            "warning" => Severity::Warning,
+            "info" => Severity::Info,
+            _ => return None,
+        },
+        item_path: string("item_path"),
+        source_line: string("source_line"),
+        start_byte: number("start_byte"),
+        end_byte: number("end_byte"),
+        end_line: number("end_line"),
+        end_column: number("end_column"),
+    })
+}
+
+/// Unit tests for the result cache.
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::position::SourcePosition;
+
+    /// Test that results survive a save and load, but not a change of options.
+    #[test]
+    fn test_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = default_path(dir.path());
+        let file = Path::new("src/lib.rs");
+        let key = hash(&[b"//! Crate.\n"]);
+        let violation = Violation {
+            source_line: Some("//! Crate".to_string()),
+            ..Violation::new(
+                "D400",
+                "First line should end with a period",
+                SourcePosition { line: 1, column: 1 },
+                Severity::Warning,
+            )
+            .with_item_path("crate")
+            .with_byte_range(0..9)
+            .ending_at(SourcePosition { line: 1, column: 10 })
+        };
+
+        let mut cache = Cache::load(&path, "options");
+        assert!(cache.get(file, key).is_none());
+        let coverage = Coverage { documented: 1, total: 2 };
+        let exports = Exports {
+            documented: vec!["crate::parse".to_string()],
+            reexports: vec![("crate::Token".to_string(), vec![None])],
+        };
+        cache.insert(file, key, CachedResult {
+            violations: vec![violation],
+            coverage,
+            exports: exports.clone(),
+        });
+        cache.save().unwrap();
+        let ignore = fs::read_to_string(dir.path().join(CACHE_DIRNAME).join(".gitignore"));
+        assert_eq!(ignore.unwrap(), "*\n");
+
+        let cache = Cache::load(&path, "options");
+        let result = cache.get(file, key).unwrap();
+        assert_eq!(result.coverage, coverage);
+        assert_eq!(result.exports, exports);
+        let violation = &result.violations[0];
+        assert_eq!((violation.rule.as_str(), violation.severity), ("D400", Severity::Warning));
+        assert_eq!(violation.item_path.as_deref(), Some("crate"));
+        assert_eq!(violation.source_line.as_deref(), Some("//! Crate"));
+        assert_eq!((violation.start_byte, violation.end_byte), (Some(0), Some(9)));
+        assert_eq!((violation.end_line, violation.end_column), (Some(1), Some(10)));
+        assert!(cache.get(file, key + 1).is_none());
+
+        assert!(Cache::load(&path, "other options").get(file, key).is_none());
+        fs::write(&path, "not json").unwrap();
+        assert!(Cache::load(&path, "options").get(file, key).is_none());
+    }
+
+    /// Test that the hash separates its parts.
+    #[test]
+    fn test_hash() {
+        assert_eq!(hash(&[b"ab", b"c"]), hash(&[b"ab", b"c"]));
+        assert_ne!(hash(&[b"ab", b"c"]), hash(&[b"a", b"bc"]));
+    }
+}
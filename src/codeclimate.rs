@@ -0,0 +1,170 @@
+//! Code Climate issue reports.
+//!
+//! GitLab merge request widgets and other code quality dashboards read reports in
+//! the Code Climate format, a JSON array with one object per issue:
+//!
+//! ```json
+//! [
+//!   {
+//!     "type": "issue",
+//!     "check_name": "D400",
+//!     "description": "First line should end with a period",
+//!     "categories": ["Style"],
+//!     "severity": "major",
+//!     "location": {
+//!       "path": "src/lib.rs",
+//!       "positions": {
+//!         "begin": { "line": 12, "column": 1 },
+//!         "end": { "line": 12, "column": 24 }
+//!       }
+//!     },
+//!     "fingerprint": "9e107d9d372bb682"
+//!   }
+//! ]
+//! ```
+//!
+//! Dashboards compare fingerprints between runs to tell new issues from fixed ones,
+//! so a fingerprint is derived from the file, item path, rule and message rather
+//! than the line, and stays the same when code above the issue changes.
+
+use std::collections::HashMap;
+
+use serde_json::{Value, json};
+
+use crate::{
+    cache::hash,
+    pep257::{Severity, Violation},
+};
+
+/// Issues of a check run, in the Code Climate format.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Issue objects in the order they were added.
+    issues: Vec<Value>,
+    /// Number of issues added per fingerprint of their identity.
+    occurrences: HashMap<u64, usize>,
+}
+
+/// Implementation of Code Climate report methods.
+impl Report {
+    /// Add an issue for a violation in the file at `path`.
+    pub fn add(&mut self, path: &str, violation: &Violation) {
+        let item_path = violation.item_path.as_deref().unwrap_or_default();
+        let identity = hash(&[
+            path.as_bytes(),
+            item_path.as_bytes(),
+            violation.rule.as_bytes(),
+            violation.message.as_bytes(),
+        ]);
+        // Repeated issues of an item are told apart by their order
+        let occurrence = self.occurrences.entry(identity).or_default();
+        let fingerprint = if *occurrence == 0 {
+            identity
+        } else {
+            hash(&[&identity.to_le_bytes(), &occurrence.to_le_bytes()])
+        };
+        *occurrence += 1;
+
+        self.issues.push(json!({
+            "type": "issue",
+            "check_name": violation.rule,
+            "description": violation.message,
+            "categories": ["Style"],
+            "severity": match violation.severity {
+                Severity::Error => "major",
+                Severity::Warning => "minor",
+                Severity::Info => "info",
+            },
+            "location": {
+                "path": path,
+                "positions": match (violation.end_line, violation.end_column) {
+                    (Some(end_line), Some(end_column)) => json!({
+                        "begin": { "line": violation.line, "column": violation.column },
+                        "end": { "line": end_line, "column": end_column },
+                    }),
+                    _ => json!({
+                        "begin": { "line": violation.line, "column": violation.column },
+                    }),
+                },
+            },
+            "fingerprint": format!("{fingerprint:016x}"),
+        }));
+    }
+
+    /// Return the report as a JSON array of issues.
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        Value::Array(self.issues.clone())
+    }
+}
+
+/// Unit tests for Code Climate reports.
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::position::SourcePosition;
+
+    /// Return a violation of a rule on an item, ending on its line.
+    fn violation(rule: &str, line: usize, item_path: &str) -> Violation {
+        Violation::new(
+            rule,
+            format!("{rule} message"),
+            SourcePosition { line, column: 5 },
+            Severity::Warning,
+        )
+        .with_item_path(item_path)
+        .ending_at(SourcePosition { line, column: 12 })
+    }
+
+    /// Test the structure of a reported issue.
+    #[test]
+    fn test_issue() {
+        let mut report = Report::default();
+        report.add("src/lib.rs", &violation("D400", 3, "crate::parse"));
+
+        let issue = &report.to_json()[0];
+        assert_eq!(issue["type"], "issue");
+        assert_eq!(issue["check_name"], "D400");
+        assert_eq!(issue["description"], "D400 message");
+        assert_eq!(issue["severity"], "minor");
+        assert_eq!(issue["location"]["path"], "src/lib.rs");
+        assert_eq!(issue["location"]["positions"]["begin"], json!({ "line": 3, "column": 5 }));
+        assert_eq!(issue["location"]["positions"]["end"], json!({ "line": 3, "column": 12 }));
+        assert_eq!(issue["fingerprint"].as_str().unwrap().len(), 16);
+    }
+
+    /// Test that fingerprints ignore lines but tell apart files, items and repeats.
+    #[test]
+    fn test_fingerprints() {
+        let fingerprints = |violations: &[(&str, Violation)]| -> Vec<String> {
+            let mut report = Report::default();
+            for (path, violation) in violations {
+                report.add(path, violation);
+            }
+            report
+                .to_json()
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|issue| issue["fingerprint"].to_string())
+                .collect()
+        };
+
+        let before = fingerprints(&[("src/lib.rs", violation("D400", 3, "crate::parse"))]);
+        let moved = fingerprints(&[("src/lib.rs", violation("D400", 30, "crate::parse"))]);
+        assert_eq!(before, moved);
+
+        let distinct = fingerprints(&[
+            ("src/lib.rs", violation("D400", 3, "crate::parse")),
+            ("src/main.rs", violation("D400", 3, "crate::parse")),
+            ("src/lib.rs", violation("D400", 3, "crate::print")),
+            ("src/lib.rs", violation("D401", 3, "crate::parse")),
+            ("src/lib.rs", violation("D400", 4, "crate::parse")),
+        ]);
+        let unique: HashSet<_> = distinct.iter().collect();
+        assert_eq!(unique.len(), distinct.len());
+        assert_eq!(distinct[0], before[0]);
+    }
+}
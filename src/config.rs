@@ -0,0 +1,516 @@
+//! Project configuration files.
+//!
+//! Projects can declare their settings in a `pep257.toml` file, or in the
+//! `[tool.pep257]` or `[package.metadata.pep257]` table of their `Cargo.toml`,
+//! instead of passing command-line flags on every run:
+//!
+//! ```toml
+//! # Opt-in rules to enable
+//! enable = ["R409"]
+//! # Gitignore-syntax patterns of paths to skip, relative to the configuration file
+//! exclude = ["src/generated/", "benches/"]
+//! # Extra gitignore-syntax files to respect, besides `.pep257ignore`
+//! ignore-files = [".docignore"]
+//! # Words public async function docs should mention (R412)
+//! async-keywords = ["Cancellation", "cancel safe"]
+//! # Words the docs of default trait methods should mention (R414)
+//! default-method-keywords = ["default", "override"]
+//! # Macros and methods that make a function panic (R416)
+//! panic-constructs = ["panic!", "unreachable!", "unwrap", "expect"]
+//! # Target types whose public items should include a code example (R417)
+//! example-targets = ["function", "trait"]
+//! # Minimum number of non-blank lines of crate docs (R503)
+//! crate-docs-min-lines = 20
+//! # Maximum number of blank lines between summary and description (R202)
+//! max-summary-blank-lines = 2
+//! # How to check items marked `#[doc(hidden)]`
+//! doc-hidden = "skip-all"
+//! # File to cache results in, relative to the configuration file
+//! cache-file = "target/pep257-cache"
+//! # Rule set to pin, reporting rules added after it as info instead of failing
+//! rules-version = "1"
+//! # Severities of the violations that fail a check
+//! fail-on = ["error"]
+//!
+//! # Severity overrides by rule code
+//! [severity]
+//! D401 = "error"
+//! R402 = "warning"
+//!
+//! # Rules not to report in the files matching glob patterns, relative to the
+//! # configuration file, by code prefix or category
+//! [per-file-ignores]
+//! "tests/**" = ["D103", "D401"]
+//! "build.rs" = ["D1"]
+//! ```
+//!
+//! Settings from the command line add to the ones from the configuration file.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum as _;
+
+use crate::{
+    analyzer::AnalyzerOptions,
+    cache,
+    file_collector::CollectOptions,
+    pep257::{ExampleTargets, HiddenPolicy, OPT_IN_RULES, Severity},
+    report::FailPolicy,
+    rules::{PerFileIgnores, RULES_VERSION, parse_rule_prefix},
+};
+
+/// Name of the dedicated configuration file.
+pub const CONFIG_FILENAME: &str = "pep257.toml";
+
+/// Name of the Cargo manifest file, which may hold the configuration.
+const MANIFEST_FILENAME: &str = "Cargo.toml";
+
+/// Errors that can occur when loading a configuration file.
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    /// The configuration file couldn't be read.
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The configuration file is invalid.
+    #[error("Invalid configuration in {}: {message}", path.display())]
+    Invalid {
+        /// Path of the configuration file.
+        path: PathBuf,
+        /// Description of the problem.
+        message: String,
+    },
+}
+
+/// Settings loaded from a configuration file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    /// Directory of the configuration file, which exclude patterns are relative to.
+    pub root: PathBuf,
+    /// Opt-in rules to enable.
+    pub enable: Vec<String>,
+    /// Severity overrides by rule code.
+    pub severity: Vec<(String, Severity)>,
+    /// Gitignore-syntax patterns of paths to skip, relative to the root.
+    pub exclude: Vec<String>,
+    /// Names of extra gitignore-syntax files to respect.
+    pub ignore_files: Vec<String>,
+    /// Words, one of which public async function docs should mention (R412).
+    pub async_keywords: Vec<String>,
+    /// Words, one of which the docs of default trait methods should mention (R414).
+    pub default_method_keywords: Vec<String>,
+    /// Macros and methods that make a function panic (R416).
+    pub panic_constructs: Vec<String>,
+    /// Target types whose public items should include a code example (R417).
+    pub example_targets: Vec<String>,
+    /// Minimum number of non-blank lines of crate docs (R503).
+    pub crate_docs_min_lines: Option<usize>,
+    /// Maximum number of blank lines between summary and description (R202).
+    pub max_summary_blank_lines: Option<usize>,
+    /// How items marked `#[doc(hidden)]` are checked.
+    pub doc_hidden: Option<HiddenPolicy>,
+    /// File to cache results in, instead of the default cache directory in the root.
+    pub cache_file: Option<PathBuf>,
+    /// Version of the rule set to pin, so rules added after it don't fail checks.
+    pub rules_version: Option<u32>,
+    /// Severities of the violations that fail a check.
+    pub fail_on: Option<Vec<Severity>>,
+    /// Glob patterns of files, relative to the root, with rules not to report in them.
+    pub per_file_ignores: Vec<(String, Vec<String>)>,
+}
+
+/// Implementation of configuration methods.
+impl Config {
+    /// Find and load the configuration for a checked path.
+    ///
+    /// The path's directory and its parents are searched for a `pep257.toml` file,
+    /// or a `Cargo.toml` file with a `pep257` table; the nearest one is used, with
+    /// `pep257.toml` taking precedence within a directory. Returns `None` if there
+    /// is no configuration.
+    pub fn discover(path: &Path) -> Result<Option<Self>, ConfigError> {
+        let path = path.canonicalize()?;
+        for dir in path.ancestors().filter(|dir| dir.is_dir()) {
+            let config_file = dir.join(CONFIG_FILENAME);
+            if config_file.is_file() {
+                return Self::load(&config_file).map(Some);
+            }
+
+            let manifest = dir.join(MANIFEST_FILENAME);
+            if manifest.is_file() {
+                let table = Self::read_table(&manifest)?;
+                if let Some(settings) = Self::manifest_settings(&table) {
+                    return Self::from_table(settings, dir)
+                        .map(Some)
+                        .map_err(|message| ConfigError::Invalid { path: manifest, message });
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Load a configuration file, either a `pep257.toml` file or a `Cargo.toml` file.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let table = Self::read_table(path)?;
+        let root = path.parent().unwrap_or_else(|| Path::new(".")).to_path_buf();
+        let invalid = |message: String| ConfigError::Invalid { path: path.to_path_buf(), message };
+
+        if path.file_name().is_some_and(|name| name == MANIFEST_FILENAME) {
+            let settings = Self::manifest_settings(&table)
+                .ok_or_else(|| invalid("no `[tool.pep257]` table".to_string()))?;
+            Self::from_table(settings, &root).map_err(invalid)
+        } else {
+            Self::from_table(&table, &root).map_err(invalid)
+        }
+    }
+
+    /// Parse the settings table of a configuration file in a directory.
+    pub fn from_table(table: &toml::Table, root: &Path) -> Result<Self, String> {
+        let mut config = Self { root: root.to_path_buf(), ..Self::default() };
+
+        for (key, value) in table {
+            match key.as_str() {
+                "enable" => {
+                    config.enable = Self::strings(key, value)?;
+                    if let Some(rule) =
+                        config.enable.iter().find(|rule| !OPT_IN_RULES.contains(&rule.as_str()))
+                    {
+                        return Err(format!(
+                            "`{rule}` is not an opt-in rule (expected one of {})",
+                            OPT_IN_RULES.join(", ")
+                        ));
+                    }
+                }
+                "exclude" => config.exclude = Self::strings(key, value)?,
+                "ignore-files" => config.ignore_files = Self::strings(key, value)?,
+                "async-keywords" => config.async_keywords = Self::strings(key, value)?,
+                "default-method-keywords" => {
+                    config.default_method_keywords = Self::strings(key, value)?;
+                }
+                "panic-constructs" => config.panic_constructs = Self::strings(key, value)?,
+                "example-targets" => {
+                    config.example_targets = Self::strings(key, value)?;
+                    for target in &config.example_targets {
+                        ExampleTargets::default()
+                            .add(target)
+                            .map_err(|e| format!("{e} in `example-targets`"))?;
+                    }
+                }
+                "crate-docs-min-lines" => {
+                    config.crate_docs_min_lines = Some(Self::count(key, value)?);
+                }
+                "max-summary-blank-lines" => {
+                    config.max_summary_blank_lines = Some(Self::count(key, value)?);
+                }
+                "doc-hidden" => {
+                    let policy =
+                        value.as_str().and_then(|name| HiddenPolicy::from_str(name, false).ok());
+                    config.doc_hidden = Some(policy.ok_or_else(|| {
+                        "`doc-hidden` should be \"skip-missing-docs\", \"skip-all\" or \"check-all\""
+                            .to_string()
+                    })?);
+                }
+                "cache-file" => {
+                    let file = value
+                        .as_str()
+                        .ok_or_else(|| "`cache-file` should be a string".to_string())?;
+                    config.cache_file = Some(root.join(file));
+                }
+                "rules-version" => {
+                    // Written as a string or an integer
+                    let version = value
+                        .as_str()
+                        .and_then(|version| version.parse().ok())
+                        .or_else(|| value.as_integer().and_then(|version| version.try_into().ok()));
+                    config.rules_version = Some(
+                        version
+                            .filter(|version| (1..=RULES_VERSION).contains(version))
+                            .ok_or_else(|| {
+                                format!(
+                                    "`rules-version` should be a version from 1 to {RULES_VERSION}"
+                                )
+                            })?,
+                    );
+                }
+                "fail-on" => {
+                    let fail_on = Self::strings(key, value)?
+                        .iter()
+                        .map(|name| Severity::from_name(name).ok_or(name))
+                        .collect::<Result<_, _>>()
+                        .map_err(|name| format!("unknown severity `{name}` in `fail-on`"))?;
+                    config.fail_on = Some(fail_on);
+                }
+                "severity" => {
+                    let rules = value
+                        .as_table()
+                        .ok_or_else(|| "`severity` should be a table".to_string())?;
+                    for (rule, severity) in rules {
+                        let severity = match severity.as_str() {
+                            Some("error") => Severity::Error,
+                            Some("warning") => Severity::Warning,
+                            _ => {
+                                return Err(format!(
+                                    "severity of `{rule}` should be \"error\" or \"warning\""
+                                ));
+                            }
+                        };
+                        config.severity.push((rule.clone(), severity));
+                    }
+                }
+                "per-file-ignores" => {
+                    let patterns = value
+                        .as_table()
+                        .ok_or_else(|| "`per-file-ignores` should be a table".to_string())?;
+                    for (pattern, rules) in patterns {
+                        let rules = Self::strings(pattern, rules)?
+                            .iter()
+                            .map(|rule| parse_rule_prefix(rule))
+                            .collect::<Result<_, _>>()?;
+                        config.per_file_ignores.push((pattern.clone(), rules));
+                    }
+                    PerFileIgnores::new(root, &config.per_file_ignores)
+                        .map_err(|e| format!("invalid pattern in `per-file-ignores`: {e}"))?;
+                }
+                _ => return Err(format!("unknown key `{key}`")),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Add the settings to analyzer options built from the command line.
+    pub fn apply(&self, options: &mut AnalyzerOptions) {
+        for rule in &self.enable {
+            if !options.checker.is_enabled(rule) {
+                options.checker.enabled_rules.push(rule.clone());
+            }
+        }
+        options.severities.extend(self.severity.iter().cloned());
+        options.checker.async_keywords.extend(self.async_keywords.iter().cloned());
+        options
+            .checker
+            .default_method_keywords
+            .extend(self.default_method_keywords.iter().cloned());
+        options.checker.panic_constructs.extend(self.panic_constructs.iter().cloned());
+        for target in &self.example_targets {
+            // Targets were validated when the configuration was parsed
+            options.checker.example_targets.add(target).unwrap_or_default();
+        }
+        options.checker.crate_docs_min_lines =
+            options.checker.crate_docs_min_lines.or(self.crate_docs_min_lines);
+        options.checker.max_summary_blank_lines =
+            options.checker.max_summary_blank_lines.or(self.max_summary_blank_lines);
+        options.checker.doc_hidden = options.checker.doc_hidden.or(self.doc_hidden);
+        options.rules_version = options.rules_version.or(self.rules_version);
+        // Patterns were validated when the configuration was parsed
+        options.per_file_ignores =
+            PerFileIgnores::new(&self.root, &self.per_file_ignores).unwrap_or_default();
+    }
+
+    /// Return the policy deciding which violations fail a check.
+    #[must_use]
+    pub fn fail_policy(&self) -> FailPolicy {
+        self.fail_on.clone().map_or_else(FailPolicy::default, |fail_on| FailPolicy { fail_on })
+    }
+
+    /// Return the file to cache results in.
+    #[must_use]
+    pub fn cache_file(&self) -> PathBuf {
+        self.cache_file.clone().unwrap_or_else(|| cache::default_path(&self.root))
+    }
+
+    /// Return the file collection options, adding extra ignore file names and exclude patterns.
+    ///
+    /// The patterns are relative to the current directory.
+    #[must_use]
+    pub fn collect_options(
+        &self,
+        ignore_filenames: &[String],
+        exclude: &[String],
+    ) -> CollectOptions {
+        CollectOptions {
+            ignore_filenames: self.ignore_files.iter().chain(ignore_filenames).cloned().collect(),
+            exclude: self.exclude.clone(),
+            exclude_root: self.root.clone(),
+            current_dir_exclude: exclude.to_vec(),
+        }
+    }
+
+    /// Return the `pep257` settings table of a Cargo manifest, if it has one.
+    fn manifest_settings(manifest: &toml::Table) -> Option<&toml::Table> {
+        let tool = manifest.get("tool").and_then(|tool| tool.get("pep257"));
+        let metadata = ["package", "workspace"]
+            .iter()
+            .find_map(|section| manifest.get(*section)?.get("metadata")?.get("pep257"));
+        tool.or(metadata)?.as_table()
+    }
+
+    /// Read and parse a TOML file.
+    fn read_table(path: &Path) -> Result<toml::Table, ConfigError> {
+        fs::read_to_string(path)?.parse().map_err(|e: toml::de::Error| ConfigError::Invalid {
+            path: path.to_path_buf(),
+            message: e.message().to_string(),
+        })
+    }
+
+    /// Parse a setting holding a number of lines.
+    fn count(key: &str, value: &toml::Value) -> Result<usize, String> {
+        value
+            .as_integer()
+            .and_then(|count| usize::try_from(count).ok())
+            .ok_or_else(|| format!("`{key}` should be a non-negative integer"))
+    }
+
+    /// Parse a setting holding a list of strings.
+    fn strings(key: &str, value: &toml::Value) -> Result<Vec<String>, String> {
+        value
+            .as_array()
+            .and_then(|values| {
+                values.iter().map(|value| value.as_str().map(str::to_string)).collect()
+            })
+            .ok_or_else(|| format!("`{key}` should be a list of strings"))
+    }
+}
+
+/// Unit tests for configuration files.
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// Test parsing every setting.
+    #[test]
+    fn test_from_table() {
+        let table: toml::Table = r#"
+enable = ["R409"]
+exclude = ["generated/"]
+ignore-files = [".docignore"]
+async-keywords = ["Cancellation"]
+default-method-keywords = ["override"]
+panic-constructs = ["unreachable!"]
+example-targets = ["trait"]
+crate-docs-min-lines = 20
+max-summary-blank-lines = 2
+doc-hidden = "skip-all"
+cache-file = "target/pep257-cache"
+rules-version = "1"
+fail-on = ["error"]
+
+[severity]
+D401 = "error"
+
+[per-file-ignores]
+"tests/**" = ["d103", "category:grammar"]
+"#
+        .parse()
+        .unwrap();
+        let config = Config::from_table(&table, Path::new("/project")).unwrap();
+        assert_eq!(config, Config {
+            root: PathBuf::from("/project"),
+            enable: vec!["R409".to_string()],
+            severity: vec![("D401".to_string(), Severity::Error)],
+            exclude: vec!["generated/".to_string()],
+            ignore_files: vec![".docignore".to_string()],
+            async_keywords: vec!["Cancellation".to_string()],
+            default_method_keywords: vec!["override".to_string()],
+            panic_constructs: vec!["unreachable!".to_string()],
+            example_targets: vec!["trait".to_string()],
+            crate_docs_min_lines: Some(20),
+            max_summary_blank_lines: Some(2),
+            doc_hidden: Some(HiddenPolicy::SkipAll),
+            cache_file: Some(PathBuf::from("/project/target/pep257-cache")),
+            rules_version: Some(1),
+            fail_on: Some(vec![Severity::Error]),
+            per_file_ignores: vec![("tests/**".to_string(), vec![
+                "D103".to_string(),
+                "category:grammar".to_string()
+            ])],
+        });
+        assert_eq!(config.fail_policy(), FailPolicy { fail_on: vec![Severity::Error] });
+        assert_eq!(Config::default().fail_policy(), FailPolicy::default());
+        assert_eq!(Config::default().cache_file(), PathBuf::from(".pep257-cache/results.json"));
+
+        let mut options = AnalyzerOptions::default();
+        options.checker.enabled_rules.push("R409".to_string());
+        config.apply(&mut options);
+        assert_eq!(options.checker.enabled_rules, vec!["R409"]);
+        assert_eq!(options.severities, vec![("D401".to_string(), Severity::Error)]);
+        assert_eq!(options.checker.async_keywords, vec!["Cancellation"]);
+        assert_eq!(options.checker.panic_constructs, vec!["unreachable!"]);
+        assert_eq!(options.checker.default_method_keywords, vec!["override"]);
+        assert_eq!(options.checker.crate_docs_min_lines, Some(20));
+        assert_eq!(options.checker.max_summary_blank_lines, Some(2));
+        assert_eq!(options.checker.doc_hidden, Some(HiddenPolicy::SkipAll));
+        assert_eq!(options.rules_version, Some(1));
+        assert!(!options.per_file_ignores.allows(Path::new("/project/tests/cli.rs"), "D103"));
+        assert!(options.per_file_ignores.allows(Path::new("/project/src/cli.rs"), "D103"));
+    }
+
+    /// Test that invalid settings are rejected.
+    #[test]
+    fn test_invalid_settings() {
+        let error =
+            |toml: &str| Config::from_table(&toml.parse().unwrap(), Path::new(".")).unwrap_err();
+        assert_eq!(error("warnings = true"), "unknown key `warnings`");
+        assert!(error(r#"enable = ["D400"]"#).starts_with("`D400` is not an opt-in rule"));
+        assert_eq!(error(r#"exclude = "target""#), "`exclude` should be a list of strings");
+        assert_eq!(
+            error("max-summary-blank-lines = -1"),
+            "`max-summary-blank-lines` should be a non-negative integer"
+        );
+        assert_eq!(error(r#"fail-on = ["fatal"]"#), "unknown severity `fatal` in `fail-on`");
+        assert_eq!(
+            error(r#"doc-hidden = "skip""#),
+            r#"`doc-hidden` should be "skip-missing-docs", "skip-all" or "check-all""#
+        );
+        assert!(
+            error(r#"example-targets = ["closure"]"#)
+                .starts_with("unknown target `closure`, expected one of: function,")
+        );
+        assert_eq!(error("rules-version = 0"), "`rules-version` should be a version from 1 to 2");
+        assert_eq!(
+            error("[severity]\nD400 = \"fatal\""),
+            "severity of `D400` should be \"error\" or \"warning\""
+        );
+        assert_eq!(
+            error("[per-file-ignores]\n\"tests/**\" = \"D103\""),
+            "`tests/**` should be a list of strings"
+        );
+        assert!(
+            error("[per-file-ignores]\n\"tests/**\" = [\"D-1\"]")
+                .starts_with("`D-1` is not a rule code")
+        );
+        assert!(
+            error("[per-file-ignores]\n\"[\" = [\"D103\"]")
+                .starts_with("invalid pattern in `per-file-ignores`")
+        );
+    }
+
+    /// Test finding the nearest configuration, in either kind of file.
+    #[test]
+    fn test_discover() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("member/src")).unwrap();
+        fs::write(root.join("pep257.toml"), "enable = [\"R409\"]\n").unwrap();
+        fs::write(root.join("member/Cargo.toml"), "[package]\nname = \"member\"\n").unwrap();
+
+        // Manifests without settings are skipped
+        let config = Config::discover(&root.join("member/src")).unwrap().unwrap();
+        assert_eq!(
+            (config.root.as_path(), config.enable.as_slice()),
+            (root.as_path(), ["R409".to_string()].as_slice())
+        );
+
+        fs::write(
+            root.join("member/Cargo.toml"),
+            "[package]\nname = \"member\"\n\n[tool.pep257]\nexclude = [\"src/gen.rs\"]\n",
+        )
+        .unwrap();
+        let config = Config::discover(&root.join("member/src")).unwrap().unwrap();
+        assert_eq!(config.root, root.join("member"));
+        assert_eq!(config.exclude, vec!["src/gen.rs"]);
+    }
+}
@@ -0,0 +1,331 @@
+//! Crate-level knowledge available to the checks.
+//!
+//! Most rules only look at a single docstring, but some depend on where the item
+//! lives: the crate it belongs to, the crate's edition, or the other items of its
+//! module. An [`AnalysisContext`] carries this knowledge for a source file.
+
+use std::{
+    env, fmt, fs, io,
+    ops::Range,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+
+use clap::ValueEnum;
+
+use crate::{
+    pep257::{Docstring, DocstringTarget},
+    position::SourcePosition,
+};
+
+/// Name of the Cargo manifest file.
+const MANIFEST_FILENAME: &str = "Cargo.toml";
+
+/// Rust edition of a crate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub enum Edition {
+    /// Rust 2015, the default for manifests without an `edition` key
+    #[default]
+    #[value(name = "2015")]
+    E2015,
+    /// Rust 2018
+    #[value(name = "2018")]
+    E2018,
+    /// Rust 2021
+    #[value(name = "2021")]
+    E2021,
+    /// Rust 2024
+    #[value(name = "2024")]
+    E2024,
+}
+
+/// Parse an edition from its year.
+impl FromStr for Edition {
+    type Err = String;
+
+    /// Parse an edition such as `2021`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "2015" => Ok(Self::E2015),
+            "2018" => Ok(Self::E2018),
+            "2021" => Ok(Self::E2021),
+            "2024" => Ok(Self::E2024),
+            _ => Err(format!("unknown edition `{s}`")),
+        }
+    }
+}
+
+/// Display an edition as its year.
+impl fmt::Display for Edition {
+    /// Format the edition's year.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let year = match self {
+            Self::E2015 => "2015",
+            Self::E2018 => "2018",
+            Self::E2021 => "2021",
+            Self::E2024 => "2024",
+        };
+        write!(f, "{year}")
+    }
+}
+
+/// Package metadata read from a `Cargo.toml` manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateInfo {
+    /// Name of the crate as used in paths, such as `pep257_rs` for `pep257-rs`.
+    pub name: String,
+    /// Edition of the crate.
+    pub edition: Edition,
+}
+
+/// Implementation of crate metadata methods.
+impl CrateInfo {
+    /// Find the package manifest of a source file and read its metadata.
+    ///
+    /// The nearest `Cargo.toml` with a `[package]` table above the file is used.
+    /// Returns `None` if there is none, or if it can't be read.
+    #[must_use]
+    pub fn for_file(path: &Path) -> Option<Self> {
+        let path = path.canonicalize().ok()?;
+        path.ancestors().skip(1).find_map(|dir| {
+            let manifest = Self::read_manifest(&dir.join(MANIFEST_FILENAME))?;
+            manifest.contains_key("package").then(|| Self::from_manifest(&manifest, dir))?
+        })
+    }
+
+    /// Read the metadata of a parsed package manifest in a directory.
+    ///
+    /// Keys inherited with `workspace = true` are looked up in the workspace
+    /// manifest above the directory.
+    fn from_manifest(manifest: &toml::Table, dir: &Path) -> Option<Self> {
+        let package = manifest.get("package")?.as_table()?;
+        let name = manifest
+            .get("lib")
+            .and_then(|lib| lib.get("name"))
+            .or_else(|| package.get("name"))?
+            .as_str()?
+            .replace('-', "_");
+
+        let edition = match package.get("edition") {
+            None => Some(Edition::default()),
+            Some(toml::Value::String(edition)) => edition.parse().ok(),
+            Some(_) => Self::workspace_edition(dir),
+        };
+        Some(Self { name, edition: edition.unwrap_or_default() })
+    }
+
+    /// Return the edition declared by the workspace manifest above a directory.
+    fn workspace_edition(dir: &Path) -> Option<Edition> {
+        dir.ancestors().find_map(|dir| {
+            let manifest = Self::read_manifest(&dir.join(MANIFEST_FILENAME))?;
+            let workspace = manifest.get("workspace")?;
+            workspace.get("package")?.get("edition")?.as_str()?.parse().ok()
+        })
+    }
+
+    /// Read and parse a manifest file, if it exists.
+    fn read_manifest(path: &Path) -> Option<toml::Table> {
+        fs::read_to_string(path).ok()?.parse().ok()
+    }
+}
+
+/// Find the root directory of the Cargo workspace of a manifest with `cargo metadata`.
+///
+/// Without a manifest, the one in `CARGO_MANIFEST_DIR` is used if that is set, and
+/// otherwise cargo looks for the manifest of the current directory. The cargo that
+/// ran this process is used if there is one.
+pub fn workspace_root(manifest_path: Option<&Path>) -> io::Result<PathBuf> {
+    let mut command = Command::new(env::var_os("CARGO").unwrap_or_else(|| "cargo".into()));
+    command.args(["metadata", "--no-deps", "--format-version", "1"]);
+    let manifest_path = manifest_path.map(Path::to_path_buf).or_else(|| {
+        env::var_os("CARGO_MANIFEST_DIR").map(|dir| PathBuf::from(dir).join(MANIFEST_FILENAME))
+    });
+    if let Some(manifest_path) = manifest_path {
+        command.arg("--manifest-path").arg(manifest_path);
+    }
+
+    let output = command.output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!("`cargo metadata` failed: {}", stderr.trim())));
+    }
+    let metadata: serde_json::Value =
+        serde_json::from_slice(&output.stdout).map_err(io::Error::other)?;
+    metadata["workspace_root"]
+        .as_str()
+        .map(PathBuf::from)
+        .ok_or_else(|| io::Error::other("`cargo metadata` reported no workspace root"))
+}
+
+/// An item of a source file, as indexed while parsing the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FileItem {
+    /// Path of the item within its file, such as `Parser::new`, or empty for the file's own module.
+    pub path: String,
+    /// Kind of item.
+    pub kind: DocstringTarget,
+    /// Source positions where the item starts and ends, excluding its outer docs.
+    pub span: Range<SourcePosition>,
+    /// Whether the item is part of the public API.
+    pub is_public: bool,
+    /// Whether the item has docs.
+    pub is_documented: bool,
+}
+
+/// Implementation of file item methods.
+impl FileItem {
+    /// Index the item of a docstring, if its span is known.
+    pub(crate) fn of_docstring(docstring: &Docstring) -> Option<Self> {
+        Some(Self {
+            path: docstring.item_path.clone(),
+            kind: docstring.target_type,
+            span: docstring.attributes.span.clone()?,
+            is_public: docstring.is_public,
+            is_documented: !docstring.content.trim().is_empty(),
+        })
+    }
+
+    /// Return the name of the item, the last segment of its path.
+    pub(crate) fn name(&self) -> &str {
+        self.path.rsplit("::").next().unwrap_or_default()
+    }
+}
+
+/// Knowledge about a source file that the checks may use beyond its docstrings.
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisContext {
+    /// Name of the crate the file belongs to, as used in paths.
+    pub crate_name: Option<String>,
+    /// Edition of the crate the file belongs to.
+    pub edition: Option<Edition>,
+    /// Module path of the file, such as `crate::parser`.
+    pub module_path: String,
+    /// Items of the file in source order, such as `Parser` and `Parser::new`.
+    pub(crate) items: Vec<FileItem>,
+}
+
+/// Implementation of analysis context methods.
+impl AnalysisContext {
+    /// Create the context of a file with the given module path.
+    #[must_use]
+    pub fn new(module_path: &str, crate_info: Option<&CrateInfo>) -> Self {
+        Self {
+            crate_name: crate_info.map(|info| info.name.clone()),
+            edition: crate_info.map(|info| info.edition),
+            module_path: module_path.to_string(),
+            items: Vec::new(),
+        }
+    }
+
+    /// Check whether the file is the root of its crate.
+    #[must_use]
+    pub fn is_crate_root(&self) -> bool {
+        self.module_path == "crate"
+    }
+
+    /// Return the names of the other items in the same module or impl as an item.
+    ///
+    /// Impl blocks are left out, since they are named after their type.
+    pub fn siblings<'a>(&'a self, item_path: &'a str) -> impl Iterator<Item = &'a str> {
+        let parent = Self::parent(item_path);
+        self.items
+            .iter()
+            .filter(move |item| {
+                !matches!(item.kind, DocstringTarget::ImplBlock | DocstringTarget::ExternBlock)
+                    && !item.path.is_empty()
+                    && item.path != item_path
+                    && Self::parent(&item.path) == parent
+            })
+            .map(FileItem::name)
+    }
+
+    /// Return the parent of an in-file item path, which is empty for top-level items.
+    fn parent(item_path: &str) -> &str {
+        item_path.rsplit_once("::").map_or("", |(parent, _)| parent)
+    }
+}
+
+/// Unit tests for the analysis context.
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::parser::RustParser;
+
+    /// Test reading crate names and editions, including inherited editions.
+    #[test]
+    fn test_crate_info() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"*\"]\n\n[workspace.package]\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        let write_crate = |name: &str, manifest: &str| {
+            fs::create_dir_all(root.join(name).join("src")).unwrap();
+            fs::write(root.join(name).join("Cargo.toml"), manifest).unwrap();
+            fs::write(root.join(name).join("src/lib.rs"), "").unwrap();
+            root.join(name).join("src/lib.rs")
+        };
+
+        let file = write_crate("a", "[package]\nname = \"my-crate\"\nedition = \"2024\"\n");
+        assert_eq!(
+            CrateInfo::for_file(&file),
+            Some(CrateInfo { name: "my_crate".to_string(), edition: Edition::E2024 })
+        );
+
+        let file = write_crate("b", "[package]\nname = \"b\"\nedition.workspace = true\n");
+        assert_eq!(CrateInfo::for_file(&file).unwrap().edition, Edition::E2021);
+
+        let file = write_crate("c", "[package]\nname = \"c\"\n\n[lib]\nname = \"renamed\"\n");
+        assert_eq!(
+            CrateInfo::for_file(&file),
+            Some(CrateInfo { name: "renamed".to_string(), edition: Edition::E2015 })
+        );
+
+        assert_eq!(CrateInfo::for_file(&root.join("Cargo.toml")), None);
+    }
+
+    /// Test finding the workspace root from the manifest of a member crate.
+    #[test]
+    fn test_workspace_root() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"member\"]\n").unwrap();
+        fs::create_dir_all(root.join("member/src")).unwrap();
+        fs::write(root.join("member/Cargo.toml"), "[package]\nname = \"member\"\n").unwrap();
+        fs::write(root.join("member/src/lib.rs"), "").unwrap();
+
+        assert_eq!(workspace_root(Some(&root.join("member/Cargo.toml"))).unwrap(), root);
+        assert!(workspace_root(Some(&root.join("missing/Cargo.toml"))).is_err());
+    }
+
+    /// Test finding the sibling items of an item.
+    #[test]
+    fn test_siblings() {
+        let source = r"
+pub struct Parser;
+
+impl Parser {
+    fn new() {}
+    fn parse() {}
+}
+
+fn parse() {}
+
+mod tests {
+    fn test_parse() {}
+}
+";
+        let context = AnalysisContext {
+            items: RustParser::new().unwrap().parse(source).unwrap().items,
+            ..AnalysisContext::new("crate", None)
+        };
+        assert_eq!(context.siblings("Parser::new").collect::<Vec<_>>(), vec!["parse"]);
+        assert_eq!(context.siblings("parse").collect::<Vec<_>>(), vec!["Parser", "tests"]);
+        assert!(context.is_crate_root());
+    }
+}
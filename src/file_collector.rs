@@ -1,6 +1,42 @@
 //! File collection module for finding Rust source files.
 
-use std::path::PathBuf;
+use std::{
+    env,
+    path::{self, Component, Path, PathBuf},
+};
+
+use clap::ValueEnum;
+
+/// Name of the gitignore-syntax file always respected when collecting files.
+pub const DEFAULT_IGNORE_FILENAME: &str = ".pep257ignore";
+
+/// Options controlling which files are collected from a directory.
+#[derive(Debug, Clone, Default)]
+pub struct CollectOptions {
+    /// Names of extra gitignore-syntax files to respect.
+    ///
+    /// These are respected besides `.gitignore`, `.ignore` and `.pep257ignore`.
+    pub ignore_filenames: Vec<String>,
+    /// Gitignore-syntax patterns of paths to skip, relative to `exclude_root`.
+    pub exclude: Vec<String>,
+    /// Directory the exclude patterns are relative to.
+    pub exclude_root: PathBuf,
+    /// Gitignore-syntax patterns of paths to skip, relative to the current directory.
+    ///
+    /// These are the patterns given with `--exclude`.
+    pub current_dir_exclude: Vec<String>,
+}
+
+/// Style of the file paths printed in reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PathStyle {
+    /// Relative to the current directory
+    Relative,
+    /// Absolute
+    Absolute,
+    /// Relative to the repository root, the nearest directory containing `.git`
+    FromRoot,
+}
 
 /// Check if a directory should be skipped based on target directory rules.
 /// Returns true if the directory should be skipped.
@@ -37,13 +73,58 @@ pub(crate) fn should_skip_target_dir(path: &std::path::Path) -> bool {
 /// This respects .gitignore files and applies custom target directory filtering.
 pub fn collect_rust_files_recursive(
     dir: &PathBuf,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    collect_files_recursive(dir, &["rs"])
+}
+
+/// Collect files with any of the given extensions in a directory recursively.
+/// This respects .gitignore files and applies custom target directory filtering.
+pub fn collect_files_recursive(
+    dir: &PathBuf,
+    extensions: &[&str],
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    collect_files_with_options(dir, extensions, &CollectOptions::default())
+}
+
+/// Collect files with any of the given extensions in a directory recursively.
+///
+/// Besides `.gitignore`, `.ignore` and `.pep257ignore` files, gitignore-syntax files
+/// with the names in the options are respected. Patterns in these custom files take
+/// precedence over `.ignore` files, which take precedence over `.gitignore` files,
+/// so that e.g. `!generated.rs` in a `.pep257ignore` file checks a file that git
+/// ignores. Among the custom names, later ones take precedence. Paths matching the
+/// exclude patterns of the options are always skipped.
+pub fn collect_files_with_options(
+    dir: &PathBuf,
+    extensions: &[&str],
+    options: &CollectOptions,
 ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
     let mut files = Vec::new();
 
+    // Exclude patterns are matched against absolute paths, whatever form `dir` has
+    let mut excludes = Vec::new();
+    for (root, patterns) in [
+        (options.exclude_root.as_path(), &options.exclude),
+        (Path::new("."), &options.current_dir_exclude),
+    ] {
+        if !patterns.is_empty() {
+            let mut builder = ignore::gitignore::GitignoreBuilder::new(path::absolute(root)?);
+            for pattern in patterns {
+                builder.add_line(None, pattern)?;
+            }
+            excludes.push(builder.build()?);
+        }
+    }
+
     // Use ignore::WalkBuilder which respects .gitignore, .ignore files, etc.
-    let walker = ignore::WalkBuilder::new(dir)
-        .standard_filters(true)  // Enable standard ignore filters (.gitignore, etc.)
-        .filter_entry(|entry| {
+    let mut builder = ignore::WalkBuilder::new(dir);
+    builder.standard_filters(true); // Enable standard ignore filters (.gitignore, etc.)
+    builder.add_custom_ignore_filename(DEFAULT_IGNORE_FILENAME);
+    for filename in &options.ignore_filenames {
+        builder.add_custom_ignore_filename(filename);
+    }
+    let walker = builder
+        .filter_entry(move |entry| {
             let path = entry.path();
 
             // Apply custom target directory filtering
@@ -51,7 +132,12 @@ pub fn collect_rust_files_recursive(
                 return false;
             }
 
-            true
+            excludes.is_empty()
+                || path::absolute(path).is_ok_and(|absolute| {
+                    excludes
+                        .iter()
+                        .all(|exclude| !exclude.matched(&absolute, path.is_dir()).is_ignore())
+                })
         })
         .build();
 
@@ -59,10 +145,112 @@ pub fn collect_rust_files_recursive(
         let entry = result?;
         let path = entry.path();
 
-        if path.is_file() && path.extension().is_some_and(|ext| ext == "rs") {
+        if path.is_file()
+            && path.extension().is_some_and(|ext| extensions.iter().any(|&wanted| ext == wanted))
+        {
             files.push(path.to_path_buf());
         }
     }
 
     Ok(files)
 }
+
+/// Characters that make a path argument a glob pattern.
+const GLOB_CHARS: &[char] = &['*', '?', '[', '{'];
+
+/// Check whether a path argument is a glob pattern, such as `src/**/*.rs`.
+#[must_use]
+pub fn is_glob(path: &Path) -> bool {
+    path.to_str().is_some_and(|path| path.contains(GLOB_CHARS))
+}
+
+/// Return the directory named by the components of a glob pattern before its first wildcard.
+///
+/// The current directory is returned if there are no such components.
+#[must_use]
+pub fn glob_base(pattern: &Path) -> PathBuf {
+    split_glob(pattern).0
+}
+
+/// Split a glob pattern into its base directory and the pattern of the paths relative to it.
+fn split_glob(pattern: &Path) -> (PathBuf, PathBuf) {
+    let literal = pattern
+        .components()
+        .take_while(|component| !component.as_os_str().to_string_lossy().contains(GLOB_CHARS))
+        .count();
+    let base: PathBuf = pattern.components().take(literal).collect();
+    let relative = pattern.components().skip(literal).collect();
+    if base.as_os_str().is_empty() { (PathBuf::from("."), relative) } else { (base, relative) }
+}
+
+/// Collect files with any of the given extensions matching a glob pattern.
+///
+/// The pattern is expanded here rather than by the shell, so it works the same on
+/// every platform: `*` and `?` match within a path component, `**` matches any
+/// number of components, and `[...]` and `{a,b}` match one of several characters or
+/// alternatives. The directory before the first wildcard is walked like any other,
+/// respecting ignore files and the options.
+pub fn collect_glob_files(
+    pattern: &Path,
+    extensions: &[&str],
+    options: &CollectOptions,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let (base, relative) = split_glob(pattern);
+    let matcher = globset::GlobBuilder::new(&relative.to_string_lossy())
+        .literal_separator(true)
+        .build()?
+        .compile_matcher();
+
+    let files = collect_files_with_options(&base, extensions, options)?;
+    Ok(files
+        .into_iter()
+        .filter_map(|file| {
+            let relative =
+                file.strip_prefix(&base).ok().filter(|file| matcher.is_match(file))?.to_path_buf();
+            // Files matching a pattern without a base directory are named like the pattern
+            Some(if pattern.starts_with(&base) { file } else { relative })
+        })
+        .collect())
+}
+
+/// Format a file path in the given style.
+///
+/// Paths that can't be resolved, and paths outside the repository for
+/// [`PathStyle::FromRoot`], are returned as given.
+#[must_use]
+pub fn styled_path(path: &Path, style: PathStyle) -> PathBuf {
+    let Ok(absolute) = path.canonicalize() else {
+        return path.to_path_buf();
+    };
+    match style {
+        PathStyle::Absolute => absolute,
+        PathStyle::Relative => env::current_dir()
+            .and_then(|dir| dir.canonicalize())
+            .map_or_else(|_| path.to_path_buf(), |dir| relative_path(&absolute, &dir)),
+        PathStyle::FromRoot => absolute
+            .ancestors()
+            .skip(1)
+            .find(|dir| dir.join(".git").exists())
+            .map_or_else(|| path.to_path_buf(), |root| relative_path(&absolute, root)),
+    }
+}
+
+/// Return a path relative to a base directory, going up with `..` where needed.
+///
+/// Both paths should be absolute, or both relative to the same directory.
+#[must_use]
+pub fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let path_components: Vec<Component<'_>> = path.components().collect();
+    let base_components: Vec<Component<'_>> = base.components().collect();
+    let common = path_components
+        .iter()
+        .zip(&base_components)
+        .take_while(|(path_component, base_component)| path_component == base_component)
+        .count();
+
+    base_components[common..]
+        .iter()
+        .map(|_| Component::ParentDir)
+        .chain(path_components[common..].iter().copied())
+        .collect()
+}
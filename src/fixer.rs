@@ -0,0 +1,899 @@
+//! Automatic fixes for mechanically fixable violations.
+//!
+//! Fixes are edits of the original source text, so everything outside the fixed
+//! spans, including the formatting of the code around the docs, is left untouched.
+//! Only docs written as comments are fixed: `#[doc = "..."]` attributes may hold
+//! escapes that make their content differ from their source text. Fixes that would
+//! make a line wider than the nearest `rustfmt.toml` allows are left out, so the
+//! next `cargo fmt` doesn't rewrap or flag the fixed lines.
+
+use std::{collections::BTreeMap, fmt::Write as _, fs, ops::Range, path::Path};
+
+use crate::{
+    pep257::{
+        BulletMarker, COMMON_RUST_TYPES, CheckerOptions, Docstring, DocstringTarget, Pep257Checker,
+        TrailingComment, Violation,
+    },
+    position::{LineIndex, SourcePosition},
+    rules::find_rule,
+};
+
+/// Rules whose violations the fixer can resolve.
+pub const FIXABLE_RULES: &[&str] = &[
+    "D201", "D202", "D210", "D400", "D403", "R108", "R201", "R202", "R204", "R402", "R405", "R406",
+    "R408",
+];
+
+/// Source code with fixes applied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixedSource {
+    /// The fixed source code.
+    pub source: String,
+    /// Number of violations fixed, per rule.
+    pub fixes: BTreeMap<String, usize>,
+}
+
+/// Implementation of fixed source methods.
+impl FixedSource {
+    /// Return the total number of violations fixed.
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.fixes.values().sum()
+    }
+}
+
+/// Names of rustfmt's configuration files, in the order rustfmt looks for them.
+const RUSTFMT_FILENAMES: &[&str] = &[".rustfmt.toml", "rustfmt.toml"];
+
+/// Line width settings of rustfmt that fixed lines must stay within.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RustfmtSettings {
+    /// Maximum width of each line.
+    pub max_width: usize,
+    /// Maximum width of comments, if `wrap_comments` is set.
+    pub comment_width: usize,
+    /// Whether rustfmt rewraps comments wider than `comment_width`.
+    pub wrap_comments: bool,
+}
+
+/// Use rustfmt's defaults.
+impl Default for RustfmtSettings {
+    /// Return the settings rustfmt uses without a configuration file.
+    fn default() -> Self {
+        Self { max_width: 100, comment_width: 80, wrap_comments: false }
+    }
+}
+
+/// Implementation of rustfmt settings methods.
+impl RustfmtSettings {
+    /// Read the settings of the rustfmt configuration file nearest to a source file.
+    ///
+    /// Like rustfmt, the first directory above the file with a configuration file
+    /// decides, and settings it doesn't set keep their defaults. Returns the defaults
+    /// if there is no configuration file or it can't be read.
+    #[must_use]
+    pub fn for_file(path: &Path) -> Self {
+        let Ok(path) = path.canonicalize() else {
+            return Self::default();
+        };
+        let config = path.ancestors().skip(1).find_map(|dir| {
+            RUSTFMT_FILENAMES.iter().map(|name| dir.join(name)).find(|path| path.is_file())
+        });
+        config.map_or_else(Self::default, |config| Self::read(&config))
+    }
+
+    /// Read the settings of a rustfmt configuration file.
+    fn read(path: &Path) -> Self {
+        let defaults = Self::default();
+        let Some(table) =
+            fs::read_to_string(path).ok().and_then(|text| text.parse::<toml::Table>().ok())
+        else {
+            return defaults;
+        };
+        let width = |name: &str, default: usize| {
+            table
+                .get(name)
+                .and_then(toml::Value::as_integer)
+                .and_then(|width| usize::try_from(width).ok())
+                .unwrap_or(default)
+        };
+        Self {
+            max_width: width("max_width", defaults.max_width),
+            comment_width: width("comment_width", defaults.comment_width),
+            wrap_comments: table
+                .get("wrap_comments")
+                .and_then(toml::Value::as_bool)
+                .unwrap_or(defaults.wrap_comments),
+        }
+    }
+
+    /// Return the maximum width of a line with a doc comment.
+    #[must_use]
+    pub fn doc_width(&self) -> usize {
+        if self.wrap_comments { self.comment_width.min(self.max_width) } else { self.max_width }
+    }
+}
+
+/// Replacement of a byte range of the source that fixes one violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Fix {
+    /// Rule whose violation the fix resolves.
+    pub rule: &'static str,
+    /// Byte range of the source to replace.
+    pub range: Range<usize>,
+    /// Text to replace the range with.
+    pub replacement: String,
+}
+
+/// Return the fixes for the violations found in a docstring.
+pub(crate) fn docstring_fixes(
+    docstring: &Docstring,
+    violations: &[Violation],
+    index: &LineIndex<'_>,
+    options: &CheckerOptions,
+) -> Vec<Fix> {
+    let raw = docstring.raw_content.trim_start();
+    let is_comment = ["///", "//!", "/**", "/*!"].iter().any(|marker| raw.starts_with(marker));
+    let is_line_comment = raw.starts_with("///") || raw.starts_with("//!");
+    if !is_comment || docstring.line_positions.is_empty() {
+        return Vec::new();
+    }
+
+    let has = |rule: &str| violations.iter().any(|v| v.rule == rule);
+    let mut fixes = Vec::new();
+    if has("D201") && is_line_comment {
+        fixes.extend(blank_line_fixes(docstring, index, "D201", false));
+    }
+    if has("D202") && is_line_comment {
+        fixes.extend(blank_line_fixes(docstring, index, "D202", true));
+    }
+    if is_line_comment {
+        fixes.extend(violations.iter().filter_map(|v| match v.rule.as_str() {
+            "D210" => trailing_whitespace_fix(index, v.line),
+            "R201" => indent_fix(index, v.line),
+            _ => None,
+        }));
+    }
+    if let Some(violation) = violations.iter().find(|v| v.rule == "R202")
+        && is_line_comment
+    {
+        fixes.extend(summary_separation_fixes(docstring, index, violation.line));
+    }
+    if has("R204") && is_line_comment {
+        fixes.extend(summary_reflow_fix(docstring, index));
+    }
+    if has("D400") {
+        fixes.extend(summary_period_fix(docstring, index));
+    }
+    if has("D403") {
+        fixes.extend(capitalization_fix(docstring, index));
+    }
+    if has("R402") {
+        fixes.extend(inline_code_fixes(docstring, index));
+    }
+    if has("R405") {
+        fixes.extend(empty_code_fixes(docstring, index, is_line_comment));
+    }
+    fixes.extend(
+        violations.iter().filter(|v| v.rule == "R406").filter_map(|v| autolink_fix(index, v)),
+    );
+    if has("R408") {
+        fixes.extend(bullet_marker_fixes(docstring, index, options));
+    }
+    fixes
+}
+
+/// Return a fix inserting a template of the docs of an undocumented item.
+///
+/// The template holds a placeholder summary and the sections its signature calls for.
+/// Items that don't start their line are left alone, as are items whose missing docs aren't
+/// among `violations`.
+pub(crate) fn doc_template_fix(
+    docstring: &Docstring,
+    violations: &[Violation],
+    index: &LineIndex<'_>,
+) -> Option<Fix> {
+    if !docstring.content.trim().is_empty() {
+        return None;
+    }
+    let rule = violations.first().and_then(|violation| find_rule(&violation.rule))?.code;
+
+    let attributes = &docstring.attributes;
+    let mut paragraphs = Vec::new();
+    let (marker, start) = if docstring.target_type == DocstringTarget::Package {
+        paragraphs.push("TODO: Summarize this module.".to_string());
+        ("//!", SourcePosition { line: 1, column: 1 })
+    } else {
+        let name = docstring.item_path.rsplit("::").next().unwrap_or_default();
+        paragraphs.push(if name.is_empty() {
+            format!("TODO: Summarize this {}.", docstring.target_type)
+        } else {
+            format!("TODO: Summarize `{name}`.")
+        });
+        if docstring.target_type.is_function() {
+            if !attributes.parameters.is_empty() {
+                let arguments: Vec<String> = attributes
+                    .parameters
+                    .iter()
+                    .map(|parameter| format!("* `{parameter}` - TODO"))
+                    .collect();
+                paragraphs.push(format!("# Arguments\n\n{}", arguments.join("\n")));
+            }
+            if attributes.returns_result {
+                paragraphs.push("# Errors\n\nTODO".to_string());
+            }
+            if attributes.modifiers.is_unsafe {
+                paragraphs.push("# Safety\n\nTODO".to_string());
+            }
+        }
+        let start = attributes
+            .attributes_start
+            .unwrap_or(SourcePosition { line: docstring.line, column: docstring.column });
+        ("///", start)
+    };
+
+    let line = index.line_text(start.line);
+    let indent = line.get(..start.column - 1).filter(|indent| indent.trim().is_empty())?;
+    let newline = if index.line_range(start.line).len() > line.len() + 1 { "\r\n" } else { "\n" };
+    let mut replacement = String::new();
+    for text in paragraphs.join("\n\n").split('\n') {
+        let separator = if text.is_empty() { "" } else { " " };
+        let _ = write!(replacement, "{indent}{marker}{separator}{text}{newline}");
+    }
+    if marker == "//!" {
+        replacement.push_str(newline);
+    }
+
+    let offset = index.line_range(start.line).start;
+    Some(Fix { rule, range: offset..offset, replacement })
+}
+
+/// Return a fix moving a comment that trails a field or variant into a `///` line above it.
+///
+/// The new line takes the member's indentation. Members spanning several lines are left
+/// alone.
+pub(crate) fn trailing_comment_fix(
+    comment: &TrailingComment,
+    index: &LineIndex<'_>,
+) -> Option<Fix> {
+    if comment.text.is_empty() || comment.position.line != comment.member_line {
+        return None;
+    }
+    let line = index.line_text(comment.member_line);
+    let code = line.get(..comment.position.column - 1)?.trim_end();
+    let indent = &code[..code.len() - code.trim_start().len()];
+    let newline =
+        if index.line_range(comment.member_line).len() > line.len() + 1 { "\r\n" } else { "\n" };
+
+    let start = index.line_range(comment.member_line).start;
+    Some(Fix {
+        rule: "R108",
+        range: start..start + line.len(),
+        replacement: format!("{indent}/// {}{newline}{code}", comment.text),
+    })
+}
+
+/// Return whether a fix keeps its lines within a width, or doesn't widen them.
+///
+/// Widths are counted in characters. A fix spanning several lines is compared with
+/// the widest of the lines it replaces.
+pub(crate) fn fits_width(fix: &Fix, index: &LineIndex<'_>, width: usize) -> bool {
+    let (first, last) = (index.line_at(fix.range.start), index.line_at(fix.range.end));
+    let first_text = index.line_text(first);
+    let last_text = index.line_text(last);
+    let before = first_text.get(..fix.range.start - index.line_range(first).start);
+    let after = last_text.get(fix.range.end - index.line_range(last).start..);
+
+    let fixed =
+        format!("{}{}{}", before.unwrap_or(first_text), fix.replacement, after.unwrap_or(""));
+    let widest = |lines: &mut dyn Iterator<Item = &str>| {
+        lines.map(|line| line.chars().count()).max().unwrap_or_default()
+    };
+    let replaced = widest(&mut (first..=last).map(|line| index.line_text(line)));
+    widest(&mut fixed.split('\n')) <= width.max(replaced)
+}
+
+/// Apply fixes to source code, skipping fixes that overlap one applied before.
+pub(crate) fn apply_fixes(source: &str, mut fixes: Vec<Fix>) -> FixedSource {
+    fixes.sort_by_key(|fix| (fix.range.start, fix.range.end));
+
+    let mut fixed =
+        FixedSource { source: String::with_capacity(source.len()), ..FixedSource::default() };
+    let mut copied = 0;
+    for fix in fixes {
+        if fix.range.start < copied {
+            continue;
+        }
+        fixed.source.push_str(&source[copied..fix.range.start]);
+        fixed.source.push_str(&fix.replacement);
+        copied = fix.range.end;
+        *fixed.fixes.entry(fix.rule.to_string()).or_default() += 1;
+    }
+    fixed.source.push_str(&source[copied..]);
+    fixed
+}
+
+/// Show the lines changed by a fix, prefixed with `-` and `+`, between the unchanged lines.
+///
+/// The unchanged lines before and after them are prefixed with a space.
+#[must_use]
+pub fn line_diff(before: &str, after: &str) -> String {
+    let before: Vec<&str> = before.lines().collect();
+    let after: Vec<&str> = after.lines().collect();
+    let prefix = before.iter().zip(&after).take_while(|(a, b)| a == b).count();
+    let suffix = before[prefix..]
+        .iter()
+        .rev()
+        .zip(after[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut diff = String::new();
+    let mut push = |marker: char, lines: &[&str]| {
+        for line in lines {
+            diff.push(marker);
+            diff.push_str(line);
+            diff.push('\n');
+        }
+    };
+    push(' ', &before[..prefix]);
+    push('-', &before[prefix..before.len() - suffix]);
+    push('+', &after[prefix..after.len() - suffix]);
+    push(' ', &before[before.len() - suffix..]);
+    diff
+}
+
+/// Delete the blank doc comment lines at the start or the end of a docstring.
+fn blank_line_fixes(
+    docstring: &Docstring,
+    index: &LineIndex<'_>,
+    rule: &'static str,
+    from_end: bool,
+) -> Vec<Fix> {
+    let lines: Vec<&str> = docstring.content.split('\n').collect();
+    let blank = |&line_index: &usize| lines[line_index].trim().is_empty();
+    let line_indexes: Vec<usize> = if from_end {
+        (0..lines.len()).rev().take_while(blank).collect()
+    } else {
+        (0..lines.len()).take_while(blank).collect()
+    };
+    blank_line_deletions(docstring, index, rule, line_indexes)
+}
+
+/// Delete the blank doc comment lines over the limit between the summary and the description.
+///
+/// Lines are deleted from the line the R202 violation was reported on.
+fn summary_separation_fixes(docstring: &Docstring, index: &LineIndex<'_>, line: usize) -> Vec<Fix> {
+    let lines: Vec<&str> = docstring.content.split('\n').collect();
+    let Some(start) = docstring.line_positions.iter().position(|position| position.line == line)
+    else {
+        return Vec::new();
+    };
+    let line_indexes =
+        (start..lines.len()).take_while(|&line_index| lines[line_index].trim().is_empty());
+    blank_line_deletions(docstring, index, "R202", line_indexes.collect())
+}
+
+/// Delete the doc comment lines of a docstring with the given content line indexes.
+///
+/// Only lines holding nothing but the comment marker are deleted.
+fn blank_line_deletions(
+    docstring: &Docstring,
+    index: &LineIndex<'_>,
+    rule: &'static str,
+    line_indexes: Vec<usize>,
+) -> Vec<Fix> {
+    line_indexes
+        .into_iter()
+        .filter_map(|line_index| {
+            let line = docstring.line_positions.get(line_index)?.line;
+            let text = index.line_text(line).trim();
+            matches!(text, "///" | "//!").then(|| Fix {
+                rule,
+                range: index.line_range(line),
+                replacement: String::new(),
+            })
+        })
+        .collect()
+}
+
+/// Return the text after the doc comment marker on a line, with its byte offset in the source.
+fn line_comment_text<'a>(index: &LineIndex<'a>, line: usize) -> Option<(usize, &'a str)> {
+    let text = index.line_text(line);
+    let comment = text.trim_start();
+    let after = comment.strip_prefix("///").or_else(|| comment.strip_prefix("//!"))?;
+    Some((index.line_range(line).start + text.len() - after.len(), after))
+}
+
+/// Delete the whitespace at the end of a doc comment line.
+fn trailing_whitespace_fix(index: &LineIndex<'_>, line: usize) -> Option<Fix> {
+    let (start, text) = line_comment_text(index, line)?;
+    let trimmed = text.trim_end();
+    (trimmed.len() < text.len()).then(|| Fix {
+        rule: "D210",
+        range: start + trimmed.len()..start + text.len(),
+        replacement: String::new(),
+    })
+}
+
+/// Replace the indentation of a doc comment line's text with a single space.
+fn indent_fix(index: &LineIndex<'_>, line: usize) -> Option<Fix> {
+    let (start, text) = line_comment_text(index, line)?;
+    let indent = text.len() - text.trim_start().len();
+    (indent > 1 && !text.trim().is_empty()).then(|| Fix {
+        rule: "R201",
+        range: start..start + indent,
+        replacement: " ".to_string(),
+    })
+}
+
+/// Add the missing period at the end of a one-line summary.
+///
+/// Summaries ending with other punctuation, or wrapped onto the next line, are left
+/// for the author to rephrase.
+fn summary_period_fix(docstring: &Docstring, index: &LineIndex<'_>) -> Option<Fix> {
+    let (offset, line) = first_line(docstring)?;
+    let next_line = docstring.content[offset + line.len()..].lines().nth(1);
+    if next_line.is_some_and(|next| !next.trim().is_empty()) {
+        return None;
+    }
+
+    let summary = line.trim_end();
+    let last = summary.chars().last()?;
+    if !(last.is_alphanumeric() || matches!(last, '`' | ')' | ']' | '"' | '\'' | '*' | '_')) {
+        return None;
+    }
+    let end = index.offset(docstring.position_at(offset + summary.len()));
+    Some(Fix { rule: "D400", range: end..end, replacement: ".".to_string() })
+}
+
+/// Join the lines of a summary sentence wrapped across several lines.
+fn summary_reflow_fix(docstring: &Docstring, index: &LineIndex<'_>) -> Option<Fix> {
+    let (start, end) = Pep257Checker::wrapped_summary(&docstring.content)?;
+    let lines: Vec<&str> = docstring.content.split('\n').collect();
+    let line_end = |line_index: usize| {
+        let offset: usize = lines[..line_index].iter().map(|line| line.len() + 1).sum();
+        index.offset(docstring.position_at(offset + lines[line_index].trim_end().len()))
+    };
+    let rest: Vec<&str> = lines[start + 1..=end].iter().map(|line| line.trim()).collect();
+    Some(Fix {
+        rule: "R204",
+        range: line_end(start)..line_end(end),
+        replacement: format!(" {}", rest.join(" ")),
+    })
+}
+
+/// Capitalize the first word of the summary.
+///
+/// Words that look like identifiers, such as `snake_case` or `iOS`, are left alone.
+fn capitalization_fix(docstring: &Docstring, index: &LineIndex<'_>) -> Option<Fix> {
+    let (offset, line) = first_line(docstring)?;
+    let start = line.len() - line.trim_start().len();
+    let word = line[start..].split_whitespace().next()?;
+    let first = word.chars().next()?;
+    if !first.is_lowercase()
+        || word.chars().skip(1).any(|c| c.is_uppercase() || matches!(c, '_' | '(' | ':'))
+    {
+        return None;
+    }
+
+    let start = index.offset(docstring.position_at(offset + start));
+    Some(Fix {
+        rule: "D403",
+        range: start..start + first.len_utf8(),
+        replacement: first.to_uppercase().collect(),
+    })
+}
+
+/// Replace links to common Rust types with inline code.
+fn inline_code_fixes(docstring: &Docstring, index: &LineIndex<'_>) -> Vec<Fix> {
+    let content = &docstring.content;
+    Pep257Checker::bracketed_texts(content)
+        .into_iter()
+        .filter(|(_, text, _)| COMMON_RUST_TYPES.contains(&text.trim()))
+        .filter_map(|(offset, text, destination)| {
+            let mut end = offset + text.len() + 2;
+            if let Some(open) = destination {
+                let close = if open == '(' { ')' } else { ']' };
+                let open_offset = end + content[end..].find(open)?;
+                end = open_offset + content[open_offset..].find(close)? + 1;
+            }
+            if content[offset..end].contains('\n') {
+                return None;
+            }
+
+            let start = index.offset(docstring.position_at(offset));
+            let end = index.offset(docstring.position_at(end));
+            Some(Fix { rule: "R402", range: start..end, replacement: format!("`{}`", text.trim()) })
+        })
+        .collect()
+}
+
+/// Remove empty code spans, with a space next to them, and the lines of empty code blocks.
+///
+/// Code blocks are only removed from line comments, whose lines can be deleted one
+/// by one, along with a blank line before them that would be left at the end of the
+/// docs or next to another blank line.
+fn empty_code_fixes(
+    docstring: &Docstring,
+    index: &LineIndex<'_>,
+    is_line_comment: bool,
+) -> Vec<Fix> {
+    let content = &docstring.content;
+    let lines: Vec<&str> = content.split('\n').collect();
+    Pep257Checker::empty_code(content)
+        .into_iter()
+        .filter_map(|(range, is_block, _)| {
+            let start = docstring.position_at(range.start);
+            let end = docstring.position_at(range.end);
+            if is_block {
+                if !is_line_comment {
+                    return None;
+                }
+                let first = content[..range.start].matches('\n').count();
+                let last = content[..range.end].matches('\n').count();
+                let is_blank = |line_index: usize| {
+                    lines.get(line_index).is_none_or(|line| line.trim().is_empty())
+                };
+                let first = if first > 0 && is_blank(first - 1) && is_blank(last + 1) {
+                    first - 1
+                } else {
+                    first
+                };
+                let first_line = docstring.source_line(first);
+                return Some(Fix {
+                    rule: "R405",
+                    range: index.line_range(first_line).start..index.line_range(end.line).end,
+                    replacement: String::new(),
+                });
+            }
+
+            if start.line != end.line {
+                return None;
+            }
+            let text = index.line_text(start.line);
+            let (mut from, mut to) = (start.column - 1, end.column - 1);
+            let after = text[to..].chars().next();
+            if text[..from].ends_with(' ')
+                && after.is_none_or(|c| c == ' ' || c.is_ascii_punctuation())
+            {
+                from -= 1;
+            } else if after == Some(' ') {
+                to += 1;
+            }
+            let line_start = index.line_range(start.line).start;
+            Some(Fix {
+                rule: "R405",
+                range: line_start + from..line_start + to,
+                replacement: String::new(),
+            })
+        })
+        .collect()
+}
+
+/// Wrap a bare URL in angle brackets, making it an autolink.
+fn autolink_fix(index: &LineIndex<'_>, violation: &Violation) -> Option<Fix> {
+    if violation.end_line != Some(violation.line) {
+        return None;
+    }
+    let text = index.line_text(violation.line);
+    let url = text.get(violation.column - 1..violation.end_column? - 1)?;
+    let start = index.offset(SourcePosition { line: violation.line, column: violation.column });
+    Some(Fix { rule: "R406", range: start..start + url.len(), replacement: format!("<{url}>") })
+}
+
+/// Replace the markers of list items that differ from the configured marker.
+///
+/// Without a configured marker, the first marker of each list is used.
+fn bullet_marker_fixes(
+    docstring: &Docstring,
+    index: &LineIndex<'_>,
+    options: &CheckerOptions,
+) -> Vec<Fix> {
+    let preferred = options.bullet_marker.map(BulletMarker::as_char);
+    Pep257Checker::mismatched_bullets(&docstring.content, preferred)
+        .into_iter()
+        .map(|(offset, _, expected)| {
+            let start = index.offset(docstring.position_at(offset));
+            Fix { rule: "R408", range: start..start + 1, replacement: expected.to_string() }
+        })
+        .collect()
+}
+
+/// Return the content offset and text of the first non-blank line of a docstring.
+fn first_line(docstring: &Docstring) -> Option<(usize, &str)> {
+    let mut offset = 0;
+    for line in docstring.content.split('\n') {
+        if !line.trim().is_empty() {
+            return Some((offset, line));
+        }
+        offset += line.len() + 1;
+    }
+    None
+}
+
+/// Unit tests for the fixer.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analyzer::{AnalyzerOptions, RustDocAnalyzer},
+        rules::{RuleSelection, find_rule},
+    };
+
+    /// Fix source code with the default analyzer.
+    fn fix(source: &str) -> FixedSource {
+        RustDocAnalyzer::new().unwrap().fix_source(source, None).unwrap()
+    }
+
+    /// Test fixing summaries, leaving the rest of the source untouched.
+    #[test]
+    fn test_fix_summary() {
+        let fixed = fix("/// return the [Option]  \nfn f() {}\n\n/// Parse it\r\nfn g() {}\n");
+        assert_eq!(
+            fixed.source,
+            "/// Return the `Option`.\nfn f() {}\n\n/// Parse it.\r\nfn g() {}\n"
+        );
+        assert_eq!(
+            fixed.fixes,
+            BTreeMap::from([
+                ("D210".to_string(), 1),
+                ("D400".to_string(), 2),
+                ("D403".to_string(), 1),
+                ("R402".to_string(), 1)
+            ])
+        );
+    }
+
+    /// Test removing blank doc lines around the docs and after the summary.
+    #[test]
+    fn test_fix_blank_lines() {
+        let fixed =
+            fix("mod m {\n    ///\n    /// Parse it.\n    ///\n    ///\n    fn f() {}\n}\n");
+        assert_eq!(fixed.source, "mod m {\n    /// Parse it.\n    fn f() {}\n}\n");
+        assert_eq!(fixed.total(), 3);
+
+        let fixed = fix("/// Parse it.\n///\n///\n///\n/// More.\nfn f() {}\n");
+        assert_eq!(fixed.source, "/// Parse it.\n///\n/// More.\nfn f() {}\n");
+        assert_eq!(fixed.fixes, BTreeMap::from([("R202".to_string(), 2)]));
+    }
+
+    /// Test that the registry example of every fixable rule is fixed.
+    #[test]
+    fn test_fix_examples() {
+        for code in FIXABLE_RULES {
+            let example = find_rule(code).and_then(|rule| rule.example).unwrap();
+            let rules = RuleSelection { select: vec![(*code).to_string()], ignore: Vec::new() };
+            let mut analyzer = RustDocAnalyzer::with_options(AnalyzerOptions {
+                rules,
+                ..AnalyzerOptions::default()
+            })
+            .unwrap();
+            let fixed = analyzer.fix_source(example, None).unwrap();
+            assert_eq!(fixed.fixes, BTreeMap::from([((*code).to_string(), 1)]), "{code}");
+        }
+    }
+
+    /// Test wrapping bare URLs in angle brackets.
+    #[test]
+    fn test_fix_bare_urls() {
+        let fixed = fix("/// See https://example.com/a_b, or http://x.y.\nfn f() {}\n");
+        assert_eq!(
+            fixed.source,
+            "/// See <https://example.com/a_b>, or <http://x.y>.\nfn f() {}\n"
+        );
+        assert_eq!(fixed.fixes, BTreeMap::from([("R406".to_string(), 2)]));
+
+        let fixed =
+            fix("/** See https://example.com.\n\n[link](https://example.com) */\nfn f() {}\n");
+        assert_eq!(
+            fixed.source,
+            "/** See <https://example.com>.\n\n[link](https://example.com) */\nfn f() {}\n"
+        );
+    }
+
+    /// Test rewriting list markers to the list's first marker, or the configured one.
+    #[test]
+    fn test_fix_bullet_markers() {
+        let source = "/// Parse it.\n///\n/// - one\n/// * two\n/// + three\nfn f() {}\n";
+        let fixed = fix(source);
+        assert_eq!(
+            fixed.source,
+            "/// Parse it.\n///\n/// - one\n/// - two\n/// - three\nfn f() {}\n"
+        );
+        assert_eq!(fixed.fixes, BTreeMap::from([("R408".to_string(), 2)]));
+
+        let mut analyzer = RustDocAnalyzer::with_options(AnalyzerOptions {
+            checker: CheckerOptions {
+                bullet_marker: Some(BulletMarker::Asterisk),
+                ..CheckerOptions::default()
+            },
+            ..AnalyzerOptions::default()
+        })
+        .unwrap();
+        let fixed = analyzer.fix_source(source, None).unwrap();
+        assert_eq!(
+            fixed.source,
+            "/// Parse it.\n///\n/// * one\n/// * two\n/// * three\nfn f() {}\n"
+        );
+    }
+
+    /// Test moving comments trailing fields and variants above them.
+    #[test]
+    fn test_fix_trailing_comments() {
+        let fixed = fix(
+            "/// A point.\npub struct Point {\n    pub x: i32, // The x coordinate.\n    pub y: i32, /// The y coordinate.\n}\n",
+        );
+        assert_eq!(
+            fixed.source,
+            "/// A point.\npub struct Point {\n    /// The x coordinate.\n    pub x: i32,\n    /// The y coordinate.\n    pub y: i32,\n}\n"
+        );
+        assert_eq!(fixed.fixes, BTreeMap::from([("R108".to_string(), 2)]));
+
+        let fixed = fix("/// A mode.\r\npub enum Mode {\r\n    Fast, // Skip the checks.\r\n}\r\n");
+        assert_eq!(
+            fixed.source,
+            "/// A mode.\r\npub enum Mode {\r\n    /// Skip the checks.\r\n    Fast,\r\n}\r\n"
+        );
+
+        // Members spanning several lines keep their comments
+        let source = "/// A point.\npub struct Point {\n    pub x: Vec<\n        i32,\n    >, // The x coordinates.\n}\n";
+        assert_eq!(fix(source).source, source);
+    }
+
+    /// Test removing empty code spans and code blocks.
+    #[test]
+    fn test_fix_empty_code() {
+        let fixed = fix("/// Return `` and ` ` values.\n///\n/// ```\n/// ```\nfn f() {}\n");
+        assert_eq!(fixed.source, "/// Return and values.\nfn f() {}\n");
+        assert_eq!(fixed.fixes, BTreeMap::from([("R405".to_string(), 3)]));
+
+        // Block comments keep their lines, but lose their empty spans
+        let fixed = fix("/** Return ``the value.\n\n```\n```\n*/\nfn f() {}\n");
+        assert_eq!(fixed.source, "/** Return the value.\n\n```\n```\n*/\nfn f() {}\n");
+    }
+
+    /// Test reducing the indentation of the first doc line.
+    #[test]
+    fn test_fix_indented_first_line() {
+        let fixed = fix("//!   Open.\n\n///    Parse the input.\n///     code\nfn f() {}\n");
+        assert_eq!(fixed.source, "//! Open.\n\n/// Parse the input.\n///     code\nfn f() {}\n");
+        assert_eq!(fixed.fixes, BTreeMap::from([("R201".to_string(), 2)]));
+
+        // Block comments are left alone
+        let source = "/**   Parse the input. */\nfn f() {}\n";
+        assert_eq!(fix(source).source, source);
+    }
+
+    /// Test showing the lines changed by a fix.
+    #[test]
+    fn test_line_diff() {
+        assert_eq!(
+            line_diff("///\n/// Parse.\nfn f() {}\n", "/// Parse.\nfn f() {}\n"),
+            "-///\n /// Parse.\n fn f() {}\n"
+        );
+        assert_eq!(
+            line_diff("/// a\nfn f() {}", "/// A.\nfn f() {}"),
+            "-/// a\n+/// A.\n fn f() {}\n"
+        );
+    }
+
+    /// Test that violations without a safe fix are left alone.
+    #[test]
+    fn test_unfixable() {
+        for source in [
+            "/// Parse it:\nfn f() {}\n",
+            "/// Parse the value\n/// - and return it.\nfn f() {}\n",
+            "/** Parse the value\n and return it. */\nfn f() {}\n",
+            "/// iOS support.\nfn f() {}\n",
+            "#[doc = \"parse it\"]\nfn f() {}\n",
+        ] {
+            let fixed = fix(source);
+            assert_eq!((fixed.source.as_str(), fixed.total()), (source, 0));
+        }
+    }
+
+    /// Test joining a wrapped summary within the rustfmt width of the file.
+    #[test]
+    fn test_fix_wrapped_summary() {
+        let source = "mod m {\n    /// Parse the value\n    /// of the input  \n    /// and return it.\n    ///\n    /// More.\n    fn f() {}\n}\n";
+        let fixed = fix(source);
+        assert_eq!(
+            fixed.source,
+            "mod m {\n    /// Parse the value of the input and return it.\n    ///\n    /// More.\n    fn f() {}\n}\n"
+        );
+        assert_eq!(fixed.fixes, BTreeMap::from([("R204".to_string(), 1)]));
+
+        // The joined summary would be 51 characters wide
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        fs::write(&file, source).unwrap();
+        fs::write(dir.path().join("rustfmt.toml"), "max_width = 50\n").unwrap();
+        let fixed = RustDocAnalyzer::new().unwrap().fix_source(source, Some(&file)).unwrap();
+        assert_eq!(fixed.source, source.replace("input  ", "input"));
+        assert_eq!(fixed.fixes, BTreeMap::from([("D210".to_string(), 1)]));
+    }
+
+    /// Test inserting doc templates for undocumented public items.
+    #[test]
+    fn test_fix_doc_templates() {
+        let source = "pub mod m {\n    #[must_use]\n    pub unsafe fn parse(input: &str, mut n: u8) -> io::Result<u8> {\n        todo!()\n    }\n}\r\n\r\npub struct S;\r\n";
+        assert_eq!(fix(source).total(), 0);
+
+        let fixed = RustDocAnalyzer::new()
+            .unwrap()
+            .with_doc_templates(true)
+            .fix_source(source, None)
+            .unwrap();
+        assert_eq!(
+            fixed.source,
+            "//! TODO: Summarize this module.\n\n/// TODO: Summarize `m`.\npub mod m {\n    /// TODO: Summarize `parse`.\n    ///\n    /// # Arguments\n    ///\n    /// * `input` - TODO\n    /// * `n` - TODO\n    ///\n    /// # Errors\n    ///\n    /// TODO\n    ///\n    /// # Safety\n    ///\n    /// TODO\n    #[must_use]\n    pub unsafe fn parse(input: &str, mut n: u8) -> io::Result<u8> {\n        todo!()\n    }\n}\r\n\r\n/// TODO: Summarize `S`.\r\npub struct S;\r\n"
+        );
+        assert_eq!(
+            fixed.fixes,
+            BTreeMap::from([
+                ("D100".to_string(), 1),
+                ("D101".to_string(), 1),
+                ("D103".to_string(), 1),
+                ("D104".to_string(), 1)
+            ])
+        );
+    }
+
+    /// Test reading the nearest rustfmt configuration file.
+    #[test]
+    fn test_rustfmt_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("src/lib.rs");
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(&file, "").unwrap();
+        assert_eq!(RustfmtSettings::for_file(&file), RustfmtSettings::default());
+
+        fs::write(
+            dir.path().join("rustfmt.toml"),
+            "max_width = 90
+wrap_comments = true
+",
+        )
+        .unwrap();
+        let settings = RustfmtSettings::for_file(&file);
+        assert_eq!(settings, RustfmtSettings {
+            max_width: 90,
+            comment_width: 80,
+            wrap_comments: true
+        });
+        assert_eq!(settings.doc_width(), 80);
+
+        // `.rustfmt.toml` takes precedence, and without `wrap_comments` only `max_width` counts
+        fs::write(
+            dir.path().join(".rustfmt.toml"),
+            "max_width = 60
+comment_width = 40
+",
+        )
+        .unwrap();
+        assert_eq!(RustfmtSettings::for_file(&file).doc_width(), 60);
+    }
+
+    /// Test that fixes don't widen lines beyond the rustfmt width of the file.
+    #[test]
+    fn test_fix_within_width() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        let source = "/// parse [Option]\nfn f() {}\n\n/// Parse it\nfn g() {}\n";
+        fs::write(&file, source).unwrap();
+        fs::write(dir.path().join("rustfmt.toml"), "max_width = 18\n").unwrap();
+
+        // The period would make the first line 19 characters wide
+        let fixed = RustDocAnalyzer::new().unwrap().fix_source(source, Some(&file)).unwrap();
+        assert_eq!(fixed.source, "/// Parse `Option`\nfn f() {}\n\n/// Parse it.\nfn g() {}\n");
+        assert_eq!(
+            fixed.fixes,
+            BTreeMap::from([
+                ("D400".to_string(), 1),
+                ("D403".to_string(), 1),
+                ("R402".to_string(), 1)
+            ])
+        );
+    }
+}
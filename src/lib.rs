@@ -1,10 +1,42 @@
 //! PEP 257 docstring style checker for Rust code.
 
+/// Editor-agnostic actions for violations, for simple editor plugins.
+pub mod actions;
 /// Analyzer module for Rust documentation.
 pub mod analyzer;
+/// Language backends that extract docstrings from source files.
+mod backend;
+/// Baselines of known violations.
+pub mod baseline;
+/// Synthetic workloads for benchmarks.
+pub mod bench;
+/// On-disk cache of analysis results.
+pub mod cache;
+/// Code Climate issue reports for code quality dashboards.
+pub mod codeclimate;
+/// Project configuration files.
+pub mod config;
+/// Crate-level knowledge available to the checks.
+pub mod context;
 /// File collection module for finding Rust source files.
 pub mod file_collector;
+/// Automatic fixes for mechanically fixable violations.
+pub mod fixer;
+/// Language server publishing docstring diagnostics.
+pub mod lsp;
 /// Parser module for extracting docstrings.
 pub mod parser;
 /// PEP 257 checker implementation.
 pub mod pep257;
+/// Source position handling for reported violations.
+pub mod position;
+/// Reports of check runs, which can be merged across CI shards.
+pub mod report;
+/// Selection of the rules to report.
+pub mod rules;
+/// Diagnostics in the JSON format of rustc, for cargo-aware tools.
+pub mod rustc_json;
+/// Inline suppression comments.
+pub mod suppression;
+/// Fixture-based test harness for the analyzer.
+pub mod testing;
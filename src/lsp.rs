@@ -0,0 +1,354 @@
+//! Language server publishing docstring diagnostics.
+//!
+//! The server speaks the Language Server Protocol over stdio, or any other pair of
+//! streams. Documents are checked leniently whenever they're opened or changed, so
+//! unsaved buffers with syntax errors still get diagnostics for the items that could
+//! be parsed, and the diagnostics are published for the editor to show. Violations
+//! with a mechanical fix get a quick fix code action, applying the edits that `pep257
+//! fix` would make.
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
+
+use serde_json::{Value, json};
+
+use crate::{
+    analyzer::RustDocAnalyzer,
+    fixer::Fix,
+    pep257::{Severity, Violation},
+    position::{ColumnUnit, LineIndex},
+};
+
+/// JSON-RPC error code for requests with an unknown method.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Language server checking the documents an editor has open.
+pub struct LanguageServer {
+    analyzer: RustDocAnalyzer,
+    /// Text of the open documents, by URI.
+    documents: HashMap<String, String>,
+}
+
+/// Implementation of language server methods.
+impl LanguageServer {
+    /// Create a server checking documents with the given analyzer.
+    ///
+    /// The analyzer should count columns in UTF-16 code units, as the protocol does.
+    #[must_use]
+    pub fn new(analyzer: RustDocAnalyzer) -> Self {
+        Self { analyzer, documents: HashMap::new() }
+    }
+
+    /// Serve messages from `input` until the client sends `exit` or closes the stream.
+    pub fn serve(&mut self, input: &mut impl BufRead, output: &mut impl Write) -> io::Result<()> {
+        while let Some(message) = read_message(input)? {
+            let method = message.get("method").and_then(Value::as_str).unwrap_or_default();
+            if method == "exit" {
+                break;
+            }
+            for response in self.handle(method, &message) {
+                write_message(output, &response)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle a message, returning the messages to send back.
+    fn handle(&mut self, method: &str, message: &Value) -> Vec<Value> {
+        let id = message.get("id").cloned();
+        let params = message.get("params").unwrap_or(&Value::Null);
+        let document = &params["textDocument"];
+        let uri = document["uri"].as_str().unwrap_or_default().to_string();
+
+        match (method, id) {
+            ("initialize", Some(id)) => vec![json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": {
+                    // Full document sync: every change sends the whole text
+                    "capabilities": { "textDocumentSync": 1, "codeActionProvider": true },
+                    "serverInfo": { "name": "pep257", "version": env!("CARGO_PKG_VERSION") },
+                },
+            })],
+            ("textDocument/codeAction", Some(id)) => {
+                let diagnostics = params["context"]["diagnostics"].as_array();
+                let actions = self.code_actions(&uri, diagnostics.map_or(&[], Vec::as_slice));
+                vec![json!({ "jsonrpc": "2.0", "id": id, "result": actions })]
+            }
+            ("shutdown", Some(id)) => vec![json!({ "jsonrpc": "2.0", "id": id, "result": null })],
+            ("textDocument/didOpen", None) => {
+                let text = document["text"].as_str().unwrap_or_default().to_string();
+                self.documents.insert(uri.clone(), text);
+                vec![self.diagnostics(&uri)]
+            }
+            ("textDocument/didChange", None) => {
+                // With full sync, the last change holds the whole text
+                let changes = params["contentChanges"].as_array();
+                if let Some(text) = changes.and_then(|c| c.last()).and_then(|c| c["text"].as_str())
+                {
+                    self.documents.insert(uri.clone(), text.to_string());
+                }
+                vec![self.diagnostics(&uri)]
+            }
+            ("textDocument/didClose", None) => {
+                self.documents.remove(&uri);
+                vec![publish(&uri, &[])]
+            }
+            (_, Some(id)) => vec![json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": METHOD_NOT_FOUND, "message": format!("unknown method `{method}`") },
+            })],
+            // Other notifications, such as `initialized`, need no answer
+            (_, None) => Vec::new(),
+        }
+    }
+
+    /// Check an open document, returning the notification publishing its diagnostics.
+    fn diagnostics(&mut self, uri: &str) -> Value {
+        let source = self.documents.get(uri).map_or("", String::as_str);
+        let check = self.analyzer.analyze_source_lossy(source);
+        publish(uri, &check.violations)
+    }
+
+    /// Return the quick fixes of the given diagnostics of an open document.
+    ///
+    /// A diagnostic is fixed by the fixes of its rule in the docstring it was reported
+    /// in. Documents that don't parse have no fixes.
+    fn code_actions(&mut self, uri: &str, diagnostics: &[Value]) -> Vec<Value> {
+        let source = self.documents.get(uri).map_or("", String::as_str);
+        let Ok(docstring_fixes) = self.analyzer.docstring_fixes(source, None) else {
+            return Vec::new();
+        };
+        let index = LineIndex::new(source);
+
+        let mut actions = Vec::new();
+        for diagnostic in diagnostics {
+            let Some(rule) = diagnostic["code"].as_str() else {
+                continue;
+            };
+            let line = diagnostic["range"]["start"]["line"]
+                .as_u64()
+                .and_then(|line| usize::try_from(line).ok().map(|line| line + 1));
+            let fixes: Vec<&Fix> = docstring_fixes
+                .iter()
+                .filter(|(violations, _)| {
+                    violations.iter().any(|v| v.rule == rule && Some(v.line) == line)
+                })
+                .flat_map(|(_, fixes)| fixes.iter().filter(|fix| fix.rule == rule))
+                .collect();
+            if fixes.is_empty() {
+                continue;
+            }
+
+            let edits: Vec<Value> = fixes
+                .iter()
+                .map(|fix| {
+                    json!({
+                        "range": {
+                            "start": lsp_position(&index, fix.range.start),
+                            "end": lsp_position(&index, fix.range.end),
+                        },
+                        "newText": fix.replacement,
+                    })
+                })
+                .collect();
+            actions.push(json!({
+                "title": format!("Fix {rule}: {}", diagnostic["message"].as_str().unwrap_or(rule)),
+                "kind": "quickfix",
+                "diagnostics": [diagnostic],
+                "isPreferred": true,
+                "edit": { "changes": { uri: edits } },
+            }));
+        }
+        actions
+    }
+}
+
+/// Return the protocol position of a byte offset into a source.
+///
+/// Columns are counted in UTF-16 code units from 0.
+fn lsp_position(index: &LineIndex<'_>, offset: usize) -> Value {
+    let line = index.line_at(offset);
+    let column = offset - index.line_range(line).start + 1;
+    json!({
+        "line": line - 1,
+        "character": index.convert_column(line, column, ColumnUnit::Utf16) - 1,
+    })
+}
+
+/// Build the notification publishing the violations of a document as diagnostics.
+fn publish(uri: &str, violations: &[Violation]) -> Value {
+    let diagnostics: Vec<Value> = violations
+        .iter()
+        .map(|violation| {
+            // The protocol counts lines and columns from 0
+            let start = json!({
+                "line": violation.line.saturating_sub(1),
+                "character": violation.column.saturating_sub(1),
+            });
+            let end = match (violation.end_line, violation.end_column) {
+                (Some(line), Some(column)) => json!({
+                    "line": line.saturating_sub(1),
+                    "character": column.saturating_sub(1),
+                }),
+                _ => start.clone(),
+            };
+            json!({
+                "range": { "start": start, "end": end },
+                "severity": match violation.severity {
+                    Severity::Error => 1,
+                    Severity::Warning => 2,
+                    Severity::Info => 3,
+                },
+                "code": violation.rule,
+                "source": "pep257",
+                "message": violation.message,
+            })
+        })
+        .collect();
+
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+/// Read a message framed by a `Content-Length` header, or `None` at the end of input.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("Content-Length")
+        {
+            content_length = value.trim().parse().ok();
+        }
+    }
+
+    let length = content_length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "message without a Content-Length header")
+    })?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body).map(Some).map_err(io::Error::from)
+}
+
+/// Write a message framed by a `Content-Length` header.
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    output.flush()
+}
+
+/// Unit tests for the language server.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Frame messages as a client would send them.
+    fn frame(messages: &[Value]) -> Vec<u8> {
+        let mut input = Vec::new();
+        for message in messages {
+            write_message(&mut input, message).unwrap();
+        }
+        input
+    }
+
+    /// Test a session that opens, changes and closes a document.
+    #[test]
+    fn test_session() {
+        let uri = "file:///project/src/lib.rs";
+        let input = frame(&[
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+            json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+            json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+                "textDocument": { "uri": uri, "languageId": "rust", "version": 1,
+                                  "text": "//! Crate.\n\n/// parse it\npub fn parse() {}\n" },
+            }}),
+            json!({ "jsonrpc": "2.0", "method": "textDocument/didChange", "params": {
+                "textDocument": { "uri": uri, "version": 2 },
+                "contentChanges": [{ "text": "//! Crate.\n\n/// Parse it.\npub fn parse() {}\n" }],
+            }}),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "textDocument/hover", "params": {} }),
+            json!({ "jsonrpc": "2.0", "method": "textDocument/didClose", "params": {
+                "textDocument": { "uri": uri },
+            }}),
+            json!({ "jsonrpc": "2.0", "id": 3, "method": "shutdown" }),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+        ]);
+
+        let mut server = LanguageServer::new(RustDocAnalyzer::new().unwrap());
+        let mut output = Vec::new();
+        server.serve(&mut input.as_slice(), &mut output).unwrap();
+
+        let mut output = output.as_slice();
+        let mut messages = Vec::new();
+        while let Some(message) = read_message(&mut output).unwrap() {
+            messages.push(message);
+        }
+        assert_eq!(messages.len(), 6);
+        assert_eq!(messages[0]["result"]["capabilities"]["textDocumentSync"], 1);
+
+        let opened = &messages[1]["params"]["diagnostics"];
+        let codes: Vec<&str> =
+            opened.as_array().unwrap().iter().map(|d| d["code"].as_str().unwrap()).collect();
+        assert_eq!(codes, vec!["D400", "D403"]);
+        assert_eq!(opened[0]["range"]["start"], json!({ "line": 2, "character": 0 }));
+        assert_eq!(opened[0]["range"]["end"], json!({ "line": 2, "character": 12 }));
+        assert_eq!(opened[0]["severity"], 1);
+
+        assert_eq!(messages[2]["params"]["diagnostics"], json!([]));
+        assert_eq!(messages[3]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(messages[4]["params"]["diagnostics"], json!([]));
+        assert_eq!(messages[5], json!({ "jsonrpc": "2.0", "id": 3, "result": null }));
+    }
+
+    /// Test the quick fixes offered for the diagnostics of a document.
+    #[test]
+    fn test_code_actions() {
+        let uri = "file:///project/src/lib.rs";
+        let mut server = LanguageServer::new(RustDocAnalyzer::new().unwrap());
+        server.handle("textDocument/didOpen", &json!({ "params": {
+            "textDocument": { "uri": uri, "text": "//! Crate.\n\n/// parsé it\npub fn parse() {}\n" },
+        }}));
+
+        let diagnostic = |code: &str| {
+            let position = json!({ "line": 2, "character": 0 });
+            json!({ "range": { "start": position, "end": position }, "code": code, "message": "m" })
+        };
+        let responses = server.handle("textDocument/codeAction", &json!({ "id": 1, "params": {
+            "textDocument": { "uri": uri },
+            "range": { "start": { "line": 2, "character": 0 }, "end": { "line": 2, "character": 0 } },
+            "context": { "diagnostics": [diagnostic("D400"), diagnostic("D403"), diagnostic("D401")] },
+        }}));
+
+        let actions = responses[0]["result"].as_array().unwrap();
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0]["kind"], "quickfix");
+        assert_eq!(actions[0]["diagnostics"][0]["code"], "D400");
+        // The period goes after `é`, which is one UTF-16 code unit but two bytes
+        let position = json!({ "line": 2, "character": 12 });
+        assert_eq!(
+            actions[0]["edit"]["changes"][uri],
+            json!([{ "range": { "start": position, "end": position }, "newText": "." }])
+        );
+        assert_eq!(
+            actions[1]["edit"]["changes"][uri],
+            json!([{
+                "range": { "start": { "line": 2, "character": 4 }, "end": { "line": 2, "character": 5 } },
+                "newText": "P",
+            }])
+        );
+    }
+}
@@ -1,11 +1,73 @@
-use std::{path::PathBuf, process};
+use std::{
+    collections::{BTreeMap, HashSet},
+    env,
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, BufWriter, IsTerminal as _, Write},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    time::{Duration, Instant},
+};
 
-use clap::{CommandFactory as _, Parser as ClapParser, Subcommand, ValueEnum};
+use anstyle::{AnsiColor, Style};
+use clap::{
+    Args, ColorChoice, CommandFactory as _, FromArgMatches as _, Parser as ClapParser, Subcommand,
+    ValueEnum, builder::PossibleValuesParser,
+};
 use clap_verbosity_flag::Verbosity;
+use notify::{RecursiveMode, Watcher as _};
 use pep257::{
-    analyzer::RustDocAnalyzer, file_collector::collect_rust_files_recursive, pep257::Severity,
+    actions::violation_actions,
+    analyzer::{
+        AnalyzerOptions, CfgPolicy, Coverage, FileTimings, ReexportPolicy, RustDocAnalyzer,
+    },
+    baseline::Baseline,
+    bench::write_synthetic_crate,
+    cache::{self, Cache},
+    codeclimate,
+    config::Config,
+    context::{Edition, workspace_root},
+    file_collector::{
+        CollectOptions, PathStyle, collect_files_with_options, collect_glob_files, glob_base,
+        is_glob, styled_path,
+    },
+    fixer::{FIXABLE_RULES, line_diff},
+    lsp::LanguageServer,
+    pep257::{
+        BulletMarker, CheckerOptions, ExampleTargets, HiddenPolicy, OPT_IN_RULES, ProseLimits,
+        Severity, Violation,
+    },
+    position::{ColumnUnit, chars_before},
+    report::{FailPolicy, FileError, FileResult, Report, ReportStatistics, Shard},
+    rules::{PerFileIgnores, RULES_VERSION, Rule, RuleSelection, find_rule, parse_rule_prefix},
+    rustc_json::diagnostic,
+    suppression::{format_comment, insert_comments},
+    testing,
 };
 
+/// Exit code when violations with error severity are found.
+const EXIT_ERRORS: i32 = 1;
+
+/// Exit code when only violations with warning severity are found.
+const EXIT_WARNINGS: i32 = 2;
+
+/// Exit code when files, reports or the configuration can't be read.
+const EXIT_FAILURE: i32 = 3;
+
+/// Exit code when the user interrupts a check with Ctrl-C.
+///
+/// Shells report the same code for a process killed by SIGINT.
+const EXIT_INTERRUPTED: i32 = 130;
+
+/// How long to wait for further changes before re-checking in watch mode.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
 /// Command-line interface configuration.
 #[derive(ClapParser, Debug)]
 #[command(name = "pep257")]
@@ -41,14 +103,67 @@ struct Cli {
     #[arg(short, long)]
     warnings: bool,
 
+    /// When to color text output
+    ///
+    /// `auto` colors it when printing to a terminal and `NO_COLOR` isn't set
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
     /// Output format
+    ///
+    /// `codeclimate` prints a JSON array of Code Climate issues for GitLab code quality
+    /// reports, and `rustc-json` prints a diagnostic per line like `rustc --error-format=json`
     #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
     format: OutputFormat,
 
+    /// How file paths are printed; paths are printed as found by default
+    #[arg(long, value_enum, value_name = "STYLE")]
+    path_style: Option<PathStyle>,
+
+    /// Show the source line of each violation in text output, with a caret under its column
+    #[arg(long)]
+    show_source: bool,
+
+    /// Configuration file to use instead of searching for one
+    ///
+    /// By default, the checked path and its parents are searched for a `pep257.toml` file or a
+    /// `Cargo.toml` file with a `[tool.pep257]` table
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
     /// Exit with code 0 even if violations are found
     #[arg(long)]
     no_fail: bool,
 
+    /// Exit with code 0 if only violations with warning severity are found, instead of 2
+    #[arg(long)]
+    no_fail_on_warnings: bool,
+
+    /// Severities of the violations that fail the run
+    ///
+    /// Defaults to the `fail-on` setting or `error,warning`; `--no-fail` and
+    /// `--no-fail-on-warnings` remove severities from these
+    #[arg(
+        long,
+        value_name = "SEVERITY",
+        value_delimiter = ',',
+        value_parser = PossibleValuesParser::new(Severity::ALL.map(Severity::name))
+    )]
+    fail_on: Option<Vec<String>>,
+
+    /// Exit with code 0 if at most this many violations are found
+    ///
+    /// A budget of existing violations can then be lowered over time
+    #[arg(long, value_name = "N")]
+    max_violations: Option<usize>,
+
+    /// Check the Cargo workspace of this manifest when no path is given
+    ///
+    /// Without it, the current directory is checked; `cargo pep257` checks the workspace of the
+    /// current directory
+    #[arg(long, value_name = "PATH")]
+    manifest_path: Option<PathBuf>,
+
     /// Generate markdown help
     #[cfg(feature = "clap-markdown")]
     #[arg(long, hide = true)]
@@ -59,22 +174,528 @@ struct Cli {
 #[derive(Debug, Subcommand)]
 enum Commands {
     /// Check a file or directory (defaults to current directory)
-    Check {
-        /// Path to check (file or directory, defaults to current directory)
-        path: Option<PathBuf>,
-    },
+    Check(CheckArgs),
+    /// Insert suppression comments for the current violations of a file or directory
+    Suppress(SuppressArgs),
+    /// Record the current violations of a file or directory in a baseline file
+    Baseline(BaselineArgs),
+    /// Fix the mechanically fixable violations of a file or directory
+    Fix(FixArgs),
+    /// Describe a rule, with an example of the violations it reports
+    Explain(ExplainArgs),
+    /// Run a language server publishing diagnostics over stdio, for editors
+    Lsp(LspArgs),
+    /// Measure the throughput of checking generated files, with and without the cache
+    Bench(BenchArgs),
+    /// Combine the reports of several checks, such as CI shards, and print their violations
+    Merge(MergeArgs),
+    /// Check the bundled test fixtures, to confirm this binary reports the expected
+    /// violations on this platform
+    SelfTest,
+}
+
+/// Implementation of subcommand methods.
+impl Commands {
+    /// Default the path argument of the subcommand to `root` if it takes one and none was given.
+    ///
+    /// Returns whether it did.
+    fn default_path(&mut self, root: &Path) -> bool {
+        let path = match self {
+            Self::Check(args) => {
+                if !args.paths.is_empty() {
+                    return false;
+                }
+                args.paths.push(root.to_path_buf());
+                return true;
+            }
+            Self::Suppress(args) => &mut args.path,
+            Self::Baseline(args) => &mut args.path,
+            Self::Fix(args) => &mut args.path,
+            Self::Explain(_) | Self::Lsp(_) | Self::Bench(_) | Self::Merge(_) | Self::SelfTest => {
+                return false;
+            }
+        };
+        if path.is_some() {
+            return false;
+        }
+        *path = Some(root.to_path_buf());
+        true
+    }
+}
+
+/// Arguments for the check subcommand.
+#[derive(Args, Debug)]
+struct CheckArgs {
+    /// Files, directories and glob patterns such as `src/**/*.rs` to check
+    ///
+    /// Defaults to the current directory; the configuration is searched for from the first one
+    #[arg(value_name = "PATH")]
+    paths: Vec<PathBuf>,
+
+    #[command(flatten)]
+    analyzer: AnalyzerArgs,
+
+    /// Report the time spent parsing and checking each file, the slowest files and totals on stderr
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "text"
+    )]
+    timings: Option<OutputFormat>,
+
+    /// Report only violations of rules with these code prefixes or categories (e.g.
+    /// `D4,R401` or `category:markdown`); selecting an opt-in rule by its full code
+    /// enables it
+    #[arg(long, value_name = "PREFIX", value_delimiter = ',', value_parser = parse_rule_prefix)]
+    select: Vec<String>,
+
+    /// Don't report violations of rules with these code prefixes or categories (e.g.
+    /// `D401` or `category:grammar`); the longest prefix matching a rule decides
+    /// between `--select` and `--ignore`, and categories are the least specific
+    #[arg(long, value_name = "PREFIX", value_delimiter = ',', value_parser = parse_rule_prefix)]
+    ignore: Vec<String>,
+
+    /// Don't report violations recorded in this baseline file (see the baseline subcommand)
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+
+    /// Print the number of violations of each rule and the coverage of each top-level module
+    ///
+    /// The counts come with an example message, and are printed after the violations
+    #[arg(long)]
+    statistics: bool,
+
+    /// Write a JSON report of the check to this file
+    ///
+    /// The merge subcommand can combine it with the reports of other shards
+    #[arg(long, value_name = "FILE")]
+    report: Option<PathBuf>,
+
+    /// Write the violations to this file in the chosen format, instead of stdout
+    ///
+    /// Stdout is left to statistics
+    #[arg(long, value_name = "FILE", conflicts_with = "watch")]
+    output: Option<PathBuf>,
+
+    /// Check only the files of this shard, written as `INDEX/COUNT` (e.g. `2/8`)
+    ///
+    /// This splits a check across CI jobs; files are assigned by the hash of their path, so
+    /// every job must check the same path
+    #[arg(long, value_name = "INDEX/COUNT", conflicts_with = "watch")]
+    shard: Option<Shard>,
+
+    /// Keep running after the check, re-checking files whenever they change
+    #[arg(long)]
+    watch: bool,
+
+    /// Add suppression comments for every violation the check would report, instead of reporting
+    ///
+    /// This lets a codebase adopt pep257 with no failing checks
+    #[arg(long, conflicts_with_all = ["watch", "baseline"])]
+    add_noqa: bool,
+
+    /// Check every file, instead of reusing the cached results of unchanged files
+    #[arg(long)]
+    no_cache: bool,
+
+    /// File to cache results in
+    ///
+    /// Defaults to `.pep257-cache/results.json` next to the configuration file, or in the
+    /// current directory
+    #[arg(long, value_name = "FILE", conflicts_with = "no_cache")]
+    cache_file: Option<PathBuf>,
+}
+
+/// Arguments for the suppress subcommand.
+#[derive(Args, Debug)]
+struct SuppressArgs {
+    /// Path to suppress violations in (file or directory, defaults to current directory)
+    path: Option<PathBuf>,
+
+    #[command(flatten)]
+    analyzer: AnalyzerArgs,
+
+    /// Suppress warnings as well as errors
+    #[arg(long)]
+    all: bool,
+
+    /// Write the suppression comments into the files instead of listing them
+    #[arg(long)]
+    in_place: bool,
+}
+
+/// Arguments for the baseline subcommand.
+#[derive(Args, Debug)]
+struct BaselineArgs {
+    /// Path to record violations of (file or directory, defaults to current directory)
+    path: Option<PathBuf>,
+
+    #[command(flatten)]
+    analyzer: AnalyzerArgs,
+
+    /// Baseline file to write
+    #[arg(short, long, value_name = "FILE", default_value = "pep257-baseline.json")]
+    output: PathBuf,
+}
+
+/// Arguments for the fix subcommand.
+#[derive(Args, Debug)]
+struct FixArgs {
+    /// Path to fix (file, directory or glob pattern, defaults to current directory)
+    path: Option<PathBuf>,
+
+    #[command(flatten)]
+    analyzer: AnalyzerArgs,
+
+    /// Fix only violations of rules with these code prefixes (e.g. `D400,D403`)
+    #[arg(long, value_name = "PREFIX", value_delimiter = ',', value_parser = parse_rule_prefix)]
+    select: Vec<String>,
+
+    /// Don't fix violations of rules with these code prefixes (e.g. `R402`)
+    #[arg(long, value_name = "PREFIX", value_delimiter = ',', value_parser = parse_rule_prefix)]
+    ignore: Vec<String>,
+
+    /// List the files that would be fixed without writing them
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Insert doc comment templates for undocumented public items
+    ///
+    /// Templates have a summary placeholder and `# Arguments`, `# Errors` and `# Safety`
+    /// sections as needed
+    #[arg(long)]
+    add_missing_docs: bool,
+}
+
+/// Arguments for the explain subcommand.
+#[derive(Args, Debug)]
+struct ExplainArgs {
+    /// Code of the rule to describe (e.g. `D400`)
+    #[arg(value_parser = parse_rule_code)]
+    rule: &'static Rule,
+
+    /// Show how the rule's automatic fix changes the example
+    #[arg(long)]
+    show_fix: bool,
+}
+
+/// Parse the code of a registered rule.
+fn parse_rule_code(value: &str) -> Result<&'static Rule, String> {
+    find_rule(&value.trim().to_ascii_uppercase()).ok_or_else(|| format!("unknown rule `{value}`"))
+}
+
+/// Arguments for the lsp subcommand.
+#[derive(Args, Debug)]
+struct LspArgs {
+    #[command(flatten)]
+    analyzer: AnalyzerArgs,
+}
+
+/// Arguments for the bench subcommand.
+#[derive(Args, Debug)]
+struct BenchArgs {
+    /// Number of synthetic modules to generate and check
+    #[arg(long, value_name = "FILES")]
+    synthetic: usize,
+
+    /// Directory to generate the files in and keep afterwards
+    ///
+    /// Defaults to a temporary directory that is removed
+    #[arg(long, value_name = "DIR")]
+    dir: Option<PathBuf>,
+
+    #[command(flatten)]
+    analyzer: AnalyzerArgs,
+}
+
+/// Arguments for the merge subcommand.
+#[derive(Args, Debug)]
+struct MergeArgs {
+    /// Report files written by `check --report`
+    #[arg(required = true, value_name = "REPORT")]
+    reports: Vec<PathBuf>,
+
+    /// Write the merged report to this file
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+/// Arguments that control how source files are analyzed.
+#[derive(Args, Debug)]
+struct AnalyzerArgs {
+    /// Treat every `pub` item as public, ignoring the visibility of enclosing modules and types
+    #[arg(long)]
+    syntactic_visibility: bool,
+
+    /// Skip items behind `#[cfg(...)]` gates, including items in gated modules
+    #[arg(long, conflicts_with = "only_cfg")]
+    skip_cfg_gated: bool,
+
+    /// Check cfg-gated items only when their gates match this predicate; may be repeated
+    ///
+    /// For example `doc`, `unix` or `feature = "serde"`
+    #[arg(long, value_name = "PREDICATE")]
+    only_cfg: Vec<String>,
+
+    /// Don't suggest enabling the `missing_docs` lint in crate roots (R104)
+    #[arg(long)]
+    no_lint_suggestion: bool,
+
+    /// Require feature-gated items to have `#[doc(cfg(...))]` (R404)
+    ///
+    /// Otherwise mentioning the feature in their docs is enough
+    #[arg(long)]
+    require_doc_cfg: bool,
+
+    /// Maximum lines of prose a docstring may have without a section heading (R407)
+    ///
+    /// Either for all items or for one target type (e.g. `40` or `module=80`); `0` disables the
+    /// check; may be repeated
+    #[arg(long, value_name = "[TARGET=]LINES", value_parser = parse_prose_limit)]
+    max_prose_lines: Vec<(Option<String>, usize)>,
+
+    /// Require every bullet list item to use this marker (R408)
+    ///
+    /// Without it, only lists that mix markers are flagged
+    #[arg(long, value_enum, value_name = "MARKER")]
+    bullet_marker: Option<BulletMarker>,
+
+    /// How to check items marked `#[doc(hidden)]` and the items inside them
+    ///
+    /// Rustdoc leaves them out, so by default only the missing docstring rules skip them
+    #[arg(long, value_enum, value_name = "POLICY")]
+    doc_hidden: Option<HiddenPolicy>,
+
+    /// Enable an opt-in rule; may be repeated or comma-separated
+    #[arg(
+        long,
+        value_name = "RULE",
+        value_delimiter = ',',
+        value_parser = PossibleValuesParser::new(OPT_IN_RULES)
+    )]
+    enable: Vec<String>,
+
+    /// Word that public async function docs should mention to document cancellation (R412)
+    ///
+    /// Defaults to `cancel`; may be repeated
+    #[arg(long, value_name = "WORD")]
+    async_keyword: Vec<String>,
+
+    /// Word that the docs of default trait methods should mention (R414)
+    ///
+    /// Defaults to `default`; may be repeated
+    #[arg(long, value_name = "WORD")]
+    default_method_keyword: Vec<String>,
+
+    /// Macro or method that makes a function panic (R416), such as `panic!` or `unwrap`
+    ///
+    /// Defaults to the `panic!` and `assert!` macros, `unwrap` and `expect`; may be repeated
+    #[arg(long, value_name = "NAME")]
+    panic_construct: Vec<String>,
+
+    /// Target type whose public items should include a code example (R417)
+    ///
+    /// Defaults to `function` and `struct`; may be repeated
+    #[arg(long, value_name = "TARGET", value_parser = parse_example_target)]
+    example_target: Vec<String>,
+
+    /// Minimum number of non-blank lines crate docs should have (R503), instead of 10
+    #[arg(long, value_name = "LINES")]
+    crate_docs_min_lines: Option<usize>,
+
+    /// Maximum number of blank lines between the summary and the description (R202)
+    ///
+    /// Defaults to 1
+    #[arg(long, value_name = "LINES")]
+    max_summary_blank_lines: Option<usize>,
+
+    /// Pin the rule set to this version
+    ///
+    /// Violations of rules and kinds of items added after it get info severity, which doesn't
+    /// fail the check
+    #[arg(long, value_name = "VERSION", value_parser = clap::value_parser!(u32).range(1..=i64::from(RULES_VERSION)))]
+    rules_version: Option<u32>,
+
+    /// How to report `pub use` re-exports without docs (R105)
+    ///
+    /// Either always, or only when an item they re-export isn't documented in the checked files
+    #[arg(long, value_enum, value_name = "POLICY", default_value_t = ReexportPolicy::RequireDocs)]
+    reexports: ReexportPolicy,
+
+    /// Unit used to count columns in reported positions; editors using LSP expect `utf-16`
+    #[arg(long, value_enum, default_value_t = ColumnUnit::Utf8)]
+    column_unit: ColumnUnit,
+
+    /// Load the tree-sitter Rust grammar from this shared library instead of the bundled one
+    ///
+    /// For example to support newer syntax
+    #[arg(long, value_name = "PATH")]
+    grammar: Option<PathBuf>,
+
+    /// Also skip files matched by gitignore-syntax files with this name; may be repeated
+    ///
+    /// These are skipped besides those matched by `.gitignore`, `.ignore` and `.pep257ignore`
+    #[arg(long, value_name = "NAME")]
+    ignore_file: Vec<String>,
+
+    /// Skip paths matching this gitignore-syntax pattern; may be repeated
+    ///
+    /// Patterns are relative to the current directory, such as `src/generated/**`
+    #[arg(long, value_name = "PATTERN")]
+    exclude: Vec<String>,
+
+    /// Parse files as this edition of Rust, instead of the edition of their crate
+    ///
+    /// The edition of a crate is read from its `Cargo.toml`
+    #[arg(long, value_enum)]
+    edition: Option<Edition>,
+
+    /// Number of files to check in parallel, instead of one per available CPU
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<NonZeroUsize>,
+}
+
+/// Parse a `--max-prose-lines` value of the form `LINES` or `TARGET=LINES`.
+fn parse_prose_limit(value: &str) -> Result<(Option<String>, usize), String> {
+    let (target, limit) = match value.split_once('=') {
+        Some((target, limit)) => (Some(target.trim().to_string()), limit),
+        None => (None, value),
+    };
+    let limit = limit.trim().parse().map_err(|e| format!("invalid line count `{limit}`: {e}"))?;
+    if let Some(target) = &target {
+        ProseLimits::default().set(target, limit)?;
+    }
+    Ok((target, limit))
+}
+
+/// Parse an `--example-target` value, a target type such as `function`.
+fn parse_example_target(value: &str) -> Result<String, String> {
+    ExampleTargets::default().add(value)?;
+    Ok(value.to_string())
+}
+
+/// Build analyzer options from the analysis arguments.
+impl From<&AnalyzerArgs> for AnalyzerOptions {
+    /// Convert the command-line flags into analyzer options.
+    fn from(args: &AnalyzerArgs) -> Self {
+        let cfg_gated = if args.skip_cfg_gated {
+            CfgPolicy::SkipAll
+        } else if !args.only_cfg.is_empty() {
+            CfgPolicy::CheckOnly(args.only_cfg.clone())
+        } else {
+            CfgPolicy::CheckAll
+        };
+
+        let mut prose_limits = ProseLimits::default();
+        for (target, limit) in &args.max_prose_lines {
+            match target {
+                // Targets were validated when the arguments were parsed
+                Some(target) => prose_limits.set(target, *limit).unwrap_or_default(),
+                None => prose_limits.default = *limit,
+            }
+        }
+
+        let mut example_targets = ExampleTargets::default();
+        for target in &args.example_target {
+            // Targets were validated when the arguments were parsed
+            example_targets.add(target).unwrap_or_default();
+        }
+
+        let checker = CheckerOptions {
+            suggest_missing_docs_lint: !args.no_lint_suggestion,
+            require_doc_cfg: args.require_doc_cfg,
+            prose_limits,
+            bullet_marker: args.bullet_marker,
+            enabled_rules: args.enable.clone(),
+            async_keywords: args.async_keyword.clone(),
+            default_method_keywords: args.default_method_keyword.clone(),
+            panic_constructs: args.panic_construct.clone(),
+            example_targets,
+            crate_docs_min_lines: args.crate_docs_min_lines,
+            max_summary_blank_lines: args.max_summary_blank_lines,
+            doc_hidden: args.doc_hidden,
+        };
+
+        Self {
+            syntactic_visibility: args.syntactic_visibility,
+            cfg_gated,
+            checker,
+            column_unit: args.column_unit,
+            grammar: args.grammar.clone(),
+            severities: Vec::new(),
+            rules_version: args.rules_version,
+            edition: args.edition,
+            rules: RuleSelection::default(),
+            per_file_ignores: PerFileIgnores::default(),
+            reexports: args.reexports,
+        }
+    }
+}
+
+/// Destination of the printed violations.
+struct Output {
+    /// Writer the violations go to.
+    writer: Box<dyn Write>,
+    /// Whether to color text output with ANSI escape sequences.
+    color: bool,
+}
+
+/// Implementation of output methods.
+impl Output {
+    /// Return output to stdout, colored as chosen.
+    fn stdout(color: ColorChoice) -> Self {
+        let color = match color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+            }
+        };
+        Self { writer: Box::new(io::stdout()), color }
+    }
+
+    /// Return output to a new file, colored only if colors are always chosen.
+    fn file(path: &Path, color: ColorChoice) -> Result<Self, String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Couldn't create output file {}: {e}", path.display()))?;
+        Ok(Self { writer: Box::new(BufWriter::new(file)), color: color == ColorChoice::Always })
+    }
+
+    /// Return text in a style if the output is colored, or as it is otherwise.
+    fn paint(&self, text: &str, style: Style) -> String {
+        if self.color { format!("{style}{text}{style:#}") } else { text.to_string() }
+    }
+}
+
+/// Writing of violations to the output's writer.
+impl Write for Output {
+    /// Write to the underlying writer.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    /// Flush the underlying writer.
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
 }
 
 /// Output format options.
-#[derive(Clone, Debug, ValueEnum)]
+#[derive(Clone, Copy, Debug, ValueEnum)]
 enum OutputFormat {
     Text,
     Json,
+    Codeclimate,
+    RustcJson,
+    ActionsJson,
 }
 
-/// Entry point for the application.
-fn main() {
-    let cli = Cli::parse();
+/// Entry point for the application, shared with `cargo pep257`.
+pub(crate) fn main() {
+    let (mut cli, cargo_subcommand) = parse_args();
 
     #[cfg(feature = "clap-markdown")]
     if cli.markdown_help {
@@ -85,29 +706,307 @@ fn main() {
     // Initialize the logger based on verbosity level
     env_logger::Builder::new().filter_level(cli.verbose.into()).init();
 
-    if let Err(e) = run(&cli) {
+    let workspace = if cargo_subcommand || cli.manifest_path.is_some() {
+        use_workspace(&mut cli)
+    } else {
+        Ok(())
+    };
+    if let Err(e) = workspace.and_then(|()| run(&cli)) {
         eprintln!("Error: {e}");
-        process::exit(1);
+        process::exit(EXIT_FAILURE);
+    }
+}
+
+/// Parse the command line, returning whether the program runs as `cargo pep257`.
+///
+/// Cargo passes the subcommand name as the first argument, and a bare `cargo pep257`
+/// runs the check subcommand.
+fn parse_args() -> (Cli, bool) {
+    let mut args: Vec<OsString> = env::args_os().collect();
+    if args.get(1).is_none_or(|arg| arg != "pep257") {
+        return (Cli::try_parse_from(args).unwrap_or_else(|e| exit_usage(&e)), false);
+    }
+
+    args.remove(1);
+    let parse = |args: &[OsString]| {
+        Cli::command()
+            .bin_name("cargo pep257")
+            .try_get_matches_from(args)
+            .and_then(|matches| Cli::from_arg_matches(&matches))
+            .unwrap_or_else(|e| exit_usage(&e))
+    };
+    let mut cli = parse(&args);
+    if cli.command.is_none() {
+        args.push("check".into());
+        cli = parse(&args);
     }
+    (cli, true)
+}
+
+/// Print a command-line parsing error, or the requested help or version, and exit.
+///
+/// Usage errors exit with the code of failures rather than clap's 2, which is the
+/// code of runs with warnings.
+fn exit_usage(error: &clap::Error) -> ! {
+    let _ = error.print();
+    process::exit(if error.use_stderr() { EXIT_FAILURE } else { 0 });
+}
+
+/// Default the path of the subcommand to the root of the Cargo workspace.
+///
+/// Paths are then printed relative to the current directory, unless `--path-style`
+/// is given.
+fn use_workspace(cli: &mut Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let root = workspace_root(cli.manifest_path.as_deref())?;
+    if cli.command.as_mut().is_some_and(|command| command.default_path(&root)) {
+        cli.path_style.get_or_insert(PathStyle::Relative);
+    }
+    Ok(())
 }
 
 /// Run the main logic of the application.
 fn run(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
-    let mut analyzer = RustDocAnalyzer::new()?;
-    let mut total_violations = 0;
+    let exit_code;
 
     match &cli.command {
-        Some(Commands::Check { path }) => {
-            let target_path = path.clone().unwrap_or_else(|| PathBuf::from("."));
+        Some(Commands::Check(args)) => {
+            // Statistics would break up the single document of these formats on stdout
+            if args.statistics
+                && args.output.is_none()
+                && matches!(
+                    cli.format,
+                    OutputFormat::Codeclimate | OutputFormat::RustcJson | OutputFormat::ActionsJson
+                )
+            {
+                return Err(format!(
+                    "`--statistics` can't be combined with `--format {}`",
+                    cli.format.to_possible_value().unwrap().get_name()
+                )
+                .into());
+            }
+            if matches!(cli.format, OutputFormat::RustcJson)
+                && args.analyzer.column_unit != ColumnUnit::Utf8
+            {
+                return Err("`--format rustc-json` counts columns in characters like rustc, \
+                            so it can't be combined with `--column-unit`"
+                    .into());
+            }
+            let start = Instant::now();
+            let config = load_config(cli, args.paths.first().map(PathBuf::as_path))?;
+            let rules = RuleSelection { select: args.select.clone(), ignore: args.ignore.clone() };
+            let mut analyzer = create_analyzer(&args.analyzer, &config, rules)?;
+            let setup = start.elapsed();
+            let target_paths =
+                if args.paths.is_empty() { vec![PathBuf::from(".")] } else { args.paths.clone() };
+            let mut timings = Vec::new();
+            let mut statistics = Statistics::default();
+            let mut baseline = args.baseline.as_ref().map(Baseline::load).transpose()?;
+            // Checks use up baseline entries, so each re-check starts from the loaded ones
+            let loaded_baseline = baseline.clone().filter(|_| args.watch);
+
+            let collect =
+                config.collect_options(&args.analyzer.ignore_file, &args.analyzer.exclude);
+            let mut files = target_files_of_paths(&analyzer, &collect, &target_paths)?;
+            if let Some(shard) = args.shard {
+                files.retain(|file| shard.contains(file));
+            }
+            if args.add_noqa {
+                // The comments cover what the check reports, so the next check passes
+                let (comments, changed_files) =
+                    suppress_files(&mut analyzer, &files, cli.warnings, true, cli.path_style)?;
+                eprintln!("Added {comments} suppression comments to {changed_files} files");
+                return Ok(());
+            }
+            let mut cache = (!args.no_cache).then(|| {
+                let path = args.cache_file.clone().unwrap_or_else(|| config.cache_file());
+                analyzer.load_cache(&path)
+            });
+            // Watch mode runs until interrupted, so Ctrl-C keeps ending it right away
+            if !args.watch {
+                analyzer = analyzer.with_interrupt(interrupt_on_ctrl_c());
+            }
+            let mut output = match &args.output {
+                Some(path) => Output::file(path, cli.color)?,
+                None => Output::stdout(cli.color),
+            };
+            let results = check_files(
+                &mut analyzer,
+                &files,
+                cli,
+                baseline.as_mut(),
+                cache.as_mut(),
+                &mut timings,
+                &mut statistics,
+                &mut output,
+            )?;
+            // Exiting below skips destructors, which would flush the file
+            output.flush()?;
+            exit_code =
+                violations_exit_code(&results.statistics(), cli, &fail_policy(cli, &config));
+            if let Some(path) = &args.report {
+                results.save(path)?;
+            }
+            if let Some(cache) = &cache
+                && let Err(e) = cache.save()
+            {
+                log::warn!("Couldn't write the result cache: {e}");
+            }
+
+            if args.statistics {
+                report_statistics(&statistics, cli.format)?;
+            }
+            if let Some(format) = args.timings {
+                report_timings(&timings, setup, start.elapsed(), format, cli.path_style)?;
+            }
+            let checked = results.files.len() + results.errors.len();
+            if checked < files.len() {
+                eprintln!("Run interrupted after {checked} of {} files", files.len());
+                process::exit(EXIT_INTERRUPTED);
+            }
+            if args.watch {
+                watch(&mut analyzer, &collect, &target_paths, cli, loaded_baseline.as_ref())?;
+            }
+            if !results.errors.is_empty() {
+                return Err(format!("{} files couldn't be checked", results.errors.len()).into());
+            }
+        }
+        Some(Commands::Suppress(args)) => {
+            let config = load_config(cli, args.path.as_deref())?;
+            let mut analyzer = create_analyzer(&args.analyzer, &config, RuleSelection::default())?;
+            let collect =
+                config.collect_options(&args.analyzer.ignore_file, &args.analyzer.exclude);
+            let files = target_files(&analyzer, &collect, args.path.as_deref())?;
+
+            let (comments, changed_files) = suppress_files(
+                &mut analyzer,
+                &files,
+                args.all || cli.warnings,
+                args.in_place,
+                cli.path_style,
+            )?;
+
+            let action = if args.in_place { "Added" } else { "Would add" };
+            eprintln!("{action} {comments} suppression comments to {changed_files} files");
+            return Ok(());
+        }
+        Some(Commands::Baseline(args)) => {
+            let config = load_config(cli, args.path.as_deref())?;
+            let mut analyzer = create_analyzer(&args.analyzer, &config, RuleSelection::default())?;
+            let collect =
+                config.collect_options(&args.analyzer.ignore_file, &args.analyzer.exclude);
+            let files = target_files(&analyzer, &collect, args.path.as_deref())?;
+
+            let mut baseline = Baseline::default();
+            for report in analyzer.analyze_files(&files) {
+                let target = analyzer.target(&report.path);
+                for violation in &report.result? {
+                    baseline.record(&target, violation);
+                }
+            }
+            baseline.save(&args.output)?;
+
+            eprintln!("Recorded {} violations in {}", baseline.len(), args.output.display());
+            return Ok(());
+        }
+        Some(Commands::Fix(args)) => {
+            let config = load_config(cli, args.path.as_deref())?;
+            let rules = RuleSelection { select: args.select.clone(), ignore: args.ignore.clone() };
+            let mut analyzer = create_analyzer(&args.analyzer, &config, rules)?
+                .with_doc_templates(args.add_missing_docs);
+            let collect =
+                config.collect_options(&args.analyzer.ignore_file, &args.analyzer.exclude);
+            let files = target_files(&analyzer, &collect, args.path.as_deref())?;
+
+            let mut fixes: BTreeMap<String, usize> = BTreeMap::new();
+            let mut changed_files = 0;
+            for file in &files {
+                let fixed = analyzer.fix_file(file, args.dry_run)?;
+                if fixed.total() > 0 {
+                    println!("{}: {} fixes", display_path(file, cli.path_style), fixed.total());
+                    changed_files += 1;
+                }
+                for (rule, count) in fixed.fixes {
+                    *fixes.entry(rule).or_default() += count;
+                }
+            }
 
-            if target_path.is_file() {
-                total_violations += check_file(&mut analyzer, &target_path, cli)?;
-            } else if target_path.is_dir() {
-                total_violations += check_directory(&mut analyzer, &target_path, cli)?;
-            } else {
-                eprintln!("Path does not exist: {}", target_path.display());
-                process::exit(1);
+            let action = if args.dry_run { "Would fix" } else { "Fixed" };
+            let total: usize = fixes.values().sum();
+            eprintln!("{action} {total} violations in {changed_files} files");
+            for (rule, count) in &fixes {
+                eprintln!("  {rule}  {count}");
             }
+            return Ok(());
+        }
+        Some(Commands::Explain(args)) => {
+            explain(args.rule, args.show_fix)?;
+            return Ok(());
+        }
+        Some(Commands::SelfTest) => return self_test(),
+        Some(Commands::Bench(args)) => {
+            let dir = args
+                .dir
+                .clone()
+                .unwrap_or_else(|| env::temp_dir().join(format!("pep257-bench-{}", process::id())));
+            let result = bench(args, &dir);
+            if args.dir.is_none() {
+                let _ = fs::remove_dir_all(&dir);
+            }
+            return result;
+        }
+        Some(Commands::Merge(args)) => {
+            if matches!(cli.format, OutputFormat::ActionsJson) {
+                return Err("`--format actions-json` needs the checked sources, \
+                            so it can't be used with `merge`"
+                    .into());
+            }
+            let mut merged = Report::default();
+            for (index, path) in args.reports.iter().enumerate() {
+                let report = Report::load(path)
+                    .map_err(|e| format!("{}: {e}", display_path(path, cli.path_style)))?;
+                if index == 0 {
+                    merged.tool = report.tool.clone();
+                }
+                merged.merge(report)?;
+            }
+            if let Some(output) = &args.output {
+                merged.save(output)?;
+            }
+
+            let mut output = Output::stdout(cli.color);
+            for file in &merged.files {
+                // Reports don't record the column unit of their violations
+                print_file(file, cli, ColumnUnit::Utf8, &mut output)?;
+            }
+            if matches!(cli.format, OutputFormat::Codeclimate) {
+                print_issues(&merged, &mut output)?;
+            }
+            for error in &merged.errors {
+                eprintln!("Error: {}: {}", error.path, error.message);
+            }
+
+            let statistics = merged.statistics();
+            eprintln!(
+                "Merged {} reports with {} violations in {} files",
+                args.reports.len(),
+                statistics.violations,
+                statistics.files
+            );
+            if statistics.errors > 0 {
+                return Err(format!("{} files couldn't be checked", statistics.errors).into());
+            }
+            let config = load_config(cli, None)?;
+            exit_code = violations_exit_code(&statistics, cli, &fail_policy(cli, &config));
+        }
+        Some(Commands::Lsp(args)) => {
+            let config = load_config(cli, None)?;
+            let mut options = AnalyzerOptions::from(&args.analyzer);
+            config.apply(&mut options);
+            // The protocol counts columns in UTF-16 code units
+            options.column_unit = ColumnUnit::Utf16;
+            let mut server = LanguageServer::new(RustDocAnalyzer::with_options(options)?);
+            server.serve(&mut io::stdin().lock(), &mut io::stdout().lock())?;
+            return Ok(());
         }
         None => {
             // Show help when no command is provided
@@ -116,68 +1015,744 @@ fn run(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    if total_violations > 0 && !cli.no_fail {
-        process::exit(1);
+    if exit_code != 0 {
+        process::exit(exit_code);
     }
 
     Ok(())
 }
 
-/// Check a single file for violations.
-fn check_file(
+/// Return a flag that is set when the user presses Ctrl-C.
+///
+/// A second Ctrl-C exits without waiting for the files being checked.
+fn interrupt_on_ctrl_c() -> Arc<AtomicBool> {
+    let interrupt = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&interrupt);
+    let handler = ctrlc::set_handler(move || {
+        if flag.swap(true, Ordering::Relaxed) {
+            process::exit(EXIT_INTERRUPTED);
+        }
+    });
+    if let Err(e) = handler {
+        log::warn!("Couldn't handle Ctrl-C: {e}");
+    }
+    interrupt
+}
+
+/// Return the policy deciding which violations fail a run.
+///
+/// The policy comes from the command line or the configuration.
+fn fail_policy(cli: &Cli, config: &Config) -> FailPolicy {
+    let mut policy = match &cli.fail_on {
+        Some(names) => FailPolicy {
+            fail_on: names.iter().filter_map(|name| Severity::from_name(name)).collect(),
+        },
+        None => config.fail_policy(),
+    };
+    if cli.no_fail {
+        policy.fail_on.clear();
+    }
+    if cli.no_fail_on_warnings {
+        policy.fail_on.retain(|&severity| severity != Severity::Warning);
+    }
+    policy
+}
+
+/// Return the exit code for the violations of a run.
+///
+/// Errors take precedence over other severities, and only severities the policy
+/// fails on count. A count within `--max-violations` is a success.
+fn violations_exit_code(statistics: &ReportStatistics, cli: &Cli, policy: &FailPolicy) -> i32 {
+    if let Some(max) = cli.max_violations {
+        if statistics.violations <= max {
+            return 0;
+        }
+        eprintln!("Found {} violations, more than the maximum of {max}", statistics.violations);
+    }
+    match policy.failure(statistics) {
+        Some(Severity::Error) => EXIT_ERRORS,
+        Some(Severity::Warning | Severity::Info) => EXIT_WARNINGS,
+        None => 0,
+    }
+}
+
+/// Check a generated crate three times, printing the throughput of each run.
+///
+/// The first run checks every file, the second fills the cache and the third reuses it.
+fn bench(args: &BenchArgs, dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let files = write_synthetic_crate(dir, args.synthetic)?;
+    let lines: usize = files
+        .iter()
+        .map(|file| fs::read_to_string(file).map(|source| source.lines().count()))
+        .sum::<io::Result<_>>()?;
+    println!("Generated {} files with {lines} lines in {}", files.len(), dir.display());
+
+    let mut analyzer =
+        create_analyzer(&args.analyzer, &Config::default(), RuleSelection::default())?;
+    let cache_path = cache::default_path(dir);
+    let _ = fs::remove_file(&cache_path);
+    let mut cache = analyzer.load_cache(&cache_path);
+    for run in ["uncached", "cache cold", "cache warm"] {
+        let start = Instant::now();
+        let reports = if run == "uncached" {
+            analyzer.analyze_files(&files)
+        } else {
+            analyzer.analyze_files_cached(&files, &mut cache)
+        };
+        let elapsed = start.elapsed();
+        for report in reports {
+            report.result?;
+        }
+
+        let seconds = elapsed.as_secs_f64();
+        // File and line counts are far below the precision limit of f64
+        #[allow(clippy::cast_precision_loss)]
+        let (files_per_second, lines_per_second) =
+            (files.len() as f64 / seconds, lines as f64 / seconds);
+        println!(
+            "  {run:<10}  {:>10.3}ms  {files_per_second:>10.0} files/s  {lines_per_second:>10.0} lines/s",
+            seconds * 1000.0
+        );
+    }
+    Ok(())
+}
+
+/// Check the bundled fixtures, printing whether each reported its annotated violations.
+fn self_test() -> Result<(), Box<dyn std::error::Error>> {
+    let reports = testing::self_test()?;
+    let failed = reports.iter().filter(|(_, report)| !report.is_ok()).count();
+    for (name, report) in &reports {
+        if report.is_ok() {
+            println!("ok {name}");
+        } else {
+            println!("FAILED {name}");
+            for line in report.to_string().lines() {
+                println!("    {line}");
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{failed} of {} fixtures failed", reports.len()).into());
+    }
+    println!("All {} fixtures passed", reports.len());
+    Ok(())
+}
+
+/// Print the description of a rule, its example and, optionally, the example's fix.
+fn explain(rule: &Rule, show_fix: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let categories: Vec<&str> = rule.categories.iter().map(|category| category.name()).collect();
+    let fixable = FIXABLE_RULES.contains(&rule.code);
+    println!("{}: {}", rule.code, rule.summary);
+    println!("Categories: {}", categories.join(", "));
+    println!("Fixable: {}", if fixable { "yes" } else { "no" });
+    println!("Since: rule set version {}", rule.since);
+    if OPT_IN_RULES.contains(&rule.code) {
+        println!("Opt-in: enable with `--enable {}`", rule.code);
+    }
+
+    if let Some(example) = rule.example {
+        println!("\nExample:");
+        for line in example.lines() {
+            println!("    {line}");
+        }
+    }
+
+    if show_fix && !fixable {
+        println!("\n{} has no automatic fix", rule.code);
+    } else if let Some(example) = rule.example.filter(|_| show_fix) {
+        let rules = RuleSelection { select: vec![rule.code.to_string()], ignore: Vec::new() };
+        let mut analyzer =
+            RustDocAnalyzer::with_options(AnalyzerOptions { rules, ..AnalyzerOptions::default() })?;
+        let fixed = analyzer.fix_source(example, None)?;
+        println!("\nFix:\n--- before\n+++ after");
+        print!("{}", line_diff(example, &fixed.source));
+    }
+    Ok(())
+}
+
+/// Load the configuration given on the command line, or the one for a checked path.
+///
+/// Without a configuration file, the default settings are used.
+fn load_config(cli: &Cli, path: Option<&Path>) -> Result<Config, Box<dyn std::error::Error>> {
+    if let Some(config) = &cli.config {
+        return Ok(Config::load(config)?);
+    }
+    let target_path = match path {
+        Some(path) if is_glob(path) && !path.exists() => &glob_base(path),
+        Some(path) => path,
+        None => Path::new("."),
+    };
+    if !target_path.exists() {
+        return Ok(Config::default()); // Reported when the path is checked
+    }
+    Ok(Config::discover(target_path)?.unwrap_or_default())
+}
+
+/// Create an analyzer from the command-line flags and the configuration.
+///
+/// Opt-in rules selected by their full code are enabled.
+fn create_analyzer(
+    args: &AnalyzerArgs,
+    config: &Config,
+    rules: RuleSelection,
+) -> Result<RustDocAnalyzer, Box<dyn std::error::Error>> {
+    let mut options = AnalyzerOptions::from(args);
+    config.apply(&mut options);
+    for rule in OPT_IN_RULES {
+        if rules.selects_exactly(rule) && !options.checker.is_enabled(rule) {
+            options.checker.enabled_rules.push((*rule).to_string());
+        }
+    }
+    if options.checker.is_enabled("D212") && options.checker.is_enabled("D213") {
+        return Err("D212 and D213 ask for opposite summary positions, enable only one".into());
+    }
+    options.rules = rules;
+    let analyzer = RustDocAnalyzer::with_options(options)?;
+    Ok(match args.jobs {
+        Some(jobs) => analyzer.with_jobs(jobs),
+        None => analyzer,
+    })
+}
+
+/// Return the files to analyze for a path argument (defaults to current directory).
+fn target_files(
+    analyzer: &RustDocAnalyzer,
+    collect: &CollectOptions,
+    path: Option<&Path>,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let target_path = path.unwrap_or_else(|| Path::new("."));
+    if target_path.is_file() {
+        Ok(vec![target_path.to_path_buf()])
+    } else if target_path.is_dir() {
+        Ok(collect_files_with_options(&target_path.to_path_buf(), analyzer.extensions(), collect)?)
+    } else if is_glob(target_path) {
+        let files = collect_glob_files(target_path, analyzer.extensions(), collect)?;
+        if files.is_empty() {
+            eprintln!("No files match {}", target_path.display());
+            process::exit(EXIT_FAILURE);
+        }
+        Ok(files)
+    } else {
+        eprintln!("Path does not exist: {}", target_path.display());
+        process::exit(EXIT_FAILURE);
+    }
+}
+
+/// Return the files to check under several paths, each file once.
+fn target_files_of_paths(
+    analyzer: &RustDocAnalyzer,
+    collect: &CollectOptions,
+    paths: &[PathBuf],
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut seen = HashSet::new();
+    let mut files = Vec::new();
+    for path in paths {
+        for file in target_files(analyzer, collect, Some(path))? {
+            // Overlapping paths, such as `src/` and `src/lib.rs`, name a file once
+            if seen.insert(file.canonicalize().unwrap_or_else(|_| file.clone())) {
+                files.push(file);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Format a file path for output in the chosen style.
+fn display_path(file: &Path, path_style: Option<PathStyle>) -> String {
+    match path_style {
+        Some(style) => styled_path(file, style).display().to_string(),
+        None => file.display().to_string(),
+    }
+}
+
+/// Filter the violations of a file and print them, adding the file to the report.
+///
+/// Violations recorded in the baseline are not reported.
+fn report_file(
     analyzer: &mut RustDocAnalyzer,
-    file: &PathBuf,
+    file: &Path,
+    violations: Vec<Violation>,
+    edition: Option<Edition>,
+    coverage: Coverage,
     cli: &Cli,
+    column_unit: ColumnUnit,
+    baseline: Option<&mut Baseline>,
+    statistics: &mut Statistics,
+    report: &mut Report,
+    output: &mut Output,
 ) -> Result<usize, Box<dyn std::error::Error>> {
-    let violations = analyzer.analyze_file(file)?;
-
+    let mut baseline = baseline.map(|baseline| (analyzer.target(file), baseline));
     let filtered_violations: Vec<_> = violations
         .into_iter()
+        .filter(|v| {
+            !baseline.as_mut().is_some_and(|(target, baseline)| baseline.matches(target, v))
+        })
         .filter(|v| cli.warnings || matches!(v.severity, Severity::Error))
         .collect();
+    for violation in &filtered_violations {
+        *statistics.rules.entry(violation.rule.clone()).or_default() += 1;
+        statistics
+            .examples
+            .entry(violation.rule.clone())
+            .or_insert_with(|| violation.message.clone());
+    }
 
+    let result = FileResult {
+        path: display_path(file, cli.path_style),
+        edition,
+        coverage,
+        violations: filtered_violations,
+    };
+    print_file(&result, cli, column_unit, output)?;
+    if matches!(cli.format, OutputFormat::ActionsJson) {
+        // Files that aren't UTF-8 are reported rather than checked, with no fixes
+        let source = String::from_utf8_lossy(&fs::read(file)?).into_owned();
+        for object in violation_actions(analyzer, &result.path, file, &source, &result.violations)?
+        {
+            writeln!(output, "{object}")?;
+        }
+    }
+    let count = result.violations.len();
+    report.files.push(result);
+    Ok(count)
+}
+
+/// Print the reported violations of a file to `output`.
+///
+/// The columns of the violations count units of `column_unit`.
+fn print_file(
+    file: &FileResult,
+    cli: &Cli,
+    column_unit: ColumnUnit,
+    output: &mut Output,
+) -> Result<(), Box<dyn std::error::Error>> {
     match cli.format {
         OutputFormat::Text => {
-            for violation in &filtered_violations {
-                println!("{}:{}", file.display(), violation);
+            for violation in &file.violations {
+                let severity = severity_style(violation.severity);
+                writeln!(
+                    output,
+                    "{}:{}:{} {} [{}]: {}",
+                    output.paint(&file.path, Style::new().bold()),
+                    violation.line,
+                    violation.column,
+                    output.paint(violation.severity.name(), severity),
+                    output.paint(&violation.rule, Style::new().bold()),
+                    violation.message
+                )?;
+                if cli.show_source
+                    && let Some(line) = &violation.source_line
+                {
+                    // Tabs are kept, so the caret lines up however wide they're shown
+                    let indent: String = line
+                        .chars()
+                        .take(chars_before(line, violation.column, column_unit))
+                        .map(|c| if c == '\t' { '\t' } else { ' ' })
+                        .collect();
+                    writeln!(output, "{line}\n{indent}{}", output.paint("^", severity))?;
+                }
             }
         }
         OutputFormat::Json => {
             let json_output = serde_json::json!({
-                "file": file.display().to_string(),
-                "violations": filtered_violations.iter().map(|v| {
+                "file": file.path,
+                "edition": file.edition.map(|edition| edition.to_string()),
+                "violations": file.violations.iter().map(|v| {
                     serde_json::json!({
                         "rule": v.rule,
                         "message": v.message,
                         "line": v.line,
                         "column": v.column,
+                        "start_byte": v.start_byte,
+                        "end_byte": v.end_byte,
+                        "end_line": v.end_line,
+                        "end_column": v.end_column,
+                        "item_path": v.item_path,
                         "severity": match v.severity {
                             Severity::Error => "error",
                             Severity::Warning => "warning",
+                            Severity::Info => "info",
                         }
                     })
                 }).collect::<Vec<_>>()
             });
-            println!("{}", serde_json::to_string_pretty(&json_output)?);
+            writeln!(output, "{}", serde_json::to_string_pretty(&json_output)?)?;
+        }
+        OutputFormat::RustcJson => {
+            // Spans quote the source, which a merged report's files may not be next to
+            let source = fs::read_to_string(&file.path).ok();
+            for violation in &file.violations {
+                writeln!(output, "{}", diagnostic(&file.path, source.as_deref(), violation))?;
+            }
+        }
+        // Code Climate reports are a single JSON array, printed by `print_issues`, and
+        // actions need the file's fixes, printed by `report_file` with the analyzer
+        OutputFormat::Codeclimate | OutputFormat::ActionsJson => {}
+    }
+    Ok(())
+}
+
+/// Return the style of a severity in colored output.
+fn severity_style(severity: Severity) -> Style {
+    let color = match severity {
+        Severity::Error => AnsiColor::Red,
+        Severity::Warning => AnsiColor::Yellow,
+        Severity::Info => AnsiColor::Cyan,
+    };
+    Style::new().bold().fg_color(Some(color.into()))
+}
+
+/// Print the violations of a report to `output` as a JSON array of Code Climate issues.
+fn print_issues(report: &Report, output: &mut Output) -> Result<(), Box<dyn std::error::Error>> {
+    let mut issues = codeclimate::Report::default();
+    for file in &report.files {
+        for violation in &file.violations {
+            issues.add(&file.path, violation);
         }
     }
+    writeln!(output, "{}", serde_json::to_string_pretty(&issues.to_json())?)?;
+    Ok(())
+}
 
-    Ok(filtered_violations.len())
+/// Add suppression comments for the violations of files.
+///
+/// Returns how many are needed and in how many files.
+fn suppress_files(
+    analyzer: &mut RustDocAnalyzer,
+    files: &[PathBuf],
+    include_warnings: bool,
+    in_place: bool,
+    path_style: Option<PathStyle>,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let mut comments = 0;
+    let mut changed_files = 0;
+    for file in files {
+        let added = suppress_file(analyzer, file, include_warnings, in_place, path_style)?;
+        comments += added;
+        changed_files += usize::from(added > 0);
+    }
+    Ok((comments, changed_files))
 }
 
-/// Check all files in a directory recursively.
-fn check_directory(
+/// Add suppression comments for the violations of a file, returning how many are needed.
+///
+/// Without `in_place`, the comments are listed with the line they go above instead.
+fn suppress_file(
     analyzer: &mut RustDocAnalyzer,
-    dir: &PathBuf,
-    cli: &Cli,
+    file: &Path,
+    include_warnings: bool,
+    in_place: bool,
+    path_style: Option<PathStyle>,
 ) -> Result<usize, Box<dyn std::error::Error>> {
-    let mut total_violations = 0;
+    let source = fs::read_to_string(file)?;
+    let comments: Vec<(usize, Vec<String>)> = analyzer
+        .analyze_items(&source, Some(file))?
+        .into_iter()
+        .filter_map(|(line, violations)| {
+            let rules: Vec<String> = violations
+                .into_iter()
+                .filter(|v| include_warnings || matches!(v.severity, Severity::Error))
+                .map(|v| v.rule)
+                .collect();
+            (!rules.is_empty()).then_some((line, rules))
+        })
+        .collect();
 
-    let entries = collect_rust_files_recursive(dir)?;
+    if in_place {
+        if !comments.is_empty() {
+            fs::write(file, insert_comments(&source, &comments))?;
+        }
+    } else {
+        for (line, rules) in &comments {
+            println!("{}:{line}: {}", display_path(file, path_style), format_comment(rules));
+        }
+    }
+
+    Ok(comments.len())
+}
+
+/// Check a batch of files, such as those of a directory.
+///
+/// Files that can't be checked are reported on stderr and recorded in the report.
+fn check_files(
+    analyzer: &mut RustDocAnalyzer,
+    files: &[PathBuf],
+    cli: &Cli,
+    mut baseline: Option<&mut Baseline>,
+    cache: Option<&mut Cache>,
+    timings: &mut Vec<(PathBuf, FileTimings)>,
+    statistics: &mut Statistics,
+    output: &mut Output,
+) -> Result<Report, Box<dyn std::error::Error>> {
+    let mut results = Report::default();
+
+    let reports = match cache {
+        Some(cache) => analyzer.analyze_files_cached(files, cache),
+        None => analyzer.analyze_files(files),
+    };
+    for report in reports {
+        timings.push((report.path.clone(), report.timings));
+        let violations = match report.result {
+            Ok(violations) => violations,
+            Err(e) => {
+                let path = display_path(&report.path, cli.path_style);
+                eprintln!("Error: {path}: {e}");
+                results.errors.push(FileError { path, message: e.to_string() });
+                continue;
+            }
+        };
+        let edition = analyzer.edition(&report.path);
+        let module = analyzer.top_level_module(&report.path);
+        statistics.modules.entry(module).or_default().add(report.coverage);
+        report_file(
+            analyzer,
+            &report.path,
+            violations,
+            edition,
+            report.coverage,
+            cli,
+            analyzer.options().column_unit,
+            baseline.as_deref_mut(),
+            statistics,
+            &mut results,
+            output,
+        )?;
+    }
 
-    for file in entries {
-        total_violations += check_file(analyzer, &file, cli)?;
+    if matches!(cli.format, OutputFormat::Codeclimate) {
+        print_issues(&results, output)?;
     }
 
-    Ok(total_violations)
+    Ok(results)
+}
+
+/// Re-check the files of the target paths whenever they change, until interrupted.
+///
+/// Changes arriving in quick succession, as when an editor saves several files, are
+/// checked together.
+fn watch(
+    analyzer: &mut RustDocAnalyzer,
+    collect: &CollectOptions,
+    target_paths: &[PathBuf],
+    cli: &Cli,
+    baseline: Option<&Baseline>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    for target_path in target_paths {
+        // Editors often save by replacing a file, so a single file is watched via its
+        // directory
+        let (watched, mode) = if target_path.is_file() {
+            let parent = target_path.parent().filter(|parent| !parent.as_os_str().is_empty());
+            (parent.unwrap_or_else(|| Path::new(".")).to_path_buf(), RecursiveMode::NonRecursive)
+        } else if is_glob(target_path) && !target_path.exists() {
+            (glob_base(target_path), RecursiveMode::Recursive)
+        } else {
+            (target_path.clone(), RecursiveMode::Recursive)
+        };
+        watcher.watch(&watched, mode)?;
+    }
+    let watched: Vec<String> =
+        target_paths.iter().map(|path| display_path(path, cli.path_style)).collect();
+    eprintln!("Watching {} for changes", watched.join(", "));
+
+    let mut changed = HashSet::new();
+    while let Ok(event) = receiver.recv() {
+        changed.extend(changed_paths(event));
+        while let Ok(event) = receiver.recv_timeout(WATCH_DEBOUNCE) {
+            changed.extend(changed_paths(event));
+        }
+
+        // Collect again, so new files are checked and ignored files stay ignored
+        let files: Vec<PathBuf> = target_files_of_paths(analyzer, collect, target_paths)?
+            .into_iter()
+            .filter(|file| file.canonicalize().is_ok_and(|path| changed.contains(&path)))
+            .collect();
+        changed.clear();
+        if files.is_empty() {
+            continue;
+        }
+
+        let mut baseline = baseline.cloned();
+        let violations = check_files(
+            analyzer,
+            &files,
+            cli,
+            baseline.as_mut(),
+            None,
+            &mut Vec::new(),
+            &mut Statistics::default(),
+            &mut Output::stdout(cli.color),
+        )?
+        .statistics()
+        .violations;
+        eprintln!("Found {violations} violations in {} changed files", files.len());
+    }
+    Ok(())
+}
+
+/// Return the canonical paths of the files created or modified by a watch event.
+fn changed_paths(event: notify::Result<notify::Event>) -> Vec<PathBuf> {
+    match event {
+        Ok(event) if event.kind.is_create() || event.kind.is_modify() => {
+            event.paths.iter().filter_map(|path| path.canonicalize().ok()).collect()
+        }
+        Ok(_) => Vec::new(),
+        Err(e) => {
+            log::warn!("Watch error: {e}");
+            Vec::new()
+        }
+    }
+}
+
+/// Totals of a check run, for the `--statistics` summary.
+#[derive(Debug, Default)]
+struct Statistics {
+    /// Number of reported violations per rule.
+    rules: BTreeMap<String, usize>,
+    /// Message of the first reported violation of each rule.
+    examples: BTreeMap<String, String>,
+    /// Documentation coverage per top-level module.
+    modules: BTreeMap<String, Coverage>,
+}
+
+/// Print the violations per rule with an example message, and the coverage per top-level module.
+fn report_statistics(
+    statistics: &Statistics,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut total = Coverage::default();
+    for coverage in statistics.modules.values() {
+        total.add(*coverage);
+    }
+
+    match format {
+        OutputFormat::Text => {
+            // The most common violations come first, as the ones to tackle first
+            let mut rules: Vec<_> = statistics.rules.iter().collect();
+            rules.sort_by_key(|&(rule, count)| (std::cmp::Reverse(count), rule));
+            println!("Violations by rule:");
+            for (rule, count) in rules {
+                let example = statistics.examples.get(rule).map_or("", String::as_str);
+                println!("  {rule}  {count:>6}  {example}");
+            }
+            let width =
+                statistics.modules.keys().map(String::len).max().unwrap_or(0).max("total".len());
+            println!("Documented public items by module:");
+            for (module, coverage) in
+                statistics.modules.iter().chain([(&"total".to_string(), &total)])
+            {
+                println!(
+                    "  {module:<width$}  {:>6}/{:<6} {:>5.1}%",
+                    coverage.documented,
+                    coverage.total,
+                    coverage.percent()
+                );
+            }
+        }
+        OutputFormat::Json
+        | OutputFormat::Codeclimate
+        | OutputFormat::RustcJson
+        | OutputFormat::ActionsJson => {
+            let coverage_json = |coverage: &Coverage| {
+                serde_json::json!({
+                    "documented": coverage.documented,
+                    "total": coverage.total,
+                    "percent": coverage.percent(),
+                })
+            };
+            let json_output = serde_json::json!({
+                "statistics": {
+                    "rules": statistics.rules,
+                    "examples": statistics.examples,
+                    "modules": statistics.modules.iter().map(|(module, coverage)| {
+                        (module.clone(), coverage_json(coverage))
+                    }).collect::<serde_json::Map<_, _>>(),
+                    "coverage": coverage_json(&total),
+                }
+            });
+            println!("{}", serde_json::to_string_pretty(&json_output)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Number of slowest files listed in the timings report.
+const SLOWEST_FILES: usize = 10;
+
+/// Report per-file timings, the slowest files and totals on stderr.
+fn report_timings(
+    timings: &[(PathBuf, FileTimings)],
+    setup: Duration,
+    wall: Duration,
+    format: OutputFormat,
+    path_style: Option<PathStyle>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut totals = FileTimings::default();
+    for (_, file_timings) in timings {
+        totals.read += file_timings.read;
+        totals.parse += file_timings.parse;
+        totals.check += file_timings.check;
+    }
+
+    let mut slowest: Vec<_> = timings.iter().collect();
+    slowest.sort_by_key(|(_, file_timings)| std::cmp::Reverse(file_timings.total()));
+    slowest.truncate(SLOWEST_FILES);
+
+    let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+    let phases = |file_timings: &FileTimings| {
+        serde_json::json!({
+            "read_ms": ms(file_timings.read),
+            "parse_ms": ms(file_timings.parse),
+            "check_ms": ms(file_timings.check),
+            "total_ms": ms(file_timings.total()),
+        })
+    };
+
+    match format {
+        OutputFormat::Text => {
+            eprintln!("Timings for {} files:", timings.len());
+            eprintln!("  setup  {:>10.3}ms", ms(setup));
+            eprintln!("  read   {:>10.3}ms", ms(totals.read));
+            eprintln!("  parse  {:>10.3}ms", ms(totals.parse));
+            eprintln!("  check  {:>10.3}ms", ms(totals.check));
+            eprintln!("  total  {:>10.3}ms", ms(wall));
+            eprintln!("Slowest files:");
+            for (file, file_timings) in slowest {
+                eprintln!(
+                    "  {:>10.3}ms  {} (parse {:.3}ms, check {:.3}ms)",
+                    ms(file_timings.total()),
+                    display_path(file, path_style),
+                    ms(file_timings.parse),
+                    ms(file_timings.check)
+                );
+            }
+        }
+        OutputFormat::Json
+        | OutputFormat::Codeclimate
+        | OutputFormat::RustcJson
+        | OutputFormat::ActionsJson => {
+            let json_output = serde_json::json!({
+                "files": timings.iter().map(|(file, file_timings)| {
+                    let mut entry = phases(file_timings);
+                    entry["file"] = display_path(file, path_style).into();
+                    entry
+                }).collect::<Vec<_>>(),
+                "slowest": slowest.iter().map(|(file, _)| display_path(file, path_style)).collect::<Vec<_>>(),
+                "totals": {
+                    "files": timings.len(),
+                    "setup_ms": ms(setup),
+                    "read_ms": ms(totals.read),
+                    "parse_ms": ms(totals.parse),
+                    "check_ms": ms(totals.check),
+                    "wall_ms": ms(wall),
+                },
+            });
+            eprintln!("{}", serde_json::to_string_pretty(&json_output)?);
+        }
+    }
+
+    Ok(())
 }
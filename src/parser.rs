@@ -1,9 +1,21 @@
-use std::{fs, path::Path};
+use std::{ops::Range, path::Path};
 
+use libloading::Library;
 use streaming_iterator::StreamingIterator as _;
 use tree_sitter::{Language, Parser, Query, QueryCursor, Tree};
-
-use crate::pep257::{Docstring, DocstringTarget};
+use tree_sitter_language::LanguageFn;
+
+use crate::{
+    analyzer::{AnalyzerOptions, CfgPolicy},
+    backend::{LanguageBackend, ParsedFile},
+    context::{Edition, FileItem},
+    pep257::{
+        DanglingDoc, Docstring, DocstringTarget, FunctionModifiers, ItemAttributes,
+        TrailingComment, UnsafeBlock,
+    },
+    position::SourcePosition,
+    suppression::Suppression,
+};
 
 /// Errors that can occur during parsing.
 #[derive(thiserror::Error, Debug)]
@@ -14,37 +26,120 @@ pub enum ParseError {
     TreeSitter,
     #[error("Query error: {0}")]
     Query(String),
+    #[error("Failed to load grammar: {0}")]
+    Grammar(String),
 }
 
+/// Symbol exported by tree-sitter Rust grammar libraries.
+const GRAMMAR_SYMBOL: &[u8] = b"tree_sitter_rust";
+
 /// Rust parser using tree-sitter.
 pub(crate) struct RustParser {
     parser: Parser,
     language: Language,
+    options: AnalyzerOptions,
+    /// Shared library of a grammar loaded at runtime, kept loaded while the language is used.
+    _grammar_library: Option<Library>,
+}
+
+/// Rust source files parsed with tree-sitter-rust.
+impl LanguageBackend for RustParser {
+    /// Return the name of the language.
+    fn name(&self) -> &'static str {
+        "rust"
+    }
+
+    /// Return the file extensions of Rust source files.
+    fn extensions(&self) -> &'static [&'static str] {
+        &["rs"]
+    }
+
+    /// Parse Rust source code and extract its docstrings.
+    fn parse(&mut self, source: &str, edition: Option<Edition>) -> Result<ParsedFile, ParseError> {
+        self.parse_edition(source, edition)
+    }
 }
 
 /// Implementation of parser methods.
 impl RustParser {
     /// Create a new Rust parser instance.
+    #[cfg(test)]
     pub(crate) fn new() -> Result<Self, ParseError> {
-        let language = tree_sitter_rust::LANGUAGE.into();
+        Self::with_options(AnalyzerOptions::default())
+    }
+
+    /// Create a new Rust parser instance with the given options.
+    ///
+    /// The bundled grammar is used unless the options name a grammar library.
+    pub(crate) fn with_options(options: AnalyzerOptions) -> Result<Self, ParseError> {
+        let (language, grammar_library) = match &options.grammar {
+            Some(path) => {
+                let (language, library) = Self::load_grammar(path)?;
+                (language, Some(library))
+            }
+            None => (tree_sitter_rust::LANGUAGE.into(), None),
+        };
         let mut parser = Parser::new();
 
-        parser.set_language(&language).map_err(|_| ParseError::TreeSitter)?;
+        parser.set_language(&language).map_err(|e| match &options.grammar {
+            Some(path) => ParseError::Grammar(format!("{}: {e}", path.display())),
+            None => ParseError::TreeSitter,
+        })?;
 
-        Ok(Self { parser, language })
+        Ok(Self { parser, language, options, _grammar_library: grammar_library })
     }
 
-    /// Parses a Rust file and extracts docstrings.
-    pub(crate) fn parse_file<P: AsRef<Path>>(
-        &mut self,
-        path: P,
-    ) -> Result<Vec<Docstring>, ParseError> {
-        let source_code = fs::read_to_string(path)?;
-        self.parse_source(&source_code)
+    /// Load a tree-sitter Rust grammar from a shared library.
+    ///
+    /// The library must export the `tree_sitter_rust` language function, as grammars
+    /// built with the tree-sitter CLI do.
+    fn load_grammar(path: &Path) -> Result<(Language, Library), ParseError> {
+        let error = |message: String| ParseError::Grammar(format!("{}: {message}", path.display()));
+        let load_error = |e: libloading::Error| match std::error::Error::source(&e) {
+            Some(source) => error(format!("{e}: {source}")),
+            None => error(e.to_string()),
+        };
+
+        // SAFETY: Loading runs the library's initializers; the user chose to trust this grammar.
+        let library = unsafe { Library::new(path) }.map_err(load_error)?;
+
+        // SAFETY: Tree-sitter grammars export their language function with this signature.
+        let language_fn =
+            unsafe { library.get::<unsafe extern "C" fn() -> *const ()>(GRAMMAR_SYMBOL) }
+                .map_err(load_error)?;
+
+        // SAFETY: The function returns a pointer to a static `TSLanguage` in the library,
+        // which stays loaded as long as the parser using the language.
+        let language = Language::new(unsafe { LanguageFn::from_raw(*language_fn) });
+
+        let version = language.abi_version();
+        if !(tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION)
+            .contains(&version)
+        {
+            return Err(error(format!("unsupported grammar ABI version {version}")));
+        }
+
+        Ok((language, library))
     }
 
     /// Parses Rust source code and extracts docstrings.
+    #[cfg(test)]
     pub(crate) fn parse_source(&mut self, source_code: &str) -> Result<Vec<Docstring>, ParseError> {
+        Ok(self.parse(source_code)?.docstrings)
+    }
+
+    /// Parses Rust source code and extracts docstrings and file-level information.
+    #[cfg(test)]
+    pub(crate) fn parse(&mut self, source_code: &str) -> Result<ParsedFile, ParseError> {
+        self.parse_edition(source_code, None)
+    }
+
+    /// Parses Rust source code of a known edition, also finding syntax it doesn't accept.
+    pub(crate) fn parse_edition(
+        &mut self,
+        source_code: &str,
+        edition: Option<Edition>,
+    ) -> Result<ParsedFile, ParseError> {
         let tree = self.parser.parse(source_code, None).ok_or(ParseError::TreeSitter)?;
 
         let mut docstrings = Vec::new();
@@ -55,15 +150,273 @@ pub(crate) fn parse_source(&mut self, source_code: &str) -> Result<Vec<Docstring
         // Extract docstrings from various Rust constructs
         docstrings.extend(self.extract_function_docs(&tree, source_code)?);
         docstrings.extend(self.extract_struct_docs(&tree, source_code)?);
+        docstrings.extend(self.extract_field_docs(&tree, source_code)?);
         docstrings.extend(self.extract_enum_docs(&tree, source_code)?);
         docstrings.extend(self.extract_trait_docs(&tree, source_code)?);
+        docstrings.extend(self.extract_trait_member_docs(&tree, source_code)?);
         docstrings.extend(self.extract_impl_docs(&tree, source_code)?);
+        docstrings.extend(self.extract_extern_docs(&tree, source_code)?);
         docstrings.extend(self.extract_mod_docs(&tree, source_code)?);
         docstrings.extend(self.extract_const_docs(&tree, source_code)?);
+        docstrings.extend(self.extract_static_docs(&tree, source_code)?);
         docstrings.extend(self.extract_type_alias_docs(&tree, source_code)?);
         docstrings.extend(self.extract_macro_docs(&tree, source_code)?);
+        docstrings.extend(self.extract_reexport_docs(&tree, source_code)?);
+
+        let inner_attributes = Self::extract_inner_attributes(&tree, source_code);
+        let syntax_errors = Self::find_syntax_errors(&tree);
+        let edition_errors = edition
+            .map(|edition| Self::find_edition_errors(&tree, source_code, edition))
+            .unwrap_or_default();
+        let trailing_comments = self.extract_trailing_comments(&tree, source_code)?;
+        let dangling_docs = Self::find_dangling_docs(&tree, source_code);
+        let unsafe_blocks = self.find_unsafe_blocks(&tree, source_code);
+
+        let mut items: Vec<FileItem> =
+            docstrings.iter().filter_map(FileItem::of_docstring).collect();
+        items.sort_by_key(|item| (item.span.start.line, item.span.start.column));
+
+        Ok(ParsedFile {
+            docstrings,
+            items,
+            inner_attributes,
+            syntax_errors,
+            edition_errors,
+            trailing_comments,
+            dangling_docs,
+            unsafe_blocks,
+        })
+    }
 
-        Ok(docstrings)
+    /// Find outer doc comments at the end of a file or block, which no item follows.
+    ///
+    /// Only the first comment of each run is reported. Attributes and other comments
+    /// between the docs and the end are skipped, and trailing comments after a field or
+    /// variant are left to R108. Macro token trees are skipped, since their docs may be
+    /// matched by the macro.
+    fn find_dangling_docs(tree: &Tree, source: &str) -> Vec<DanglingDoc> {
+        let is_outer_doc = |node: tree_sitter::Node<'_>| {
+            matches!(node.kind(), "line_comment" | "block_comment")
+                && Self::is_outer_doc_comment(node.utf8_text(source.as_bytes()).unwrap_or_default())
+        };
+
+        // Trailing comments on a field or variant are reported by R108
+        let is_trailing = |node: tree_sitter::Node<'_>| {
+            node.parent()
+                .is_some_and(|p| matches!(p.kind(), "field_declaration_list" | "enum_variant_list"))
+                && node.prev_sibling().is_some_and(|prev| {
+                    !prev.is_extra() && prev.end_position().row == node.start_position().row
+                })
+        };
+
+        let mut docs = Vec::new();
+        let mut cursor = tree.walk();
+        loop {
+            let node = cursor.node();
+            let starts_run = is_outer_doc(node)
+                && !is_trailing(node)
+                && node.prev_sibling().is_none_or(|prev| !is_outer_doc(prev) || is_trailing(prev));
+            if starts_run {
+                let mut next = node.next_sibling();
+                while let Some(sibling) =
+                    next.filter(|n| n.is_extra() || n.kind() == "attribute_item")
+                {
+                    next = sibling.next_sibling();
+                }
+                if next.is_none_or(|n| n.kind() == "}") {
+                    let start = node.start_position();
+                    docs.push(DanglingDoc {
+                        item_path: node
+                            .parent()
+                            .map(|p| Self::item_path(p, source))
+                            .unwrap_or_default(),
+                        position: SourcePosition { line: start.row + 1, column: start.column + 1 },
+                    });
+                }
+            }
+
+            if node.kind() != "token_tree" && cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return docs;
+                }
+            }
+        }
+    }
+
+    /// Find `unsafe` blocks in function bodies and whether a `// SAFETY:` comment precedes each.
+    ///
+    /// The comment may be any of the comments directly above the statement holding the block, so
+    /// `let` bindings and tail expressions count. Blocks outside functions, such as in constants,
+    /// and macro token trees are skipped.
+    fn find_unsafe_blocks(&self, tree: &Tree, source: &str) -> Vec<UnsafeBlock> {
+        let mut blocks = Vec::new();
+        let mut cursor = tree.walk();
+        loop {
+            let node = cursor.node();
+            if node.kind() == "unsafe_block"
+                && let Some(function) = Self::enclosing_function(node)
+            {
+                let start = node.start_position();
+                blocks.push(UnsafeBlock {
+                    item_path: Self::item_path(function, source),
+                    is_public: self.is_visible(function, source),
+                    has_safety_comment: Self::has_safety_comment(node, source),
+                    position: SourcePosition { line: start.row + 1, column: start.column + 1 },
+                });
+            }
+
+            if node.kind() != "token_tree" && cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return blocks;
+                }
+            }
+        }
+    }
+
+    /// Return the innermost function whose body holds a node.
+    fn enclosing_function(node: tree_sitter::Node<'_>) -> Option<tree_sitter::Node<'_>> {
+        let mut current = node.parent();
+        while let Some(parent) = current {
+            match parent.kind() {
+                "function_item" => return Some(parent),
+                // Items nested in a body aren't part of the function
+                "const_item" | "static_item" | "impl_item" | "mod_item" | "trait_item" => {
+                    return None;
+                }
+                _ => current = parent.parent(),
+            }
+        }
+        None
+    }
+
+    /// Check whether the comments directly above the statement holding a node include `// SAFETY:`.
+    ///
+    /// The comment is matched in any case.
+    fn has_safety_comment(node: tree_sitter::Node<'_>, source: &str) -> bool {
+        let mut statement = node;
+        while let Some(parent) = statement.parent().filter(|parent| parent.kind() != "block") {
+            statement = parent;
+        }
+
+        let mut previous = statement.prev_sibling();
+        while let Some(sibling) = previous
+            .filter(|n| matches!(n.kind(), "line_comment" | "block_comment" | "attribute_item"))
+        {
+            let text = sibling.utf8_text(source.as_bytes()).unwrap_or_default();
+            if text.to_ascii_uppercase().contains("SAFETY:") {
+                return true;
+            }
+            previous = sibling.prev_sibling();
+        }
+        false
+    }
+
+    /// Find syntax that the grammar accepts but an edition of Rust doesn't.
+    ///
+    /// The grammar parses every edition alike, so `async fn` parses in a Rust 2015
+    /// crate, and an item named `gen` parses in a Rust 2024 crate, although rustc
+    /// rejects both. Macro token trees are skipped, since keywords are valid tokens.
+    fn find_edition_errors(
+        tree: &Tree,
+        source: &str,
+        edition: Edition,
+    ) -> Vec<(SourcePosition, String)> {
+        let mut errors = Vec::new();
+        let mut cursor = tree.walk();
+        loop {
+            let node = cursor.node();
+            let message = match node.kind() {
+                "async" if edition < Edition::E2018 => {
+                    Some(format!("`async` is not available in Rust {edition}"))
+                }
+                "await_expression" if edition < Edition::E2018 => {
+                    Some(format!("`.await` is not available in Rust {edition}"))
+                }
+                "gen_block" if edition < Edition::E2024 => {
+                    Some(format!("`gen` blocks are not available in Rust {edition}"))
+                }
+                "identifier" | "type_identifier" | "field_identifier" => {
+                    let name = node.utf8_text(source.as_bytes()).unwrap_or_default();
+                    Self::is_reserved(name, edition)
+                        .then(|| format!("`{name}` is a keyword in Rust {edition}, use `r#{name}`"))
+                }
+                _ => None,
+            };
+            if let Some(message) = message {
+                let start = node.start_position();
+                errors.push((
+                    SourcePosition { line: start.row + 1, column: start.column + 1 },
+                    message,
+                ));
+            }
+
+            if node.kind() != "token_tree" && cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return errors;
+                }
+            }
+        }
+    }
+
+    /// Check whether an identifier is a keyword reserved by an edition.
+    fn is_reserved(name: &str, edition: Edition) -> bool {
+        match name {
+            "async" | "await" | "dyn" | "try" => edition >= Edition::E2018,
+            "gen" => edition >= Edition::E2024,
+            _ => false,
+        }
+    }
+
+    /// Find the outermost error and missing nodes in a syntax tree.
+    fn find_syntax_errors(tree: &Tree) -> Vec<SourcePosition> {
+        let mut errors = Vec::new();
+        if !tree.root_node().has_error() {
+            return errors;
+        }
+
+        // Walk the tree without recursion, descending only into subtrees with errors
+        let mut cursor = tree.walk();
+        loop {
+            let node = cursor.node();
+            if node.is_error() || node.is_missing() {
+                let start = node.start_position();
+                errors.push(SourcePosition { line: start.row + 1, column: start.column + 1 });
+            } else if node.has_error() && cursor.goto_first_child() {
+                continue;
+            }
+
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return errors;
+                }
+            }
+        }
+    }
+
+    /// Extract the contents of file-level inner attributes (`#![...]`).
+    fn extract_inner_attributes(tree: &Tree, source: &str) -> Vec<String> {
+        let root_node = tree.root_node();
+        let mut cursor = root_node.walk();
+
+        root_node
+            .children(&mut cursor)
+            .filter(|child| child.kind() == "inner_attribute_item")
+            .filter_map(|child| {
+                let mut attr_cursor = child.walk();
+                let attribute =
+                    child.named_children(&mut attr_cursor).find(|n| n.kind() == "attribute")?;
+                attribute.utf8_text(source.as_bytes()).ok().map(str::to_string)
+            })
+            .collect()
     }
 
     /// Extract crate/package-level documentation (inner doc comments).
@@ -73,6 +426,7 @@ pub(crate) fn parse_source(&mut self, source_code: &str) -> Result<Vec<Docstring
     fn extract_package_docs(tree: &Tree, source: &str) -> Vec<Docstring> {
         let root_node = tree.root_node();
         let mut inner_doc_comments = Vec::new();
+        let mut include = None;
 
         // Look for inner doc comments (//! or /*!  */) at the start of the file
         let mut cursor = root_node.walk();
@@ -82,8 +436,10 @@ fn extract_package_docs(tree: &Tree, source: &str) -> Vec<Docstring> {
                 "line_comment" => {
                     if let Ok(comment_text) = child.utf8_text(source.as_bytes()) {
                         if comment_text.trim().starts_with("//!") {
-                            inner_doc_comments.push(comment_text);
-                        } else if !comment_text.trim().starts_with("///") {
+                            inner_doc_comments.push((comment_text, child.start_position()));
+                        } else if !comment_text.trim().starts_with("///")
+                            && Suppression::parse(comment_text).is_none()
+                        {
                             // Stop at first non-doc comment
                             break;
                         }
@@ -92,16 +448,22 @@ fn extract_package_docs(tree: &Tree, source: &str) -> Vec<Docstring> {
                 "block_comment" => {
                     if let Ok(comment_text) = child.utf8_text(source.as_bytes()) {
                         if comment_text.trim().starts_with("/*!") {
-                            inner_doc_comments.push(comment_text);
+                            inner_doc_comments.push((comment_text, child.start_position()));
                         } else if !comment_text.trim().starts_with("/**") {
                             // Stop at first non-doc comment
                             break;
                         }
                     }
                 }
-                "whitespace" => {
-                    // Skip whitespace
+                "inner_attribute_item" => {
+                    // Skip crate-level attributes like #![warn(missing_docs)], noting docs
+                    // included from another file
+                    if include.is_none() {
+                        include = Self::included_doc_file(child, source)
+                            .map(|file| (file, child.start_position()));
+                    }
                 }
+                "whitespace" => {}
                 _ => {
                     // Stop at first non-comment, non-whitespace node
                     break;
@@ -111,17 +473,49 @@ fn extract_package_docs(tree: &Tree, source: &str) -> Vec<Docstring> {
 
         // If we found inner doc comments, process them
         if !inner_doc_comments.is_empty() {
-            let content = Self::process_inner_doc_comments(&inner_doc_comments);
+            let (content, line_positions) = Self::process_inner_doc_comments(&inner_doc_comments);
             let is_multiline = inner_doc_comments.len() > 1 || content.contains('\n');
+            let start_point = inner_doc_comments[0].1;
 
             return vec![Docstring {
                 content,
-                raw_content: inner_doc_comments.join("\n"),
-                line: 1,
-                column: 1,
+                raw_content: Self::join_comment_text(&inner_doc_comments),
+                line: start_point.row + 1,
+                column: start_point.column + 1,
                 is_multiline,
                 is_public: true, // Package-level docs are always public
                 target_type: DocstringTarget::Package,
+                attributes: ItemAttributes {
+                    span: Some(Self::span(root_node)),
+                    byte_range: Some(root_node.byte_range()),
+                    allows_missing_docs: Self::allows_missing_docs(root_node, source),
+                    ..ItemAttributes::default()
+                },
+                line_positions,
+                item_path: String::new(),
+            }];
+        }
+
+        // Docs included from another file are loaded by the analyzer, which knows where
+        // the file is
+        if let Some((file, start_point)) = include {
+            return vec![Docstring {
+                content: String::new(),
+                raw_content: String::new(),
+                line: start_point.row + 1,
+                column: start_point.column + 1,
+                is_multiline: false,
+                is_public: true,
+                target_type: DocstringTarget::Package,
+                attributes: ItemAttributes {
+                    span: Some(Self::span(root_node)),
+                    byte_range: Some(root_node.byte_range()),
+                    allows_missing_docs: Self::allows_missing_docs(root_node, source),
+                    doc_include: Some(file),
+                    ..ItemAttributes::default()
+                },
+                line_positions: Vec::new(),
+                item_path: String::new(),
             }];
         }
 
@@ -145,6 +539,14 @@ fn extract_package_docs(tree: &Tree, source: &str) -> Vec<Docstring> {
                 is_multiline: false,
                 is_public: true,
                 target_type: DocstringTarget::Package,
+                attributes: ItemAttributes {
+                    span: Some(Self::span(root_node)),
+                    byte_range: Some(root_node.byte_range()),
+                    allows_missing_docs: Self::allows_missing_docs(root_node, source),
+                    ..ItemAttributes::default()
+                },
+                line_positions: Vec::new(),
+                item_path: String::new(),
             }]
         } else {
             // No public items, probably just a test snippet - don't report missing
@@ -180,9 +582,18 @@ fn extract_function_docs(
                 .find(|capture| capture.index == 1)
                 .map_or_else(|| query_match.captures[0].node, |capture| capture.node);
 
+            // Functions in impl blocks are methods
+            let in_impl = function_node
+                .parent()
+                .filter(|body| body.kind() == "declaration_list")
+                .and_then(|body| body.parent())
+                .is_some_and(|item| item.kind() == "impl_item");
+            let target_type =
+                if in_impl { DocstringTarget::Method } else { DocstringTarget::Function };
+
             // Look for documentation comments before this node
             if let Some(docstring) =
-                Self::extract_preceding_docs(function_node, source, DocstringTarget::Function)?
+                self.extract_preceding_docs(function_node, source, target_type)?
             {
                 docstrings.push(docstring);
             }
@@ -217,7 +628,42 @@ fn extract_struct_docs(&self, tree: &Tree, source: &str) -> Result<Vec<Docstring
 
             // Look for documentation comments before this node
             if let Some(docstring) =
-                Self::extract_preceding_docs(struct_node, source, DocstringTarget::Struct)?
+                self.extract_preceding_docs(struct_node, source, DocstringTarget::Struct)?
+            {
+                docstrings.push(docstring);
+            }
+        }
+
+        Ok(docstrings)
+    }
+
+    /// Extract documentation from the named fields of structs and unions.
+    ///
+    /// Fields of enum variants have no visibility of their own and are never public.
+    fn extract_field_docs(&self, tree: &Tree, source: &str) -> Result<Vec<Docstring>, ParseError> {
+        let query = Query::new(
+            &self.language,
+            r"
+            (field_declaration
+                name: (field_identifier) @name
+            ) @field
+            ",
+        )
+        .map_err(|e| ParseError::Query(e.to_string()))?;
+
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        let mut docstrings = Vec::new();
+
+        while let Some(query_match) = matches.next() {
+            let field_node = query_match
+                .captures
+                .iter()
+                .find(|capture| capture.index == 1)
+                .map_or_else(|| query_match.captures[0].node, |capture| capture.node);
+
+            if let Some(docstring) =
+                self.extract_preceding_docs(field_node, source, DocstringTarget::Field)?
             {
                 docstrings.push(docstring);
             }
@@ -252,7 +698,7 @@ fn extract_enum_docs(&self, tree: &Tree, source: &str) -> Result<Vec<Docstring>,
 
             // Look for documentation comments before this node
             if let Some(docstring) =
-                Self::extract_preceding_docs(enum_node, source, DocstringTarget::Enum)?
+                self.extract_preceding_docs(enum_node, source, DocstringTarget::Enum)?
             {
                 docstrings.push(docstring);
             }
@@ -287,7 +733,49 @@ fn extract_trait_docs(&self, tree: &Tree, source: &str) -> Result<Vec<Docstring>
 
             // Look for documentation comments before this node
             if let Some(docstring) =
-                Self::extract_preceding_docs(trait_node, source, DocstringTarget::Trait)?
+                self.extract_preceding_docs(trait_node, source, DocstringTarget::Trait)?
+            {
+                docstrings.push(docstring);
+            }
+        }
+
+        Ok(docstrings)
+    }
+
+    /// Extract documentation from the required methods and associated types of traits.
+    ///
+    /// Provided methods and associated consts are found with the other functions and
+    /// consts.
+    fn extract_trait_member_docs(
+        &self,
+        tree: &Tree,
+        source: &str,
+    ) -> Result<Vec<Docstring>, ParseError> {
+        let query = Query::new(
+            &self.language,
+            r"
+            (trait_item
+                body: (declaration_list
+                    [(function_signature_item) (associated_type)] @member)
+            )
+            ",
+        )
+        .map_err(|e| ParseError::Query(e.to_string()))?;
+
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        let mut docstrings = Vec::new();
+
+        while let Some(query_match) = matches.next() {
+            let member_node = query_match.captures[0].node;
+            let target_type = if member_node.kind() == "associated_type" {
+                DocstringTarget::TypeAlias
+            } else {
+                DocstringTarget::Function
+            };
+
+            if let Some(docstring) =
+                self.extract_preceding_docs(member_node, source, target_type)?
             {
                 docstrings.push(docstring);
             }
@@ -315,7 +803,7 @@ fn extract_impl_docs(&self, tree: &Tree, source: &str) -> Result<Vec<Docstring>,
 
             // Look for documentation comments before this node
             if let Some(docstring) =
-                Self::extract_preceding_docs(impl_node, source, DocstringTarget::Impl)?
+                self.extract_preceding_docs(impl_node, source, DocstringTarget::ImplBlock)?
             {
                 docstrings.push(docstring);
             }
@@ -324,6 +812,42 @@ fn extract_impl_docs(&self, tree: &Tree, source: &str) -> Result<Vec<Docstring>,
         Ok(docstrings)
     }
 
+    /// Extract documentation from `extern` blocks and the functions they declare.
+    ///
+    /// Statics declared in `extern` blocks are found with the other statics.
+    fn extract_extern_docs(&self, tree: &Tree, source: &str) -> Result<Vec<Docstring>, ParseError> {
+        let query = Query::new(
+            &self.language,
+            r"
+            (foreign_mod_item) @extern
+            (foreign_mod_item
+                body: (declaration_list (function_signature_item) @function)
+            )
+            ",
+        )
+        .map_err(|e| ParseError::Query(e.to_string()))?;
+
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        let mut docstrings = Vec::new();
+
+        while let Some(query_match) = matches.next() {
+            let node = query_match.captures[0].node;
+            let target_type = if node.kind() == "foreign_mod_item" {
+                DocstringTarget::ExternBlock
+            } else {
+                DocstringTarget::Function
+            };
+
+            // Look for documentation comments before this node
+            if let Some(docstring) = self.extract_preceding_docs(node, source, target_type)? {
+                docstrings.push(docstring);
+            }
+        }
+
+        Ok(docstrings)
+    }
+
     /// Extract documentation from module declarations.
     fn extract_mod_docs(&self, tree: &Tree, source: &str) -> Result<Vec<Docstring>, ParseError> {
         let query = Query::new(
@@ -350,7 +874,7 @@ fn extract_mod_docs(&self, tree: &Tree, source: &str) -> Result<Vec<Docstring>,
 
             // Look for documentation comments before this node
             if let Some(docstring) =
-                Self::extract_preceding_docs(mod_node, source, DocstringTarget::Module)?
+                self.extract_preceding_docs(mod_node, source, DocstringTarget::Module)?
             {
                 docstrings.push(docstring);
             }
@@ -385,7 +909,42 @@ fn extract_const_docs(&self, tree: &Tree, source: &str) -> Result<Vec<Docstring>
 
             // Look for documentation comments before this node
             if let Some(docstring) =
-                Self::extract_preceding_docs(const_node, source, DocstringTarget::Const)?
+                self.extract_preceding_docs(const_node, source, DocstringTarget::Const)?
+            {
+                docstrings.push(docstring);
+            }
+        }
+
+        Ok(docstrings)
+    }
+
+    /// Extract documentation from static declarations.
+    fn extract_static_docs(&self, tree: &Tree, source: &str) -> Result<Vec<Docstring>, ParseError> {
+        let query = Query::new(
+            &self.language,
+            r"
+            (static_item
+                name: (identifier) @name
+            ) @static
+            ",
+        )
+        .map_err(|e| ParseError::Query(e.to_string()))?;
+
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        let mut docstrings = Vec::new();
+
+        while let Some(query_match) = matches.next() {
+            // Find the static node (not the name node)
+            let static_node = query_match
+                .captures
+                .iter()
+                .find(|capture| capture.index == 1)
+                .map_or_else(|| query_match.captures[0].node, |capture| capture.node);
+
+            // Look for documentation comments before this node
+            if let Some(docstring) =
+                self.extract_preceding_docs(static_node, source, DocstringTarget::Static)?
             {
                 docstrings.push(docstring);
             }
@@ -424,7 +983,7 @@ fn extract_type_alias_docs(
 
             // Look for documentation comments before this node
             if let Some(docstring) =
-                Self::extract_preceding_docs(type_alias_node, source, DocstringTarget::TypeAlias)?
+                self.extract_preceding_docs(type_alias_node, source, DocstringTarget::TypeAlias)?
             {
                 docstrings.push(docstring);
             }
@@ -459,7 +1018,7 @@ fn extract_macro_docs(&self, tree: &Tree, source: &str) -> Result<Vec<Docstring>
 
             // Look for documentation comments before this node
             if let Some(docstring) =
-                Self::extract_preceding_docs(macro_node, source, DocstringTarget::Macro)?
+                self.extract_preceding_docs(macro_node, source, DocstringTarget::Macro)?
             {
                 docstrings.push(docstring);
             }
@@ -468,28 +1027,56 @@ fn extract_macro_docs(&self, tree: &Tree, source: &str) -> Result<Vec<Docstring>
         Ok(docstrings)
     }
 
-    /// Generic function to extract documentation using a tree-sitter query.
-    #[allow(dead_code)]
-    fn extract_docs_with_query(
+    /// Extract documentation from `use` and `extern crate` declarations that re-export items.
+    ///
+    /// Glob re-exports and `_` imports are skipped, since they add no named item to
+    /// document. The item path of a declaration ends with the first name it binds,
+    /// and re-exported crates have paths starting with `::`.
+    fn extract_reexport_docs(
+        &self,
         tree: &Tree,
         source: &str,
-        query: &Query,
-        target_type: DocstringTarget,
     ) -> Result<Vec<Docstring>, ParseError> {
+        let query =
+            Query::new(&self.language, "[(use_declaration) (extern_crate_declaration)] @use")
+                .map_err(|e| ParseError::Query(e.to_string()))?;
+
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(query, tree.root_node(), source.as_bytes());
+        let mut matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
         let mut docstrings = Vec::new();
 
         while let Some(query_match) = matches.next() {
-            // Get the main node (the function/struct/etc itself, not just the name)
-            let main_node = query_match
-                .captures
-                .iter()
-                .find(|capture| capture.index == 0)
-                .map_or_else(|| query_match.captures[0].node, |capture| capture.node);
+            let use_node = query_match.captures[0].node;
+            let mut children = use_node.walk();
+            if !use_node.children(&mut children).any(|child| child.kind() == "visibility_modifier")
+            {
+                continue;
+            }
+            let mut leaves = Vec::new();
+            if let Some(argument) = use_node.child_by_field_name("argument") {
+                Self::use_tree_leaves(argument, "", source, &mut leaves);
+            } else if let Some(name) = use_node.child_by_field_name("name") {
+                let name = name.utf8_text(source.as_bytes()).unwrap_or_default();
+                let path = if name == "self" { "crate".to_string() } else { format!("::{name}") };
+                let alias = use_node.child_by_field_name("alias");
+                match alias.map(|alias| alias.utf8_text(source.as_bytes()).unwrap_or_default()) {
+                    Some("_") => {}
+                    Some(alias) => leaves.push((path, alias.to_string())),
+                    None => leaves.push((path, name.to_string())),
+                }
+            }
+            let Some((_, first_name)) = leaves.first() else {
+                continue;
+            };
 
-            // Look for documentation comments before this node
-            if let Some(docstring) = Self::extract_preceding_docs(main_node, source, target_type)? {
+            if let Some(mut docstring) =
+                self.extract_preceding_docs(use_node, source, DocstringTarget::Reexport)?
+            {
+                docstring.item_path = match docstring.item_path.as_str() {
+                    "" => first_name.clone(),
+                    parent => format!("{parent}::{first_name}"),
+                };
+                docstring.attributes.reexports = leaves.into_iter().map(|(path, _)| path).collect();
                 docstrings.push(docstring);
             }
         }
@@ -497,65 +1084,220 @@ fn extract_docs_with_query(
         Ok(docstrings)
     }
 
-    /// Extract documentation comments preceding a given node.
-    fn extract_preceding_docs(
+    /// Collect the paths a `use` tree imports, with the names it binds them to.
+    fn use_tree_leaves(
         node: tree_sitter::Node<'_>,
+        prefix: &str,
         source: &str,
-        target_type: DocstringTarget,
-    ) -> Result<Option<Docstring>, ParseError> {
+        leaves: &mut Vec<(String, String)>,
+    ) {
+        let text = |node: tree_sitter::Node<'_>| {
+            let text = node.utf8_text(source.as_bytes()).unwrap_or_default();
+            text.split_whitespace().collect::<String>()
+        };
+        let join = |path: String| match (prefix, path.as_str()) {
+            ("", _) => path,
+            (_, "self") => prefix.to_string(),
+            _ => format!("{prefix}::{path}"),
+        };
+
+        match node.kind() {
+            "use_as_clause" => {
+                let path = node.child_by_field_name("path").map(text);
+                let alias = node.child_by_field_name("alias").map(text);
+                if let (Some(path), Some(alias)) = (path, alias)
+                    && alias != "_"
+                {
+                    leaves.push((join(path), alias));
+                }
+            }
+            "scoped_use_list" => {
+                let prefix = node
+                    .child_by_field_name("path")
+                    .map_or(prefix.to_string(), |path| join(text(path)));
+                if let Some(list) = node.child_by_field_name("list") {
+                    Self::use_tree_leaves(list, &prefix, source, leaves);
+                }
+            }
+            "use_list" => {
+                let mut cursor = node.walk();
+                for child in node.named_children(&mut cursor) {
+                    Self::use_tree_leaves(child, prefix, source, leaves);
+                }
+            }
+            "use_wildcard" | "line_comment" | "block_comment" => {}
+            _ => {
+                let path = join(text(node));
+                let name = path.rsplit("::").next().unwrap_or_default().to_string();
+                leaves.push((path, name));
+            }
+        }
+    }
+
+    /// Extract comments written after struct fields and enum variants on the same line.
+    fn extract_trailing_comments(
+        &self,
+        tree: &Tree,
+        source: &str,
+    ) -> Result<Vec<TrailingComment>, ParseError> {
+        let query = Query::new(
+            &self.language,
+            r"
+            [(field_declaration_list) (enum_variant_list)] @members
+            ",
+        )
+        .map_err(|e| ParseError::Query(e.to_string()))?;
+
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        let mut comments = Vec::new();
+
+        while let Some(query_match) = matches.next() {
+            let list = query_match.captures[0].node;
+            let Some(item) = list.parent() else {
+                continue;
+            };
+            if self.is_cfg_excluded(&Self::collect_item_attributes(item, source).cfg) {
+                continue;
+            }
+
+            let mut member = None;
+            let mut list_cursor = list.walk();
+            for child in list.children(&mut list_cursor) {
+                match child.kind() {
+                    "field_declaration" | "enum_variant" => member = Some(child),
+                    "line_comment" => {
+                        let Some(member_node) = member.take() else {
+                            continue;
+                        };
+                        if child.start_position().row != member_node.end_position().row {
+                            continue;
+                        }
+                        let Some(name) = member_node
+                            .child_by_field_name("name")
+                            .and_then(|name| name.utf8_text(source.as_bytes()).ok())
+                        else {
+                            continue;
+                        };
+                        let text = child
+                            .utf8_text(source.as_bytes())
+                            .map_err(|_| ParseError::TreeSitter)?;
+                        let is_doc = text.starts_with("///") && !text.starts_with("////");
+                        let start = child.start_position();
+                        comments.push(TrailingComment {
+                            text: text.trim_start_matches('/').trim().to_string(),
+                            is_doc,
+                            member_kind: if member_node.kind() == "enum_variant" {
+                                "variant"
+                            } else {
+                                "field"
+                            },
+                            member: name.to_string(),
+                            item_path: format!("{}::{name}", Self::item_path(item, source)),
+                            member_line: member_node.start_position().row + 1,
+                            position: SourcePosition {
+                                line: start.row + 1,
+                                column: start.column + 1,
+                            },
+                        });
+                    }
+                    "," => {}
+                    _ => member = None,
+                }
+            }
+        }
+
+        Ok(comments)
+    }
+
+    /// Generic function to extract documentation using a tree-sitter query.
+    #[allow(dead_code)]
+    fn extract_docs_with_query(
+        &self,
+        tree: &Tree,
+        source: &str,
+        query: &Query,
+        target_type: DocstringTarget,
+    ) -> Result<Vec<Docstring>, ParseError> {
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(query, tree.root_node(), source.as_bytes());
+        let mut docstrings = Vec::new();
+
+        while let Some(query_match) = matches.next() {
+            // Get the main node (the function/struct/etc itself, not just the name)
+            let main_node = query_match
+                .captures
+                .iter()
+                .find(|capture| capture.index == 0)
+                .map_or_else(|| query_match.captures[0].node, |capture| capture.node);
+
+            // Look for documentation comments before this node
+            if let Some(docstring) = self.extract_preceding_docs(main_node, source, target_type)? {
+                docstrings.push(docstring);
+            }
+        }
+
+        Ok(docstrings)
+    }
+
+    /// Extract documentation comments preceding a given node.
+    fn extract_preceding_docs(
+        &self,
+        node: tree_sitter::Node<'_>,
+        source: &str,
+        target_type: DocstringTarget,
+    ) -> Result<Option<Docstring>, ParseError> {
+        let mut attributes = Self::collect_item_attributes(node, source);
+        if self.is_cfg_excluded(&attributes.cfg) {
+            return Ok(None);
+        }
+        attributes.span = Some(Self::span(node));
+        attributes.byte_range = Some(node.byte_range());
+        let trait_item = node
+            .parent()
+            .filter(|body| body.kind() == "declaration_list")
+            .and_then(|body| body.parent())
+            .filter(|item| item.kind() == "trait_item");
+        attributes.is_trait_member = trait_item.is_some();
+        attributes.is_default_method = node.kind() == "function_item"
+            && trait_item.is_some_and(|item| self.is_visible(item, source));
+        attributes.is_extern_member = node
+            .parent()
+            .filter(|body| body.kind() == "declaration_list")
+            .and_then(|body| body.parent())
+            .is_some_and(|item| item.kind() == "foreign_mod_item");
+
         let mut doc_comments = Vec::new();
         let mut doc_attributes = Vec::new();
         let mut current_node = node;
         let mut first_doc_node = None;
 
-        // Walk backwards to find preceding comments and attributes
+        // Walk backwards to find preceding comments and attributes. Outer attributes
+        // and plain comments may sit anywhere between the docs and the item, in any
+        // order, so only another item or inner attribute ends the docs.
         while let Some(prev_sibling) = current_node.prev_sibling() {
-            if prev_sibling.kind() == "line_comment" {
-                let comment_text = prev_sibling
-                    .utf8_text(source.as_bytes())
-                    .map_err(|_| ParseError::TreeSitter)?;
-
-                // Check if it's a doc comment (starts with ///)
-                if comment_text.trim_start().starts_with("///") {
-                    doc_comments.insert(0, comment_text);
-                    if first_doc_node.is_none() {
-                        first_doc_node = Some(prev_sibling);
-                    }
-                } else {
-                    break; // Stop if we hit a non-doc comment
-                }
-            } else if prev_sibling.kind() == "block_comment" {
+            if prev_sibling.kind() == "line_comment" || prev_sibling.kind() == "block_comment" {
                 let comment_text = prev_sibling
                     .utf8_text(source.as_bytes())
                     .map_err(|_| ParseError::TreeSitter)?;
 
-                // Check if it's a doc comment (starts with /**)
-                if comment_text.trim_start().starts_with("/**") {
-                    doc_comments.insert(0, comment_text);
-                    if first_doc_node.is_none() {
-                        first_doc_node = Some(prev_sibling);
-                    }
-                    break; // Block comments usually stand alone
+                // Only `///` and `/** */` comments are docs, `////` and `/***` are plain
+                if Self::is_outer_doc_comment(comment_text) {
+                    doc_comments.insert(0, (comment_text, prev_sibling.start_position()));
+                    first_doc_node.get_or_insert(prev_sibling);
                 }
-                break;
             } else if prev_sibling.kind() == "attribute_item"
                 || prev_sibling.kind() == "outer_attribute_item"
             {
                 // Check for #[doc = "..."] attributes
                 if let Some(doc_content) = Self::extract_doc_attribute(&prev_sibling, source)? {
                     doc_attributes.insert(0, doc_content);
-                    if first_doc_node.is_none() {
-                        first_doc_node = Some(prev_sibling);
-                    }
+                    first_doc_node.get_or_insert(prev_sibling);
                 }
-            } else if prev_sibling.kind() == "whitespace"
-                || prev_sibling.utf8_text(source.as_bytes()).unwrap_or("").trim().is_empty()
+            } else if prev_sibling.kind() != "whitespace"
+                && !prev_sibling.utf8_text(source.as_bytes()).unwrap_or("").trim().is_empty()
             {
-                // Skip whitespace and continue looking
-                current_node = prev_sibling;
-                continue;
-            } else {
-                break; // Stop if we hit something else
+                break; // Stop at the previous item
             }
             current_node = prev_sibling;
         }
@@ -590,6 +1332,9 @@ fn extract_preceding_docs(
                 }
                 check_node = prev;
             }
+        } else if let Some(trait_item) = trait_item {
+            // Trait members have no visibility of their own, they're as public as the trait
+            is_public = self.is_visible(trait_item, source);
         } else {
             // For other types, use standard visibility checking
             if let Some(visibility_node) = node.child_by_field_name("visibility") {
@@ -608,6 +1353,12 @@ fn extract_preceding_docs(
                     is_public = true;
                 }
             }
+
+            // A `pub` item is only part of the public API if every enclosing
+            // module and type is public too
+            if is_public && !self.options.syntactic_visibility {
+                is_public = Self::is_effectively_public(node, source);
+            }
         }
 
         // Combine doc attributes and comments
@@ -624,174 +1375,1766 @@ fn extract_preceding_docs(
                 is_multiline: false,
                 is_public,
                 target_type,
+                attributes,
+                line_positions: Vec::new(),
+                item_path: Self::item_path(node, source),
             }));
         }
 
-        // Process the documentation (attributes take precedence, then comments)
-        let raw_content = if doc_attributes.is_empty() {
-            doc_comments.join("\n")
-        } else {
-            doc_attributes.join("\n")
-        };
+        // Process the documentation (attributes take precedence, then comments)
+        let raw_content = if doc_attributes.is_empty() {
+            Self::join_comment_text(&doc_comments)
+        } else {
+            doc_attributes
+                .iter()
+                .map(|(content, _)| content.as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let (processed_content, line_positions) = if doc_attributes.is_empty() {
+            Self::process_doc_comments(&doc_comments)
+        } else {
+            (
+                raw_content.clone(),
+                doc_attributes.into_iter().flat_map(|(_, positions)| positions).collect(),
+            )
+        };
+
+        let is_multiline = processed_content.lines().count() > 1;
+
+        // Get position of the first documentation element
+        let start_point = first_doc_node.unwrap_or(node).start_position();
+
+        Ok(Some(Docstring {
+            content: processed_content,
+            raw_content,
+            line: start_point.row + 1, // Convert to 1-based indexing
+            column: start_point.column + 1,
+            is_multiline,
+            is_public,
+            target_type,
+            attributes,
+            line_positions,
+            item_path: Self::item_path(node, source),
+        }))
+    }
+
+    /// Check whether a comment is an outer doc comment, `///` or `/** */`.
+    fn is_outer_doc_comment(text: &str) -> bool {
+        (text.starts_with("///") && !text.starts_with("////"))
+            || (text.starts_with("/**") && !text.starts_with("/***") && text != "/**/")
+    }
+
+    /// Return the source positions where a node starts and ends.
+    fn span(node: tree_sitter::Node<'_>) -> Range<SourcePosition> {
+        let position = |point: tree_sitter::Point| SourcePosition {
+            line: point.row + 1,
+            column: point.column + 1,
+        };
+        position(node.start_position())..position(node.end_position())
+    }
+
+    /// Check whether an item should be skipped because of its cfg gates.
+    fn is_cfg_excluded(&self, predicates: &[String]) -> bool {
+        if predicates.is_empty() {
+            return false;
+        }
+
+        match &self.options.cfg_gated {
+            CfgPolicy::CheckAll => false,
+            CfgPolicy::SkipAll => true,
+            CfgPolicy::CheckOnly(allowed) => {
+                let allowed: Vec<String> =
+                    allowed.iter().map(|predicate| Self::normalize_cfg(predicate)).collect();
+                !predicates.iter().all(|predicate| {
+                    allowed.contains(&Self::normalize_cfg(predicate))
+                        || Self::cfg_atoms(predicate).iter().any(|atom| allowed.contains(atom))
+                })
+            }
+        }
+    }
+
+    /// Collect attribute metadata for an item, including attributes on enclosing items.
+    ///
+    /// File-level `#![cfg(...)]` attributes gate every item in the file.
+    fn collect_item_attributes(node: tree_sitter::Node<'_>, source: &str) -> ItemAttributes {
+        let mut attributes = ItemAttributes::default();
+        let mut outer_attributes = Vec::new();
+        let mut own_attributes = Vec::new();
+        let mut current = Some(node);
+
+        while let Some(item) = current {
+            if let Some(parameters) = item.child_by_field_name("type_parameters") {
+                attributes.generics.extend(Self::generic_names(parameters, source));
+            }
+            if item.kind() == "source_file" {
+                let mut cursor = item.walk();
+                outer_attributes.extend(
+                    item.children(&mut cursor)
+                        .filter(|child| child.kind() == "inner_attribute_item"),
+                );
+                break;
+            }
+
+            // Attributes are preceding siblings of the item they apply to
+            let mut sibling = item.prev_sibling();
+            while let Some(prev) = sibling {
+                match prev.kind() {
+                    "attribute_item" => {
+                        if item == node {
+                            own_attributes.push(prev);
+                        }
+                        outer_attributes.push(prev);
+                    }
+                    "line_comment" | "block_comment" => {}
+                    _ => break,
+                }
+                sibling = prev.prev_sibling();
+            }
+
+            current = item.parent();
+        }
+
+        attributes.modifiers = Self::function_modifiers(node, source);
+        attributes.attributes_start = own_attributes.last().map(|&attr_node| {
+            let start = attr_node.start_position();
+            SourcePosition { line: start.row + 1, column: start.column + 1 }
+        });
+        if let Some(parameters) = node.child_by_field_name("parameters") {
+            let mut cursor = parameters.walk();
+            attributes.parameters = parameters
+                .named_children(&mut cursor)
+                .filter_map(|parameter| parameter.child_by_field_name("pattern"))
+                .filter_map(|pattern| pattern.utf8_text(source.as_bytes()).ok())
+                .map(str::to_string)
+                .collect();
+        }
+        attributes.returns_result = node
+            .child_by_field_name("return_type")
+            .and_then(|return_type| return_type.utf8_text(source.as_bytes()).ok())
+            .and_then(|return_type| return_type.split('<').next())
+            .is_some_and(|path| path.trim().rsplit("::").next() == Some("Result"));
+        attributes.calls = Self::body_calls(node, source);
+        attributes.allows_missing_docs = Self::allows_missing_docs(node, source);
+
+        // Only the item's own doc options describe the item, in source order
+        for &attr_node in own_attributes.iter().rev() {
+            Self::record_doc_options(attr_node, source, &mut attributes);
+        }
+
+        for attr_node in outer_attributes {
+            // Hiding an item also hides the items inside it
+            let mut options = ItemAttributes::default();
+            Self::record_doc_options(attr_node, source, &mut options);
+            attributes.doc_hidden |= options.doc_hidden;
+
+            if let Some(predicate) = Self::cfg_predicate(attr_node, source) {
+                // Features gating an enclosing item are for its docs to mention
+                if own_attributes.contains(&attr_node) {
+                    attributes.features.extend(Self::cfg_atoms(&predicate).iter().filter_map(
+                        |atom| {
+                            atom.strip_prefix("feature=\"")
+                                .and_then(|rest| rest.strip_suffix('"'))
+                                .map(str::to_string)
+                        },
+                    ));
+                }
+                attributes.cfg.push(predicate);
+            } else if attr_node
+                .utf8_text(source.as_bytes())
+                .is_ok_and(|text| Self::normalize_cfg(text).contains("doc(cfg("))
+            {
+                attributes.has_doc_cfg = true;
+            }
+        }
+
+        attributes
+    }
+
+    /// Check whether the nearest `missing_docs` lint level of an item is `allow` or `expect`.
+    ///
+    /// Levels are set by the outer attributes of the item and its enclosing items, and
+    /// by the inner attributes of enclosing modules and the file. As in rustc, the
+    /// last attribute of a scope wins.
+    fn allows_missing_docs(node: tree_sitter::Node<'_>, source: &str) -> bool {
+        let mut current = Some(node);
+        while let Some(item) = current {
+            let mut attr_nodes = Vec::new();
+            let mut sibling = item.prev_sibling();
+            while let Some(prev) = sibling {
+                match prev.kind() {
+                    "attribute_item" => attr_nodes.insert(0, prev),
+                    "line_comment" | "block_comment" => {}
+                    _ => break,
+                }
+                sibling = prev.prev_sibling();
+            }
+            // Inner attributes apply to their module, including a module's own docs
+            let scope = if item == node && item.kind() == "mod_item" {
+                item.child_by_field_name("body")
+            } else {
+                Some(item).filter(|item| matches!(item.kind(), "source_file" | "declaration_list"))
+            };
+            if let Some(scope) = scope {
+                let mut cursor = scope.walk();
+                attr_nodes.extend(
+                    scope
+                        .children(&mut cursor)
+                        .filter(|child| child.kind() == "inner_attribute_item"),
+                );
+            }
+
+            let level = attr_nodes
+                .iter()
+                .filter_map(|&attr_node| Self::lint_level(attr_node, source, "missing_docs"))
+                .next_back();
+            if let Some(level) = level {
+                return matches!(level, "allow" | "expect");
+            }
+            current = item.parent();
+        }
+        false
+    }
+
+    /// Return the level an attribute such as `#[allow(missing_docs, unused)]` sets for a lint.
+    ///
+    /// Returns `None` if it sets none.
+    fn lint_level<'a>(
+        attr_node: tree_sitter::Node<'_>,
+        source: &'a str,
+        lint: &str,
+    ) -> Option<&'a str> {
+        let mut cursor = attr_node.walk();
+        let attribute =
+            attr_node.named_children(&mut cursor).find(|child| child.kind() == "attribute")?;
+        let text = attribute.utf8_text(source.as_bytes()).ok()?;
+        let (level, lints) = text.split_once('(')?;
+        let level = level.trim();
+        (matches!(level, "allow" | "expect" | "warn" | "deny" | "forbid")
+            && lints.trim_end().trim_end_matches(')').split(',').any(|name| name.trim() == lint))
+        .then_some(level)
+    }
+
+    /// Return the names of the parameters in a generic parameter list, such as `'a` or `T`.
+    fn generic_names(parameters: tree_sitter::Node<'_>, source: &str) -> Vec<String> {
+        let mut cursor = parameters.walk();
+        parameters
+            .named_children(&mut cursor)
+            .filter_map(|parameter| parameter.child_by_field_name("name"))
+            .filter_map(|name| name.utf8_text(source.as_bytes()).ok())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Collect the `async`, `const`, `unsafe` and `extern` qualifiers of a function.
+    fn function_modifiers(node: tree_sitter::Node<'_>, source: &str) -> FunctionModifiers {
+        let mut modifiers = FunctionModifiers::default();
+        let mut cursor = node.walk();
+        let Some(modifiers_node) =
+            node.children(&mut cursor).find(|child| child.kind() == "function_modifiers")
+        else {
+            return modifiers;
+        };
+
+        let mut cursor = modifiers_node.walk();
+        for modifier in modifiers_node.children(&mut cursor) {
+            match modifier.kind() {
+                "async" => modifiers.is_async = true,
+                "const" => modifiers.is_const = true,
+                "unsafe" => modifiers.is_unsafe = true,
+                "extern_modifier" => {
+                    let mut abi_cursor = modifier.walk();
+                    let abi = modifier
+                        .children(&mut abi_cursor)
+                        .find(|child| child.kind() == "string_literal")
+                        .and_then(|literal| literal.utf8_text(source.as_bytes()).ok())
+                        .map_or("C", |literal| literal.trim_matches('"'));
+                    modifiers.abi = Some(abi.to_string());
+                }
+                _ => {}
+            }
+        }
+        modifiers
+    }
+
+    /// Return the macros and methods called in the body of a function item.
+    ///
+    /// Calls such as `panic!` and `unwrap` are listed once each, outermost calls first. Items
+    /// nested in the body aren't part of the function and are skipped. Macro arguments aren't
+    /// parsed, so calls in them are found from their tokens.
+    fn body_calls(node: tree_sitter::Node<'_>, source: &str) -> Vec<String> {
+        let mut calls = Vec::new();
+        let Some(body) =
+            node.child_by_field_name("body").filter(|_| node.kind() == "function_item")
+        else {
+            return calls;
+        };
+        let text = |node: tree_sitter::Node<'_>| node.utf8_text(source.as_bytes()).unwrap_or("");
+
+        let mut cursor = body.walk();
+        loop {
+            let node = cursor.node();
+            let call = match node.kind() {
+                "macro_invocation" => node
+                    .child_by_field_name("macro")
+                    .map(|name| format!("{}!", text(name).rsplit("::").next().unwrap_or(""))),
+                "call_expression" => node
+                    .child_by_field_name("function")
+                    .filter(|function| function.kind() == "field_expression")
+                    .and_then(|function| function.child_by_field_name("field"))
+                    .map(|field| text(field).to_string()),
+                // `name!` and `.name(` in macro arguments
+                "identifier" if node.parent().is_some_and(|p| p.kind() == "token_tree") => {
+                    let next = node.next_sibling();
+                    if next.is_some_and(|next| next.kind() == "!") {
+                        Some(format!("{}!", text(node)))
+                    } else if node.prev_sibling().is_some_and(|prev| prev.kind() == ".")
+                        && next.is_some_and(|next| text(next).starts_with('('))
+                    {
+                        Some(text(node).to_string())
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            };
+            if let Some(call) = call
+                && !calls.contains(&call)
+            {
+                calls.push(call);
+            }
+
+            let nested_item = matches!(
+                node.kind(),
+                "function_item"
+                    | "impl_item"
+                    | "trait_item"
+                    | "mod_item"
+                    | "const_item"
+                    | "static_item"
+            );
+            if !nested_item && cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() || cursor.node() == body {
+                    return calls;
+                }
+            }
+        }
+    }
+
+    /// Extract the predicate from a `#[cfg(...)]` or `#![cfg(...)]` attribute.
+    fn cfg_predicate(attr_node: tree_sitter::Node<'_>, source: &str) -> Option<String> {
+        let mut cursor = attr_node.walk();
+        let attribute = attr_node.named_children(&mut cursor).find(|n| n.kind() == "attribute")?;
+        let name = attribute.named_child(0)?.utf8_text(source.as_bytes()).ok()?;
+        if name != "cfg" {
+            return None;
+        }
+        let arguments = attribute.child_by_field_name("arguments")?;
+        let text = arguments.utf8_text(source.as_bytes()).ok()?;
+        let predicate = text.strip_prefix('(')?.strip_suffix(')')?;
+        Some(predicate.trim().to_string())
+    }
+
+    /// Normalize a cfg predicate for comparison by removing whitespace.
+    fn normalize_cfg(predicate: &str) -> String {
+        predicate.chars().filter(|c| !c.is_whitespace()).collect()
+    }
+
+    /// Split a cfg predicate into the positive atoms combined by `all(...)`/`any(...)`.
+    ///
+    /// Atoms under `not(...)` are left out, as they describe where the item is absent.
+    fn cfg_atoms(predicate: &str) -> Vec<String> {
+        let mut atoms = Vec::new();
+
+        // Work through nested predicates with an explicit stack, so deeply nested
+        // input can't overflow the call stack
+        let mut pending = vec![predicate];
+        while let Some(predicate) = pending.pop() {
+            let predicate = predicate.trim();
+            let inner = predicate
+                .strip_prefix("all")
+                .or_else(|| predicate.strip_prefix("any"))
+                .map(str::trim_start)
+                .and_then(|rest| rest.strip_prefix('('))
+                .and_then(|rest| rest.strip_suffix(')'));
+
+            if let Some(inner) = inner {
+                let mut parts = Vec::new();
+                let mut depth = 0usize;
+                let mut start = 0;
+                for (i, ch) in inner.char_indices() {
+                    match ch {
+                        '(' => depth += 1,
+                        ')' => depth = depth.saturating_sub(1),
+                        ',' if depth == 0 => {
+                            parts.push(&inner[start..i]);
+                            start = i + 1;
+                        }
+                        _ => {}
+                    }
+                }
+                parts.push(&inner[start..]);
+                pending.extend(parts.into_iter().rev());
+            } else if !predicate.is_empty() && !predicate.starts_with("not") {
+                atoms.push(Self::normalize_cfg(predicate));
+            }
+        }
+
+        atoms
+    }
+
+    /// Check whether an item is public.
+    ///
+    /// Its own `pub` keyword decides with syntactic visibility, and its effective visibility
+    /// otherwise.
+    fn is_visible(&self, node: tree_sitter::Node<'_>, source: &str) -> bool {
+        if self.options.syntactic_visibility {
+            Self::has_pub_visibility(node, source)
+        } else {
+            Self::is_effectively_public(node, source)
+        }
+    }
+
+    /// Check whether a node carries an unrestricted `pub` visibility modifier.
+    ///
+    /// Restricted forms such as `pub(crate)` or `pub(super)` are not public API.
+    fn has_pub_visibility(node: tree_sitter::Node<'_>, source: &str) -> bool {
+        let mut cursor = node.walk();
+        node.children(&mut cursor)
+            .find(|child| child.kind() == "visibility_modifier")
+            .and_then(|vis| vis.utf8_text(source.as_bytes()).ok())
+            .is_some_and(|vis_text| vis_text.trim() == "pub")
+    }
+
+    /// Compute the effective visibility of an item.
+    ///
+    /// The item must be `pub` itself, and every enclosing module and the
+    /// self type of an enclosing inherent impl must be effectively public as well.
+    /// Impl self types that are not defined in the same file are assumed public.
+    fn is_effectively_public(node: tree_sitter::Node<'_>, source: &str) -> bool {
+        if !Self::has_pub_visibility(node, source) {
+            return false;
+        }
+
+        let mut current = node;
+        while let Some(parent) = current.parent() {
+            match parent.kind() {
+                "source_file" => return true,
+                "block" => return false, // Items inside function bodies are never exported
+                "mod_item" | "struct_item" | "union_item"
+                    if !Self::has_pub_visibility(parent, source) =>
+                {
+                    return false;
+                }
+                "impl_item"
+                    if parent.child_by_field_name("trait").is_none()
+                        && Self::find_impl_type_definition(parent, source).is_some_and(
+                            |type_def| !Self::is_effectively_public(type_def, source),
+                        ) =>
+                {
+                    return false;
+                }
+                _ => {}
+            }
+            current = parent;
+        }
+
+        true
+    }
+
+    /// Return the path of an item within its file, such as `outer::Type::method`.
+    ///
+    /// Impl blocks contribute their self type, or `<Type as Trait>` for trait impls,
+    /// and an impl block's own path is that segment.
+    fn item_path(node: tree_sitter::Node<'_>, source: &str) -> String {
+        let mut segments = Vec::new();
+        let mut current = Some(node);
+
+        while let Some(item) = current {
+            let segment = match item.kind() {
+                "impl_item" => Self::impl_segment(item, source),
+                "mod_item"
+                | "trait_item"
+                | "function_item"
+                | "struct_item"
+                | "enum_item"
+                | "union_item"
+                | "const_item"
+                | "static_item"
+                | "type_item"
+                | "macro_definition"
+                | "field_declaration"
+                | "function_signature_item"
+                | "associated_type" => item
+                    .child_by_field_name("name")
+                    .and_then(|name| name.utf8_text(source.as_bytes()).ok())
+                    .map(str::to_string),
+                _ => None,
+            };
+            segments.extend(segment);
+            current = item.parent();
+        }
+
+        segments.reverse();
+        segments.join("::")
+    }
+
+    /// Return the path segment contributed by an impl block.
+    fn impl_segment(impl_node: tree_sitter::Node<'_>, source: &str) -> Option<String> {
+        let text = |field: &str| {
+            let node = impl_node.child_by_field_name(field)?;
+            let text = node.utf8_text(source.as_bytes()).ok()?;
+            // Drop generic arguments, which aren't part of the path
+            Some(text.split('<').next().unwrap_or(text).trim().to_string())
+        };
+
+        let self_type = text("type")?;
+        Some(match text("trait") {
+            Some(trait_name) => format!("<{self_type} as {trait_name}>"),
+            None => self_type,
+        })
+    }
+
+    /// Find the type definition targeted by an inherent impl in the same scope.
+    fn find_impl_type_definition<'tree>(
+        impl_node: tree_sitter::Node<'tree>,
+        source: &str,
+    ) -> Option<tree_sitter::Node<'tree>> {
+        let mut type_node = impl_node.child_by_field_name("type")?;
+        if type_node.kind() == "generic_type" {
+            type_node = type_node.child_by_field_name("type")?;
+        }
+        if type_node.kind() != "type_identifier" {
+            return None;
+        }
+        let type_name = type_node.utf8_text(source.as_bytes()).ok()?;
+
+        let scope = impl_node.parent()?;
+        let mut cursor = scope.walk();
+        scope.named_children(&mut cursor).find(|child| {
+            matches!(child.kind(), "struct_item" | "enum_item" | "union_item" | "trait_item")
+                && child
+                    .child_by_field_name("name")
+                    .and_then(|name| name.utf8_text(source.as_bytes()).ok())
+                    == Some(type_name)
+        })
+    }
+
+    /// Extract documentation from a #[doc = "..."] attribute.
+    ///
+    /// Only the `doc = "..."` form carries content, also when it's added conditionally
+    /// with `#[cfg_attr(docsrs, doc = "...")]`. List forms such as `#[doc(hidden)]`
+    /// or `#[doc(alias = "x")]` are recorded as item metadata instead. The source
+    /// position of each content line is returned alongside the content.
+    fn extract_doc_attribute(
+        attr_node: &tree_sitter::Node<'_>,
+        source: &str,
+    ) -> Result<Option<(String, Vec<SourcePosition>)>, ParseError> {
+        let value = match Self::doc_attribute(*attr_node, source) {
+            Some(attribute) => attribute.child_by_field_name("value"),
+            None => Self::cfg_attr_doc_value(*attr_node, source),
+        };
+        let Some(value) = value else {
+            return Ok(None);
+        };
+        let value_text = value.utf8_text(source.as_bytes()).map_err(|_| ParseError::TreeSitter)?;
+
+        let literal = match value.kind() {
+            "string_literal" => {
+                value_text.strip_prefix('"').and_then(|rest| rest.strip_suffix('"'))
+            }
+            "raw_string_literal" => {
+                let mut cursor = value.walk();
+                let content_node = value
+                    .named_children(&mut cursor)
+                    .find(|child| child.kind() == "string_content");
+                match content_node {
+                    Some(child) => child.utf8_text(source.as_bytes()).ok(),
+                    None => value_text.find('"').map(|quote| &value_text[quote + 1..quote + 1]),
+                }
+            }
+            _ => None,
+        };
+        let Some(literal) = literal else {
+            return Ok(None);
+        };
+
+        let content = if value.kind() == "string_literal" {
+            Self::unescape_string(literal)
+        } else {
+            literal.to_string()
+        };
+
+        // Lines map directly onto the source unless escapes changed the text
+        let start = value.start_position();
+        let positions = if content == literal {
+            literal
+                .split('\n')
+                .map(|line| SourcePosition::of_slice(value_text, start, line))
+                .collect()
+        } else {
+            let first = SourcePosition::of_slice(value_text, start, literal);
+            vec![first; content.split('\n').count()]
+        };
+
+        Ok(Some((content, positions)))
+    }
+
+    /// Return the file a `#![doc = include_str!("...")]` attribute includes as docs.
+    fn included_doc_file(attr_node: tree_sitter::Node<'_>, source: &str) -> Option<String> {
+        let mut cursor = attr_node.walk();
+        let attribute = attr_node.named_children(&mut cursor).find(|n| n.kind() == "attribute")?;
+        if attribute.named_child(0)?.utf8_text(source.as_bytes()).ok()? != "doc" {
+            return None;
+        }
+        let value = attribute.child_by_field_name("value")?;
+        let name = value.child_by_field_name("macro")?.utf8_text(source.as_bytes()).ok()?;
+        if value.kind() != "macro_invocation" || name.rsplit("::").next() != Some("include_str") {
+            return None;
+        }
+
+        let mut cursor = value.walk();
+        let arguments = value.named_children(&mut cursor).find(|n| n.kind() == "token_tree")?;
+        let mut cursor = arguments.walk();
+        let literal = arguments.named_children(&mut cursor).next()?;
+        let text = literal.utf8_text(source.as_bytes()).ok()?;
+        (literal.kind() == "string_literal")
+            .then(|| text.strip_prefix('"')?.strip_suffix('"').map(Self::unescape_string))
+            .flatten()
+    }
+
+    /// Return the string literal of the `doc = "..."` attribute wrapped by a `cfg_attr` attribute.
+    ///
+    /// Returns `None` if the `#[cfg_attr(predicate, ...)]` item wraps no doc attribute.
+    fn cfg_attr_doc_value<'tree>(
+        attr_node: tree_sitter::Node<'tree>,
+        source: &str,
+    ) -> Option<tree_sitter::Node<'tree>> {
+        let mut cursor = attr_node.walk();
+        let attribute = attr_node.named_children(&mut cursor).find(|n| n.kind() == "attribute")?;
+        if attribute.named_child(0)?.utf8_text(source.as_bytes()).ok()? != "cfg_attr" {
+            return None;
+        }
+
+        // The wrapped attributes follow the predicate, after the first top-level comma
+        let arguments = attribute.child_by_field_name("arguments")?;
+        let mut cursor = arguments.walk();
+        let tokens: Vec<_> =
+            arguments.children(&mut cursor).skip_while(|token| token.kind() != ",").collect();
+        tokens.windows(3).find_map(|window| {
+            let [name, equals, value] = window else {
+                return None;
+            };
+            (name.utf8_text(source.as_bytes()).ok()? == "doc"
+                && equals.kind() == "="
+                && matches!(value.kind(), "string_literal" | "raw_string_literal"))
+            .then_some(*value)
+        })
+    }
+
+    /// Return the inner `attribute` node if an attribute item is a `#[doc ...]` attribute.
+    fn doc_attribute<'tree>(
+        attr_node: tree_sitter::Node<'tree>,
+        source: &str,
+    ) -> Option<tree_sitter::Node<'tree>> {
+        let mut cursor = attr_node.walk();
+        let attribute = attr_node.named_children(&mut cursor).find(|n| n.kind() == "attribute")?;
+        let name = attribute.named_child(0)?.utf8_text(source.as_bytes()).ok()?;
+        (name == "doc").then_some(attribute)
+    }
+
+    /// Record the options of a `#[doc(...)]` attribute such as `alias`, `hidden` or `cfg`.
+    fn record_doc_options(
+        attr_node: tree_sitter::Node<'_>,
+        source: &str,
+        attributes: &mut ItemAttributes,
+    ) {
+        let Some(arguments) =
+            Self::doc_attribute(attr_node, source).and_then(|a| a.child_by_field_name("arguments"))
+        else {
+            return;
+        };
+
+        let mut cursor = arguments.walk();
+        let tokens: Vec<_> = arguments.children(&mut cursor).collect();
+        let string_value = |node: tree_sitter::Node<'_>| {
+            node.utf8_text(source.as_bytes()).ok().and_then(|text| {
+                text.strip_prefix('"')?.strip_suffix('"').map(Self::unescape_string)
+            })
+        };
+
+        let mut index = 0;
+        while index < tokens.len() {
+            let token = tokens[index];
+            index += 1;
+            if token.kind() != "identifier" {
+                continue;
+            }
+            let Ok(name) = token.utf8_text(source.as_bytes()) else {
+                continue;
+            };
+
+            // Collect the option's values from `name = "value"` or `name("a", "b")`
+            let mut values = Vec::new();
+            let mut has_arguments = false;
+            match tokens.get(index).map(tree_sitter::Node::kind) {
+                Some("=") => {
+                    values.extend(tokens.get(index + 1).and_then(|&value| string_value(value)));
+                    index += 2;
+                }
+                Some("token_tree") => {
+                    let mut list_cursor = tokens[index].walk();
+                    values.extend(
+                        tokens[index]
+                            .named_children(&mut list_cursor)
+                            .filter(|child| child.kind() == "string_literal")
+                            .filter_map(string_value),
+                    );
+                    has_arguments = true;
+                    index += 1;
+                }
+                _ => {}
+            }
+
+            match name {
+                "alias" => attributes.doc_aliases.extend(values),
+                "hidden" => attributes.doc_hidden = true,
+                "cfg" if has_arguments => attributes.has_doc_cfg = true,
+                _ => attributes.doc_flags.push(name.to_string()),
+            }
+        }
+    }
+
+    /// Resolve the escape sequences of a Rust string literal's contents.
+    fn unescape_string(text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            if ch != '\\' {
+                result.push(ch);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('r') => result.push('\r'),
+                Some('t') => result.push('\t'),
+                Some('0') => result.push('\0'),
+                Some('x') => {
+                    let hex: String = chars.by_ref().take(2).collect();
+                    result.extend(u8::from_str_radix(&hex, 16).ok().map(char::from));
+                }
+                Some('u') => {
+                    let hex: String = chars
+                        .by_ref()
+                        .skip_while(|&c| c == '{')
+                        .take_while(|&c| c != '}')
+                        .collect();
+                    result.extend(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32));
+                }
+                Some('\n') => {
+                    // Line continuation: skip the newline and leading whitespace
+                    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                }
+                Some(other) => result.push(other),
+                None => result.push('\\'),
+            }
+        }
+
+        result
+    }
+
+    /// Process documentation comments to extract clean content.
+    ///
+    /// Each comment is given with its start position, and the source position of each
+    /// content line is returned alongside the content.
+    fn process_doc_comments(
+        comments: &[(&str, tree_sitter::Point)],
+    ) -> (String, Vec<SourcePosition>) {
+        let mut processed_lines = Vec::new();
+        let mut positions = Vec::new();
+
+        for &(comment, start) in comments {
+            let trimmed = comment.trim();
+
+            if let Some(content) = trimmed.strip_prefix("///") {
+                // Handle /// style comments
+                let clean_content = content.trim_start();
+                processed_lines.push(clean_content);
+                positions.push(SourcePosition::of_slice(comment, start, clean_content));
+            } else if let Some(content) = trimmed.strip_prefix("/**") {
+                // Handle /** */ style comments
+                for clean_line in Self::block_comment_lines(content) {
+                    processed_lines.push(clean_line);
+                    positions.push(SourcePosition::of_slice(comment, start, clean_line));
+                }
+            }
+        }
+
+        // DO NOT remove empty lines at the beginning and end
+        // We need to preserve them for D201 and D202 checks
+        (processed_lines.join("\n"), positions)
+    }
+
+    /// Process inner documentation comments (//! and /*! */) to extract clean content.
+    fn process_inner_doc_comments(
+        comments: &[(&str, tree_sitter::Point)],
+    ) -> (String, Vec<SourcePosition>) {
+        let mut processed_lines = Vec::new();
+        let mut positions = Vec::new();
+
+        for &(comment, start) in comments {
+            let trimmed = comment.trim();
+
+            if let Some(content) = trimmed.strip_prefix("//!") {
+                // Handle //! style comments
+                let clean_content = content.trim_start();
+                processed_lines.push(clean_content);
+                positions.push(SourcePosition::of_slice(comment, start, clean_content));
+            } else if let Some(content) = trimmed.strip_prefix("/*!") {
+                // Handle /*! */ style comments
+                for clean_line in Self::block_comment_lines(content) {
+                    processed_lines.push(clean_line);
+                    positions.push(SourcePosition::of_slice(comment, start, clean_line));
+                }
+            }
+        }
+
+        // DO NOT remove empty lines at the beginning and end
+        // We need to preserve them for D201 and D202 checks
+        (processed_lines.join("\n"), positions)
+    }
+
+    /// Return the text lines of a block doc comment, given the text after its opening marker.
+    ///
+    /// Like rustdoc, the lines holding only the opening or closing marker aren't part
+    /// of the docs, and a leading `*` is stripped from the other lines when every one
+    /// after the first has it, as in the ` * ` decoration style.
+    fn block_comment_lines(content: &str) -> Vec<&str> {
+        let content = content.strip_suffix("*/").unwrap_or(content);
+        let mut lines: Vec<&str> = content.lines().collect();
+        let opened_alone = lines.len() > 1 && lines[0].trim().is_empty();
+        if opened_alone {
+            lines.remove(0);
+        }
+        if lines.len() > 1 && lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+
+        // Text on the opening line directly follows the marker, never a decoration
+        let first = usize::from(!opened_alone);
+        let decorated = lines.len() > first
+            && lines[first..].iter().all(|line| line.trim_start().starts_with('*'));
+        lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                let line = line.trim_start();
+                let line = if decorated && index >= first { &line[1..] } else { line };
+                line.trim_start()
+            })
+            .collect()
+    }
+
+    /// Join the text of doc comments into the raw docstring content.
+    fn join_comment_text(comments: &[(&str, tree_sitter::Point)]) -> String {
+        comments.iter().map(|&(comment, _)| comment).collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// Unit tests for the parser.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pep257::{CheckerOptions, HiddenPolicy, Pep257Checker};
+
+    /// Test parsing a simple function with documentation.
+    #[test]
+    fn test_parse_simple_function() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+/// Calculate the sum of two numbers.
+fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+";
+
+        let docstrings = parser.parse_source(source).unwrap();
+        assert_eq!(docstrings.len(), 1);
+        assert_eq!(docstrings[0].content, "Calculate the sum of two numbers.");
+        assert!(!docstrings[0].is_multiline);
+    }
+
+    /// Test parsing a public function sets is_public = true.
+    #[test]
+    fn test_parse_public_function_sets_is_public() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+/// Public function docs.
+pub fn public_add(a: i32, b: i32) -> i32 {
+    a + b
+}
+";
+
+        let docstrings = parser.parse_source(source).unwrap();
+        // Should have package doc (empty) + function doc
+        assert_eq!(docstrings.len(), 2);
+
+        let function_doc =
+            docstrings.iter().find(|d| matches!(d.target_type, DocstringTarget::Function)).unwrap();
+        assert_eq!(function_doc.content, "Public function docs.");
+        assert!(function_doc.is_public, "Expected is_public to be true for pub fn");
+    }
+
+    /// Test that a pub method on a private struct is not effectively public.
+    #[test]
+    fn test_effective_visibility_private_struct_method() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+struct Hidden;
+
+impl Hidden {
+    pub fn method(&self) {}
+}
+
+/// Public type.
+pub struct Shown;
+
+impl Shown {
+    pub fn method(&self) {}
+}
+";
+
+        let docstrings = parser.parse_source(source).unwrap();
+        let methods: Vec<_> = docstrings
+            .iter()
+            .filter(|d| matches!(d.target_type, DocstringTarget::Method))
+            .collect();
+        assert_eq!(methods.len(), 2);
+        assert!(!methods[0].is_public, "Method on private struct should not be public");
+        assert!(methods[1].is_public, "Method on public struct should be public");
+    }
+
+    /// Test that items in private modules and `pub(crate)` items are not effectively public.
+    #[test]
+    fn test_effective_visibility_module_chain() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+mod private {
+    pub fn inner() {}
+}
+
+pub mod public {
+    pub fn inner() {}
+
+    pub(crate) fn restricted() {}
+}
+";
+
+        let docstrings = parser.parse_source(source).unwrap();
+        let functions: Vec<_> = docstrings
+            .iter()
+            .filter(|d| matches!(d.target_type, DocstringTarget::Function))
+            .collect();
+        assert_eq!(functions.len(), 3);
+        assert!(!functions[0].is_public);
+        assert!(functions[1].is_public);
+        assert!(!functions[2].is_public);
+    }
+
+    /// Test that syntactic visibility mode only looks at the item's own keyword.
+    #[test]
+    fn test_syntactic_visibility_fallback() {
+        let options = AnalyzerOptions { syntactic_visibility: true, ..AnalyzerOptions::default() };
+        let mut parser = RustParser::with_options(options).unwrap();
+        let source = r"
+mod private {
+    pub fn inner() {}
+}
+";
+
+        let docstrings = parser.parse_source(source).unwrap();
+        let function_doc =
+            docstrings.iter().find(|d| matches!(d.target_type, DocstringTarget::Function)).unwrap();
+        assert!(function_doc.is_public);
+    }
+
+    /// Test that cfg-gated items are checked by default and skipped on request.
+    #[test]
+    fn test_cfg_gated_items_policy() {
+        let source = r"
+#[cfg(doc)]
+pub fn doc_only() {}
+
+#[cfg(unix)]
+pub mod sys {
+    pub fn platform() {}
+}
+
+pub fn always() {}
+";
+
+        let mut parser = RustParser::new().unwrap();
+        let docstrings = parser.parse_source(source).unwrap();
+        let functions =
+            docstrings.iter().filter(|d| matches!(d.target_type, DocstringTarget::Function));
+        assert_eq!(functions.count(), 3);
+
+        let options =
+            AnalyzerOptions { cfg_gated: CfgPolicy::SkipAll, ..AnalyzerOptions::default() };
+        let mut parser = RustParser::with_options(options).unwrap();
+        let docstrings = parser.parse_source(source).unwrap();
+        let functions: Vec<_> = docstrings
+            .iter()
+            .filter(|d| matches!(d.target_type, DocstringTarget::Function))
+            .collect();
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].line, 10);
+        assert!(!docstrings.iter().any(|d| matches!(d.target_type, DocstringTarget::Module)));
+    }
+
+    /// Test that only items whose cfg gates match the allowed predicates are checked.
+    #[test]
+    fn test_cfg_gated_items_check_only() {
+        let source = r#"
+#[cfg(doc)]
+pub fn doc_only() {}
+
+#[cfg(all(windows, feature = "extra"))]
+pub fn windows_extra() {}
+
+#[cfg(target_os = "linux")]
+pub fn linux_only() {}
+"#;
+
+        let options = AnalyzerOptions {
+            cfg_gated: CfgPolicy::CheckOnly(vec![
+                "doc".to_string(),
+                "feature = \"extra\"".to_string(),
+            ]),
+            ..AnalyzerOptions::default()
+        };
+        let mut parser = RustParser::with_options(options).unwrap();
+        let docstrings = parser.parse_source(source).unwrap();
+        let lines: Vec<_> = docstrings
+            .iter()
+            .filter(|d| matches!(d.target_type, DocstringTarget::Function))
+            .map(|d| d.line)
+            .collect();
+        assert_eq!(lines, vec![3, 6]);
+    }
+
+    /// Test collecting feature gates and doc(cfg) attributes as item metadata.
+    #[test]
+    fn test_feature_gate_attributes() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r#"
+#[cfg(feature = "serde")]
+pub mod serde_support {
+    /// Serialize the value.
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    #[cfg(all(feature = "json", unix))]
+    pub fn to_json() {}
+}
+"#;
+
+        let docstrings = parser.parse_source(source).unwrap();
+        let function_doc =
+            docstrings.iter().find(|d| matches!(d.target_type, DocstringTarget::Function)).unwrap();
+        assert_eq!(function_doc.attributes.features, vec!["json"]);
+        assert!(function_doc.attributes.has_doc_cfg);
+
+        let module_doc =
+            docstrings.iter().find(|d| matches!(d.target_type, DocstringTarget::Module)).unwrap();
+        assert_eq!(module_doc.attributes.features, vec!["serde"]);
+        assert!(!module_doc.attributes.has_doc_cfg);
+    }
+
+    /// Test capturing the qualifiers of function signatures.
+    #[test]
+    fn test_function_modifiers() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r#"
+/// Fetch the value.
+pub async unsafe fn fetch() {}
+
+/// Compute the value.
+const extern "system" fn compute() {}
+
+/// Call back into C.
+extern fn callback() {}
+
+/// Process the value.
+fn process() {}
+"#;
+
+        let docstrings = parser.parse_source(source).unwrap();
+        let modifiers: Vec<_> = docstrings
+            .iter()
+            .filter(|d| d.target_type == DocstringTarget::Function)
+            .map(|d| (d.item_path.as_str(), &d.attributes.modifiers))
+            .collect();
+        assert_eq!(modifiers, vec![
+            ("fetch", &FunctionModifiers { is_async: true, is_unsafe: true, ..Default::default() }),
+            ("compute", &FunctionModifiers {
+                is_const: true,
+                abi: Some("system".to_string()),
+                ..Default::default()
+            }),
+            ("callback", &FunctionModifiers { abi: Some("C".to_string()), ..Default::default() }),
+            ("process", &FunctionModifiers::default()),
+        ]);
+    }
+
+    /// Test collecting the generic parameters in scope for items.
+    #[test]
+    fn test_generic_parameters() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+/// Wrap a value.
+pub struct Wrapper<'a, T: Clone, const N: usize>(&'a [T; N]);
+
+/// Implement the wrapper.
+impl<'a, T: Clone, const N: usize> Wrapper<'a, T, N> {
+    /// Map the values.
+    pub fn map<U = u8, E>(&self) {}
+}
+";
+
+        let docstrings = parser.parse_source(source).unwrap();
+        let generics = |item_path: &str| {
+            docstrings
+                .iter()
+                .find(|d| d.item_path == item_path)
+                .unwrap()
+                .attributes
+                .generics
+                .clone()
+        };
+        assert_eq!(generics("Wrapper"), vec!["'a", "T", "N"]);
+        assert_eq!(generics("Wrapper::map"), vec!["U", "E", "'a", "T", "N"]);
+    }
+
+    /// Test extracting `pub use` and `pub extern crate` re-exports and the paths they re-export.
+    ///
+    /// Also test checking the docs written for them.
+    #[test]
+    fn test_reexports() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+/// The parser.
+pub use crate::parser::Parser;
+pub use self::lexer::{Token, span::Span as TokenSpan, self, prelude::*};
+pub use prelude::*;
+pub use traits::Trait as _;
+pub(crate) use internal::Helper;
+use private::Item;
+
+pub mod outer {
+    pub use super::Parser as OuterParser;
+}
+
+/// the serde crate
+pub extern crate serde as ser;
+pub extern crate log;
+pub extern crate self as this;
+pub extern crate alloc as _;
+extern crate core;
+";
+
+        let docstrings = parser.parse_source(source).unwrap();
+        let reexports: Vec<(&str, bool, bool, Vec<&str>)> = docstrings
+            .iter()
+            .filter(|d| d.target_type == DocstringTarget::Reexport)
+            .map(|d| {
+                let paths = d.attributes.reexports.iter().map(String::as_str).collect();
+                (d.item_path.as_str(), d.is_public, d.content.is_empty(), paths)
+            })
+            .collect();
+        assert_eq!(reexports, vec![
+            ("Parser", true, false, vec!["crate::parser::Parser"]),
+            ("Token", true, true, vec![
+                "self::lexer::Token",
+                "self::lexer::span::Span",
+                "self::lexer"
+            ]),
+            ("Helper", false, true, vec!["internal::Helper"]),
+            ("outer::OuterParser", true, true, vec!["super::Parser"]),
+            ("ser", true, false, vec!["::serde"]),
+            ("log", true, true, vec!["::log"]),
+            ("this", true, true, vec!["crate"]),
+        ]);
+
+        let ser = docstrings.iter().find(|d| d.item_path == "ser").unwrap();
+        let rules: Vec<String> =
+            Pep257Checker::check_docstring(ser).into_iter().map(|v| v.rule).collect();
+        assert_eq!(rules, vec!["D400", "D401", "D403"]);
+    }
+
+    /// Test indexing the items of a file in source order, with their spans.
+    #[test]
+    fn test_item_index() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"//! The crate.
+
+/// The parser.
+pub struct Parser {
+    input: String,
+}
+
+impl Parser {
+    /// Parse the input.
+    pub fn parse(&self) {}
+}
+
+fn helper() {}
+";
+
+        let items = parser.parse(source).unwrap().items;
+        let index: Vec<(&str, &str, DocstringTarget, bool, bool)> = items
+            .iter()
+            .map(|item| {
+                (item.path.as_str(), item.name(), item.kind, item.is_public, item.is_documented)
+            })
+            .collect();
+        assert_eq!(index, vec![
+            ("", "", DocstringTarget::Package, true, true),
+            ("Parser", "Parser", DocstringTarget::Struct, true, true),
+            ("Parser::input", "input", DocstringTarget::Field, false, false),
+            ("Parser", "Parser", DocstringTarget::ImplBlock, false, false),
+            ("Parser::parse", "parse", DocstringTarget::Method, true, true),
+            ("helper", "helper", DocstringTarget::Function, false, false),
+        ]);
+
+        let position = |line, column| SourcePosition { line, column };
+        assert_eq!(items[0].span, position(1, 1)..position(14, 1));
+        assert_eq!(items[1].span, position(4, 1)..position(6, 2));
+        assert_eq!(items[2].span, position(5, 5)..position(5, 18));
+        assert_eq!(items[4].span, position(10, 5)..position(10, 27));
+    }
+
+    /// Test finding docs separated from their item by attributes and plain comments.
+    #[test]
+    fn test_docs_around_attributes() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+/// Blank lines and attributes.
+
+#[derive(Debug)]
+
+#[cfg(test)]
+struct Spaced;
+
+/// A plain comment.
+// Not part of the docs.
+#[derive(Debug)]
+struct Commented;
+
+#[derive(Debug)]
+/// Docs between attributes.
+/* Not part of the docs. */
+#[repr(C)]
+struct Between;
+
+//// Four slashes aren't docs.
+#[derive(Debug)]
+struct Undocumented;
+";
+
+        let docstrings = parser.parse_source(source).unwrap();
+        let summary: Vec<_> =
+            docstrings.iter().map(|d| (d.item_path.as_str(), d.content.as_str(), d.line)).collect();
+        assert_eq!(summary, vec![
+            ("Spaced", "Blank lines and attributes.", 2),
+            ("Commented", "A plain comment.", 9),
+            ("Between", "Docs between attributes.", 15),
+            ("Undocumented", "", 22),
+        ]);
+    }
+
+    /// Test that doc attributes added with `cfg_attr` are documentation.
+    #[test]
+    fn test_cfg_attr_doc_attribute() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r#"
+#[cfg_attr(docsrs, doc = "Parse the input.")]
+pub fn parse() {}
+
+#[cfg_attr(all(doc, unix), doc = r"Read the input.")]
+pub fn read() {}
+
+#[cfg_attr(docsrs, doc(cfg(unix)))]
+pub fn write() {}
+"#;
+
+        let mut docstrings = parser.parse_source(source).unwrap();
+        docstrings.retain(|d| d.target_type == DocstringTarget::Function);
+        let contents: Vec<(&str, &str)> =
+            docstrings.iter().map(|d| (d.item_path.as_str(), d.content.as_str())).collect();
+        assert_eq!(contents, vec![
+            ("parse", "Parse the input."),
+            ("read", "Read the input."),
+            ("write", ""),
+        ]);
+        assert_eq!(docstrings[0].line_positions, vec![SourcePosition { line: 2, column: 27 }]);
+    }
+
+    /// Test that non-content doc attributes become metadata instead of content.
+    #[test]
+    fn test_doc_attribute_options_metadata() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r#"
+#[doc = "Parse the \"input\" value."]
+#[doc(alias = "read", hidden)]
+#[doc(alias("load", "decode"), inline)]
+fn parse() {}
+"#;
+
+        let docstrings = parser.parse_source(source).unwrap();
+        assert_eq!(docstrings.len(), 1);
+        assert_eq!(docstrings[0].content, "Parse the \"input\" value.");
+        assert_eq!(docstrings[0].attributes.doc_aliases, vec!["read", "load", "decode"]);
+        assert!(docstrings[0].attributes.doc_hidden);
+        assert_eq!(docstrings[0].attributes.doc_flags, vec!["inline"]);
+    }
+
+    /// Test checking items hidden with `#[doc(hidden)]` under each policy.
+    #[test]
+    fn test_doc_hidden_items() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+#[doc(hidden)]
+pub fn undocumented() {}
+
+#[doc(hidden)]
+pub mod internal {
+    pub fn nested() {}
+}
+
+/// parse it
+#[doc(hidden)]
+pub fn badly_documented() {}
+";
 
-        let processed_content = if doc_attributes.is_empty() {
-            Self::process_doc_comments(&doc_comments)
-        } else {
-            doc_attributes.join("\n")
+        let mut docstrings = parser.parse_source(source).unwrap();
+        docstrings.retain(|d| d.target_type != DocstringTarget::Package);
+        assert!(docstrings.iter().all(|d| d.attributes.doc_hidden));
+        let rules = |doc_hidden: HiddenPolicy| -> Vec<String> {
+            let options =
+                CheckerOptions { doc_hidden: Some(doc_hidden), ..CheckerOptions::default() };
+            let mut rules: Vec<String> = docstrings
+                .iter()
+                .flat_map(|d| Pep257Checker::check_docstring_with(d, &options))
+                .map(|v| v.rule)
+                .collect();
+            rules.sort();
+            rules
         };
+        assert_eq!(rules(HiddenPolicy::SkipMissingDocs), vec!["D400", "D403"]);
+        assert!(rules(HiddenPolicy::SkipAll).is_empty());
+        assert_eq!(rules(HiddenPolicy::CheckAll), vec!["D100", "D103", "D103", "D400", "D403"]);
+    }
 
-        let is_multiline = processed_content.lines().count() > 1;
+    /// Test that missing docs aren't reported where the `missing_docs` lint is allowed.
+    #[test]
+    fn test_allowed_missing_docs() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+#![allow(missing_docs)]
 
-        // Get position of the first documentation element
-        let start_point = first_doc_node.unwrap_or(node).start_position();
+pub fn allowed_by_crate() {}
 
-        Ok(Some(Docstring {
-            content: processed_content,
-            raw_content,
-            line: start_point.row + 1, // Convert to 1-based indexing
-            column: start_point.column + 1,
-            is_multiline,
-            is_public,
-            target_type,
-        }))
-    }
+#[deny(missing_docs)]
+pub mod strict {
+    pub fn denied() {}
 
-    /// Extract documentation from a #[doc = "..."] attribute.
-    fn extract_doc_attribute(
-        attr_node: &tree_sitter::Node<'_>,
-        source: &str,
-    ) -> Result<Option<String>, ParseError> {
-        let attr_text =
-            attr_node.utf8_text(source.as_bytes()).map_err(|_| ParseError::TreeSitter)?;
-
-        // Check if it's a doc attribute
-        if attr_text.contains("doc") {
-            // Parse #[doc = "content"] or #[doc(hidden)] etc.
-            if let Some(start) = attr_text.find("doc") {
-                let remaining = &attr_text[start..];
-
-                // Look for doc = "..." pattern
-                if let Some(eq_pos) = remaining.find('=') {
-                    let after_eq = &remaining[eq_pos + 1..].trim_start();
-
-                    // Extract string content between quotes
-                    if let Some(stripped) = after_eq.strip_prefix('"') {
-                        if let Some(end_quote) = stripped.find('"') {
-                            let content = &stripped[..end_quote];
-                            return Ok(Some(content.to_string()));
-                        }
-                    } else if let Some(stripped) = after_eq.strip_prefix("r#\"") {
-                        // Handle raw strings r#"..."#
-                        if let Some(end_pos) = stripped.find("\"#") {
-                            let content = &stripped[..end_pos];
-                            return Ok(Some(content.to_string()));
-                        }
-                    }
-                }
-            }
-        }
+    #[allow(dead_code, missing_docs)]
+    pub fn allowed() {}
+
+    pub mod inner {
+        #![expect(missing_docs)]
 
-        Ok(None)
+        pub fn expected() {}
     }
+}
+";
 
-    /// Process documentation comments to extract clean content.
-    fn process_doc_comments(comments: &[&str]) -> String {
-        let mut processed_lines = Vec::new();
+        let docstrings = parser.parse_source(source).unwrap();
+        let reported: Vec<&str> = docstrings
+            .iter()
+            .filter(|d| !Pep257Checker::check_docstring(d).is_empty())
+            .map(|d| d.item_path.as_str())
+            .collect();
+        assert_eq!(reported, vec!["strict::denied", "strict"]);
+    }
 
-        for comment in comments {
-            let trimmed = comment.trim();
+    /// Test that an item with only non-content doc attributes is reported as undocumented.
+    #[test]
+    fn test_doc_attribute_options_only() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r#"
+#[doc(alias = "other")]
+fn aliased() {}
+"#;
 
-            if let Some(content) = trimmed.strip_prefix("///") {
-                // Handle /// style comments
-                let clean_content = content.trim_start();
-                processed_lines.push(clean_content);
-            } else if let Some(content) = trimmed.strip_prefix("/**") {
-                // Handle /** */ style comments
-                let content = content.strip_suffix("*/").unwrap_or(content);
-                let lines: Vec<&str> = content.lines().collect();
+        let docstrings = parser.parse_source(source).unwrap();
+        assert_eq!(docstrings.len(), 1);
+        assert_eq!(docstrings[0].content, "");
+    }
 
-                for line in lines {
-                    let clean_line = line.trim_start_matches('*').trim_start();
-                    processed_lines.push(clean_line);
-                }
-            }
-        }
+    /// Test computing item paths from module, trait and impl nesting.
+    #[test]
+    fn test_item_paths() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+/// Outer module.
+mod outer {
+    /// A wrapper.
+    pub struct Wrapper<T>(T);
+
+    /// Wrapper methods.
+    impl<T> Wrapper<T> {
+        /// Create a wrapper.
+        pub fn new(value: T) -> Self { Self(value) }
+    }
 
-        // DO NOT remove empty lines at the beginning and end
-        // We need to preserve them for D201 and D202 checks
-        processed_lines.join("\n")
+    /// Display a wrapper.
+    impl<T> fmt::Display for Wrapper<T> {
+        /// Format the wrapper.
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { Ok(()) }
     }
+}
+";
 
-    /// Process inner documentation comments (//! and /*! */) to extract clean content.
-    fn process_inner_doc_comments(comments: &[&str]) -> String {
-        let mut processed_lines = Vec::new();
+        let mut paths: Vec<String> =
+            parser.parse_source(source).unwrap().into_iter().map(|d| d.item_path).collect();
+        paths.sort();
+        assert_eq!(paths, vec![
+            "outer",
+            "outer::<Wrapper as fmt::Display>",
+            "outer::<Wrapper as fmt::Display>::fmt",
+            "outer::Wrapper",
+            "outer::Wrapper",
+            "outer::Wrapper::new",
+        ]);
+    }
 
-        for comment in comments {
-            let trimmed = comment.trim();
+    /// Test extracting comments that trail fields and variants.
+    #[test]
+    fn test_trailing_comments() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+pub struct Point {
+    pub x: i32, /// the x
+    y: i32, // the y
+    /// Documented properly.
+    z: i32,
+}
 
-            if let Some(content) = trimmed.strip_prefix("//!") {
-                // Handle //! style comments
-                let clean_content = content.trim_start();
-                processed_lines.push(clean_content);
-            } else if let Some(content) = trimmed.strip_prefix("/*!") {
-                // Handle /*! */ style comments
-                let content = content.strip_suffix("*/").unwrap_or(content);
-                let lines: Vec<&str> = content.lines().collect();
+enum Mode {
+    Fast, // fast mode
+    Slow,
+}
+";
 
-                for line in lines {
-                    let clean_line = line.trim_start_matches('*').trim_start();
-                    processed_lines.push(clean_line);
-                }
-            }
-        }
+        let comments = parser.parse(source).unwrap().trailing_comments;
+        let summary: Vec<_> = comments
+            .iter()
+            .map(|c| (c.item_path.as_str(), c.is_doc, c.text.as_str(), c.position.line))
+            .collect();
+        assert_eq!(summary, vec![
+            ("Point::x", true, "the x", 3),
+            ("Point::y", false, "the y", 4),
+            ("Mode::Fast", false, "fast mode", 10),
+        ]);
+        assert_eq!(comments[2].member_kind, "variant");
+    }
 
-        // DO NOT remove empty lines at the beginning and end
-        // We need to preserve them for D201 and D202 checks
-        processed_lines.join("\n")
+    /// Test finding doc comments that no item follows.
+    #[test]
+    fn test_dangling_docs() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+pub struct Point {
+    pub x: i32, /// the x
+    /// Dangling after the last field.
+}
+
+impl Point {
+    /// Documented method.
+    pub fn norm(&self) -> i32 {
+        self.x
+        /// Dangling in a block.
+        // A plain comment.
     }
 }
 
-/// Unit tests for the parser.
+macro_rules! docs {
+    () => { /// Matched by the macro.
+    };
+}
+
+/// Dangling at the end of the file,
+/// reported once.
 #[cfg(test)]
-mod tests {
-    use super::*;
+";
 
-    /// Test parsing a simple function with documentation.
+        let docs = parser.parse(source).unwrap().dangling_docs;
+        let summary: Vec<_> =
+            docs.iter().map(|d| (d.item_path.as_str(), d.position.line)).collect();
+        assert_eq!(summary, vec![("Point", 4), ("Point::norm", 11), ("", 21)]);
+    }
+
+    /// Test marking the default methods of public traits.
     #[test]
-    fn test_parse_simple_function() {
+    fn test_default_methods() {
         let mut parser = RustParser::new().unwrap();
         let source = r"
-/// Calculate the sum of two numbers.
-fn add(a: i32, b: i32) -> i32 {
-    a + b
+/// Write output.
+pub trait Write {
+    /// Write bytes.
+    fn write(&mut self, bytes: &[u8]);
+    /// Flush the output.
+    fn flush(&mut self) {}
+}
+
+/// Read input.
+trait Read {
+    /// Read bytes.
+    fn read(&mut self) {}
+}
+
+/// A sink.
+pub struct Sink;
+
+impl Write for Sink {
+    /// Write nothing.
+    fn write(&mut self, _bytes: &[u8]) {}
 }
 ";
 
-        let docstrings = parser.parse_source(source).unwrap();
-        assert_eq!(docstrings.len(), 1);
-        assert_eq!(docstrings[0].content, "Calculate the sum of two numbers.");
-        assert!(!docstrings[0].is_multiline);
+        let docstrings = parser.parse(source).unwrap().docstrings;
+        let defaults: Vec<_> = docstrings
+            .iter()
+            .filter(|d| d.attributes.is_default_method)
+            .map(|d| d.item_path.as_str())
+            .collect();
+        assert_eq!(defaults, vec!["Write::flush"]);
     }
 
-    /// Test parsing a public function sets is_public = true.
+    /// Test parsing the members of traits, which are as public as their trait.
     #[test]
-    fn test_parse_public_function_sets_is_public() {
+    fn test_parse_trait_members() {
         let mut parser = RustParser::new().unwrap();
         let source = r"
-/// Public function docs.
-pub fn public_add(a: i32, b: i32) -> i32 {
-    a + b
+/// A source of items.
+pub trait Source {
+    /// Type of the items.
+    type Item;
+    const SIZE: usize;
+    /// Read the next item.
+    fn next(&mut self) -> Self::Item;
+    fn skip(&mut self) {}
+}
+
+/// A private source.
+trait Private {
+    fn next(&mut self);
+}
+
+impl Source for u8 {
+    type Item = u8;
+    const SIZE: usize = 1;
+    fn next(&mut self) -> u8 {
+        0
+    }
 }
 ";
 
         let docstrings = parser.parse_source(source).unwrap();
-        // Should have package doc (empty) + function doc
-        assert_eq!(docstrings.len(), 2);
+        let mut members: Vec<(&str, DocstringTarget, bool)> = docstrings
+            .iter()
+            .filter(|d| d.attributes.is_trait_member)
+            .map(|d| (d.item_path.as_str(), d.target_type, d.is_public))
+            .collect();
+        members.sort_by_key(|&(path, ..)| path);
+        assert_eq!(members, vec![
+            ("Private::next", DocstringTarget::Function, false),
+            ("Source::Item", DocstringTarget::TypeAlias, true),
+            ("Source::SIZE", DocstringTarget::Const, true),
+            ("Source::next", DocstringTarget::Function, true),
+            ("Source::skip", DocstringTarget::Function, true),
+        ]);
+
+        let missing: Vec<(String, String)> = docstrings
+            .iter()
+            .filter(|d| d.attributes.is_trait_member)
+            .flat_map(Pep257Checker::check_docstring)
+            .map(|v| (v.rule, v.message))
+            .collect();
+        assert_eq!(missing, vec![
+            ("R107".to_string(), "Missing docstring in public trait method `skip`".to_string()),
+            ("R107".to_string(), "Missing docstring in public associated const `SIZE`".to_string()),
+        ]);
+    }
 
-        let function_doc =
-            docstrings.iter().find(|d| matches!(d.target_type, DocstringTarget::Function)).unwrap();
-        assert_eq!(function_doc.content, "Public function docs.");
-        assert!(function_doc.is_public, "Expected is_public to be true for pub fn");
+    /// Test collecting the macros and methods a function body calls.
+    #[test]
+    fn test_body_calls() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r#"
+/// Load the config.
+pub fn load(path: &str) -> Config {
+    let text = std::fs::read_to_string(path).expect("readable");
+    assert!(!text.is_empty());
+    let parse = |line: &str| line.parse().unwrap();
+    format!("{}", parse(&text).unwrap());
+    std::panic!("done");
+
+    /// Nested.
+    fn nested() {
+        todo!()
+    }
+}
+"#;
+
+        let docstrings = parser.parse(source).unwrap().docstrings;
+        let load = docstrings.iter().find(|d| d.item_path == "load").unwrap();
+        assert_eq!(load.attributes.calls, vec![
+            "expect", "assert!", "is_empty", "unwrap", "parse", "format!", "panic!"
+        ]);
+        let nested = docstrings.iter().find(|d| d.item_path.ends_with("nested")).unwrap();
+        assert_eq!(nested.attributes.calls, vec!["todo!"]);
+    }
+
+    /// Test finding unsafe blocks and their safety comments.
+    #[test]
+    fn test_unsafe_blocks() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+pub fn read(ptr: *const u8) -> u8 {
+    // SAFETY: the caller passes a valid pointer.
+    let value = unsafe { *ptr };
+    // Read it again.
+    #[allow(unused_unsafe)]
+    let again = unsafe { *ptr };
+    value + unsafe { *ptr }
+}
+
+fn private(ptr: *const u8) -> u8 {
+    // Safety: checked by the caller.
+    unsafe { *ptr }
+}
+
+pub struct Buffer;
+
+impl Buffer {
+    pub fn get(&self) -> u8 {
+        const ZERO: u8 = unsafe { 0 };
+        unsafe { ZERO }
+    }
+}
+";
+
+        let blocks = parser.parse(source).unwrap().unsafe_blocks;
+        let summary: Vec<_> = blocks
+            .iter()
+            .map(|b| (b.item_path.as_str(), b.position.line, b.is_public, b.has_safety_comment))
+            .collect();
+        assert_eq!(summary, vec![
+            ("read", 4, true, true),
+            ("read", 7, true, false),
+            ("read", 8, true, false),
+            ("private", 13, false, true),
+            ("Buffer::get", 21, true, false),
+        ]);
+    }
+
+    /// Test finding syntax that the crate's edition doesn't accept.
+    #[test]
+    fn test_edition_errors() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+pub async fn fetch() {
+    let gen = load().await;
+    let value = gen { 1 };
+}
+pub fn r#try() { quote!(async gen); }
+";
+        let mut errors = |edition: Edition| -> Vec<(usize, String)> {
+            let parsed = parser.parse_edition(source, Some(edition)).unwrap();
+            parsed
+                .edition_errors
+                .into_iter()
+                .map(|(position, message)| (position.line, message))
+                .collect()
+        };
+
+        assert_eq!(errors(Edition::E2015), vec![
+            (2, "`async` is not available in Rust 2015".to_string()),
+            (3, "`.await` is not available in Rust 2015".to_string()),
+            (4, "`gen` blocks are not available in Rust 2015".to_string()),
+        ]);
+        assert_eq!(errors(Edition::E2021), vec![(
+            4,
+            "`gen` blocks are not available in Rust 2021".to_string()
+        )]);
+        assert_eq!(errors(Edition::E2024), vec![(
+            3,
+            "`gen` is a keyword in Rust 2024, use `r#gen`".to_string()
+        )]);
+        assert!(parser.parse(source).unwrap().edition_errors.is_empty());
+    }
+
+    /// Test resolving escape sequences in doc attribute strings.
+    #[test]
+    fn test_unescape_string() {
+        assert_eq!(RustParser::unescape_string(r"a\tb\\c\u{e9}\x41"), "a\tb\\c\u{e9}A");
+        assert_eq!(RustParser::unescape_string("line \\\n    continued"), "line continued");
+    }
+
+    /// Test that a grammar library that can't be loaded is reported.
+    #[test]
+    fn test_load_grammar_failure() {
+        let options = AnalyzerOptions {
+            grammar: Some("/nonexistent/libtree-sitter-rust.so".into()),
+            ..AnalyzerOptions::default()
+        };
+
+        let result = RustParser::with_options(options);
+        assert!(
+            matches!(result, Err(ParseError::Grammar(message)) if message.contains("/nonexistent"))
+        );
+    }
+
+    /// Test splitting cfg predicates into positive atoms.
+    #[test]
+    fn test_cfg_atoms() {
+        assert_eq!(RustParser::cfg_atoms("unix"), vec!["unix"]);
+        assert_eq!(RustParser::cfg_atoms("any(unix, all(doc, feature = \"x\"), not(test))"), vec![
+            "unix",
+            "doc",
+            "feature=\"x\""
+        ]);
+    }
+
+    /// Test extracting file-level inner attributes.
+    #[test]
+    fn test_parse_inner_attributes() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"//! Crate docs.
+#![warn(missing_docs)]
+#![allow(clippy::all)]
+
+fn main() {}
+";
+
+        let parsed = parser.parse(source).unwrap();
+        assert_eq!(parsed.inner_attributes, vec!["warn(missing_docs)", "allow(clippy::all)"]);
     }
 
     /// Test parsing a multiline function documentation.
@@ -828,9 +3171,61 @@ struct Point {
 ";
 
         let docstrings = parser.parse_source(source).unwrap();
-        assert_eq!(docstrings.len(), 1);
+        assert_eq!(docstrings.len(), 3);
         assert_eq!(docstrings[0].content, "Represents a point in 2D space.");
         assert!(matches!(docstrings[0].target_type, DocstringTarget::Struct));
+        // The fields have no docs
+        assert!(docstrings[1..].iter().all(|d| d.target_type == DocstringTarget::Field));
+    }
+
+    /// Test parsing struct fields, which are public if they and their struct are.
+    #[test]
+    fn test_parse_fields() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+/// A user account.
+pub struct User {
+    /// The name of the user.
+    pub name: String,
+    #[serde(default)]
+    /// Whether the account is active.
+    pub(crate) active: bool,
+    id: u64,
+}
+
+/// A private point.
+struct Point {
+    /// The x-coordinate.
+    pub x: f64,
+}
+
+/// An event.
+pub enum Event {
+    /// A key press.
+    Key {
+        /// The pressed key.
+        code: u32,
+    },
+}
+";
+
+        let docstrings = parser.parse_source(source).unwrap();
+        let fields: Vec<(&str, &str, bool)> = docstrings
+            .iter()
+            .filter(|d| d.target_type == DocstringTarget::Field)
+            .map(|d| (d.item_path.as_str(), d.content.as_str(), d.is_public))
+            .collect();
+        assert_eq!(fields, vec![
+            ("User::name", "The name of the user.", true),
+            ("User::active", "Whether the account is active.", false),
+            ("User::id", "", false),
+            ("Point::x", "The x-coordinate.", false),
+            ("Event::code", "The pressed key.", false),
+        ]);
+
+        let name = docstrings.iter().find(|d| d.item_path == "User::name").unwrap();
+        assert_eq!(name.line, 4);
+        assert!(Pep257Checker::check_docstring(name).is_empty());
     }
 
     /// Test parsing a type alias with documentation.
@@ -854,6 +3249,101 @@ fn test_parse_type_alias() {
         assert!(type_alias_doc.is_public);
     }
 
+    /// Test parsing statics, which are checked like consts.
+    #[test]
+    fn test_parse_static() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+/// Number of requests served.
+pub static REQUESTS: AtomicUsize = AtomicUsize::new(0);
+
+pub static mut COUNTER: u32 = 0;
+
+static PRIVATE: u8 = 0;
+";
+
+        let docstrings = parser.parse_source(source).unwrap();
+        let statics: Vec<(&str, &str, bool)> = docstrings
+            .iter()
+            .filter(|d| d.target_type == DocstringTarget::Static)
+            .map(|d| (d.item_path.as_str(), d.content.as_str(), d.is_public))
+            .collect();
+        assert_eq!(statics, vec![
+            ("REQUESTS", "Number of requests served.", true),
+            ("COUNTER", "", true),
+            ("PRIVATE", "", false),
+        ]);
+
+        let counter = docstrings.iter().find(|d| d.item_path == "COUNTER").unwrap();
+        let violations = Pep257Checker::check_docstring(counter);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].message, "Missing docstring in public static `COUNTER`");
+    }
+
+    /// Test that functions in impl blocks are methods, apart from their impl block.
+    #[test]
+    fn test_parse_methods() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r"
+/// Parser of input.
+pub struct Parser;
+
+/// Parsing methods.
+impl Parser {
+    pub fn parse(&self) {}
+}
+
+pub fn helper() {}
+";
+
+        let docstrings = parser.parse_source(source).unwrap();
+        let targets: Vec<(&str, DocstringTarget, usize)> = docstrings
+            .iter()
+            .filter(|d| d.target_type != DocstringTarget::Package)
+            .map(|d| (d.item_path.as_str(), d.target_type, d.line))
+            .collect();
+        assert_eq!(targets, vec![
+            ("Parser::parse", DocstringTarget::Method, 7),
+            ("helper", DocstringTarget::Function, 10),
+            ("Parser", DocstringTarget::Struct, 2),
+            ("Parser", DocstringTarget::ImplBlock, 5),
+        ]);
+
+        let method = docstrings.iter().find(|d| d.item_path == "Parser::parse").unwrap();
+        let violations = Pep257Checker::check_docstring(method);
+        assert_eq!(violations.len(), 1);
+        assert_eq!((violations[0].rule.as_str(), violations[0].line), ("D102", 7));
+    }
+
+    /// Test parsing `extern` blocks and the items they declare.
+    #[test]
+    fn test_parse_extern_block() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r#"
+/// Bindings to the C library.
+extern "C" {
+    /// Return the absolute value of an integer.
+    pub fn abs(x: i32) -> i32;
+    pub static errno: i32;
+    fn exit(status: i32);
+}
+"#;
+
+        let docstrings = parser.parse_source(source).unwrap();
+        let mut items: Vec<(&str, DocstringTarget, &str, bool)> = docstrings
+            .iter()
+            .filter(|d| d.target_type != DocstringTarget::Package)
+            .map(|d| (d.item_path.as_str(), d.target_type, d.content.as_str(), d.is_public))
+            .collect();
+        items.sort_by_key(|item| item.0);
+        assert_eq!(items, vec![
+            ("", DocstringTarget::ExternBlock, "Bindings to the C library.", false),
+            ("abs", DocstringTarget::Function, "Return the absolute value of an integer.", true),
+            ("errno", DocstringTarget::Static, "", true),
+            ("exit", DocstringTarget::Function, "", false),
+        ]);
+    }
+
     /// Test parsing a macro with documentation.
     #[test]
     fn test_parse_macro() {
@@ -983,4 +3473,30 @@ fn main() {
         assert!(package_docs[0].content.contains("Command-line tool"));
         assert!(package_docs[0].is_public);
     }
+
+    /// Test that block comments keep their layout out of the docs.
+    ///
+    /// The layout is the lines holding only a marker and the ` * ` decoration.
+    #[test]
+    fn test_parse_block_comment_layout() {
+        let mut parser = RustParser::new().unwrap();
+        let source = "/**\n * Create a value.\n *\n * * first\n */\npub fn a() {}\n\n/** Create a value.\n    * first\n*/\npub fn b() {}\n\n/**\n   Create a value.\n   * first\n */\npub fn c() {}\n\n/** Create a value. */\npub fn d() {}\n";
+
+        let docstrings: Vec<_> = parser
+            .parse_source(source)
+            .unwrap()
+            .into_iter()
+            .filter(|d| matches!(d.target_type, DocstringTarget::Function))
+            .collect();
+        let contents: Vec<&str> = docstrings.iter().map(|d| d.content.as_str()).collect();
+        assert_eq!(contents, vec![
+            "Create a value.\n\n* first",
+            "Create a value.\nfirst",
+            "Create a value.\n* first",
+            "Create a value. ",
+        ]);
+        assert_eq!(docstrings[0].line_positions[0], SourcePosition { line: 2, column: 4 });
+        assert!(!docstrings[3].is_multiline);
+        assert!(Pep257Checker::check_docstring(&docstrings[0]).is_empty());
+    }
 }
@@ -1,7 +1,9 @@
-use std::fmt;
+use std::{fmt, ops::Range, sync::OnceLock};
 
+use clap::ValueEnum;
 use imperative::Mood;
-use regex::Regex;
+
+use crate::{context::AnalysisContext, position::SourcePosition};
 
 /// Represents a PEP 257 violation.
 #[derive(Debug, Clone)]
@@ -11,138 +13,1982 @@ pub struct Violation {
     pub line: usize,
     pub column: usize,
     pub severity: Severity,
+    /// Path of the documented item, such as `crate::parser::RustParser::parse`.
+    pub item_path: Option<String>,
+    /// Text of the source line the violation is on, without its line ending.
+    pub source_line: Option<String>,
+    /// Byte offset into the source where the offending region starts.
+    pub start_byte: Option<usize>,
+    /// Byte offset into the source where the offending region ends, exclusive.
+    pub end_byte: Option<usize>,
+    /// Line where the offending region ends.
+    pub end_line: Option<usize>,
+    /// Column where the offending region ends, exclusive, in the unit of `column`.
+    pub end_column: Option<usize>,
 }
 
 /// Severity level for violations.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Severity {
     Error,
     Warning,
+    /// Violations of rules newer than the pinned rule set, which don't fail a check.
+    Info,
+}
+
+/// Implementation of severity methods.
+impl Severity {
+    /// Every severity, most severe first.
+    pub const ALL: [Self; 3] = [Self::Error, Self::Warning, Self::Info];
+
+    /// Return the name of the severity, as printed in reports.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Info => "info",
+        }
+    }
+
+    /// Look up a severity by its name.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|severity| severity.name() == name)
+    }
+}
+
+/// Implementation of violation methods.
+impl Violation {
+    /// Return a violation of a rule starting at a source position.
+    ///
+    /// The item path, source line and region it covers are unknown until set with the
+    /// other methods, and otherwise filled in by the analyzer.
+    #[must_use]
+    pub(crate) fn new(
+        rule: &str,
+        message: impl Into<String>,
+        position: SourcePosition,
+        severity: Severity,
+    ) -> Self {
+        Self {
+            rule: rule.to_string(),
+            message: message.into(),
+            line: position.line,
+            column: position.column,
+            severity,
+            item_path: None,
+            source_line: None,
+            start_byte: None,
+            end_byte: None,
+            end_line: None,
+            end_column: None,
+        }
+    }
+
+    /// Return the violation of the item with the given path.
+    #[must_use]
+    pub(crate) fn with_item_path(self, item_path: impl Into<String>) -> Self {
+        Self { item_path: Some(item_path.into()), ..self }
+    }
+
+    /// Return the violation covering a byte range of the source.
+    #[must_use]
+    pub(crate) fn with_byte_range(self, range: Range<usize>) -> Self {
+        Self { start_byte: Some(range.start), end_byte: Some(range.end), ..self }
+    }
+
+    /// Return the violation covering the source up to a position, exclusive.
+    #[must_use]
+    pub(crate) fn ending_at(self, end: SourcePosition) -> Self {
+        Self { end_line: Some(end.line), end_column: Some(end.column), ..self }
+    }
+}
+
+/// Format a violation for display.
+impl fmt::Display for Violation {
+    /// Format the violation as a string.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{} {} [{}]: {}", // filename will be added by caller
+            self.line,
+            self.column,
+            self.severity.name(),
+            self.rule,
+            self.message
+        )
+    }
+}
+
+/// Represents a docstring found in the code.
+#[derive(Debug, Clone)]
+pub(crate) struct Docstring {
+    pub content: String,
+    pub raw_content: String,
+    pub line: usize,
+    pub column: usize,
+    pub is_multiline: bool,
+    pub is_public: bool,
+    pub target_type: DocstringTarget,
+    pub attributes: ItemAttributes,
+    /// Source position where each line of `content` starts, when known.
+    pub line_positions: Vec<SourcePosition>,
+    /// Path of the item within its file, such as `RustParser::parse`.
+    ///
+    /// Empty for the file's own module.
+    pub item_path: String,
+}
+
+/// Implementation of docstring methods.
+impl Docstring {
+    /// Return the declared name of the item, the last segment of its path.
+    ///
+    /// The name is empty for the file's own module.
+    pub(crate) fn name(&self) -> &str {
+        self.item_path.rsplit("::").next().unwrap_or_default()
+    }
+
+    /// Return the source position where the docstring starts.
+    pub(crate) const fn position(&self) -> SourcePosition {
+        SourcePosition { line: self.line, column: self.column }
+    }
+
+    /// Return the version of the rule set that first checked the docstring's item kind for a rule.
+    ///
+    /// Fields, statics and `extern` blocks with their functions were first checked by rule set 2,
+    /// as were methods for missing docs, which rule set 1 reported as functions.
+    pub(crate) fn checked_since(&self, rule: &str) -> u32 {
+        match self.target_type {
+            DocstringTarget::Field | DocstringTarget::Static | DocstringTarget::ExternBlock => 2,
+            DocstringTarget::Method if rule == "D102" => 2,
+            _ if self.attributes.is_extern_member => 2,
+            _ => 1,
+        }
+    }
+
+    /// Return the first source line of the docstring's comments or attributes.
+    pub(crate) fn first_line(&self) -> usize {
+        self.line_positions.iter().map(|position| position.line).fold(self.line, usize::min)
+    }
+
+    /// Return the source position of the given byte offset into `content`.
+    ///
+    /// Without recorded line positions, lines are assumed to follow the docstring's
+    /// first line one per source line.
+    pub(crate) fn position_at(&self, offset: usize) -> SourcePosition {
+        let before = &self.content[..offset.min(self.content.len())];
+        let line_index = before.matches('\n').count();
+        let line_offset = before.len() - before.rfind('\n').map_or(0, |newline| newline + 1);
+
+        match self.line_positions.get(line_index) {
+            Some(start) => SourcePosition { line: start.line, column: start.column + line_offset },
+            None => {
+                SourcePosition { line: self.line + line_index, column: self.column + line_offset }
+            }
+        }
+    }
+
+    /// Return the source line of the given line index into `content`.
+    pub(crate) fn source_line(&self, line_index: usize) -> usize {
+        self.line_positions.get(line_index).map_or(self.line + line_index, |start| start.line)
+    }
+}
+
+/// A comment written after a struct field or enum variant on the same line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TrailingComment {
+    /// Text of the comment without its marker.
+    pub text: String,
+    /// Whether the comment is a `///` doc comment.
+    pub is_doc: bool,
+    /// Kind of member the comment follows, `field` or `variant`.
+    pub member_kind: &'static str,
+    /// Name of the field or variant.
+    pub member: String,
+    /// Path of the member within its file, such as `Config::verbose`.
+    pub item_path: String,
+    /// 1-based line of the field or variant.
+    pub member_line: usize,
+    /// Position of the comment.
+    pub position: SourcePosition,
+}
+
+/// A doc comment that no item follows, so rustdoc drops it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DanglingDoc {
+    /// Path of the item whose body ends after the comment, empty at the end of a file.
+    pub item_path: String,
+    /// Position of the first line of the comment.
+    pub position: SourcePosition,
+}
+
+/// An `unsafe` block in the body of a function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UnsafeBlock {
+    /// Path of the function containing the block.
+    pub item_path: String,
+    /// Whether the function is part of the public API.
+    pub is_public: bool,
+    /// Whether a `// SAFETY:` comment directly precedes the statement with the block.
+    pub has_safety_comment: bool,
+    /// Position of the `unsafe` keyword.
+    pub position: SourcePosition,
+}
+
+/// Attribute and signature metadata about the documented item.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ItemAttributes {
+    /// Predicates of the `#[cfg(...)]` gates on the item and its enclosing items.
+    pub cfg: Vec<String>,
+    /// Cargo features required by the item's own cfg gates, e.g. `serde` for `feature = "serde"`.
+    pub features: Vec<String>,
+    /// Whether the item has a `#[doc(cfg(...))]` attribute, possibly inside `cfg_attr`.
+    pub has_doc_cfg: bool,
+    /// Search aliases from `#[doc(alias = "...")]` and `#[doc(alias("...", ...))]`.
+    pub doc_aliases: Vec<String>,
+    /// Whether the item or an enclosing item is marked `#[doc(hidden)]`.
+    pub doc_hidden: bool,
+    /// File that a `#![doc = include_str!("...")]` attribute includes as the docs.
+    ///
+    /// The path is relative to the source file, and kept until the analyzer loads its content.
+    pub doc_include: Option<String>,
+    /// Whether the nearest `missing_docs` lint level of the item is `allow` or `expect`.
+    ///
+    /// The level may be set on the item or an enclosing item or module.
+    pub allows_missing_docs: bool,
+    /// Other `#[doc(...)]` options on the item, such as `inline` or `notable_trait`.
+    pub doc_flags: Vec<String>,
+    /// Qualifiers of the function signature, for function items.
+    pub modifiers: FunctionModifiers,
+    /// Whether the item is a method, associated const or associated type of a trait.
+    pub is_trait_member: bool,
+    /// Whether the item is a method with a default body in a public trait.
+    pub is_default_method: bool,
+    /// Whether the item is a function or static declared in an `extern` block.
+    pub is_extern_member: bool,
+    /// Generic parameters in scope for the item, such as `T`, `N` or `'a`.
+    ///
+    /// These are its own and those of enclosing impls and traits.
+    pub generics: Vec<String>,
+    /// Paths of the items a `pub use` declaration re-exports, such as `crate::parser::Parser`.
+    pub reexports: Vec<String>,
+    /// Source positions where the item starts and ends, excluding its outer docs.
+    pub span: Option<Range<SourcePosition>>,
+    /// Byte range of the item in the source, excluding its outer docs.
+    pub byte_range: Option<Range<usize>>,
+    /// Source position of the item's first outer attribute, if it has any.
+    pub attributes_start: Option<SourcePosition>,
+    /// Parameters of a function item other than `self`, as written, such as `path`.
+    pub parameters: Vec<String>,
+    /// Whether a function item returns a `Result`, of any module such as `io::Result`.
+    pub returns_result: bool,
+    /// Macros and methods called in the body of a function item, such as `panic!` and `unwrap`.
+    ///
+    /// Items nested in the body are excluded.
+    pub calls: Vec<String>,
+}
+
+/// Qualifiers written before `fn` in a function signature.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct FunctionModifiers {
+    /// Whether the function is `async`.
+    pub is_async: bool,
+    /// Whether the function is `const`.
+    pub is_const: bool,
+    /// Whether the function is `unsafe`.
+    pub is_unsafe: bool,
+    /// ABI of an `extern` function, `C` when the ABI is left implicit.
+    pub abi: Option<String>,
+}
+
+/// Type of construct that has a docstring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DocstringTarget {
+    Function,
+    Method,
+    Struct,
+    Enum,
+    Module,
+    Package,
+    ImplBlock,
+    ExternBlock,
+    Trait,
+    Const,
+    Static,
+    TypeAlias,
+    Macro,
+    Reexport,
+    Field,
+}
+
+/// Format a docstring target for display.
+impl fmt::Display for DocstringTarget {
+    /// Format the docstring target as a string.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DocstringTarget::Function => "function",
+            DocstringTarget::Method => "method",
+            DocstringTarget::Struct => "struct",
+            DocstringTarget::Enum => "enum",
+            DocstringTarget::Module => "module",
+            DocstringTarget::Package => "package",
+            DocstringTarget::ImplBlock => "impl",
+            DocstringTarget::ExternBlock => "extern block",
+            DocstringTarget::Trait => "trait",
+            DocstringTarget::Const => "const",
+            DocstringTarget::Static => "static",
+            DocstringTarget::TypeAlias => "type alias",
+            DocstringTarget::Macro => "macro",
+            DocstringTarget::Reexport => "re-export",
+            DocstringTarget::Field => "field",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Implementation of docstring target methods.
+impl DocstringTarget {
+    /// Every docstring target.
+    const ALL: [Self; 14] = [
+        Self::Function,
+        Self::Method,
+        Self::Struct,
+        Self::Enum,
+        Self::Module,
+        Self::Package,
+        Self::ImplBlock,
+        Self::ExternBlock,
+        Self::Trait,
+        Self::Const,
+        Self::Static,
+        Self::TypeAlias,
+        Self::Macro,
+        Self::Field,
+    ];
+
+    /// Check whether the target is a function, free or a method.
+    pub(crate) fn is_function(self) -> bool {
+        matches!(self, Self::Function | Self::Method)
+    }
+
+    /// Look up a target by its display name, accepting `-` or `_` in place of spaces.
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase().replace(['-', '_'], " ");
+        Self::ALL.into_iter().find(|target| target.to_string() == name)
+    }
+
+    /// Look up a target by its display name.
+    ///
+    /// The error names the valid target types if there's none.
+    fn parse_name(name: &str) -> Result<Self, String> {
+        Self::from_name(name).ok_or_else(|| {
+            let names: Vec<String> =
+                Self::ALL.iter().map(|target| target.to_string().replace(' ', "-")).collect();
+            format!("unknown target `{name}`, expected one of: {}", names.join(", "))
+        })
+    }
+}
+
+/// Maximum number of prose lines a docstring may have without a section heading (R407).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProseLimits {
+    /// Limit for targets without their own limit, or `0` to disable the check.
+    pub default: usize,
+    overrides: Vec<(DocstringTarget, usize)>,
+}
+
+/// Implementation of prose limit methods.
+impl ProseLimits {
+    /// Default number of prose lines allowed without a section heading.
+    pub const DEFAULT_LIMIT: usize = 30;
+
+    /// Set the limit for a target type such as `function` or `type-alias`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the valid target types if `target` isn't one of them.
+    pub fn set(&mut self, target: &str, limit: usize) -> Result<(), String> {
+        let target = DocstringTarget::parse_name(target)?;
+        self.overrides.retain(|(existing, _)| *existing != target);
+        self.overrides.push((target, limit));
+        Ok(())
+    }
+
+    /// Return the limit for a target type.
+    pub(crate) fn limit_for(&self, target: DocstringTarget) -> usize {
+        self.overrides
+            .iter()
+            .find(|(existing, _)| *existing == target)
+            .map_or(self.default, |&(_, limit)| limit)
+    }
+}
+
+/// Provide the default prose limits.
+impl Default for ProseLimits {
+    /// Return the same default limit for every target type.
+    fn default() -> Self {
+        Self { default: Self::DEFAULT_LIMIT, overrides: Vec::new() }
+    }
+}
+
+/// Target types whose public items should include a code example (R417).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExampleTargets {
+    /// Targets added, or none for [`Self::DEFAULT_TARGETS`].
+    targets: Vec<DocstringTarget>,
+}
+
+/// Implementation of example target methods.
+impl ExampleTargets {
+    /// Target types checked when none are added.
+    const DEFAULT_TARGETS: [DocstringTarget; 3] =
+        [DocstringTarget::Function, DocstringTarget::Method, DocstringTarget::Struct];
+
+    /// Add a target type such as `function` or `type-alias`, replacing the defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the valid target types if `target` isn't one of them.
+    pub fn add(&mut self, target: &str) -> Result<(), String> {
+        let target = DocstringTarget::parse_name(target)?;
+        if !self.targets.contains(&target) {
+            self.targets.push(target);
+        }
+        Ok(())
+    }
+
+    /// Check whether items of a target type should include an example.
+    pub(crate) fn includes(&self, target: DocstringTarget) -> bool {
+        if self.targets.is_empty() {
+            Self::DEFAULT_TARGETS.contains(&target)
+        } else {
+            self.targets.contains(&target)
+        }
+    }
+}
+
+/// Marker character of a Markdown bullet list item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BulletMarker {
+    /// `- item`
+    #[value(name = "-")]
+    Dash,
+    /// `* item`
+    #[value(name = "*")]
+    Asterisk,
+    /// `+ item`
+    #[value(name = "+")]
+    Plus,
+}
+
+/// Implementation of bullet marker methods.
+impl BulletMarker {
+    /// Return the marker character.
+    #[must_use]
+    pub fn as_char(self) -> char {
+        match self {
+            Self::Dash => '-',
+            Self::Asterisk => '*',
+            Self::Plus => '+',
+        }
+    }
+}
+
+/// How items marked `#[doc(hidden)]`, or inside such an item, are checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum HiddenPolicy {
+    /// Skip the missing docstring rules, since rustdoc doesn't show the item.
+    #[default]
+    SkipMissingDocs,
+    /// Skip every rule.
+    SkipAll,
+    /// Check hidden items like any other item.
+    CheckAll,
 }
 
-/// Format a violation for display.
-impl fmt::Display for Violation {
-    /// Format the violation as a string.
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}:{} {} [{}]: {}", // filename will be added by caller
-            self.line,
-            self.column,
-            match self.severity {
-                Severity::Error => "error",
-                Severity::Warning => "warning",
-            },
-            self.rule,
-            self.message
-        )
+/// Rules that only run when enabled with [`CheckerOptions::enabled_rules`].
+pub const OPT_IN_RULES: &[&str] = &[
+    "D212", "D213", "R409", "R410", "R411", "R412", "R413", "R414", "R415", "R416", "R417", "R501",
+    "R502", "R503", "R601",
+];
+
+/// Common Rust types that should use inline code instead of markdown links (R402).
+pub(crate) const COMMON_RUST_TYPES: &[&str] =
+    &["Option", "Result", "Vec", "Box", "Rc", "Arc", "Some", "None", "Ok", "Err"];
+
+/// Words that public async function docs mention by default when R412 is enabled.
+pub const DEFAULT_ASYNC_KEYWORDS: &[&str] = &["cancel"];
+
+/// Words that the docs of default trait methods mention by default when R414 is enabled.
+pub const DEFAULT_DEFAULT_METHOD_KEYWORDS: &[&str] = &["default"];
+
+/// Macros and methods that make a function panic by default when R416 is enabled.
+pub const DEFAULT_PANIC_CONSTRUCTS: &[&str] =
+    &["panic!", "assert!", "assert_eq!", "assert_ne!", "unwrap", "expect"];
+
+/// Minimum number of non-blank lines of crate docs when R503 is enabled.
+pub const DEFAULT_CRATE_DOCS_MIN_LINES: usize = 10;
+
+/// Maximum number of blank lines between the summary and the description (R202).
+pub const DEFAULT_MAX_SUMMARY_BLANK_LINES: usize = 1;
+
+/// Options controlling which checks run and how.
+#[derive(Debug, Clone)]
+pub struct CheckerOptions {
+    /// Suggest enabling the `missing_docs` lint in crate roots that don't configure it (R104).
+    pub suggest_missing_docs_lint: bool,
+    /// Require feature-gated items to carry `#[doc(cfg(...))]` (R404).
+    ///
+    /// Otherwise mentioning the feature in prose is enough.
+    pub require_doc_cfg: bool,
+    /// Maximum prose lines without a section heading, per target type (R407).
+    pub prose_limits: ProseLimits,
+    /// Marker every bullet list item should use (R408).
+    ///
+    /// When `None`, each list should use its first marker.
+    pub bullet_marker: Option<BulletMarker>,
+    /// Opt-in rules to run in addition to the default ones, from [`OPT_IN_RULES`].
+    pub enabled_rules: Vec<String>,
+    /// Words, one of which public async function docs should mention (R412).
+    ///
+    /// [`DEFAULT_ASYNC_KEYWORDS`] when empty.
+    pub async_keywords: Vec<String>,
+    /// Words, one of which the docs of default trait methods should mention (R414).
+    ///
+    /// [`DEFAULT_DEFAULT_METHOD_KEYWORDS`] when empty.
+    pub default_method_keywords: Vec<String>,
+    /// Macros such as `panic!` and methods such as `unwrap` that make a function panic (R416).
+    ///
+    /// [`DEFAULT_PANIC_CONSTRUCTS`] when empty.
+    pub panic_constructs: Vec<String>,
+    /// Target types whose public items should include a code example (R417).
+    pub example_targets: ExampleTargets,
+    /// Minimum number of non-blank lines of crate docs (R503).
+    ///
+    /// [`DEFAULT_CRATE_DOCS_MIN_LINES`] when `None`.
+    pub crate_docs_min_lines: Option<usize>,
+    /// Maximum number of blank lines between the summary and the description (R202).
+    ///
+    /// [`DEFAULT_MAX_SUMMARY_BLANK_LINES`] when `None`.
+    pub max_summary_blank_lines: Option<usize>,
+    /// How items hidden with `#[doc(hidden)]` are checked.
+    ///
+    /// [`HiddenPolicy::SkipMissingDocs`] when `None`.
+    pub doc_hidden: Option<HiddenPolicy>,
+}
+
+/// Provide the default checker options.
+impl Default for CheckerOptions {
+    /// Return options with all default checks enabled.
+    fn default() -> Self {
+        Self {
+            suggest_missing_docs_lint: true,
+            require_doc_cfg: false,
+            prose_limits: ProseLimits::default(),
+            bullet_marker: None,
+            enabled_rules: Vec::new(),
+            async_keywords: Vec::new(),
+            default_method_keywords: Vec::new(),
+            panic_constructs: Vec::new(),
+            example_targets: ExampleTargets::default(),
+            crate_docs_min_lines: None,
+            max_summary_blank_lines: None,
+            doc_hidden: None,
+        }
+    }
+}
+
+/// Implementation of checker option methods.
+impl CheckerOptions {
+    /// Check whether an opt-in rule is enabled.
+    #[must_use]
+    pub fn is_enabled(&self, rule: &str) -> bool {
+        self.enabled_rules.iter().any(|enabled| enabled == rule)
+    }
+}
+
+/// PEP 257 checker implementation.
+///
+/// The checks are stateless: state that is costly to build, such as the mood
+/// dictionary, is built once and shared by every thread.
+pub(crate) struct Pep257Checker;
+
+/// Return the imperative mood dictionary, building it on first use.
+fn mood() -> &'static Mood {
+    static MOOD: OnceLock<Mood> = OnceLock::new();
+    MOOD.get_or_init(Mood::new)
+}
+
+/// Implementation of checker methods.
+impl Pep257Checker {
+    /// Check a docstring against PEP 257 rules.
+    #[allow(dead_code)]
+    pub(crate) fn check_docstring(docstring: &Docstring) -> Vec<Violation> {
+        Self::check_docstring_with(docstring, &CheckerOptions::default())
+    }
+
+    /// Check a docstring against PEP 257 rules using the given options.
+    pub(crate) fn check_docstring_with(
+        docstring: &Docstring,
+        options: &CheckerOptions,
+    ) -> Vec<Violation> {
+        Self::check_docstring_in(docstring, options, &AnalysisContext::default())
+    }
+
+    /// Check a docstring using the given options and the context of its file.
+    pub(crate) fn check_docstring_in(
+        docstring: &Docstring,
+        options: &CheckerOptions,
+        context: &AnalysisContext,
+    ) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let is_missing = docstring.content.trim().is_empty();
+
+        // Rustdoc leaves hidden items out, so they needn't be documented
+        if docstring.attributes.doc_hidden {
+            match options.doc_hidden.unwrap_or_default() {
+                HiddenPolicy::SkipAll => return violations,
+                HiddenPolicy::SkipMissingDocs if is_missing => return violations,
+                _ => {}
+            }
+        }
+
+        // Items that rustc's `missing_docs` lint is allowed for needn't be documented
+        if is_missing && docstring.attributes.allows_missing_docs {
+            return violations;
+        }
+
+        // Skip empty docstrings
+        if is_missing && docstring.is_public {
+            let (rule_code, item_description) = Self::get_missing_docstring_rule(docstring);
+            let message = match docstring.name() {
+                "" => format!("Missing docstring in public {item_description}"),
+                name => format!("Missing docstring in public {item_description} `{name}`"),
+            };
+            let violation =
+                Violation::new(&rule_code, message, docstring.position(), Severity::Error);
+            violations.push(match &docstring.attributes.byte_range {
+                Some(range) => violation.with_byte_range(range.clone()),
+                None => violation,
+            });
+            return violations;
+        }
+
+        // Check for proper docstring format
+        violations.extend(Self::check_d200_series(docstring));
+        violations.extend(Self::check_d300_series(docstring));
+        violations.extend(Self::check_d400_series(docstring));
+        violations.extend(Self::check_common_rust_types(docstring));
+        violations.extend(Self::check_feature_gate_docs(docstring, options));
+        violations.extend(Self::check_empty_code(docstring));
+        violations.extend(Self::check_bare_urls(docstring));
+        violations.extend(Self::check_bare_paths(docstring));
+        violations.extend(Self::check_prose_length(docstring, options));
+        violations.extend(Self::check_first_line_indent(docstring));
+        violations.extend(Self::check_trailing_whitespace(docstring));
+        violations.extend(Self::check_summary_separation(docstring, options));
+        violations.extend(Self::check_wrapped_summary(docstring));
+        violations.extend(Self::check_bullet_markers(docstring, options));
+        violations.extend(Self::check_safety_section(docstring));
+        if options.is_enabled("R409") {
+            violations.extend(Self::check_function_article(docstring));
+        }
+        if options.is_enabled("R410") && Self::is_type_like(docstring.target_type) {
+            // Type docs get their own mood rule instead of the function-oriented D401
+            violations.retain(|v| v.rule != "D401");
+            violations.extend(Self::check_type_noun_phrase(docstring));
+        }
+        if options.is_enabled("R411") {
+            violations.extend(Self::check_crate_name_summary(docstring, context));
+        }
+        if options.is_enabled("R412") {
+            violations.extend(Self::check_async_cancellation(docstring, options));
+        }
+        if options.is_enabled("R413") {
+            violations.extend(Self::check_generic_references(docstring));
+        }
+        if options.is_enabled("R414") {
+            violations.extend(Self::check_default_method(docstring, options));
+        }
+        if options.is_enabled("D212") || options.is_enabled("D213") {
+            violations.extend(Self::check_block_summary_position(docstring, options));
+        }
+        if options.is_enabled("R415") {
+            violations.extend(Self::check_errors_section(docstring));
+        }
+        if options.is_enabled("R416") {
+            violations.extend(Self::check_panics_section(docstring, options));
+        }
+        if options.is_enabled("R417") {
+            violations.extend(Self::check_code_example(docstring, options));
+        }
+        if ["R501", "R502", "R503"].iter().any(|rule| options.is_enabled(rule)) {
+            violations.extend(Self::check_crate_docs(docstring, options, context));
+        }
+
+        violations
+    }
+
+    /// Check the inner attributes of a crate root (`lib.rs`) for crate-level rules.
+    ///
+    /// R104: Crate roots should configure the `missing_docs` lint so the compiler
+    /// enforces documentation coverage alongside these style checks.
+    pub(crate) fn check_crate_attributes(
+        inner_attributes: &[String],
+        options: &CheckerOptions,
+    ) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        if options.suggest_missing_docs_lint
+            && !inner_attributes.iter().any(|attr| Self::configures_lint(attr, "missing_docs"))
+        {
+            violations.push(Violation::new(
+                "R104",
+                "Crate root should enable the `missing_docs` lint, e.g. \
+                 `#![warn(missing_docs)]`",
+                SourcePosition { line: 1, column: 1 },
+                Severity::Warning,
+            ));
+        }
+
+        violations
+    }
+
+    /// Report the first syntax error of a file.
+    ///
+    /// R000: Items around a syntax error may be missing from the parsed file or
+    /// misread, so the violations of the file may be incomplete.
+    pub(crate) fn check_syntax_errors(position: SourcePosition) -> Vec<Violation> {
+        vec![Violation::new(
+            "R000",
+            "File contains syntax errors; results may be incomplete",
+            position,
+            Severity::Error,
+        )]
+    }
+
+    /// Check for fields and variants documented by a comment after them on the same line.
+    ///
+    /// R108: A trailing `///` comment documents the next member rather than the one
+    /// before it, and a trailing `//` comment doesn't document anything.
+    pub(crate) fn check_trailing_comment(comment: &TrailingComment) -> Vec<Violation> {
+        let message = if comment.is_doc {
+            format!(
+                "Trailing doc comment after {} `{}` documents the next {}; move it above `{}`",
+                comment.member_kind, comment.member, comment.member_kind, comment.member
+            )
+        } else {
+            format!(
+                "Trailing comment on {} `{}` should be a doc comment above it",
+                comment.member_kind, comment.member
+            )
+        };
+
+        vec![Violation::new("R108", message, comment.position, Severity::Warning)]
+    }
+
+    /// Check a doc comment that isn't followed by an item.
+    ///
+    /// R203: Doc comments at the end of a file or block document nothing, and rustdoc
+    /// silently drops them.
+    pub(crate) fn check_dangling_doc(doc: &DanglingDoc) -> Vec<Violation> {
+        let location = if doc.item_path.is_empty() {
+            "the file".to_string()
+        } else {
+            format!("`{}`", doc.item_path)
+        };
+
+        vec![Violation::new(
+            "R203",
+            format!("Doc comment at the end of {location} is not followed by an item"),
+            doc.position,
+            Severity::Warning,
+        )]
+    }
+
+    /// Check an `unsafe` block in a function body, if R601 is enabled.
+    ///
+    /// R601: Unsafe blocks in public functions should be preceded by a `// SAFETY:`
+    /// comment explaining why the block upholds the invariants it relies on.
+    pub(crate) fn check_unsafe_block(
+        block: &UnsafeBlock,
+        options: &CheckerOptions,
+    ) -> Vec<Violation> {
+        if !options.is_enabled("R601") || !block.is_public || block.has_safety_comment {
+            return Vec::new();
+        }
+
+        vec![Violation::new(
+            "R601",
+            format!(
+                "Unsafe block in public function `{}` should be preceded by a `// SAFETY:` \
+                 comment",
+                block.item_path
+            ),
+            block.position,
+            Severity::Warning,
+        )]
+    }
+
+    /// Check that feature-gated public items tell readers how to enable them.
+    ///
+    /// R404: The docstring should mention each required feature, unless the item has a
+    /// `#[doc(cfg(...))]` attribute that makes rustdoc render the requirement.
+    fn check_feature_gate_docs(docstring: &Docstring, options: &CheckerOptions) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let attributes = &docstring.attributes;
+
+        if !docstring.is_public || attributes.features.is_empty() || attributes.has_doc_cfg {
+            return violations;
+        }
+
+        if options.require_doc_cfg {
+            violations.push(Violation::new(
+                "R404",
+                format!(
+                    "Feature-gated {} should have a `#[doc(cfg(...))]` attribute",
+                    docstring.target_type
+                ),
+                docstring.position(),
+                Severity::Warning,
+            ));
+            return violations;
+        }
+
+        let content = docstring.content.to_lowercase();
+        for feature in &attributes.features {
+            if !content.contains(&feature.to_lowercase()) {
+                violations.push(Violation::new(
+                    "R404",
+                    format!(
+                        "Docstring should mention the `{feature}` feature that enables this {}",
+                        docstring.target_type
+                    ),
+                    docstring.position(),
+                    Severity::Warning,
+                ));
+            }
+        }
+
+        violations
+    }
+
+    /// Check for code spans and code blocks without content.
+    ///
+    /// R405: Empty inline code spans and code fences usually indicate editing accidents.
+    fn check_empty_code(docstring: &Docstring) -> Vec<Violation> {
+        Self::empty_code(&docstring.content)
+            .into_iter()
+            .map(|(range, _, message)| {
+                let position = docstring.position_at(range.start);
+                Violation::new("R405", message, position, Severity::Warning)
+                    .ending_at(docstring.position_at(range.end))
+            })
+            .collect()
+    }
+
+    /// Find the code spans and code blocks of docs that have no content.
+    ///
+    /// Returns the byte range of each, from its opening backticks or fence to its
+    /// closing ones, whether it's a code block, and the message to report it with.
+    pub(crate) fn empty_code(content: &str) -> Vec<(Range<usize>, bool, &'static str)> {
+        let mut empty = Vec::new();
+
+        // Open code fence: range of the opening fence line, its marker, and whether it
+        // has content
+        let mut fence: Option<(Range<usize>, &str, bool)> = None;
+        let mut line_offset = 0;
+
+        for line in content.split('\n') {
+            let indent = line.len() - line.trim_start().len();
+            let trimmed = line.trim();
+
+            if let Some((open, marker, has_content)) = &mut fence {
+                if Self::closes_fence(trimmed, marker) {
+                    if !*has_content {
+                        let end = line_offset + indent + trimmed.len();
+                        empty.push((open.start..end, true, "Empty code block"));
+                    }
+                    fence = None;
+                } else if !trimmed.is_empty() {
+                    *has_content = true;
+                }
+            } else if let Some(marker) = Self::fence_marker(trimmed) {
+                let start = line_offset + indent;
+                fence = Some((start..start + trimmed.len(), marker, false));
+            } else {
+                for (span, is_whitespace) in Self::empty_code_spans(line) {
+                    let message = if is_whitespace {
+                        "Code span contains only whitespace"
+                    } else {
+                        "Empty code span"
+                    };
+                    empty.push((line_offset + span.start..line_offset + span.end, false, message));
+                }
+            }
+
+            line_offset += line.len() + 1;
+        }
+
+        // A fence left open at the end of the docstring still renders as a code block
+        if let Some((open, _, false)) = fence {
+            empty.push((open, true, "Empty code block"));
+        }
+
+        empty
+    }
+
+    /// Return the opening marker of a code fence line, such as ```` ``` ```` or `~~~`.
+    fn fence_marker(line: &str) -> Option<&str> {
+        let fence_char = line.chars().next().filter(|&c| c == '`' || c == '~')?;
+        let length = line.len() - line.trim_start_matches(fence_char).len();
+        (length >= 3).then(|| &line[..length])
+    }
+
+    /// Check whether a line closes a code fence opened with the given marker.
+    fn closes_fence(line: &str, marker: &str) -> bool {
+        let fence_char = marker.chars().next().unwrap_or('`');
+        let rest = line.trim_start_matches(fence_char);
+        line.len() - rest.len() >= marker.len() && rest.trim().is_empty()
+    }
+
+    /// Find inline code spans on a line that have no content.
+    ///
+    /// Returns the byte range of each span, backticks included, and whether it
+    /// contains whitespace. An unmatched double backtick is treated as an empty span,
+    /// since that's how an empty span is usually typed.
+    fn empty_code_spans(line: &str) -> Vec<(Range<usize>, bool)> {
+        Self::code_spans(line)
+            .into_iter()
+            .filter_map(|(start, length, close)| match close {
+                Some(close) => {
+                    let inner = &line[start + length..close];
+                    inner.trim().is_empty().then_some((start..close + length, !inner.is_empty()))
+                }
+                None => (length == 2).then_some((start..start + 2, false)),
+            })
+            .collect()
+    }
+
+    /// Find the backtick runs that open inline code spans on a line.
+    ///
+    /// Returns the byte offset and length of each opening run, and the offset of its
+    /// closing run, or `None` if the run is unmatched.
+    fn code_spans(line: &str) -> Vec<(usize, usize, Option<usize>)> {
+        let bytes = line.as_bytes();
+        let backtick_run = |start: usize| bytes[start..].iter().take_while(|&&b| b == b'`').count();
+        let mut spans = Vec::new();
+        let mut i = 0;
+
+        while i < bytes.len() {
+            if bytes[i] != b'`' {
+                i += 1;
+                continue;
+            }
+
+            let start = i;
+            let length = backtick_run(start);
+            i += length;
+
+            // A code span closes at the next run of exactly the same length
+            let mut j = i;
+            let mut close = None;
+            while j < bytes.len() {
+                if bytes[j] == b'`' {
+                    let run = backtick_run(j);
+                    if run == length {
+                        close = Some(j);
+                        break;
+                    }
+                    j += run;
+                } else {
+                    j += 1;
+                }
+            }
+
+            spans.push((start, length, close));
+            if let Some(close) = close {
+                i = close + length;
+            }
+        }
+
+        spans
+    }
+
+    /// Return the prose lines of a docstring with their byte offsets in the content.
+    ///
+    /// Lines inside fenced code blocks are skipped, and inline code spans are replaced
+    /// with spaces so that offsets into the returned lines stay valid.
+    fn prose_lines(content: &str) -> Vec<(usize, String)> {
+        let mut lines = Vec::new();
+        let mut fence: Option<&str> = None;
+        let mut line_offset = 0;
+
+        for line in content.split('\n') {
+            let trimmed = line.trim();
+
+            if let Some(marker) = fence {
+                if Self::closes_fence(trimmed, marker) {
+                    fence = None;
+                }
+            } else if let Some(marker) = Self::fence_marker(trimmed) {
+                fence = Some(marker);
+            } else {
+                let mut masked = line.to_string();
+                for (start, length, close) in Self::code_spans(line) {
+                    if let Some(close) = close {
+                        let end = close + length;
+                        masked.replace_range(start..end, &" ".repeat(end - start));
+                    }
+                }
+                lines.push((line_offset, masked));
+            }
+
+            line_offset += line.len() + 1;
+        }
+
+        lines
+    }
+
+    /// Check for bare URLs that rustdoc won't render as links.
+    ///
+    /// R406: URLs in prose should be written as `<https://...>` autolinks or as
+    /// `[text](https://...)` links.
+    fn check_bare_urls(docstring: &Docstring) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        for (line_offset, line) in Self::prose_lines(&docstring.content) {
+            // Reference definitions such as `[label]: https://...` are already links
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('[') && trimmed.contains("]:") {
+                continue;
+            }
+
+            for (start, url) in Self::bare_urls(&line) {
+                let position = docstring.position_at(line_offset + start);
+                violations.push(
+                    Violation::new(
+                        "R406",
+                        format!("Bare URL should be wrapped in angle brackets: <{url}>"),
+                        position,
+                        Severity::Warning,
+                    )
+                    .ending_at(docstring.position_at(line_offset + start + url.len())),
+                );
+            }
+        }
+
+        violations
+    }
+
+    /// Check for a first doc line indented past the single space after the comment marker.
+    ///
+    /// R201: Rustdoc renders text indented by four or more spaces as a code block, so
+    /// `///    Summary.` doesn't render as a summary.
+    fn check_first_line_indent(docstring: &Docstring) -> Vec<Violation> {
+        let Some(index) = docstring.content.split('\n').position(|line| !line.trim().is_empty())
+        else {
+            return Vec::new();
+        };
+
+        // Only line comments keep a one-to-one mapping between raw and content lines
+        let Some(raw_line) = docstring.raw_content.lines().nth(index) else {
+            return Vec::new();
+        };
+        let trimmed = raw_line.trim_start();
+        let Some(text) = trimmed.strip_prefix("///").or_else(|| trimmed.strip_prefix("//!")) else {
+            return Vec::new();
+        };
+        if text.starts_with('/') {
+            return Vec::new(); // `////` is a plain comment, not documentation
+        }
+
+        let indent = text.len() - text.trim_start().len();
+        if indent <= 1 {
+            return Vec::new();
+        }
+
+        let offset: usize =
+            docstring.content.split('\n').take(index).map(|line| line.len() + 1).sum();
+        let position = docstring.position_at(offset);
+        vec![Violation::new(
+            "R201",
+            format!("First docstring line is indented by {indent} characters"),
+            position,
+            Severity::Warning,
+        )]
+    }
+
+    /// Check for doc comment lines ending with whitespace.
+    ///
+    /// D210: Trailing whitespace is invisible in the source and the rendered docs, but
+    /// shows up in diffs, and two trailing spaces make a Markdown line break.
+    fn check_trailing_whitespace(docstring: &Docstring) -> Vec<Violation> {
+        /// Return the text after the marker of a raw doc comment line.
+        fn comment_text(raw_line: &str) -> Option<&str> {
+            let raw_line = raw_line.strip_suffix('\r').unwrap_or(raw_line).trim_start();
+            raw_line.strip_prefix("///").or_else(|| raw_line.strip_prefix("//!"))
+        }
+
+        // Only line comments keep a one-to-one mapping between raw and content lines,
+        // once the line endings the comments end with are left out
+        let raw_lines: Vec<&str> =
+            docstring.raw_content.split('\n').filter(|line| !line.is_empty()).collect();
+        if raw_lines.iter().any(|raw_line| comment_text(raw_line).is_none()) {
+            return Vec::new();
+        }
+
+        raw_lines
+            .iter()
+            .zip(&docstring.line_positions)
+            .filter_map(|(raw_line, position)| {
+                let text = comment_text(raw_line)?;
+                let trimmed = text.trim_end();
+                if trimmed.len() == text.len() {
+                    return None;
+                }
+                // Positions are of the text after the marker and the whitespace after it
+                let text_start =
+                    if trimmed.is_empty() { 0 } else { text.len() - text.trim_start().len() };
+                let column = position.column + trimmed.len() - text_start;
+                let end = SourcePosition {
+                    line: position.line,
+                    column: column + text.len() - trimmed.len(),
+                };
+                Some(
+                    Violation::new(
+                        "D210",
+                        "Docstring line ends with whitespace",
+                        SourcePosition { line: position.line, column },
+                        Severity::Warning,
+                    )
+                    .ending_at(end),
+                )
+            })
+            .collect()
+    }
+
+    /// Check that the summary is not separated from the description by too many blank lines.
+    ///
+    /// R202: Complements D205, which requires at least one blank line; the violation is reported
+    /// on the first blank line over the limit.
+    fn check_summary_separation(docstring: &Docstring, options: &CheckerOptions) -> Vec<Violation> {
+        let max = options.max_summary_blank_lines.unwrap_or(DEFAULT_MAX_SUMMARY_BLANK_LINES);
+        let lines: Vec<&str> = docstring.content.split('\n').collect();
+        let is_blank = |line: &&str| line.trim().is_empty();
+        let Some(summary_start) = lines.iter().position(|line| !is_blank(line)) else {
+            return Vec::new();
+        };
+        let Some(summary_length) = lines[summary_start..].iter().position(is_blank) else {
+            return Vec::new();
+        };
+
+        let separator_start = summary_start + summary_length;
+        let blank_lines = lines[separator_start..].iter().take_while(|line| is_blank(line)).count();
+        if blank_lines <= max || separator_start + blank_lines == lines.len() {
+            return Vec::new();
+        }
+
+        vec![Violation::new(
+            "R202",
+            format!(
+                "{blank_lines} blank lines between summary and description, expected at most {max}"
+            ),
+            SourcePosition {
+                line: docstring.source_line(separator_start + max),
+                column: docstring.column,
+            },
+            Severity::Warning,
+        )]
+    }
+
+    /// Check for a summary sentence that wraps across several lines.
+    ///
+    /// R204: Rustdoc shows only the first paragraph in item lists, and tools that take
+    /// the first line as the summary cut a wrapped sentence short.
+    fn check_wrapped_summary(docstring: &Docstring) -> Vec<Violation> {
+        let Some((start, end)) = Self::wrapped_summary(&docstring.content) else {
+            return Vec::new();
+        };
+
+        // The violation covers the whole summary, up to the end of its last line
+        let lines: Vec<&str> = docstring.content.split('\n').collect();
+        let end_offset = lines[..end].iter().map(|line| line.len() + 1).sum::<usize>()
+            + lines[end].trim_end().len();
+        vec![
+            Violation::new(
+                "R204",
+                format!("Summary wraps across {} lines, it should fit on one", end - start + 1),
+                SourcePosition { line: docstring.source_line(start), column: docstring.column },
+                Severity::Warning,
+            )
+            .ending_at(docstring.position_at(end_offset)),
+        ]
+    }
+
+    /// Check whether the summary of a multi-line block doc comment starts on the expected line.
+    ///
+    /// D212: The summary should start on the first line, as in `/** Summary.`.
+    /// D213: The summary should start on the second line, below a `/**` of its own.
+    fn check_block_summary_position(
+        docstring: &Docstring,
+        options: &CheckerOptions,
+    ) -> Vec<Violation> {
+        let raw = docstring.raw_content.trim_start();
+        let Some(after_marker) = raw.strip_prefix("/**").or_else(|| raw.strip_prefix("/*!")) else {
+            return Vec::new();
+        };
+        // Comments closed on their first line leave no choice of layout
+        let Some((first_line, _)) = after_marker.split_once('\n') else {
+            return Vec::new();
+        };
+        if first_line.contains("*/") {
+            return Vec::new();
+        }
+
+        let (rule, line) =
+            if first_line.trim().is_empty() { ("D212", "first") } else { ("D213", "second") };
+        if !options.is_enabled(rule) {
+            return Vec::new();
+        }
+        vec![Violation::new(
+            rule,
+            format!("Summary of a multi-line block doc comment should start on its {line} line"),
+            docstring.position(),
+            Severity::Warning,
+        )]
+    }
+
+    /// Return the first and last content line indexes of a summary wrapped past its first line.
+    ///
+    /// The summary ends at the first line ending a sentence, or at the end of its paragraph. A
+    /// first line ending with a colon introduces a list or code rather than wrapping.
+    pub(crate) fn wrapped_summary(content: &str) -> Option<(usize, usize)> {
+        let lines: Vec<&str> = content.split('\n').collect();
+        let start = lines.iter().position(|line| !line.trim().is_empty())?;
+        let ends_sentence = |line: &str| line.trim_end().ends_with(['.', '!', '?']);
+        let first = lines[start].trim();
+        if ends_sentence(first) || first.ends_with(':') || !Self::continues_paragraph(first) {
+            return None;
+        }
+
+        let mut end = start;
+        for line in &lines[start + 1..] {
+            if !Self::continues_paragraph(line.trim()) {
+                break;
+            }
+            end += 1;
+            if ends_sentence(line) {
+                break;
+            }
+        }
+        (end > start).then_some((start, end))
+    }
+
+    /// Check whether a line continues a paragraph of prose, rather than ending it.
+    ///
+    /// Lines starting a list item, heading, code block, table or quote don't continue it.
+    fn continues_paragraph(text: &str) -> bool {
+        let ordered_item =
+            text.find(|c: char| !c.is_ascii_digit()).filter(|&digits| digits > 0).is_some_and(
+                |digits| text[digits..].starts_with(". ") || text[digits..].starts_with(") "),
+            );
+        !text.is_empty()
+            && !ordered_item
+            && Self::bullet_marker(text).is_none()
+            && !Self::is_heading(text)
+            && Self::fence_marker(text).is_none()
+            && !text.starts_with(['|', '>'])
+    }
+
+    /// Check for function docs that start with an article.
+    ///
+    /// R409: Function docs are imperative sentences ("Return the value"), while an
+    /// article usually starts a noun phrase ("The value"), which suits type docs.
+    fn check_function_article(docstring: &Docstring) -> Vec<Violation> {
+        if !docstring.target_type.is_function() {
+            return Vec::new();
+        }
+
+        let content = &docstring.content;
+        let start = content.len() - content.trim_start().len();
+        let Some(word) = content[start..].split_whitespace().next() else {
+            return Vec::new();
+        };
+        if !matches!(word.to_lowercase().as_str(), "a" | "an" | "the") {
+            return Vec::new();
+        }
+
+        let position = docstring.position_at(start);
+        vec![Violation::new(
+            "R409",
+            format!("Function docstring should start with an imperative verb, not `{word}`"),
+            position,
+            Severity::Warning,
+        )]
+    }
+
+    /// Check for public async function docs that don't mention cancellation.
+    ///
+    /// R412: Dropping the future of an async function cancels it at its current
+    /// `.await`, which may lose data or leave state half-updated, so async libraries
+    /// document what happens when their futures are cancelled.
+    fn check_async_cancellation(docstring: &Docstring, options: &CheckerOptions) -> Vec<Violation> {
+        if !docstring.target_type.is_function()
+            || !docstring.is_public
+            || !docstring.attributes.modifiers.is_async
+        {
+            return Vec::new();
+        }
+
+        let keywords: Vec<&str> = if options.async_keywords.is_empty() {
+            DEFAULT_ASYNC_KEYWORDS.to_vec()
+        } else {
+            options.async_keywords.iter().map(String::as_str).collect()
+        };
+        let content = docstring.content.to_lowercase();
+        if keywords.iter().any(|keyword| content.contains(&keyword.to_lowercase())) {
+            return Vec::new();
+        }
+
+        let keywords: Vec<String> = keywords.iter().map(|keyword| format!("`{keyword}`")).collect();
+        vec![Violation::new(
+            "R412",
+            format!(
+                "Async function docstring should document cancellation, mentioning {}",
+                keywords.join(" or ")
+            ),
+            docstring.position(),
+            Severity::Warning,
+        )]
+    }
+
+    /// Check for default trait method docs that don't say the method has a default.
+    ///
+    /// R414: Implementors of a trait need to know which methods they may leave out,
+    /// and what the default does if they do. Undocumented methods are left to the
+    /// missing docs rules.
+    fn check_default_method(docstring: &Docstring, options: &CheckerOptions) -> Vec<Violation> {
+        if !docstring.attributes.is_default_method || docstring.content.trim().is_empty() {
+            return Vec::new();
+        }
+
+        let keywords: Vec<&str> = if options.default_method_keywords.is_empty() {
+            DEFAULT_DEFAULT_METHOD_KEYWORDS.to_vec()
+        } else {
+            options.default_method_keywords.iter().map(String::as_str).collect()
+        };
+        let content = docstring.content.to_lowercase();
+        if keywords.iter().any(|keyword| content.contains(&keyword.to_lowercase())) {
+            return Vec::new();
+        }
+
+        let keywords: Vec<String> = keywords.iter().map(|keyword| format!("`{keyword}`")).collect();
+        vec![Violation::new(
+            "R414",
+            format!(
+                "Default trait method docstring should say a default is provided, mentioning {}",
+                keywords.join(" or ")
+            ),
+            docstring.position(),
+            Severity::Warning,
+        )]
+    }
+
+    /// Check for code spans naming generic parameters that the item doesn't have.
+    ///
+    /// R413: Docs that mention `` `T` `` or `` `'a` `` after the parameter was renamed or
+    /// removed describe a signature that no longer exists.
+    fn check_generic_references(docstring: &Docstring) -> Vec<Violation> {
+        if !matches!(
+            docstring.target_type,
+            DocstringTarget::Function
+                | DocstringTarget::Method
+                | DocstringTarget::Struct
+                | DocstringTarget::Enum
+                | DocstringTarget::Trait
+                | DocstringTarget::ImplBlock
+                | DocstringTarget::TypeAlias
+        ) {
+            return Vec::new();
+        }
+
+        let mut violations = Vec::new();
+        let mut fence: Option<&str> = None;
+        let mut line_offset = 0;
+
+        for line in docstring.content.split('\n') {
+            let trimmed = line.trim();
+            if let Some(marker) = fence {
+                if Self::closes_fence(trimmed, marker) {
+                    fence = None;
+                }
+            } else if let Some(marker) = Self::fence_marker(trimmed) {
+                fence = Some(marker);
+            } else {
+                for (start, length, close) in Self::code_spans(line) {
+                    let Some(close) = close else { continue };
+                    let name = line[start + length..close].trim();
+                    if !Self::looks_like_generic(name)
+                        || docstring.attributes.generics.iter().any(|generic| generic == name)
+                    {
+                        continue;
+                    }
+
+                    let position = docstring.position_at(line_offset + start);
+                    violations.push(Violation::new(
+                        "R413",
+                        format!(
+                            "Docstring refers to `{name}`, which isn't a generic parameter of \
+                             this {}",
+                            docstring.target_type
+                        ),
+                        position,
+                        Severity::Warning,
+                    ));
+                }
+            }
+            line_offset += line.len() + 1;
+        }
+
+        violations
+    }
+
+    /// Check whether code names a generic parameter by the usual conventions.
+    ///
+    /// These are lifetimes such as `'a`, other than `'static` and `'_`, and single
+    /// uppercase letters with an optional number, such as `T` or `T2`.
+    fn looks_like_generic(name: &str) -> bool {
+        if let Some(lifetime) = name.strip_prefix('\'') {
+            return lifetime != "static"
+                && lifetime != "_"
+                && lifetime.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+                && lifetime.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        }
+        let mut chars = name.chars();
+        chars.next().is_some_and(|c| c.is_ascii_uppercase()) && chars.all(|c| c.is_ascii_digit())
+    }
+
+    /// Check whether a target documents a type, whose docs are noun phrases.
+    fn is_type_like(target_type: DocstringTarget) -> bool {
+        matches!(
+            target_type,
+            DocstringTarget::Struct | DocstringTarget::Enum | DocstringTarget::Trait
+        )
+    }
+
+    /// Check for type docs that start with an imperative verb.
+    ///
+    /// R410: Type docs describe what the type is ("A point in 2D space"), so a summary
+    /// starting with a verb such as "Create" reads like function docs.
+    fn check_type_noun_phrase(docstring: &Docstring) -> Vec<Violation> {
+        let content = &docstring.content;
+        let start = content.len() - content.trim_start().len();
+        let Some(word) = content[start..].split_whitespace().next() else {
+            return Vec::new();
+        };
+        let word = word.trim_end_matches(|c: char| !c.is_alphanumeric());
+        if mood().is_imperative(&word.to_lowercase()) != Some(true) {
+            return Vec::new();
+        }
+
+        let position = docstring.position_at(start);
+        vec![Violation::new(
+            "R410",
+            format!(
+                "{} docstring should be a noun phrase, not start with the verb `{word}`",
+                Self::capitalize(&docstring.target_type.to_string())
+            ),
+            position,
+            Severity::Warning,
+        )]
+    }
+
+    /// Check for crate docs whose summary starts with the crate name.
+    ///
+    /// R411: docs.rs already shows the crate name above the crate docs, so a summary
+    /// such as "my_crate is a parser" repeats it instead of saying what the crate does.
+    fn check_crate_name_summary(
+        docstring: &Docstring,
+        context: &AnalysisContext,
+    ) -> Vec<Violation> {
+        let Some(crate_name) = &context.crate_name else {
+            return Vec::new();
+        };
+        if docstring.target_type != DocstringTarget::Package || !context.is_crate_root() {
+            return Vec::new();
+        }
+
+        let content = &docstring.content;
+        let start = content.len() - content.trim_start().len();
+        let Some(word) = content[start..].split_whitespace().next() else {
+            return Vec::new();
+        };
+        let word = word.trim_end_matches([':', ',', '.']).trim_matches('`');
+        if !word.replace('-', "_").eq_ignore_ascii_case(crate_name) {
+            return Vec::new();
+        }
+
+        let position = docstring.position_at(start);
+        vec![Violation::new(
+            "R411",
+            format!(
+                "Crate docstring should say what the crate does, not start with its name `{word}`"
+            ),
+            position,
+            Severity::Warning,
+        )]
+    }
+
+    /// Check that crate docs give docs.rs visitors what they look for first.
+    ///
+    /// R501: Crate docs should open with a summary sentence rather than a heading,
+    /// badge or code block, since search results show the first paragraph.
+    /// R502: They should show how to use the crate in at least one code block.
+    /// R503: They should have at least the configured number of non-blank lines.
+    fn check_crate_docs(
+        docstring: &Docstring,
+        options: &CheckerOptions,
+        context: &AnalysisContext,
+    ) -> Vec<Violation> {
+        if docstring.target_type != DocstringTarget::Package || !context.is_crate_root() {
+            return Vec::new();
+        }
+        let content = &docstring.content;
+        let start = content.len() - content.trim_start().len();
+        let Some(opening) = content[start..].lines().next().map(str::trim) else {
+            return Vec::new();
+        };
+
+        let position = docstring.position_at(start);
+        let violation = |rule: &str, message: String| {
+            Violation::new(rule, message, position, Severity::Warning)
+        };
+        let mut violations = Vec::new();
+
+        if options.is_enabled("R501") {
+            let opening_kind = if Self::fence_marker(opening).is_some() {
+                Some("a code block")
+            } else if opening.starts_with('#') {
+                Some("a heading")
+            } else if opening.starts_with("![") || opening.starts_with("[![") {
+                Some("an image")
+            } else if opening.starts_with('<') {
+                Some("HTML")
+            } else {
+                None
+            };
+            if let Some(kind) = opening_kind {
+                violations.push(violation(
+                    "R501",
+                    format!("Crate docstring should start with a summary sentence, not {kind}"),
+                ));
+            }
+        }
+
+        if options.is_enabled("R502")
+            && !content.lines().any(|line| Self::fence_marker(line.trim_start()).is_some())
+        {
+            violations.push(violation(
+                "R502",
+                "Crate docstring should show how to use the crate in a code example".to_string(),
+            ));
+        }
+
+        let min_lines = options.crate_docs_min_lines.unwrap_or(DEFAULT_CRATE_DOCS_MIN_LINES);
+        let lines = content.lines().filter(|line| !line.trim().is_empty()).count();
+        if options.is_enabled("R503") && lines < min_lines {
+            violations.push(violation(
+                "R503",
+                format!("Crate docstring should have at least {min_lines} lines, found {lines}"),
+            ));
+        }
+
+        violations
+    }
+
+    /// Capitalize the first letter of a word.
+    fn capitalize(word: &str) -> String {
+        let mut chars = word.chars();
+        chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+    }
+
+    /// Check that the items of each bullet list use the same marker.
+    ///
+    /// R408: Lists should stick to one of `-`, `*` and `+`, or to the configured
+    /// marker. CommonMark starts a new list when the marker changes, so mixed markers
+    /// also render as separate lists.
+    fn check_bullet_markers(docstring: &Docstring, options: &CheckerOptions) -> Vec<Violation> {
+        let preferred = options.bullet_marker.map(BulletMarker::as_char);
+        Self::mismatched_bullets(&docstring.content, preferred)
+            .into_iter()
+            .map(|(offset, marker, expected)| {
+                let message = if preferred.is_some() {
+                    format!("List item should use `{expected}` instead of `{marker}`")
+                } else {
+                    format!("List item uses `{marker}` but the list started with `{expected}`")
+                };
+                let position = docstring.position_at(offset);
+                Violation::new("R408", message, position, Severity::Warning)
+                    .ending_at(docstring.position_at(offset + 1))
+            })
+            .collect()
+    }
+
+    /// Find the list items of docs whose marker differs from the preferred one.
+    ///
+    /// Without a preferred marker, the first marker of each list is used. Returns the byte
+    /// offset of each mismatched marker, the marker, and the marker it should be.
+    pub(crate) fn mismatched_bullets(
+        content: &str,
+        preferred: Option<char>,
+    ) -> Vec<(usize, char, char)> {
+        let mut mismatched = Vec::new();
+        // Indentation and first marker of each open list, innermost last
+        let mut lists: Vec<(usize, char)> = Vec::new();
+
+        for (line_offset, line) in Self::prose_lines(content) {
+            let text = line.trim_start();
+            if text.is_empty() {
+                continue;
+            }
+            let indent = line.len() - text.len();
+
+            let Some(marker) = Self::bullet_marker(text) else {
+                // Text indented no further than a list's items ends that list
+                lists.retain(|&(list_indent, _)| list_indent < indent);
+                continue;
+            };
+
+            lists.retain(|&(list_indent, _)| list_indent <= indent);
+            let expected = match lists.last() {
+                Some(&(list_indent, first)) if list_indent == indent => first,
+                _ => {
+                    lists.push((indent, marker));
+                    marker
+                }
+            };
+
+            let expected = preferred.unwrap_or(expected);
+            if marker != expected {
+                mismatched.push((line_offset + indent, marker, expected));
+            }
+        }
+
+        mismatched
+    }
+
+    /// Return the marker of a line that starts a bullet list item.
+    fn bullet_marker(text: &str) -> Option<char> {
+        let marker = text.chars().next().filter(|c| matches!(c, '-' | '*' | '+'))?;
+        let rest = &text[1..];
+        // A line of markers alone is a thematic break or setext heading underline
+        (rest.starts_with([' ', '\t'])
+            && !rest.chars().filter(|c| !c.is_whitespace()).all(|c| c == marker))
+        .then_some(marker)
+    }
+
+    /// Check for long docstrings that aren't structured with section headings.
+    ///
+    /// R407: Prose outside code blocks shouldn't run past the configured number of lines
+    /// unless it's split into sections such as `# Examples`.
+    fn check_prose_length(docstring: &Docstring, options: &CheckerOptions) -> Vec<Violation> {
+        let limit = options.prose_limits.limit_for(docstring.target_type);
+        if limit == 0 {
+            return Vec::new();
+        }
+
+        let prose = Self::prose_lines(&docstring.content);
+        if prose.iter().any(|(_, line)| Self::is_heading(line)) {
+            return Vec::new();
+        }
+
+        let length = prose.iter().filter(|(_, line)| !line.trim().is_empty()).count();
+        if length <= limit {
+            return Vec::new();
+        }
+
+        vec![Violation::new(
+            "R407",
+            format!(
+                "Docstring has {length} lines of prose without a section heading (limit is {limit})"
+            ),
+            docstring.position(),
+            Severity::Warning,
+        )]
+    }
+
+    /// Check whether a docstring has a section heading with the given title, such as `# Safety`.
+    ///
+    /// Headings of any level count.
+    fn has_section(content: &str, title: &str) -> bool {
+        Self::prose_lines(content).iter().any(|(_, line)| {
+            Self::is_heading(line)
+                && line.trim_start().trim_start_matches('#').trim().eq_ignore_ascii_case(title)
+        })
+    }
+
+    /// Check that the docs of a public unsafe function have a `# Safety` section.
+    ///
+    /// R602: Callers of an unsafe function must uphold the conditions that make the
+    /// call sound, and look for them under the heading clippy's `missing_safety_doc`
+    /// lint asks for. Undocumented functions are left to the missing docs rules.
+    fn check_safety_section(docstring: &Docstring) -> Vec<Violation> {
+        if !docstring.target_type.is_function()
+            || !docstring.is_public
+            || !docstring.attributes.modifiers.is_unsafe
+            || docstring.content.trim().is_empty()
+            || Self::has_section(&docstring.content, "Safety")
+        {
+            return Vec::new();
+        }
+
+        vec![Violation::new(
+            "R602",
+            "Unsafe function docstring should have a `# Safety` section",
+            docstring.position(),
+            Severity::Warning,
+        )]
     }
-}
 
-/// Represents a docstring found in the code.
-#[derive(Debug, Clone)]
-pub(crate) struct Docstring {
-    pub content: String,
-    #[allow(dead_code)]
-    pub raw_content: String,
-    pub line: usize,
-    pub column: usize,
-    pub is_multiline: bool,
-    pub is_public: bool,
-    pub target_type: DocstringTarget,
-}
+    /// Check that the docs of a public function returning a `Result` have an `# Errors` section.
+    ///
+    /// R415: Callers handling the errors of a function need to know when they happen,
+    /// as clippy's `missing_errors_doc` lint asks. Undocumented functions are left to
+    /// the missing docs rules.
+    fn check_errors_section(docstring: &Docstring) -> Vec<Violation> {
+        if !docstring.target_type.is_function()
+            || !docstring.is_public
+            || !docstring.attributes.returns_result
+            || docstring.content.trim().is_empty()
+            || Self::has_section(&docstring.content, "Errors")
+        {
+            return Vec::new();
+        }
 
-/// Type of construct that has a docstring.
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) enum DocstringTarget {
-    Function,
-    Struct,
-    Enum,
-    Module,
-    Package,
-    Impl,
-    Trait,
-    Const,
-    #[allow(dead_code)]
-    Static,
-    TypeAlias,
-    Macro,
-}
+        vec![Violation::new(
+            "R415",
+            "Docstring of a function returning `Result` should have an `# Errors` \
+                      section",
+            docstring.position(),
+            Severity::Warning,
+        )]
+    }
 
-/// Format a docstring target for display.
-impl fmt::Display for DocstringTarget {
-    /// Format the docstring target as a string.
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let name = match self {
-            DocstringTarget::Function => "function",
-            DocstringTarget::Struct => "struct",
-            DocstringTarget::Enum => "enum",
-            DocstringTarget::Module => "module",
-            DocstringTarget::Package => "package",
-            DocstringTarget::Impl => "impl",
-            DocstringTarget::Trait => "trait",
-            DocstringTarget::Const => "const",
-            DocstringTarget::Static => "static",
-            DocstringTarget::TypeAlias => "type alias",
-            DocstringTarget::Macro => "macro",
+    /// Check that the docs of a public function that can panic have a `# Panics` section.
+    ///
+    /// R416: A function panics when its body calls one of the configured panicking
+    /// macros or methods, as clippy's `missing_panics_doc` lint asks. Functions calling
+    /// them only to enforce their own invariants should still say so.
+    fn check_panics_section(docstring: &Docstring, options: &CheckerOptions) -> Vec<Violation> {
+        if !docstring.target_type.is_function()
+            || !docstring.is_public
+            || docstring.content.trim().is_empty()
+            || Self::has_section(&docstring.content, "Panics")
+        {
+            return Vec::new();
+        }
+
+        let constructs: Vec<&str> = if options.panic_constructs.is_empty() {
+            DEFAULT_PANIC_CONSTRUCTS.to_vec()
+        } else {
+            options.panic_constructs.iter().map(String::as_str).collect()
         };
-        write!(f, "{name}")
+        let Some(call) =
+            docstring.attributes.calls.iter().find(|call| constructs.contains(&call.as_str()))
+        else {
+            return Vec::new();
+        };
+
+        vec![Violation::new(
+            "R416",
+            format!("Docstring of a function calling `{call}` should have a `# Panics` section"),
+            docstring.position(),
+            Severity::Warning,
+        )]
     }
-}
 
-/// PEP 257 checker implementation.
-pub(crate) struct Pep257Checker {
-    #[allow(dead_code)]
-    whitespace_regex: Regex,
-    #[allow(dead_code)]
-    leading_space_regex: Regex,
-}
+    /// Check that the docs of a public item of the configured target types include a code example.
+    ///
+    /// R417: Rustdoc compiles and runs the code blocks in docs as doctests, so an
+    /// example shows readers how to use the item and stays correct as it changes.
+    fn check_code_example(docstring: &Docstring, options: &CheckerOptions) -> Vec<Violation> {
+        if !docstring.is_public
+            || !options.example_targets.includes(docstring.target_type)
+            || docstring.content.trim().is_empty()
+            || docstring.content.lines().any(|line| Self::fence_marker(line.trim_start()).is_some())
+        {
+            return Vec::new();
+        }
 
-/// Provide a default checker instance.
-impl Default for Pep257Checker {
-    /// Return a new checker with default configuration.
-    fn default() -> Self {
-        Self::new()
+        vec![Violation::new(
+            "R417",
+            format!("Public {} docstring should include a code example", docstring.target_type),
+            docstring.position(),
+            Severity::Warning,
+        )]
     }
-}
 
-/// Implementation of checker methods.
-impl Pep257Checker {
-    /// Create a new checker instance.
-    pub(crate) fn new() -> Self {
-        Self {
-            whitespace_regex: Regex::new(r"\s+").unwrap(),
-            leading_space_regex: Regex::new(r"^\s*").unwrap(),
-        }
+    /// Check whether a line is a Markdown ATX heading such as `# Examples`.
+    fn is_heading(line: &str) -> bool {
+        let trimmed = line.trim_start();
+        let level = trimmed.len() - trimmed.trim_start_matches('#').len();
+        (1..=6).contains(&level) && trimmed[level..].starts_with([' ', '\t'])
     }
 
-    /// Check a docstring against PEP 257 rules.
-    pub(crate) fn check_docstring(docstring: &Docstring) -> Vec<Violation> {
+    /// Check for Rust paths in prose that aren't code spans or links.
+    ///
+    /// R403: Paths such as `crate::module::Type` should be intra-doc links, so rustdoc
+    /// checks that they resolve and readers can follow them.
+    fn check_bare_paths(docstring: &Docstring) -> Vec<Violation> {
         let mut violations = Vec::new();
 
-        // Skip empty docstrings
-        if docstring.content.trim().is_empty() && docstring.is_public {
-            let (rule_code, item_description) =
-                Self::get_missing_docstring_rule(docstring.target_type);
-            violations.push(Violation {
-                rule: rule_code,
-                message: format!("Missing docstring in public {item_description}"),
-                line: docstring.line,
-                column: docstring.column,
-                severity: Severity::Error,
-            });
-            return violations;
-        }
+        for (line_offset, line) in Self::prose_lines(&docstring.content) {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('[') && trimmed.contains("]:") {
+                continue; // Reference definitions name their targets
+            }
 
-        // Check for proper docstring format
-        violations.extend(Self::check_d200_series(docstring));
-        violations.extend(Self::check_d300_series(docstring));
-        violations.extend(Self::check_d400_series(docstring));
-        violations.extend(Self::check_common_rust_types(docstring));
+            for (start, path) in Self::bare_paths(&line) {
+                let position = docstring.position_at(line_offset + start);
+                violations.push(
+                    Violation::new(
+                        "R403",
+                        format!("Path `{path}` should be an intra-doc link: [`{path}`]"),
+                        position,
+                        Severity::Warning,
+                    )
+                    .ending_at(docstring.position_at(line_offset + start + path.len())),
+                );
+            }
+        }
 
         violations
     }
 
+    /// Find paths with `::` separators on a line that aren't inside links, autolinks or URLs.
+    ///
+    /// Returns the byte offset of each path and its text.
+    fn bare_paths(line: &str) -> Vec<(usize, &str)> {
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+        let mut paths = Vec::new();
+        // Brackets, link destinations and autolinks that enclose the current position
+        let mut enclosing: Vec<char> = Vec::new();
+        let mut start = None;
+
+        let mut chars = line.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            // A `scheme://...` URL encloses its text like an autolink does
+            if start.is_none()
+                && c.is_ascii_alphabetic()
+                && !line[..i].chars().next_back().is_some_and(is_ident)
+            {
+                let rest = &line[i..];
+                let scheme = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric() && !matches!(c, '+' | '-' | '.'))
+                    .unwrap_or(rest.len());
+                if rest[scheme..].starts_with("://") {
+                    let length = rest
+                        .find(|c: char| {
+                            c.is_whitespace() || matches!(c, '<' | '>' | ')' | ']' | '"' | '\'')
+                        })
+                        .unwrap_or(rest.len());
+                    while chars.next_if(|&(j, _)| j < i + length).is_some() {}
+                    continue;
+                }
+            }
+            if start.is_none() && is_ident(c) && !c.is_ascii_digit() {
+                let before = line[..i].chars().next_back();
+                if !before.is_some_and(|b| is_ident(b) || b == ':') {
+                    start = Some(i);
+                }
+            }
+            if start.is_some()
+                && (is_ident(c) || c == ':')
+                && chars.peek().is_some_and(|&(_, next)| is_ident(next) || next == ':')
+            {
+                continue;
+            }
+
+            // The current path, if any, ends after this character
+            if let Some(path_start) = start.take() {
+                let end = if is_ident(c) || c == ':' { i + c.len_utf8() } else { i };
+                let path = &line[path_start..end];
+                let segments: Vec<&str> = path.split("::").collect();
+                let is_path = segments.len() > 1
+                    && segments.iter().all(|segment| {
+                        segment.chars().next().is_some_and(|c| is_ident(c) && !c.is_ascii_digit())
+                            && segment.chars().all(is_ident)
+                    });
+                if is_path && enclosing.is_empty() {
+                    paths.push((path_start, path));
+                }
+            }
+
+            match c {
+                '[' | '<' => enclosing.push(c),
+                '(' if line[..i].ends_with(']') => enclosing.push(c),
+                ']' | '>' | ')' => {
+                    let open = match c {
+                        ']' => '[',
+                        '>' => '<',
+                        _ => '(',
+                    };
+                    if enclosing.last() == Some(&open) {
+                        enclosing.pop();
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        paths
+    }
+
+    /// Find `http://` and `https://` URLs on a line that aren't part of a link.
+    ///
+    /// Returns the byte offset of each URL and its text, without trailing punctuation.
+    fn bare_urls(line: &str) -> Vec<(usize, &str)> {
+        let mut urls = Vec::new();
+        let mut search = 0;
+
+        while let Some(found) = line[search..].find("http") {
+            let start = search + found;
+            let rest = &line[start..];
+            if !rest.starts_with("http://") && !rest.starts_with("https://") {
+                search = start + 4;
+                continue;
+            }
+
+            let length = rest
+                .find(|c: char| {
+                    c.is_whitespace() || matches!(c, '<' | '>' | ')' | ']' | '"' | '\'')
+                })
+                .unwrap_or(rest.len());
+            let url = rest[..length].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            search = start + length;
+
+            let before = &line[..start];
+            let is_linked = before.ends_with("](") || before.ends_with(['<', '[', '"', '\'']);
+            let has_host = url.split_once("://").is_some_and(|(_, host)| !host.is_empty());
+            if !is_linked && has_host {
+                urls.push((start, url));
+            }
+        }
+
+        urls
+    }
+
+    /// Check whether a lint attribute such as `warn(missing_docs, unused)` sets a level for a lint.
+    fn configures_lint(attribute: &str, lint: &str) -> bool {
+        let Some((level, args)) = attribute.split_once('(') else {
+            return false;
+        };
+        matches!(level.trim(), "allow" | "expect" | "warn" | "deny" | "forbid")
+            && args.trim_end().trim_end_matches(')').split(',').any(|arg| arg.trim() == lint)
+    }
+
     /// Check D200 series: One-line docstring whitespace issues.
     fn check_d200_series(docstring: &Docstring) -> Vec<Violation> {
         let mut violations = Vec::new();
@@ -155,30 +2001,25 @@ fn check_d200_series(docstring: &Docstring) -> Vec<Violation> {
 
         // D201: No blank lines allowed before docstring
         if content.starts_with('\n') {
-            violations.push(Violation {
-                rule: "D201".to_string(),
-                message: format!(
-                    "No blank lines allowed before {} docstring",
-                    docstring.target_type
-                ),
-                line: docstring.line,
-                column: docstring.column,
-                severity: Severity::Error,
-            });
+            violations.push(Violation::new(
+                "D201",
+                format!("No blank lines allowed before {} docstring", docstring.target_type),
+                docstring.position(),
+                Severity::Error,
+            ));
         }
 
         // D202: No blank lines allowed after docstring
         if content.ends_with('\n') {
-            violations.push(Violation {
-                rule: "D202".to_string(),
-                message: format!(
-                    "No blank lines allowed after {} docstring",
-                    docstring.target_type
-                ),
-                line: docstring.line + lines.len() - 1,
-                column: docstring.column,
-                severity: Severity::Error,
-            });
+            violations.push(Violation::new(
+                "D202",
+                format!("No blank lines allowed after {} docstring", docstring.target_type),
+                SourcePosition {
+                    line: docstring.source_line(lines.len() - 1),
+                    column: docstring.column,
+                },
+                Severity::Error,
+            ));
         }
 
         // D205: 1 blank line required between summary paragraph and description
@@ -198,14 +2039,15 @@ fn check_d200_series(docstring: &Docstring) -> Vec<Violation> {
                 && !lines[summary_end_index + 1].trim().is_empty()
             {
                 // No blank line separating summary and description
-                violations.push(Violation {
-                    rule: "D205".to_string(),
-                    message: "1 blank line required between summary line and description"
-                        .to_string(),
-                    line: docstring.line + summary_end_index + 1,
-                    column: docstring.column,
-                    severity: Severity::Error,
-                });
+                violations.push(Violation::new(
+                    "D205",
+                    "1 blank line required between summary line and description",
+                    SourcePosition {
+                        line: docstring.source_line(summary_end_index + 1),
+                        column: docstring.column,
+                    },
+                    Severity::Error,
+                ));
             }
         } else {
             // No blank line found. If there's more than one non-empty line, we need to decide
@@ -220,14 +2062,12 @@ fn check_d200_series(docstring: &Docstring) -> Vec<Violation> {
                 && (first.ends_with('.') || first.ends_with('!') || first.ends_with('?'))
             {
                 // Missing blank line between summary and description
-                violations.push(Violation {
-                    rule: "D205".to_string(),
-                    message: "1 blank line required between summary line and description"
-                        .to_string(),
-                    line: docstring.line + 1,
-                    column: docstring.column,
-                    severity: Severity::Error,
-                });
+                violations.push(Violation::new(
+                    "D205",
+                    "1 blank line required between summary line and description",
+                    SourcePosition { line: docstring.source_line(1), column: docstring.column },
+                    Severity::Error,
+                ));
             }
         }
 
@@ -254,14 +2094,12 @@ fn check_d300_series(docstring: &Docstring) -> Vec<Violation> {
             // D301: Use r""" if any backslashes in a docstring
             // Adapted for Rust: check for excessive escaping
             if docstring.content.contains("\\\\") {
-                violations.push(Violation {
-                    rule: "D301".to_string(),
-                    message: "Consider using raw strings for docstrings with backslashes"
-                        .to_string(),
-                    line: docstring.line,
-                    column: docstring.column,
-                    severity: Severity::Warning,
-                });
+                violations.push(Violation::new(
+                    "D301",
+                    "Consider using raw strings for docstrings with backslashes",
+                    docstring.position(),
+                    Severity::Warning,
+                ));
             }
         }
 
@@ -287,32 +2125,41 @@ fn check_d400_series(docstring: &Docstring) -> Vec<Violation> {
         }
 
         let first_line = lines[first_non_empty_idx].trim();
-
-        // D400: Check that the first non-empty line (the summary) ends with a period.
-        if !first_line.is_empty() && !first_line.ends_with('.') {
-            violations.push(Violation {
-                rule: "D400".to_string(),
-                message: "First line should end with a period".to_string(),
-                line: docstring.line + first_non_empty_idx,
-                column: docstring.column,
-                severity: Severity::Error,
-            });
+        // A summary wrapped across lines is reported by R204, and ends on its last line
+        let summary_end = Self::wrapped_summary(&docstring.content)
+            .and_then(|(_, end)| lines.get(end))
+            .map_or(first_line, |line| line.trim());
+
+        // D400: Check that the summary ends with a period.
+        if !summary_end.is_empty() && !summary_end.ends_with('.') {
+            violations.push(Violation::new(
+                "D400",
+                "First line should end with a period",
+                SourcePosition {
+                    line: docstring.source_line(first_non_empty_idx),
+                    column: docstring.column,
+                },
+                Severity::Error,
+            ));
         }
 
-        // D401: First line should be in imperative mood
-        if !first_line.is_empty() && Self::is_not_imperative(first_line) {
-            violations.push(Violation {
-                rule: "D401".to_string(),
-                message: "First line should be in imperative mood".to_string(),
-                line: docstring.line,
-                column: docstring.column,
-                severity: Severity::Warning,
-            });
+        // D401: First line should be in imperative mood, except for fields, which
+        // are described by noun phrases such as "The name of the user."
+        if docstring.target_type != DocstringTarget::Field
+            && !first_line.is_empty()
+            && Self::is_not_imperative(first_line)
+        {
+            violations.push(Violation::new(
+                "D401",
+                "First line should be in imperative mood",
+                docstring.position(),
+                Severity::Warning,
+            ));
         }
 
         // D402: First line should not be the function's signature
         // Only check functions, and avoid false positives from Markdown links [text](url)
-        if docstring.target_type == DocstringTarget::Function {
+        if docstring.target_type.is_function() {
             // Remove Markdown links to avoid false positives
             let without_md_links = Self::remove_markdown_links(first_line);
 
@@ -330,13 +2177,12 @@ fn check_d400_series(docstring: &Docstring) -> Vec<Violation> {
                         .is_some_and(|c| c.is_lowercase() || c == '_');
 
                 if looks_like_signature {
-                    violations.push(Violation {
-                        rule: "D402".to_string(),
-                        message: "First line should not be the function's signature".to_string(),
-                        line: docstring.line,
-                        column: docstring.column,
-                        severity: Severity::Error,
-                    });
+                    violations.push(Violation::new(
+                        "D402",
+                        "First line should not be the function's signature",
+                        docstring.position(),
+                        Severity::Error,
+                    ));
                 }
             }
         }
@@ -345,13 +2191,12 @@ fn check_d400_series(docstring: &Docstring) -> Vec<Violation> {
         if let Some(first_word) = first_line.split_whitespace().next()
             && !first_word.chars().next().unwrap_or(' ').is_uppercase()
         {
-            violations.push(Violation {
-                rule: "D403".to_string(),
-                message: "First word of the first line should be properly capitalized".to_string(),
-                line: docstring.line,
-                column: docstring.column,
-                severity: Severity::Error,
-            });
+            violations.push(Violation::new(
+                "D403",
+                "First word of the first line should be properly capitalized",
+                docstring.position(),
+                Severity::Error,
+            ));
         }
 
         // R401: Markdown links with code references should have backticks inside brackets
@@ -361,19 +2206,34 @@ fn check_d400_series(docstring: &Docstring) -> Vec<Violation> {
     }
 
     /// Get the appropriate rule code and description for a missing docstring based on target type.
-    fn get_missing_docstring_rule(target_type: DocstringTarget) -> (String, &'static str) {
-        match target_type {
+    ///
+    /// Members of traits have their own rule, since they're public along with their
+    /// trait rather than by their own visibility.
+    fn get_missing_docstring_rule(docstring: &Docstring) -> (String, &'static str) {
+        if docstring.attributes.is_trait_member {
+            return match docstring.target_type {
+                DocstringTarget::Const => ("R107".to_string(), "associated const"),
+                DocstringTarget::TypeAlias => ("R107".to_string(), "associated type"),
+                _ => ("R107".to_string(), "trait method"),
+            };
+        }
+        match docstring.target_type {
             DocstringTarget::Module => ("D100".to_string(), "module"),
             DocstringTarget::Package => ("D104".to_string(), "package"),
             DocstringTarget::Struct => ("D101".to_string(), "struct"),
             DocstringTarget::Enum => ("D101".to_string(), "enum"),
             DocstringTarget::Trait => ("D101".to_string(), "trait"),
             DocstringTarget::Function => ("D103".to_string(), "function"),
-            DocstringTarget::Impl => ("D102".to_string(), "method"),
+            // Impl and extern blocks have no visibility, so only their items are reported
+            DocstringTarget::Method | DocstringTarget::ImplBlock | DocstringTarget::ExternBlock => {
+                ("D102".to_string(), "method")
+            }
             DocstringTarget::Const => ("R102".to_string(), "const"),
             DocstringTarget::Static => ("R102".to_string(), "static"),
             DocstringTarget::TypeAlias => ("R101".to_string(), "type alias"),
             DocstringTarget::Macro => ("R103".to_string(), "macro"),
+            DocstringTarget::Reexport => ("R105".to_string(), "re-export"),
+            DocstringTarget::Field => ("R106".to_string(), "field"),
         }
     }
 
@@ -387,8 +2247,7 @@ fn is_not_imperative(line: &str) -> bool {
         let first_word = words[0];
 
         // Use the imperative crate to check if the first word is imperative
-        let mood_checker = Mood::new();
-        match mood_checker.is_imperative(first_word) {
+        match mood().is_imperative(first_word) {
             Some(true) => false, // It IS imperative, so NOT non-imperative
             Some(false) => true, // It's NOT imperative
             None => {
@@ -455,22 +2314,47 @@ fn remove_markdown_links(text: &str) -> String {
     /// This includes both markdown links `[text](url)` and standalone references `[text]`.
     fn check_markdown_link_backticks(docstring: &Docstring) -> Vec<Violation> {
         let mut violations = Vec::new();
-        let content = &docstring.content;
 
-        // Look for text in square brackets: [text] or [text](url)
-        let mut chars = content.chars().enumerate().peekable();
-        let mut line_num = docstring.line;
-        let mut col_num = docstring.column;
-        let mut in_backticks = false;
-
-        while let Some((pos, ch)) = chars.next() {
-            if ch == '\n' {
-                line_num += 1;
-                col_num = docstring.column;
-                continue;
+        for (offset, link_text, destination) in Self::bracketed_texts(&docstring.content) {
+            // Skip checking reference labels in reference-style links [text][label]
+            // Only check the display text, not the label
+            if destination != Some('[')
+                && Self::looks_like_code(link_text)
+                && !Self::has_backticks(link_text)
+            {
+                let position = docstring.position_at(offset);
+                violations.push(
+                    Violation::new(
+                        "R401",
+                        format!(
+                            concat!(
+                                "Markdown link text looks like code but lacks ",
+                                "backticks: [{}] should be [`{}`]"
+                            ),
+                            link_text.trim(),
+                            link_text.trim()
+                        ),
+                        position,
+                        Severity::Warning,
+                    )
+                    .ending_at(docstring.position_at(offset + link_text.len() + 2)),
+                );
             }
-            col_num += 1;
+        }
+
+        violations
+    }
+
+    /// Find the text in square brackets outside of inline code.
+    ///
+    /// Yields the byte offset of each `[`, the text up to the matching `]`, and the
+    /// opening delimiter of a following `(url)` or `[label]`, if any.
+    pub(crate) fn bracketed_texts(content: &str) -> Vec<(usize, &str, Option<char>)> {
+        let mut results = Vec::new();
+        let mut chars = content.char_indices().peekable();
+        let mut in_backticks = false;
 
+        while let Some((offset, ch)) = chars.next() {
             // Track when we're inside inline code (backticks)
             if ch == '`' {
                 in_backticks = !in_backticks;
@@ -478,134 +2362,35 @@ fn check_markdown_link_backticks(docstring: &Docstring) -> Vec<Violation> {
             }
 
             // Skip checking brackets inside inline code
-            if in_backticks {
+            if in_backticks || ch != '[' {
                 continue;
             }
 
-            if ch == '[' {
-                // Collect text until ]
-                let mut link_text = String::new();
-                let mut found_bracket = false;
-                let _ = pos;
-                let link_start_line = line_num;
-                let link_start_col = col_num;
-
-                while let Some((_, ch)) = chars.peek() {
-                    if *ch == ']' {
-                        found_bracket = true;
-                        chars.next(); // consume ']'
-                        col_num += 1;
-                        break;
-                    }
-                    if *ch == '\n' {
-                        line_num += 1;
-                        col_num = docstring.column;
-                    } else {
-                        col_num += 1;
-                    }
-                    if let Some((_, c)) = chars.next() {
-                        link_text.push(c);
-                    }
-                }
-
-                // Check if this is a markdown reference (with or without URL)
-                if found_bracket {
-                    let mut is_reference_label = false;
-
-                    // Peek ahead to see if there's a URL or another bracket (reference-style link)
-                    while let Some((_, ch)) = chars.peek() {
-                        if *ch == '(' {
-                            chars.next(); // consume '('
-                            col_num += 1;
-
-                            // Skip until ')'
-                            loop {
-                                match chars.peek() {
-                                    Some((_, ')')) => {
-                                        chars.next();
-                                        col_num += 1;
-                                        break;
-                                    }
-                                    Some((_, '\n')) => {
-                                        chars.next();
-                                        line_num += 1;
-                                        col_num = docstring.column;
-                                    }
-                                    Some(_) => {
-                                        chars.next();
-                                        col_num += 1;
-                                    }
-                                    None => break,
-                                }
-                            }
-                            break;
-                        } else if *ch == '[' {
-                            // This is a reference-style link: [text][label]
-                            // Skip the entire label part
-                            chars.next(); // consume '['
-                            col_num += 1;
-
-                            // Skip until ']'
-                            loop {
-                                match chars.peek() {
-                                    Some((_, ']')) => {
-                                        chars.next();
-                                        col_num += 1;
-                                        break;
-                                    }
-                                    Some((_, '\n')) => {
-                                        chars.next();
-                                        line_num += 1;
-                                        col_num = docstring.column;
-                                    }
-                                    Some(_) => {
-                                        chars.next();
-                                        col_num += 1;
-                                    }
-                                    None => break,
-                                }
-                            }
-                            is_reference_label = true;
-                            break;
-                        } else if !ch.is_whitespace() {
-                            // Not followed by URL or label, but still check standalone [text]
-                            break;
-                        }
-                        if *ch == '\n' {
-                            line_num += 1;
-                            col_num = docstring.column;
-                        } else {
-                            col_num += 1;
-                        }
-                        chars.next();
-                    }
+            // Collect text until ]
+            let text_start = offset + 1;
+            let Some((text_end, _)) = chars.find(|&(_, c)| c == ']') else {
+                break;
+            };
 
-                    // Skip checking reference labels in reference-style links [text][label]
-                    // Only check the display text, not the label
-                    if !is_reference_label
-                        && Self::looks_like_code(&link_text)
-                        && !Self::has_backticks(&link_text)
-                    {
-                        violations.push(Violation {
-                            rule: "R401".to_string(),
-                            message: format!(
-                                concat!(
-                                    "Markdown link text looks like code but lacks ",
-                                    "backticks: [{}] should be [`{}`]"
-                                ),
-                                link_text.trim(),
-                                link_text.trim()
-                            ),
-                            line: link_start_line,
-                            column: link_start_col,
-                            severity: Severity::Warning,
-                        });
-                    }
+            // Peek ahead to see if there's a URL or another bracket (reference-style link)
+            let mut destination = None;
+            while let Some(&(_, next)) = chars.peek() {
+                if next == '(' || next == '[' {
+                    let closing = if next == '(' { ')' } else { ']' };
+                    chars.next();
+                    let _ = chars.find(|&(_, c)| c == closing);
+                    destination = Some(next);
+                    break;
+                } else if !next.is_whitespace() {
+                    break;
                 }
+                chars.next();
             }
+
+            results.push((offset, &content[text_start..text_end], destination));
         }
 
-        violations
+        results
     }
 
     /// Check if text looks like code (contains :: or PascalCase identifiers).
@@ -642,151 +2427,33 @@ fn has_backticks(text: &str) -> bool {
     /// R402: Common types like [Option] and [Result] should be `Option` and `Result`.
     fn check_common_rust_types(docstring: &Docstring) -> Vec<Violation> {
         let mut violations = Vec::new();
-        let content = &docstring.content;
-
-        // List of common Rust types that should use inline code instead of markdown links
-        let common_types =
-            ["Option", "Result", "Vec", "Box", "Rc", "Arc", "Some", "None", "Ok", "Err"];
 
         // Look for [Type] or [Type](url) patterns
-        let mut chars = content.chars().enumerate().peekable();
-        let mut line_num = docstring.line;
-        let mut col_num = docstring.column;
-        let mut in_backticks = false;
-
-        while let Some((_pos, ch)) = chars.next() {
-            if ch == '\n' {
-                line_num += 1;
-                col_num = docstring.column;
-                continue;
-            }
-            col_num += 1;
+        for (offset, link_text, destination) in Self::bracketed_texts(&docstring.content) {
+            let trimmed_text = link_text.trim();
 
-            // Track when we're inside inline code (backticks)
-            if ch == '`' {
-                in_backticks = !in_backticks;
-                continue;
-            }
-
-            // Skip checking brackets inside inline code
-            if in_backticks {
+            // Skip if already has backticks
+            if Self::has_backticks(trimmed_text) {
                 continue;
             }
 
-            if ch == '[' {
-                let link_start_line = line_num;
-                let link_start_col = col_num;
-                let mut link_text = String::new();
-                let mut found_bracket = false;
-
-                // Collect text until ]
-                while let Some((_, ch)) = chars.peek() {
-                    if *ch == ']' {
-                        found_bracket = true;
-                        chars.next(); // consume ']'
-                        col_num += 1;
-                        break;
-                    }
-                    if *ch == '\n' {
-                        line_num += 1;
-                        col_num = docstring.column;
-                    } else {
-                        col_num += 1;
-                    }
-                    if let Some((_, c)) = chars.next() {
-                        link_text.push(c);
-                    }
-                }
-
-                if found_bracket {
-                    let trimmed_text = link_text.trim();
-
-                    // Skip if already has backticks
-                    if Self::has_backticks(trimmed_text) {
-                        continue;
-                    }
-
-                    // Check if it's a common Rust type (exact match)
-                    if common_types.contains(&trimmed_text) {
-                        // Peek ahead to see if followed by ( or [, but warn either way
-                        let mut has_url_or_ref = false;
-                        while let Some((_, ch)) = chars.peek() {
-                            if *ch == '(' {
-                                // [Type](url) format - consume it
-                                chars.next(); // consume '('
-                                col_num += 1;
-                                loop {
-                                    match chars.peek() {
-                                        Some((_, ')')) => {
-                                            chars.next();
-                                            col_num += 1;
-                                            break;
-                                        }
-                                        Some((_, '\n')) => {
-                                            chars.next();
-                                            line_num += 1;
-                                            col_num = docstring.column;
-                                        }
-                                        Some(_) => {
-                                            chars.next();
-                                            col_num += 1;
-                                        }
-                                        None => break,
-                                    }
-                                }
-                                has_url_or_ref = true;
-                                break;
-                            } else if *ch == '[' {
-                                // [Type][ref] format - consume the reference
-                                chars.next(); // consume '['
-                                col_num += 1;
-                                loop {
-                                    match chars.peek() {
-                                        Some((_, ']')) => {
-                                            chars.next();
-                                            col_num += 1;
-                                            break;
-                                        }
-                                        Some((_, '\n')) => {
-                                            chars.next();
-                                            line_num += 1;
-                                            col_num = docstring.column;
-                                        }
-                                        Some(_) => {
-                                            chars.next();
-                                            col_num += 1;
-                                        }
-                                        None => break,
-                                    }
-                                }
-                                has_url_or_ref = true;
-                                break;
-                            } else if !ch.is_whitespace() {
-                                break;
-                            }
-                            if *ch == '\n' {
-                                line_num += 1;
-                                col_num = docstring.column;
-                            } else {
-                                col_num += 1;
-                            }
-                            chars.next();
-                        }
-
-                        violations.push(Violation {
-                            rule: "R402".to_string(),
-                            message: format!(
-                                "Use inline code for common Rust type: [{}]{} should be `{}`",
-                                trimmed_text,
-                                if has_url_or_ref { "(...)" } else { "" },
-                                trimmed_text
-                            ),
-                            line: link_start_line,
-                            column: link_start_col,
-                            severity: Severity::Warning,
-                        });
-                    }
-                }
+            // Check if it's a common Rust type (exact match), with or without a URL or reference
+            if COMMON_RUST_TYPES.contains(&trimmed_text) {
+                let position = docstring.position_at(offset);
+                violations.push(
+                    Violation::new(
+                        "R402",
+                        format!(
+                            "Use inline code for common Rust type: [{}]{} should be `{}`",
+                            trimmed_text,
+                            if destination.is_some() { "(...)" } else { "" },
+                            trimmed_text
+                        ),
+                        position,
+                        Severity::Warning,
+                    )
+                    .ending_at(docstring.position_at(offset + link_text.len() + 2)),
+                );
             }
         }
 
@@ -799,6 +2466,40 @@ fn check_common_rust_types(docstring: &Docstring) -> Vec<Violation> {
 mod tests {
     use super::*;
 
+    /// Return a docstring of a public function on the first line, with the given content.
+    fn docstring(content: &str) -> Docstring {
+        Docstring {
+            content: content.to_string(),
+            raw_content: String::new(),
+            line: 1,
+            column: 1,
+            is_multiline: content.contains('\n'),
+            is_public: true,
+            target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
+        }
+    }
+
+    /// Return the violations of a rule in a docstring checked with the given options.
+    fn rule_hits(docstring: &Docstring, options: &CheckerOptions, rule: &str) -> Vec<Violation> {
+        rule_hits_in(docstring, options, &AnalysisContext::default(), rule)
+    }
+
+    /// Return the violations of a rule in a docstring checked in the given context.
+    fn rule_hits_in(
+        docstring: &Docstring,
+        options: &CheckerOptions,
+        context: &AnalysisContext,
+        rule: &str,
+    ) -> Vec<Violation> {
+        Pep257Checker::check_docstring_in(docstring, options, context)
+            .into_iter()
+            .filter(|v| v.rule == rule)
+            .collect()
+    }
+
     /// Test empty docstring detection.
     #[test]
     fn test_empty_docstring() {
@@ -811,11 +2512,30 @@ fn test_empty_docstring() {
             // This test verifies that D103 is reported for public functions
             is_public: true,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
+        };
+
+        let violations = Pep257Checker::check_docstring(&docstring);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "D103");
+    }
+
+    /// Test that missing docstring messages name the item.
+    #[test]
+    fn test_missing_docstring_names_item() {
+        let docstring = Docstring {
+            line: 3,
+            column: 5,
+            target_type: DocstringTarget::Method,
+            item_path: "config::Config::parse_config".to_string(),
+            ..docstring("")
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
         assert_eq!(violations.len(), 1);
-        assert_eq!(violations[0].rule, "D103");
+        assert_eq!(violations[0].message, "Missing docstring in public method `parse_config`");
     }
 
     /// Test that empty docstring for a private function does NOT trigger D103
@@ -829,6 +2549,9 @@ fn test_empty_docstring_private_no_d103() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -847,6 +2570,9 @@ fn test_empty_docstring_module() {
             is_multiline: false,
             is_public: true,
             target_type: DocstringTarget::Module,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -866,6 +2592,9 @@ fn test_empty_docstring_struct() {
             is_multiline: false,
             is_public: true,
             target_type: DocstringTarget::Struct,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -885,6 +2614,9 @@ fn test_empty_docstring_enum() {
             is_multiline: false,
             is_public: true,
             target_type: DocstringTarget::Enum,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -904,6 +2636,9 @@ fn test_empty_docstring_trait() {
             is_multiline: false,
             is_public: true,
             target_type: DocstringTarget::Trait,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -922,7 +2657,10 @@ fn test_empty_docstring_method() {
             column: 1,
             is_multiline: false,
             is_public: true,
-            target_type: DocstringTarget::Impl,
+            target_type: DocstringTarget::Method,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -942,6 +2680,9 @@ fn test_empty_docstring_const() {
             is_multiline: false,
             is_public: true,
             target_type: DocstringTarget::Const,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -961,6 +2702,9 @@ fn test_empty_docstring_static() {
             is_multiline: false,
             is_public: true,
             target_type: DocstringTarget::Static,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -980,6 +2724,9 @@ fn test_empty_docstring_type_alias() {
             is_multiline: false,
             is_public: true,
             target_type: DocstringTarget::TypeAlias,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -999,6 +2746,9 @@ fn test_empty_docstring_macro() {
             is_multiline: false,
             is_public: true,
             target_type: DocstringTarget::Macro,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1018,6 +2768,9 @@ fn test_empty_docstring_package() {
             is_multiline: false,
             is_public: true,
             target_type: DocstringTarget::Package,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1026,6 +2779,826 @@ fn test_empty_docstring_package() {
         assert!(violations[0].message.contains("package"));
     }
 
+    /// R104: Crate roots without a missing_docs lint level get a suggestion
+    #[test]
+    fn test_r104_missing_docs_lint_suggestion() {
+        let options = CheckerOptions::default();
+
+        let violations =
+            Pep257Checker::check_crate_attributes(&["allow(clippy::all)".to_string()], &options);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "R104");
+
+        for attr in ["warn(missing_docs)", "deny(unused, missing_docs)", "allow(missing_docs)"] {
+            let violations = Pep257Checker::check_crate_attributes(&[attr.to_string()], &options);
+            assert!(violations.is_empty(), "Failed for: {attr}");
+        }
+
+        let disabled =
+            CheckerOptions { suggest_missing_docs_lint: false, ..CheckerOptions::default() };
+        assert!(Pep257Checker::check_crate_attributes(&[], &disabled).is_empty());
+    }
+
+    /// R405: Empty code spans and code blocks are flagged
+    #[test]
+    fn test_r405_empty_code() {
+        let default = CheckerOptions::default();
+
+        let hits = rule_hits(&docstring("Return `` as the value."), &default, "R405");
+        assert_eq!(hits[0].message, "Empty code span");
+        let hits = rule_hits(&docstring("Return ` ` or `` `` here."), &default, "R405");
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|v| v.message == "Code span contains only whitespace"));
+        let hits = rule_hits(&docstring("Create a value.\n\n```rust\n\n```"), &default, "R405");
+        assert_eq!(hits[0].message, "Empty code block");
+        let hits = rule_hits(&docstring("Create a value.\n\n~~~"), &default, "R405");
+        assert_eq!(hits[0].message, "Empty code block");
+
+        assert!(rule_hits(&docstring("Return `x` or ``a`b`` here."), &default, "R405").is_empty());
+        let code = docstring("Create a value.\n\n```\nlet `` = 1;\n```");
+        assert!(rule_hits(&code, &default, "R405").is_empty());
+    }
+
+    /// R406: Bare URLs outside of code are flagged
+    #[test]
+    fn test_r406_bare_urls() {
+        let default = CheckerOptions::default();
+
+        let url = docstring("Fetch the page.\n\nSee https://example.com/docs.");
+        let hits = rule_hits(&url, &default, "R406");
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].line, hits[0].column), (3, 5));
+        assert_eq!(
+            hits[0].message,
+            "Bare URL should be wrapped in angle brackets: <https://example.com/docs>"
+        );
+        let parenthesized = docstring("Fetch (from http://example.com) the page.");
+        assert_eq!(rule_hits(&parenthesized, &default, "R406").len(), 1);
+
+        for content in [
+            "Fetch <https://example.com> the page.",
+            "Fetch the [page](https://example.com).",
+            "Fetch the [page].\n\n[page]: https://example.com",
+            "Fetch `https://example.com` the page.",
+            "Fetch the page.\n\n```\nlet url = https://example.com;\n```",
+            "Fetch the page over https:// only.",
+        ] {
+            assert!(rule_hits(&docstring(content), &default, "R406").is_empty(), "{content:?}");
+        }
+    }
+
+    /// R407: Long docstrings without section headings are flagged
+    #[test]
+    fn test_r407_prose_length() {
+        let mut options = CheckerOptions::default();
+        options.prose_limits.default = 3;
+
+        let long = "Create a value.\n\nFirst.\nSecond.\n\nThird.";
+        let function = docstring(long);
+        let hits = rule_hits(&function, &options, "R407");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].message,
+            "Docstring has 4 lines of prose without a section heading (limit is 3)"
+        );
+
+        // Headings, code blocks, and per-target limits
+        let with_heading = docstring(&format!("{long}\n\n# Examples"));
+        assert!(rule_hits(&with_heading, &options, "R407").is_empty());
+        let with_code = docstring("Create a value.\n\n```\n# let a = 1;\nb;\nc;\n```");
+        assert!(rule_hits(&with_code, &options, "R407").is_empty());
+
+        options.prose_limits.set("type-alias", 10).unwrap();
+        let alias = Docstring { target_type: DocstringTarget::TypeAlias, ..docstring(long) };
+        assert!(rule_hits(&alias, &options, "R407").is_empty());
+        assert_eq!(rule_hits(&function, &options, "R407").len(), 1);
+
+        options.prose_limits.set("function", 0).unwrap();
+        assert!(rule_hits(&function, &options, "R407").is_empty());
+        assert!(options.prose_limits.set("closure", 1).is_err());
+    }
+
+    /// R201: An indented first doc line is flagged
+    #[test]
+    fn test_r201_indented_first_line() {
+        let default = CheckerOptions::default();
+        let line_positions =
+            vec![SourcePosition { line: 3, column: 9 }, SourcePosition { line: 4, column: 9 }];
+
+        let indented = Docstring {
+            raw_content: "///    Create a value.".to_string(),
+            line_positions: line_positions.clone(),
+            ..docstring("Create a value.")
+        };
+        let hits = rule_hits(&indented, &default, "R201");
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].line, hits[0].column), (3, 9));
+        assert_eq!(hits[0].message, "First docstring line is indented by 4 characters");
+        let inner = Docstring {
+            raw_content: "///\n//!  Create a value.".to_string(),
+            line_positions: line_positions.clone(),
+            ..docstring("\nCreate a value.")
+        };
+        assert_eq!(rule_hits(&inner, &default, "R201").len(), 1);
+
+        for (raw_content, content) in [
+            ("/// Create a value.", "Create a value."),
+            ("///Create a value.", "Create a value."),
+            ("/// Create a value.\n///     code", "Create a value.\ncode"),
+            ("/**\n *   Create a value.\n */", "\nCreate a value.\n"),
+        ] {
+            let docstring = Docstring {
+                raw_content: raw_content.to_string(),
+                line_positions: line_positions.clone(),
+                ..docstring(content)
+            };
+            assert!(rule_hits(&docstring, &default, "R201").is_empty(), "{raw_content:?}");
+        }
+    }
+
+    /// D210: Doc comment lines ending with whitespace are flagged where it starts
+    #[test]
+    fn test_trailing_whitespace() {
+        let default = CheckerOptions::default();
+        let line_positions: Vec<SourcePosition> =
+            (1..=3).map(|line| SourcePosition { line, column: 5 }).collect();
+
+        let trailing = Docstring {
+            raw_content: "/// Create a value.  \n///   \r\n/// More.\t\n".to_string(),
+            line_positions: line_positions.clone(),
+            ..docstring("Create a value.\n\nMore.")
+        };
+        let starts: Vec<(usize, usize)> =
+            rule_hits(&trailing, &default, "D210").iter().map(|v| (v.line, v.column)).collect();
+        assert_eq!(starts, vec![(1, 20), (2, 5), (3, 10)]);
+
+        let crlf = Docstring {
+            raw_content: "/// Create a value.\r\n".to_string(),
+            line_positions: line_positions.clone(),
+            ..docstring("Create a value.")
+        };
+        assert!(rule_hits(&crlf, &default, "D210").is_empty());
+        let block = Docstring {
+            raw_content: "/** Create a value. */".to_string(),
+            line_positions,
+            ..docstring("Create a value. ")
+        };
+        assert!(rule_hits(&block, &default, "D210").is_empty());
+    }
+
+    /// R204: A summary sentence wrapped across lines is flagged, but lists are not
+    #[test]
+    fn test_wrapped_summary() {
+        assert_eq!(
+            Pep257Checker::wrapped_summary("\nParse the value\nand return\nit.\nMore text"),
+            Some((1, 3))
+        );
+        assert_eq!(
+            Pep257Checker::wrapped_summary("Parse the value\nand return it\n\nMore."),
+            Some((0, 1))
+        );
+        for content in [
+            "Parse the value.\nMore.",
+            "Parse the value\n\nMore.",
+            "Parse the value:\n- first",
+            "Parse the value\n- first",
+            "Parse the value\n1. first",
+            "Parse the value\n# Examples",
+            "Parse the value\n```\nparse();\n```",
+            "# Parse the value\nand return it.",
+        ] {
+            assert_eq!(Pep257Checker::wrapped_summary(content), None, "{content:?}");
+        }
+    }
+
+    /// D212/D213: The summary position of multi-line block comments follows the enabled convention
+    #[test]
+    fn test_block_summary_position() {
+        let content = "Create a value.\n\nMore.";
+        let d212 =
+            CheckerOptions { enabled_rules: vec!["D212".to_string()], ..CheckerOptions::default() };
+        let d213 =
+            CheckerOptions { enabled_rules: vec!["D213".to_string()], ..CheckerOptions::default() };
+
+        let second_line = Docstring {
+            raw_content: "/**\n * Create a value.\n *\n * More.\n */".to_string(),
+            ..docstring(content)
+        };
+        let first_line = Docstring {
+            raw_content: "/** Create a value.\n *\n * More.\n */".to_string(),
+            ..docstring(content)
+        };
+        assert_eq!(rule_hits(&second_line, &d212, "D212").len(), 1);
+        assert!(rule_hits(&first_line, &d212, "D212").is_empty());
+        assert_eq!(rule_hits(&first_line, &d213, "D213").len(), 1);
+        assert!(rule_hits(&second_line, &d213, "D213").is_empty());
+        let inner =
+            Docstring { raw_content: "/*! Create a value.\n */".to_string(), ..docstring(content) };
+        assert_eq!(rule_hits(&inner, &d213, "D213").len(), 1);
+
+        // One-line block comments and line comments have no summary position
+        let one_line = Docstring {
+            raw_content: "/** Create a value. */\n/// More.".to_string(),
+            ..docstring(content)
+        };
+        assert!(rule_hits(&one_line, &d213, "D213").is_empty());
+        let line_comment =
+            Docstring { raw_content: "///\n/// Create a value.".to_string(), ..docstring(content) };
+        assert!(rule_hits(&line_comment, &d212, "D212").is_empty());
+        let r409 =
+            CheckerOptions { enabled_rules: vec!["R409".to_string()], ..CheckerOptions::default() };
+        assert!(rule_hits(&second_line, &r409, "D212").is_empty());
+        assert!(rule_hits(&second_line, &r409, "D213").is_empty());
+    }
+
+    /// R202: More blank lines than allowed between summary and description are flagged
+    #[test]
+    fn test_r202_summary_separation() {
+        let default = CheckerOptions::default();
+
+        let spaced = docstring("Create a value.\n\n\n\nMore details.");
+        let hits = rule_hits(&spaced, &default, "R202");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].line, 3);
+        assert_eq!(
+            hits[0].message,
+            "3 blank lines between summary and description, expected at most 1"
+        );
+        let wrapped = docstring("\nCreate a value\nin two lines.\n\n\nMore.");
+        assert_eq!(rule_hits(&wrapped, &default, "R202").len(), 1);
+        for content in
+            ["Create a value.\n\nMore details.", "Create a value.\n\n\n", "Create a value."]
+        {
+            assert!(rule_hits(&docstring(content), &default, "R202").is_empty(), "{content:?}");
+        }
+
+        let options = CheckerOptions { max_summary_blank_lines: Some(2), ..default };
+        let two_blank = docstring("Create a value.\n\n\nMore details.");
+        assert!(rule_hits(&two_blank, &options, "R202").is_empty());
+        assert_eq!(rule_hits(&spaced, &options, "R202")[0].line, 4);
+    }
+
+    /// R408: Bullet lists mixing markers are flagged
+    #[test]
+    fn test_r408_bullet_markers() {
+        let options = CheckerOptions::default();
+
+        let mixed = docstring("Create a value.\n\n- one\n* two\n  continued\n- three");
+        let hits = rule_hits(&mixed, &options, "R408");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].line, 4);
+        assert_eq!(hits[0].message, "List item uses `*` but the list started with `-`");
+
+        // Nested lists, separate lists, code blocks and thematic breaks
+        for content in [
+            "Create a value.\n\n- one\n  * nested\n  * nested\n- two",
+            "Create a value.\n\n- one\n\nText.\n\n* two",
+            "Create a value.\n\n- one\n\n```\n* code\n```",
+            "Create a value.\n\n- one\n\n* * *",
+        ] {
+            assert!(rule_hits(&docstring(content), &options, "R408").is_empty(), "{content:?}");
+        }
+
+        let options = CheckerOptions { bullet_marker: Some(BulletMarker::Dash), ..options };
+        let stars = docstring("Create a value.\n\n* one\n* two\n- three");
+        let hits = rule_hits(&stars, &options, "R408");
+        assert_eq!(hits.iter().map(|v| v.line).collect::<Vec<_>>(), vec![3, 4]);
+        assert!(hits.iter().all(|v| v.message == "List item should use `-` instead of `*`"));
+    }
+
+    /// R108: Comments trailing a field or variant are flagged
+    #[test]
+    fn test_r108_trailing_comment() {
+        let comment = |is_doc: bool| TrailingComment {
+            text: "the x coordinate".to_string(),
+            is_doc,
+            member_kind: "field",
+            member: "x".to_string(),
+            item_path: "Point::x".to_string(),
+            member_line: 2,
+            position: SourcePosition { line: 2, column: 12 },
+        };
+
+        let violations = Pep257Checker::check_trailing_comment(&comment(true));
+        assert_eq!(violations.len(), 1);
+        assert_eq!((violations[0].line, violations[0].column), (2, 12));
+        assert_eq!(
+            violations[0].message,
+            "Trailing doc comment after field `x` documents the next field; move it above `x`"
+        );
+
+        let violations = Pep257Checker::check_trailing_comment(&comment(false));
+        assert_eq!(
+            violations[0].message,
+            "Trailing comment on field `x` should be a doc comment above it"
+        );
+    }
+
+    /// R403: Paths in prose outside code spans and links are flagged
+    #[test]
+    fn test_r403_bare_paths() {
+        let default = CheckerOptions::default();
+
+        let path = docstring("Return a crate::parser::RustParser value.");
+        let hits = rule_hits(&path, &default, "R403");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].column, 10);
+        assert_eq!(
+            hits[0].message,
+            "Path `crate::parser::RustParser` should be an intra-doc link: [`crate::parser::RustParser`]"
+        );
+        let paths = docstring("Use std::fmt or Vec::new, not self::x.");
+        assert_eq!(rule_hits(&paths, &default, "R403").len(), 3);
+        let parenthesized = docstring("See (http://x.y/a::b) for std::fmt.");
+        assert_eq!(rule_hits(&parenthesized, &default, "R403").len(), 1);
+
+        for content in [
+            "Return a [`crate::parser::RustParser`] value.",
+            "Return a [parser](crate::parser) value.",
+            "Return a `crate::parser` value.",
+            "Return a value.\n\n[parser]: crate::parser",
+            "Fetch <https://example.com> and https://example.com.",
+            "See http://x.y/a::b and [docs](https://x.y/a::b).",
+            "Match 1::2 and a:::b and ::leading.",
+        ] {
+            assert!(rule_hits(&docstring(content), &default, "R403").is_empty(), "{content:?}");
+        }
+    }
+
+    /// R409: Function docs starting with an article are flagged when enabled
+    #[test]
+    fn test_r409_function_article() {
+        let options =
+            CheckerOptions { enabled_rules: vec!["R409".to_string()], ..CheckerOptions::default() };
+
+        let function = docstring("The sum of two numbers.");
+        let hits = rule_hits(&function, &options, "R409");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].message,
+            "Function docstring should start with an imperative verb, not `The`"
+        );
+        assert_eq!(rule_hits(&docstring("An adder."), &options, "R409").len(), 1);
+
+        assert!(rule_hits(&function, &CheckerOptions::default(), "R409").is_empty());
+        let point = Docstring { target_type: DocstringTarget::Struct, ..docstring("A point.") };
+        assert!(rule_hits(&point, &options, "R409").is_empty());
+        assert!(rule_hits(&docstring("Add them."), &options, "R409").is_empty());
+        assert!(rule_hits(&docstring("Anything."), &options, "R409").is_empty());
+    }
+
+    /// Test that every thread shares one mood dictionary.
+    #[test]
+    fn test_shared_mood() {
+        let shared = std::thread::spawn(|| std::ptr::from_ref(mood()) as usize).join().unwrap();
+        assert_eq!(std::ptr::from_ref(mood()) as usize, shared);
+        assert_eq!(mood().is_imperative("return"), Some(true));
+    }
+
+    /// R410: Type docs starting with an imperative verb are flagged when enabled
+    #[test]
+    fn test_r410_type_noun_phrase() {
+        let options =
+            CheckerOptions { enabled_rules: vec!["R410".to_string()], ..CheckerOptions::default() };
+
+        let verb = Docstring {
+            target_type: DocstringTarget::Struct,
+            ..docstring("Create points from coordinates.")
+        };
+        let hits = rule_hits(&verb, &options, "R410");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].message,
+            "Struct docstring should be a noun phrase, not start with the verb `Create`"
+        );
+        assert!(rule_hits(&verb, &options, "D401").is_empty());
+
+        // D401 no longer applies to types, but still applies to functions
+        let noun = Docstring {
+            target_type: DocstringTarget::Enum,
+            ..docstring("The states of a connection.")
+        };
+        assert!(rule_hits(&noun, &options, "R410").is_empty());
+        assert!(rule_hits(&noun, &options, "D401").is_empty());
+        assert_eq!(rule_hits(&noun, &CheckerOptions::default(), "D401").len(), 1);
+        let function = docstring("The sum.");
+        assert!(rule_hits(&function, &options, "R410").is_empty());
+        let hits = rule_hits(&function, &options, "D401");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].message, "First line should be in imperative mood");
+    }
+
+    /// R411: Crate docs starting with the crate name are flagged when enabled
+    #[test]
+    fn test_r411_crate_name_summary() {
+        let options =
+            CheckerOptions { enabled_rules: vec!["R411".to_string()], ..CheckerOptions::default() };
+        let root = AnalysisContext {
+            crate_name: Some("my_crate".to_string()),
+            ..AnalysisContext::new("crate", None)
+        };
+        let module = AnalysisContext {
+            crate_name: Some("my_crate".to_string()),
+            ..AnalysisContext::new("crate::parser", None)
+        };
+
+        let named = Docstring {
+            target_type: DocstringTarget::Package,
+            ..docstring("`my-crate` is a parser for docs.")
+        };
+        let hits = rule_hits_in(&named, &options, &root, "R411");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].message,
+            "Crate docstring should say what the crate does, not start with its name `my-crate`"
+        );
+        let prefixed = Docstring {
+            target_type: DocstringTarget::Package,
+            ..docstring("My_crate: a parser for docs.")
+        };
+        assert_eq!(rule_hits_in(&prefixed, &options, &root, "R411").len(), 1);
+
+        let mentioned = Docstring {
+            target_type: DocstringTarget::Package,
+            ..docstring("Parse docs with my_crate.")
+        };
+        assert!(rule_hits_in(&mentioned, &options, &root, "R411").is_empty());
+        let leading = Docstring {
+            target_type: DocstringTarget::Package,
+            ..docstring("my_crate is a parser.")
+        };
+        assert!(rule_hits_in(&leading, &options, &module, "R411").is_empty());
+        assert!(rule_hits(&leading, &options, "R411").is_empty());
+    }
+
+    /// R414: Default trait method docs must mention the default when enabled
+    #[test]
+    fn test_r414_default_method() {
+        let mut options =
+            CheckerOptions { enabled_rules: vec!["R414".to_string()], ..CheckerOptions::default() };
+        let default_method =
+            ItemAttributes { is_default_method: true, ..ItemAttributes::default() };
+
+        let flush = Docstring {
+            is_public: false,
+            attributes: default_method.clone(),
+            ..docstring("Flush the buffer.")
+        };
+        let hits = rule_hits(&flush, &options, "R414");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].message,
+            "Default trait method docstring should say a default is provided, mentioning `default`"
+        );
+        let mentioned = Docstring {
+            is_public: false,
+            attributes: default_method.clone(),
+            ..docstring("Flush the buffer.\n\nBy default, does nothing.")
+        };
+        assert!(rule_hits(&mentioned, &options, "R414").is_empty());
+        let required = Docstring { is_public: false, ..docstring("Flush the buffer.") };
+        assert!(rule_hits(&required, &options, "R414").is_empty());
+        let missing =
+            Docstring { is_public: false, attributes: default_method.clone(), ..docstring("") };
+        assert!(rule_hits(&missing, &options, "R414").is_empty());
+        assert!(rule_hits(&flush, &CheckerOptions::default(), "R414").is_empty());
+
+        options.default_method_keywords = vec!["Override".to_string()];
+        assert_eq!(rule_hits(&mentioned, &options, "R414").len(), 1);
+        let overridden = Docstring {
+            is_public: false,
+            attributes: default_method,
+            ..docstring("Flush the buffer.\n\noverride to write.")
+        };
+        assert!(rule_hits(&overridden, &options, "R414").is_empty());
+    }
+
+    /// R501-R503: Crate docs need a summary, an example and some length when enabled
+    #[test]
+    fn test_r501_crate_docs() {
+        let options = CheckerOptions {
+            enabled_rules: ["R501", "R502", "R503"].map(str::to_string).to_vec(),
+            crate_docs_min_lines: Some(4),
+            ..CheckerOptions::default()
+        };
+        let root = AnalysisContext::new("crate", None);
+
+        let heading = Docstring {
+            target_type: DocstringTarget::Package,
+            ..docstring("# my_crate\n\nParse docs.")
+        };
+        assert_eq!(
+            rule_hits_in(&heading, &options, &root, "R501")[0].message,
+            "Crate docstring should start with a summary sentence, not a heading"
+        );
+        assert_eq!(
+            rule_hits_in(&heading, &options, &root, "R502")[0].message,
+            "Crate docstring should show how to use the crate in a code example"
+        );
+        assert_eq!(
+            rule_hits_in(&heading, &options, &root, "R503")[0].message,
+            "Crate docstring should have at least 4 lines, found 2"
+        );
+        let badge = Docstring {
+            target_type: DocstringTarget::Package,
+            ..docstring("[![CI](badge.svg)](ci)\n\nParse docs.")
+        };
+        assert_eq!(
+            rule_hits_in(&badge, &options, &root, "R501")[0].message,
+            "Crate docstring should start with a summary sentence, not an image"
+        );
+
+        let complete = Docstring {
+            target_type: DocstringTarget::Package,
+            ..docstring("Parse docs.\n\n```\nmy_crate::parse();\n```")
+        };
+        let module = AnalysisContext::new("crate::parser", None);
+        let module_heading =
+            Docstring { target_type: DocstringTarget::Package, ..docstring("# my_crate") };
+        for rule in ["R501", "R502", "R503"] {
+            assert!(rule_hits_in(&complete, &options, &root, rule).is_empty(), "{rule}");
+            assert!(rule_hits_in(&module_heading, &options, &module, rule).is_empty(), "{rule}");
+        }
+    }
+
+    /// R602: Public unsafe functions need a `# Safety` section
+    #[test]
+    fn test_r602_safety_section() {
+        let default = CheckerOptions::default();
+        let unsafe_fn = ItemAttributes {
+            modifiers: FunctionModifiers { is_unsafe: true, ..FunctionModifiers::default() },
+            ..ItemAttributes::default()
+        };
+
+        for content in [
+            "Read the byte.",
+            "Read the byte.\n\n```\n# Safety\n```",
+            "Read the byte.\n\nSafety: `ptr` must be valid.",
+        ] {
+            let docstring = Docstring { attributes: unsafe_fn.clone(), ..docstring(content) };
+            assert_eq!(rule_hits(&docstring, &default, "R602").len(), 1, "{content:?}");
+        }
+        for content in [
+            "Read the byte.\n\n# Safety\n\n`ptr` must be valid.",
+            "Read the byte.\n\n## safety\n\n`ptr` must be valid.",
+        ] {
+            let docstring = Docstring { attributes: unsafe_fn.clone(), ..docstring(content) };
+            assert!(rule_hits(&docstring, &default, "R602").is_empty(), "{content:?}");
+        }
+
+        assert!(rule_hits(&docstring("Read the byte."), &default, "R602").is_empty());
+        let private =
+            Docstring { is_public: false, attributes: unsafe_fn, ..docstring("Read the byte.") };
+        assert!(rule_hits(&private, &default, "R602").is_empty());
+    }
+
+    /// R415: Public functions returning `Result` need an `# Errors` section when enabled
+    #[test]
+    fn test_r415_errors_section() {
+        let options =
+            CheckerOptions { enabled_rules: vec!["R415".to_string()], ..CheckerOptions::default() };
+        let fallible = ItemAttributes { returns_result: true, ..ItemAttributes::default() };
+
+        let parse = Docstring { attributes: fallible.clone(), ..docstring("Parse the input.") };
+        assert_eq!(rule_hits(&parse, &options, "R415").len(), 1);
+        let prose = Docstring {
+            attributes: fallible.clone(),
+            ..docstring("Parse the input.\n\nFails on empty input.")
+        };
+        assert_eq!(rule_hits(&prose, &options, "R415").len(), 1);
+        let section = Docstring {
+            attributes: fallible,
+            ..docstring("Parse the input.\n\n# Errors\n\nFails on empty input.")
+        };
+        assert!(rule_hits(&section, &options, "R415").is_empty());
+        assert!(rule_hits(&docstring("Parse the input."), &options, "R415").is_empty());
+        let r409 =
+            CheckerOptions { enabled_rules: vec!["R409".to_string()], ..CheckerOptions::default() };
+        assert!(rule_hits(&parse, &r409, "R415").is_empty());
+    }
+
+    /// R416: Public functions calling panicking constructs need a `# Panics` section
+    #[test]
+    fn test_r416_panics_section() {
+        let mut options =
+            CheckerOptions { enabled_rules: vec!["R416".to_string()], ..CheckerOptions::default() };
+        let calls = |calls: &[&str]| ItemAttributes {
+            calls: calls.iter().map(|call| (*call).to_string()).collect(),
+            ..ItemAttributes::default()
+        };
+
+        let read = Docstring {
+            attributes: calls(&["open", "unwrap", "panic!"]),
+            ..docstring("Read the file.")
+        };
+        let hits = rule_hits(&read, &options, "R416");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].message,
+            "Docstring of a function calling `unwrap` should have a `# Panics` section"
+        );
+        let section = Docstring {
+            attributes: calls(&["unwrap"]),
+            ..docstring("Read the file.\n\n# Panics\n\nIf it's missing.")
+        };
+        assert!(rule_hits(&section, &options, "R416").is_empty());
+        let unreachable = Docstring {
+            attributes: calls(&["open", "unreachable!"]),
+            ..docstring("Read the file.")
+        };
+        assert!(rule_hits(&unreachable, &options, "R416").is_empty());
+        let unwrap = Docstring { attributes: calls(&["unwrap"]), ..docstring("Read the file.") };
+        assert!(rule_hits(&unwrap, &CheckerOptions::default(), "R416").is_empty());
+
+        options.panic_constructs = vec!["unreachable!".to_string()];
+        assert!(rule_hits(&unwrap, &options, "R416").is_empty());
+        assert_eq!(rule_hits(&unreachable, &options, "R416").len(), 1);
+    }
+
+    /// R417: Public items of the configured target types need a code example
+    #[test]
+    fn test_r417_code_example() {
+        let mut options =
+            CheckerOptions { enabled_rules: vec!["R417".to_string()], ..CheckerOptions::default() };
+
+        let function = docstring("Parse the input.");
+        let hits = rule_hits(&function, &options, "R417");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].message, "Public function docstring should include a code example");
+        let input =
+            Docstring { target_type: DocstringTarget::Struct, ..docstring("A parsed input.") };
+        assert_eq!(rule_hits(&input, &options, "R417").len(), 1);
+        let error = Docstring { target_type: DocstringTarget::Enum, ..docstring("A parse error.") };
+        assert!(rule_hits(&error, &options, "R417").is_empty());
+        let example = docstring("Parse the input.\n\n# Examples\n\n```\nparse(\"1\");\n```");
+        assert!(rule_hits(&example, &options, "R417").is_empty());
+        assert!(rule_hits(&function, &CheckerOptions::default(), "R417").is_empty());
+
+        options.example_targets.add("enum").unwrap();
+        assert!(rule_hits(&function, &options, "R417").is_empty());
+        assert_eq!(rule_hits(&error, &options, "R417").len(), 1);
+        assert!(options.example_targets.add("closure").is_err());
+    }
+
+    /// R601: Unsafe blocks in public functions need a safety comment when enabled
+    #[test]
+    fn test_r601_unsafe_blocks() {
+        let block = |is_public: bool, has_safety_comment: bool| UnsafeBlock {
+            item_path: "Buffer::get".to_string(),
+            is_public,
+            has_safety_comment,
+            position: SourcePosition { line: 3, column: 5 },
+        };
+        let enabled =
+            CheckerOptions { enabled_rules: vec!["R601".to_string()], ..CheckerOptions::default() };
+
+        let violations = Pep257Checker::check_unsafe_block(&block(true, false), &enabled);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].message,
+            "Unsafe block in public function `Buffer::get` should be preceded by a `// SAFETY:` \
+             comment"
+        );
+        assert_eq!((violations[0].line, violations[0].column), (3, 5));
+
+        assert!(Pep257Checker::check_unsafe_block(&block(true, true), &enabled).is_empty());
+        assert!(Pep257Checker::check_unsafe_block(&block(false, false), &enabled).is_empty());
+        let default = CheckerOptions::default();
+        assert!(Pep257Checker::check_unsafe_block(&block(true, false), &default).is_empty());
+    }
+
+    /// R412: Public async function docs should mention cancellation when enabled
+    #[test]
+    fn test_r412_async_cancellation() {
+        let mut options =
+            CheckerOptions { enabled_rules: vec!["R412".to_string()], ..CheckerOptions::default() };
+        let async_fn = ItemAttributes {
+            modifiers: FunctionModifiers { is_async: true, ..FunctionModifiers::default() },
+            ..ItemAttributes::default()
+        };
+
+        let fetch = Docstring { attributes: async_fn.clone(), ..docstring("Fetch the page.") };
+        let hits = rule_hits(&fetch, &options, "R412");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].message,
+            "Async function docstring should document cancellation, mentioning `cancel`"
+        );
+        let section = Docstring {
+            attributes: async_fn.clone(),
+            ..docstring("Fetch the page.\n\n# Cancellation safety\n\nSafe.")
+        };
+        assert!(rule_hits(&section, &options, "R412").is_empty());
+        assert!(rule_hits(&docstring("Fetch the page."), &options, "R412").is_empty());
+        assert!(rule_hits(&fetch, &CheckerOptions::default(), "R412").is_empty());
+
+        options.async_keywords = vec!["Drop".to_string(), "abort".to_string()];
+        let cancel = Docstring {
+            attributes: async_fn.clone(),
+            ..docstring("Fetch the page.\n\nThis is cancel safe.")
+        };
+        assert_eq!(rule_hits(&cancel, &options, "R412").len(), 1);
+        let dropping = Docstring {
+            attributes: async_fn,
+            ..docstring("Fetch the page.\n\nDropping the future is fine.")
+        };
+        assert!(rule_hits(&dropping, &options, "R412").is_empty());
+    }
+
+    /// R413: Code spans naming missing generic parameters are flagged when enabled
+    #[test]
+    fn test_r413_generic_references() {
+        let options =
+            CheckerOptions { enabled_rules: vec!["R413".to_string()], ..CheckerOptions::default() };
+        let generics = ItemAttributes {
+            generics: vec!["T".to_string(), "'a".to_string()],
+            ..ItemAttributes::default()
+        };
+
+        let map = Docstring {
+            attributes: generics.clone(),
+            ..docstring("Map `T` to `U` for `'a` and `'b`.")
+        };
+        let messages: Vec<String> =
+            rule_hits(&map, &options, "R413").into_iter().map(|v| v.message).collect();
+        assert_eq!(messages, vec![
+            "Docstring refers to `U`, which isn't a generic parameter of this function",
+            "Docstring refers to `'b`, which isn't a generic parameter of this function",
+        ]);
+        let known = Docstring {
+            attributes: generics.clone(),
+            ..docstring("Return a `Vec` of `'static` values, or `None`.")
+        };
+        assert!(rule_hits(&known, &options, "R413").is_empty());
+        let code = Docstring {
+            target_type: DocstringTarget::Struct,
+            attributes: generics.clone(),
+            ..docstring("Hold a `U`.\n\n```\nlet x: `U`;\n```")
+        };
+        assert_eq!(rule_hits(&code, &options, "R413").len(), 1);
+        let constant = Docstring {
+            target_type: DocstringTarget::Const,
+            attributes: generics,
+            ..docstring("Limit of `N` values.")
+        };
+        assert!(rule_hits(&constant, &options, "R413").is_empty());
+    }
+
+    /// R404: Feature-gated public items should mention their feature
+    #[test]
+    fn test_r404_feature_not_mentioned() {
+        let default = CheckerOptions::default();
+        let mut docstring = Docstring {
+            raw_content: "/// Serialize the value.".to_string(),
+            line: 2,
+            attributes: ItemAttributes {
+                cfg: vec!["feature = \"serde\"".to_string()],
+                features: vec!["serde".to_string()],
+                ..ItemAttributes::default()
+            },
+            ..docstring("Serialize the value.")
+        };
+
+        let hits = rule_hits(&docstring, &default, "R404");
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].message.contains("`serde`"));
+
+        docstring.content = "Serialize the value.\n\nRequires the `serde` feature.".to_string();
+        assert!(rule_hits(&docstring, &default, "R404").is_empty());
+
+        let options = CheckerOptions { require_doc_cfg: true, ..CheckerOptions::default() };
+        assert_eq!(rule_hits(&docstring, &options, "R404").len(), 1);
+
+        docstring.attributes.has_doc_cfg = true;
+        assert!(rule_hits(&docstring, &options, "R404").is_empty());
+    }
+
+    /// R404: Items inside a feature-gated module are left to the module's docs
+    #[test]
+    fn test_r404_nested_in_gated_module() {
+        let source = r#"
+/// Support serialization with the `serde` feature.
+#[cfg(feature = "serde")]
+pub mod serde_support {
+    /// Serialize the value.
+    pub fn to_json() {}
+
+    /// Deserialize the value.
+    #[cfg(feature = "json")]
+    pub fn from_json() {}
+}
+"#;
+        let mut parser = crate::parser::RustParser::new().unwrap();
+        let docstrings = parser.parse_source(source).unwrap();
+        let default = CheckerOptions::default();
+        let hits: Vec<usize> = docstrings
+            .iter()
+            .flat_map(|docstring| rule_hits(docstring, &default, "R404"))
+            .map(|v| v.line)
+            .collect();
+        assert_eq!(hits, vec![8]);
+    }
+
     /// Test a properly formatted docstring.
     #[test]
     fn test_good_docstring() {
@@ -1037,6 +3610,9 @@ fn test_good_docstring() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1054,6 +3630,9 @@ fn test_missing_period() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1071,6 +3650,9 @@ fn test_d401_create_is_imperative() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1089,6 +3671,9 @@ fn test_d401_creates_is_not_imperative() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1117,6 +3702,9 @@ fn test_d401_common_imperatives() {
                 is_multiline: false,
                 is_public: false,
                 target_type: DocstringTarget::Function,
+                attributes: ItemAttributes::default(),
+                line_positions: Vec::new(),
+                item_path: String::new(),
             };
             let violations = Pep257Checker::check_docstring(&docstring);
             assert!(!violations.iter().any(|v| v.rule == "D401"), "Failed for: {content}");
@@ -1150,6 +3738,9 @@ fn test_d402_no_false_positive_markdown_link() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         assert!(!violations.iter().any(|v| v.rule == "D402"));
@@ -1166,6 +3757,9 @@ fn test_d402_true_positive_signature() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         assert!(violations.iter().any(|v| v.rule == "D402"));
@@ -1182,6 +3776,9 @@ fn test_d402_capitalized_signature() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         // Should trigger D402 because it's a signature pattern with ->
@@ -1199,6 +3796,9 @@ fn test_r401_markdown_link_without_backticks() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         assert!(violations.iter().any(|v| v.rule == "R401"));
@@ -1217,6 +3817,9 @@ fn test_r401_markdown_link_with_backticks() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         assert!(!violations.iter().any(|v| v.rule == "R401"));
@@ -1234,6 +3837,9 @@ fn test_r401_markdown_link_plain_text() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         assert!(!violations.iter().any(|v| v.rule == "R401"));
@@ -1250,6 +3856,9 @@ fn test_r401_markdown_link_pascalcase() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         assert!(violations.iter().any(|v| v.rule == "R401"));
@@ -1267,6 +3876,9 @@ fn test_r401_standalone_bracket_reference() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         assert!(violations.iter().any(|v| v.rule == "R401"));
@@ -1287,6 +3899,9 @@ fn test_r401_standalone_backticked_link() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         assert!(!violations.iter().any(|v| v.rule == "R401"));
@@ -1303,6 +3918,9 @@ fn test_r401_reference_style_link_label() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         // Should not trigger on the label part [crate::migrations::Migrations]
@@ -1320,6 +3938,9 @@ fn test_r401_inside_backticks() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         assert!(!violations.iter().any(|v| v.rule == "R401"));
@@ -1336,6 +3957,9 @@ fn test_r402_option_standalone() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         assert!(violations.iter().any(|v| v.rule == "R402"));
@@ -1354,6 +3978,9 @@ fn test_r402_result_with_url() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         assert!(violations.iter().any(|v| v.rule == "R402"));
@@ -1370,6 +3997,9 @@ fn test_r402_option_with_backticks() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         assert!(!violations.iter().any(|v| v.rule == "R402"));
@@ -1386,6 +4016,9 @@ fn test_r402_inline_code() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         assert!(!violations.iter().any(|v| v.rule == "R402"));
@@ -1402,6 +4035,9 @@ fn test_r402_multiple_types() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         let r402_violations: Vec<_> = violations.iter().filter(|v| v.rule == "R402").collect();
@@ -1419,6 +4055,9 @@ fn test_r402_custom_type() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         assert!(!violations.iter().any(|v| v.rule == "R402"));
@@ -1435,6 +4074,9 @@ fn test_r402_inside_backticks() {
             is_multiline: false,
             is_public: false,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
         let violations = Pep257Checker::check_docstring(&docstring);
         assert!(!violations.iter().any(|v| v.rule == "R402"));
@@ -1444,13 +4086,12 @@ fn test_r402_inside_backticks() {
     /// Test Display implementation for Violation with Error severity
     #[test]
     fn test_violation_display_error() {
-        let violation = Violation {
-            rule: "D400".to_string(),
-            message: "First line should end with a period".to_string(),
-            line: 42,
-            column: 5,
-            severity: Severity::Error,
-        };
+        let violation = Violation::new(
+            "D400",
+            "First line should end with a period",
+            SourcePosition { line: 42, column: 5 },
+            Severity::Error,
+        );
 
         let formatted = format!("{violation}");
         assert_eq!(formatted, "42:5 error [D400]: First line should end with a period");
@@ -1459,13 +4100,12 @@ fn test_violation_display_error() {
     /// Test Display implementation for Violation with Warning severity
     #[test]
     fn test_violation_display_warning() {
-        let violation = Violation {
-            rule: "D401".to_string(),
-            message: "First line should be in imperative mood".to_string(),
-            line: 10,
-            column: 1,
-            severity: Severity::Warning,
-        };
+        let violation = Violation::new(
+            "D401",
+            "First line should be in imperative mood",
+            SourcePosition { line: 10, column: 1 },
+            Severity::Warning,
+        );
 
         let formatted = format!("{violation}");
         assert_eq!(formatted, "10:1 warning [D401]: First line should be in imperative mood");
@@ -1474,13 +4114,12 @@ fn test_violation_display_warning() {
     /// Test Display implementation with multi-digit line and column numbers
     #[test]
     fn test_violation_display_large_numbers() {
-        let violation = Violation {
-            rule: "D205".to_string(),
-            message: "1 blank line required between summary line and description".to_string(),
-            line: 1234,
-            column: 567,
-            severity: Severity::Error,
-        };
+        let violation = Violation::new(
+            "D205",
+            "1 blank line required between summary line and description",
+            SourcePosition { line: 1234, column: 567 },
+            Severity::Error,
+        );
 
         let formatted = format!("{violation}");
         assert_eq!(
@@ -1492,14 +4131,13 @@ fn test_violation_display_large_numbers() {
     /// Test Display implementation with special characters in message
     #[test]
     fn test_violation_display_special_chars() {
-        let violation = Violation {
-            rule: "R401".to_string(),
-            message: "Markdown link text looks like code but lacks backticks: ".to_owned()
+        let violation = Violation::new(
+            "R401",
+            "Markdown link text looks like code but lacks backticks: ".to_owned()
                 + "[SqlType::Custom] should be [`SqlType::Custom`]",
-            line: 5,
-            column: 20,
-            severity: Severity::Warning,
-        };
+            SourcePosition { line: 5, column: 20 },
+            Severity::Warning,
+        );
 
         let formatted = format!("{violation}");
         assert!(formatted.starts_with("5:20 warning [R401]:"));
@@ -1511,13 +4149,12 @@ fn test_violation_display_special_chars() {
     #[test]
     fn test_violation_display_message_preservation() {
         let message = "Use inline code for common Rust type: [Option](...) should be `Option`";
-        let violation = Violation {
-            rule: "R402".to_string(),
-            message: message.to_string(),
-            line: 99,
-            column: 8,
-            severity: Severity::Warning,
-        };
+        let violation = Violation::new(
+            "R402",
+            message,
+            SourcePosition { line: 99, column: 8 },
+            Severity::Warning,
+        );
 
         let formatted = format!("{violation}");
         assert_eq!(formatted, format!("99:8 warning [R402]: {message}"));
@@ -1526,13 +4163,12 @@ fn test_violation_display_message_preservation() {
     /// Test Display implementation with line 1, column 1
     #[test]
     fn test_violation_display_start_position() {
-        let violation = Violation {
-            rule: "D103".to_string(),
-            message: "Missing docstring in public function".to_string(),
-            line: 1,
-            column: 1,
-            severity: Severity::Error,
-        };
+        let violation = Violation::new(
+            "D103",
+            "Missing docstring in public function",
+            SourcePosition { line: 1, column: 1 },
+            Severity::Error,
+        );
 
         let formatted = format!("{violation}");
         assert_eq!(formatted, "1:1 error [D103]: Missing docstring in public function");
@@ -1541,13 +4177,12 @@ fn test_violation_display_start_position() {
     /// Test that to_string() works correctly (uses Display)
     #[test]
     fn test_violation_to_string() {
-        let violation = Violation {
-            rule: "D402".to_string(),
-            message: "First line should not be the function's signature".to_string(),
-            line: 7,
-            column: 4,
-            severity: Severity::Error,
-        };
+        let violation = Violation::new(
+            "D402",
+            "First line should not be the function's signature",
+            SourcePosition { line: 7, column: 4 },
+            Severity::Error,
+        );
 
         let as_string = violation.to_string();
         assert_eq!(
@@ -1560,27 +4195,24 @@ fn test_violation_to_string() {
     #[test]
     fn test_violation_display_consistency() {
         let violations = [
-            Violation {
-                rule: "D201".to_string(),
-                message: "No blank lines allowed before function docstring".to_string(),
-                line: 15,
-                column: 1,
-                severity: Severity::Error,
-            },
-            Violation {
-                rule: "D301".to_string(),
-                message: "Consider using raw strings for docstrings with backslashes".to_string(),
-                line: 20,
-                column: 1,
-                severity: Severity::Warning,
-            },
-            Violation {
-                rule: "D403".to_string(),
-                message: "First word of the first line should be properly capitalized".to_string(),
-                line: 25,
-                column: 1,
-                severity: Severity::Error,
-            },
+            Violation::new(
+                "D201",
+                "No blank lines allowed before function docstring",
+                SourcePosition { line: 15, column: 1 },
+                Severity::Error,
+            ),
+            Violation::new(
+                "D301",
+                "Consider using raw strings for docstrings with backslashes",
+                SourcePosition { line: 20, column: 1 },
+                Severity::Warning,
+            ),
+            Violation::new(
+                "D403",
+                "First word of the first line should be properly capitalized",
+                SourcePosition { line: 25, column: 1 },
+                Severity::Error,
+            ),
         ];
 
         // Verify each violation formats correctly and consistently
@@ -1625,6 +4257,9 @@ fn test_d201_function_with_leading_blank() {
             is_multiline: true,
             is_public: true,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1644,6 +4279,9 @@ fn test_d201_struct_with_leading_blank() {
             is_multiline: true,
             is_public: true,
             target_type: DocstringTarget::Struct,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1663,6 +4301,9 @@ fn test_d201_enum_with_leading_blank() {
             is_multiline: true,
             is_public: true,
             target_type: DocstringTarget::Enum,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1682,6 +4323,9 @@ fn test_d201_trait_with_leading_blank() {
             is_multiline: true,
             is_public: true,
             target_type: DocstringTarget::Trait,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1701,6 +4345,9 @@ fn test_d201_no_false_positive() {
             is_multiline: false,
             is_public: true,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1718,6 +4365,9 @@ fn test_d202_function_with_trailing_blank() {
             is_multiline: true,
             is_public: true,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1737,6 +4387,9 @@ fn test_d202_struct_with_trailing_blank() {
             is_multiline: true,
             is_public: true,
             target_type: DocstringTarget::Struct,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1756,6 +4409,9 @@ fn test_d202_enum_with_trailing_blank() {
             is_multiline: true,
             is_public: true,
             target_type: DocstringTarget::Enum,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1775,6 +4431,9 @@ fn test_d202_trait_with_trailing_blank() {
             is_multiline: true,
             is_public: true,
             target_type: DocstringTarget::Trait,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1794,6 +4453,9 @@ fn test_d202_const_with_trailing_blank() {
             is_multiline: true,
             is_public: true,
             target_type: DocstringTarget::Const,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1813,6 +4475,9 @@ fn test_d202_no_false_positive() {
             is_multiline: false,
             is_public: true,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1830,6 +4495,9 @@ fn test_d201_and_d202_both_violations() {
             is_multiline: true,
             is_public: true,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -1837,7 +4505,7 @@ fn test_d201_and_d202_both_violations() {
         assert!(violations.iter().any(|v| v.rule == "D202"));
     }
 
-    /// Summary paragraph wraps across lines — should trigger D400 but not D205
+    /// Summary paragraph wraps across lines — should trigger R204 but not D400 or D205
     #[test]
     fn test_wrapped_summary_no_false_positives() {
         let docstring = Docstring {
@@ -1851,12 +4519,17 @@ fn test_wrapped_summary_no_false_positives() {
             is_multiline: true,
             is_public: true,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
-        // Summary must be single-line, so wrapped summaries should trigger D400
+        // Summary must be single-line, so wrapped summaries should trigger R204, and
+        // D400 checks the period at the end of the wrapped summary instead
         // But it should NOT trigger D205 since there's no description following
-        assert!(violations.iter().any(|v| v.rule == "D400"));
+        assert!(violations.iter().any(|v| v.rule == "R204"));
+        assert!(!violations.iter().any(|v| v.rule == "D400"));
         assert!(!violations.iter().any(|v| v.rule == "D205"));
     }
 
@@ -1874,6 +4547,9 @@ fn test_missing_blank_line_triggers_d205() {
             is_multiline: true,
             is_public: true,
             target_type: DocstringTarget::Function,
+            attributes: ItemAttributes::default(),
+            line_positions: Vec::new(),
+            item_path: String::new(),
         };
 
         let violations = Pep257Checker::check_docstring(&docstring);
@@ -0,0 +1,227 @@
+//! Source position handling for reported violations.
+
+use std::ops::Range;
+
+use clap::ValueEnum;
+
+/// Unit used to count columns in reported positions.
+///
+/// Positions are computed as UTF-8 byte offsets and converted when reported, since
+/// editor protocols such as LSP count columns in UTF-16 code units.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColumnUnit {
+    /// UTF-8 bytes.
+    #[default]
+    #[value(name = "utf-8")]
+    Utf8,
+    /// UTF-16 code units.
+    #[value(name = "utf-16")]
+    Utf16,
+    /// Unicode scalar values.
+    #[value(name = "utf-32")]
+    Utf32,
+}
+
+/// Position in a source file, with a 1-based line and a 1-based UTF-8 byte column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SourcePosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Implementation of source position methods.
+impl SourcePosition {
+    /// Return the position of a slice of a node's text.
+    ///
+    /// `text` must start at `start` in the source, and `slice` must be a subslice of `text`.
+    pub(crate) fn of_slice(text: &str, start: tree_sitter::Point, slice: &str) -> Self {
+        let offset =
+            (slice.as_ptr() as usize).saturating_sub(text.as_ptr() as usize).min(text.len());
+        let before = &text[..offset];
+        match before.rfind('\n') {
+            Some(newline) => Self {
+                line: start.row + 1 + before.matches('\n').count(),
+                column: offset - newline,
+            },
+            None => Self { line: start.row + 1, column: start.column + 1 + offset },
+        }
+    }
+}
+
+/// Index of line start offsets for converting positions within a source file.
+#[derive(Debug)]
+pub(crate) struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+}
+
+/// Implementation of line index methods.
+impl<'a> LineIndex<'a> {
+    /// Build the line index for a source file.
+    pub(crate) fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        Self { source, line_starts }
+    }
+
+    /// Return the text of a 1-based line, without its line ending.
+    pub(crate) fn line_text(&self, line: usize) -> &'a str {
+        let Some(&start) = line.checked_sub(1).and_then(|index| self.line_starts.get(index)) else {
+            return "";
+        };
+        let end = self.line_starts.get(line).map_or(self.source.len(), |&next| next - 1);
+        let text = &self.source[start..end];
+        text.strip_suffix('\r').unwrap_or(text)
+    }
+
+    /// Return the 1-based line containing a byte offset into the source.
+    pub(crate) fn line_at(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset)
+    }
+
+    /// Return the byte offset into the source of a position, clamped to its line.
+    pub(crate) fn offset(&self, position: SourcePosition) -> usize {
+        let Some(&start) =
+            position.line.checked_sub(1).and_then(|index| self.line_starts.get(index))
+        else {
+            return self.source.len();
+        };
+        start + position.column.saturating_sub(1).min(self.line_text(position.line).len())
+    }
+
+    /// Return the byte range of a 1-based line, including its line ending.
+    pub(crate) fn line_range(&self, line: usize) -> Range<usize> {
+        let Some(&start) = line.checked_sub(1).and_then(|index| self.line_starts.get(index)) else {
+            return self.source.len()..self.source.len();
+        };
+        start..self.line_starts.get(line).copied().unwrap_or(self.source.len())
+    }
+
+    /// Convert a 1-based UTF-8 byte column on a 1-based line into the given unit.
+    ///
+    /// Columns past the end of the line keep their excess unchanged, and columns inside
+    /// a multi-byte character are rounded down to the start of that character.
+    pub(crate) fn convert_column(&self, line: usize, column: usize, unit: ColumnUnit) -> usize {
+        if unit == ColumnUnit::Utf8 || column == 0 {
+            return column;
+        }
+
+        let text = self.line_text(line);
+        let offset = column - 1;
+        let mut boundary = offset.min(text.len());
+        while !text.is_char_boundary(boundary) {
+            boundary -= 1;
+        }
+
+        let prefix = &text[..boundary];
+        let converted = match unit {
+            ColumnUnit::Utf8 => prefix.len(),
+            ColumnUnit::Utf16 => prefix.encode_utf16().count(),
+            ColumnUnit::Utf32 => prefix.chars().count(),
+        };
+        converted + offset.saturating_sub(text.len()) + 1
+    }
+}
+
+/// Return the number of characters before a 1-based column of a line, counted in the given unit.
+///
+/// This is the indentation of a caret pointing at the column in a terminal. Columns
+/// past the end of the line count their excess as characters.
+#[must_use]
+pub fn chars_before(line: &str, column: usize, unit: ColumnUnit) -> usize {
+    let mut remaining = column.saturating_sub(1);
+    let mut chars = 0;
+    for c in line.chars() {
+        let width = match unit {
+            ColumnUnit::Utf8 => c.len_utf8(),
+            ColumnUnit::Utf16 => c.len_utf16(),
+            ColumnUnit::Utf32 => 1,
+        };
+        if remaining < width {
+            return chars;
+        }
+        remaining -= width;
+        chars += 1;
+    }
+    chars + remaining
+}
+
+/// Unit tests for position handling.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test retrieving line text, including CRLF line endings.
+    #[test]
+    fn test_line_text() {
+        let index = LineIndex::new("first\r\nsecond\nthird");
+        assert_eq!(index.line_text(1), "first");
+        assert_eq!(index.line_text(2), "second");
+        assert_eq!(index.line_text(3), "third");
+        assert_eq!(index.line_text(4), "");
+        assert_eq!(index.line_text(0), "");
+    }
+
+    /// Test converting positions and lines into byte ranges of the source.
+    #[test]
+    fn test_offsets() {
+        let index = LineIndex::new("first\r\nsecond\nthird");
+        assert_eq!(index.offset(SourcePosition { line: 2, column: 3 }), 9);
+        assert_eq!(index.offset(SourcePosition { line: 1, column: 20 }), 5);
+        assert_eq!(index.offset(SourcePosition { line: 4, column: 1 }), 19);
+        assert_eq!(index.line_range(1), 0..7);
+        assert_eq!(index.line_range(3), 14..19);
+        assert_eq!(index.line_range(4), 19..19);
+    }
+
+    /// Test locating slices of multi-line node text, including CRLF line endings.
+    #[test]
+    fn test_source_position_of_slice() {
+        let start = tree_sitter::Point { row: 4, column: 8 };
+        let text = "/** First\r\n  * é second\r\n */";
+        let mut lines = text.lines();
+
+        let first = lines.next().unwrap().strip_prefix("/**").unwrap().trim_start();
+        assert_eq!(SourcePosition::of_slice(text, start, first), SourcePosition {
+            line: 5,
+            column: 13
+        });
+
+        let second = lines.next().unwrap().trim_start().trim_start_matches('*').trim_start();
+        assert_eq!(SourcePosition::of_slice(text, start, second), SourcePosition {
+            line: 6,
+            column: 5
+        });
+    }
+
+    /// Test converting byte columns into each column unit.
+    #[test]
+    fn test_convert_column() {
+        // "é" is 2 bytes and 1 UTF-16 unit, "😀" is 4 bytes and 2 UTF-16 units
+        let index = LineIndex::new("ab\né😀x");
+        assert_eq!(index.convert_column(2, 7, ColumnUnit::Utf8), 7);
+        assert_eq!(index.convert_column(2, 7, ColumnUnit::Utf16), 4);
+        assert_eq!(index.convert_column(2, 7, ColumnUnit::Utf32), 3);
+        assert_eq!(index.convert_column(1, 2, ColumnUnit::Utf16), 2);
+
+        // Inside a multi-byte character and past the end of the line
+        assert_eq!(index.convert_column(2, 4, ColumnUnit::Utf32), 2);
+        assert_eq!(index.convert_column(2, 10, ColumnUnit::Utf16), 7);
+    }
+
+    /// Test counting the characters before a column in each column unit.
+    #[test]
+    fn test_chars_before() {
+        // "é" is 2 bytes and 1 UTF-16 unit, "😀" is 4 bytes and 2 UTF-16 units
+        let line = "é😀x";
+        assert_eq!(chars_before(line, 7, ColumnUnit::Utf8), 2);
+        assert_eq!(chars_before(line, 4, ColumnUnit::Utf16), 2);
+        assert_eq!(chars_before(line, 3, ColumnUnit::Utf32), 2);
+        assert_eq!(chars_before(line, 1, ColumnUnit::Utf8), 0);
+
+        // Inside a multi-byte character and past the end of the line
+        assert_eq!(chars_before(line, 4, ColumnUnit::Utf8), 1);
+        assert_eq!(chars_before(line, 10, ColumnUnit::Utf8), 5);
+    }
+}
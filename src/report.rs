@@ -0,0 +1,503 @@
+//! Reports of check runs.
+//!
+//! A report collects the reported violations and coverage of each checked file, the
+//! files that couldn't be checked and the version of the checker. CI runs that split
+//! a project into shards with `check --shard` write one report per shard with
+//! `check --report`, and `pep257 merge` combines them into the report of the whole
+//! project.
+//!
+//! The report is stored as JSON, with statistics for other tools to read:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "tool": { "name": "pep257", "version": "0.2.0" },
+//!   "files": [
+//!     {
+//!       "path": "src/lib.rs",
+//!       "edition": "2024",
+//!       "coverage": [3, 4],
+//!       "violations": [
+//!         { "rule": "D400", "message": "...", "line": 1, "column": 1, "severity": "error" }
+//!       ]
+//!     }
+//!   ],
+//!   "errors": [{ "path": "src/broken.rs", "message": "..." }],
+//!   "statistics": {
+//!     "files": 2,
+//!     "violations": 1,
+//!     "errors": 1,
+//!     "rules": { "D400": 1 },
+//!     "coverage": [3, 4]
+//!   }
+//! }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Component, Path},
+    str::FromStr,
+};
+
+use serde_json::{Value, json};
+
+use crate::{
+    analyzer::Coverage,
+    cache::{hash, violation_from_json, violation_to_json},
+    context::Edition,
+    pep257::{Severity, Violation},
+};
+
+/// Version of the report format written by this crate.
+const REPORT_VERSION: u64 = 1;
+
+/// Errors that can occur when reading, writing or merging reports.
+#[derive(thiserror::Error, Debug)]
+pub enum ReportError {
+    /// The report file couldn't be read or written.
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The report file isn't valid JSON.
+    #[error("Invalid report JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The report JSON doesn't have the expected structure.
+    #[error("Invalid report: {0}")]
+    Format(String),
+    /// The reports were written by different versions of the checker.
+    #[error("Can't merge reports of {0} and {1}")]
+    ToolMismatch(String, String),
+}
+
+/// Name and version of the checker that wrote a report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolInfo {
+    /// Name of the checker.
+    pub name: String,
+    /// Version of the checker.
+    pub version: String,
+}
+
+/// Describe the running checker by default.
+impl Default for ToolInfo {
+    /// Return the name and version of this crate.
+    fn default() -> Self {
+        Self {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// Results of checking a file.
+#[derive(Debug, Clone, Default)]
+pub struct FileResult {
+    /// Path of the file, as printed.
+    pub path: String,
+    /// Edition of the file's crate, if it was found.
+    pub edition: Option<Edition>,
+    /// Documentation coverage of the file's public items.
+    pub coverage: Coverage,
+    /// Reported violations.
+    pub violations: Vec<Violation>,
+}
+
+/// A file that couldn't be checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileError {
+    /// Path of the file, as printed.
+    pub path: String,
+    /// Why the file couldn't be checked.
+    pub message: String,
+}
+
+/// Totals of a report.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReportStatistics {
+    /// Number of checked files.
+    pub files: usize,
+    /// Number of reported violations.
+    pub violations: usize,
+    /// Number of reported violations with warning severity.
+    pub warnings: usize,
+    /// Number of reported violations with info severity.
+    pub infos: usize,
+    /// Number of files that couldn't be checked.
+    pub errors: usize,
+    /// Number of reported violations per rule.
+    pub rules: BTreeMap<String, usize>,
+    /// Documentation coverage of all checked files.
+    pub coverage: Coverage,
+}
+
+/// Severities of the violations that fail a check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailPolicy {
+    /// Severities that fail a check; errors and warnings by default.
+    pub fail_on: Vec<Severity>,
+}
+
+/// Default fail policy.
+impl Default for FailPolicy {
+    /// Fail on errors and warnings, but not on info.
+    fn default() -> Self {
+        Self { fail_on: vec![Severity::Error, Severity::Warning] }
+    }
+}
+
+/// Implementation of fail policy methods.
+impl FailPolicy {
+    /// Return the most severe severity of the reported violations that fails the check.
+    ///
+    /// Returns `None` if the check passes.
+    #[must_use]
+    pub fn failure(&self, statistics: &ReportStatistics) -> Option<Severity> {
+        let errors = statistics.violations - statistics.warnings - statistics.infos;
+        [
+            (Severity::Error, errors),
+            (Severity::Warning, statistics.warnings),
+            (Severity::Info, statistics.infos),
+        ]
+        .into_iter()
+        .find(|&(severity, count)| count > 0 && self.fail_on.contains(&severity))
+        .map(|(severity, _)| severity)
+    }
+}
+
+/// Results of a check run.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// Checker that wrote the report.
+    pub tool: ToolInfo,
+    /// Results of the checked files.
+    pub files: Vec<FileResult>,
+    /// Files that couldn't be checked.
+    pub errors: Vec<FileError>,
+}
+
+/// Implementation of report methods.
+impl Report {
+    /// Merge another report into this one, such as that of another CI shard.
+    ///
+    /// Files are kept sorted by path. A file in both reports, as when a shard is
+    /// retried, keeps the results of the other report.
+    pub fn merge(&mut self, other: Self) -> Result<(), ReportError> {
+        if self.tool != other.tool {
+            let describe = |tool: &ToolInfo| format!("{} {}", tool.name, tool.version);
+            return Err(ReportError::ToolMismatch(describe(&self.tool), describe(&other.tool)));
+        }
+
+        let mut files: BTreeMap<String, FileResult> =
+            self.files.drain(..).map(|file| (file.path.clone(), file)).collect();
+        let mut errors: BTreeMap<String, FileError> =
+            self.errors.drain(..).map(|error| (error.path.clone(), error)).collect();
+        for file in other.files {
+            errors.remove(&file.path);
+            files.insert(file.path.clone(), file);
+        }
+        for error in other.errors {
+            files.remove(&error.path);
+            errors.insert(error.path.clone(), error);
+        }
+        self.files = files.into_values().collect();
+        self.errors = errors.into_values().collect();
+        Ok(())
+    }
+
+    /// Return the totals of the report.
+    #[must_use]
+    pub fn statistics(&self) -> ReportStatistics {
+        let mut statistics = ReportStatistics {
+            files: self.files.len(),
+            errors: self.errors.len(),
+            ..ReportStatistics::default()
+        };
+        for file in &self.files {
+            statistics.violations += file.violations.len();
+            statistics.coverage.add(file.coverage);
+            for violation in &file.violations {
+                statistics.warnings += usize::from(violation.severity == Severity::Warning);
+                statistics.infos += usize::from(violation.severity == Severity::Info);
+                *statistics.rules.entry(violation.rule.clone()).or_default() += 1;
+            }
+        }
+        statistics
+    }
+
+    /// Read a report from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReportError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Write the report to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ReportError> {
+        fs::write(path, self.to_json()? + "\n")?;
+        Ok(())
+    }
+
+    /// Parse a report from its JSON representation.
+    pub fn from_json(json: &str) -> Result<Self, ReportError> {
+        let value: Value = serde_json::from_str(json)?;
+        let version = value.get("version").and_then(Value::as_u64);
+        if version != Some(REPORT_VERSION) {
+            return Err(ReportError::Format(format!(
+                "unsupported version {}",
+                value.get("version").unwrap_or(&Value::Null)
+            )));
+        }
+        let invalid = |what: &str, entry: &Value| {
+            ReportError::Format(format!("{what} without valid fields: {entry}"))
+        };
+        let string =
+            |entry: &Value, name: &str| entry.get(name).and_then(Value::as_str).map(str::to_string);
+        let array = |name: &str| {
+            value
+                .get(name)
+                .and_then(Value::as_array)
+                .ok_or_else(|| ReportError::Format(format!("missing `{name}` array")))
+        };
+
+        let tool = value.get("tool").unwrap_or(&Value::Null);
+        let tool = ToolInfo {
+            name: string(tool, "name").ok_or_else(|| invalid("tool", tool))?,
+            version: string(tool, "version").ok_or_else(|| invalid("tool", tool))?,
+        };
+
+        let mut files = Vec::new();
+        for entry in array("files")? {
+            let file = (|| {
+                let coverage = entry.get("coverage")?.as_array()?;
+                let count = |index: usize| usize::try_from(coverage.get(index)?.as_u64()?).ok();
+                Some(FileResult {
+                    path: string(entry, "path")?,
+                    edition: match string(entry, "edition") {
+                        Some(edition) => Some(edition.parse().ok()?),
+                        None => None,
+                    },
+                    coverage: Coverage { documented: count(0)?, total: count(1)? },
+                    violations: entry
+                        .get("violations")?
+                        .as_array()?
+                        .iter()
+                        .map(violation_from_json)
+                        .collect::<Option<_>>()?,
+                })
+            })();
+            files.push(file.ok_or_else(|| invalid("file entry", entry))?);
+        }
+
+        let mut errors = Vec::new();
+        for entry in array("errors")? {
+            errors.push(FileError {
+                path: string(entry, "path").ok_or_else(|| invalid("error entry", entry))?,
+                message: string(entry, "message").ok_or_else(|| invalid("error entry", entry))?,
+            });
+        }
+
+        Ok(Self { tool, files, errors })
+    }
+
+    /// Serialize the report as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, ReportError> {
+        let statistics = self.statistics();
+        let json = json!({
+            "version": REPORT_VERSION,
+            "tool": { "name": self.tool.name, "version": self.tool.version },
+            "files": self.files.iter().map(|file| json!({
+                "path": file.path,
+                "edition": file.edition.map(|edition| edition.to_string()),
+                "coverage": [file.coverage.documented, file.coverage.total],
+                "violations": file.violations.iter().map(violation_to_json).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+            "errors": self.errors.iter().map(|error| json!({
+                "path": error.path,
+                "message": error.message,
+            })).collect::<Vec<_>>(),
+            "statistics": {
+                "files": statistics.files,
+                "violations": statistics.violations,
+                "errors": statistics.errors,
+                "rules": statistics.rules,
+                "coverage": [statistics.coverage.documented, statistics.coverage.total],
+            },
+        });
+        Ok(serde_json::to_string_pretty(&json)?)
+    }
+}
+
+/// One of several parts that the files of a project are split into for CI jobs.
+///
+/// A file belongs to the shard picked by the hash of its path, so every job agrees on
+/// the split without coordinating, as long as they collect files from the same path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    /// 1-based index of the shard.
+    pub index: u64,
+    /// Number of shards.
+    pub count: u64,
+}
+
+/// Implementation of shard methods.
+impl Shard {
+    /// Return whether a file belongs to this shard.
+    #[must_use]
+    pub fn contains(&self, path: &Path) -> bool {
+        // Hash `/`-separated components, so jobs on different platforms agree
+        let components: Vec<_> = path
+            .components()
+            .filter(|component| *component != Component::CurDir)
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect();
+        hash(&[components.join("/").as_bytes()]) % self.count == self.index - 1
+    }
+}
+
+/// Parse a shard written as `INDEX/COUNT`, such as `2/8`.
+impl FromStr for Shard {
+    type Err = String;
+
+    /// Parse the index and count, checking that the index is within the count.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (index, count) = value
+            .split_once('/')
+            .ok_or_else(|| format!("invalid shard `{value}`, expected `INDEX/COUNT`"))?;
+        let parse = |number: &str| {
+            number.trim().parse::<u64>().map_err(|e| format!("invalid shard `{value}`: {e}"))
+        };
+        let (index, count) = (parse(index)?, parse(count)?);
+        if index == 0 || index > count {
+            return Err(format!("invalid shard `{value}`, the index must be from 1 to {count}"));
+        }
+        Ok(Self { index, count })
+    }
+}
+
+/// Unit tests for reports.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::SourcePosition;
+
+    /// Return the result of a file with violations of the given rules.
+    fn file(path: &str, rules: &[&str]) -> FileResult {
+        FileResult {
+            path: path.to_string(),
+            edition: Some(Edition::E2021),
+            coverage: Coverage { documented: 1, total: 2 },
+            violations: rules
+                .iter()
+                .map(|rule| {
+                    Violation::new(
+                        rule,
+                        format!("{rule} message"),
+                        SourcePosition { line: 1, column: 1 },
+                        Severity::Error,
+                    )
+                    .with_item_path("crate")
+                })
+                .collect(),
+        }
+    }
+
+    /// Test that a report survives a save and load.
+    #[test]
+    fn test_round_trip() {
+        let report = Report {
+            files: vec![file("src/lib.rs", &["D400", "D401"])],
+            errors: vec![FileError {
+                path: "src/broken.rs".to_string(),
+                message: "IO error".to_string(),
+            }],
+            ..Report::default()
+        };
+
+        let loaded = Report::from_json(&report.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.tool, ToolInfo::default());
+        assert_eq!(loaded.files.len(), 1);
+        assert_eq!(loaded.files[0].edition, Some(Edition::E2021));
+        assert_eq!(loaded.files[0].violations[1].rule, "D401");
+        assert_eq!(loaded.errors, report.errors);
+        assert_eq!(loaded.statistics(), report.statistics());
+
+        assert!(Report::from_json("{\"version\": 2}").is_err());
+        assert!(Report::from_json("not json").is_err());
+    }
+
+    /// Test merging the reports of shards.
+    #[test]
+    fn test_merge() {
+        let mut report = Report {
+            files: vec![file("src/b.rs", &["D400"]), file("src/c.rs", &["D400"])],
+            ..Report::default()
+        };
+        let shard = Report {
+            files: vec![file("src/a.rs", &["D401"]), file("src/c.rs", &[])],
+            errors: vec![FileError { path: "src/d.rs".to_string(), message: "error".to_string() }],
+            ..Report::default()
+        };
+        report.merge(shard).unwrap();
+
+        let paths: Vec<_> = report.files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/a.rs", "src/b.rs", "src/c.rs"]);
+        let statistics = report.statistics();
+        assert_eq!((statistics.files, statistics.violations, statistics.errors), (3, 2, 1));
+        assert_eq!(statistics.warnings, 0);
+        assert_eq!(
+            statistics.rules,
+            BTreeMap::from([("D400".to_string(), 1), ("D401".to_string(), 1)])
+        );
+        assert_eq!(statistics.coverage, Coverage { documented: 3, total: 6 });
+
+        let other = Report {
+            tool: ToolInfo { version: "0.0.1".to_string(), ..ToolInfo::default() },
+            ..Report::default()
+        };
+        assert!(matches!(report.merge(other), Err(ReportError::ToolMismatch(..))));
+    }
+
+    /// Test which severities fail a check.
+    #[test]
+    fn test_fail_policy() {
+        let statistics = |errors, warnings, infos| ReportStatistics {
+            violations: errors + warnings + infos,
+            warnings,
+            infos,
+            ..ReportStatistics::default()
+        };
+        let policy = FailPolicy::default();
+        assert_eq!(policy.failure(&statistics(1, 1, 1)), Some(Severity::Error));
+        assert_eq!(policy.failure(&statistics(0, 1, 1)), Some(Severity::Warning));
+        assert_eq!(policy.failure(&statistics(0, 0, 1)), None);
+
+        let errors_only = FailPolicy { fail_on: vec![Severity::Error] };
+        assert_eq!(errors_only.failure(&statistics(0, 1, 0)), None);
+        assert_eq!(FailPolicy { fail_on: Vec::new() }.failure(&statistics(1, 0, 0)), None);
+        let everything = FailPolicy { fail_on: Severity::ALL.to_vec() };
+        assert_eq!(everything.failure(&statistics(0, 0, 1)), Some(Severity::Info));
+    }
+
+    /// Test parsing shards and splitting files between them.
+    #[test]
+    fn test_shard() {
+        assert_eq!("2/8".parse(), Ok(Shard { index: 2, count: 8 }));
+        assert!("0/8".parse::<Shard>().is_err());
+        assert!("9/8".parse::<Shard>().is_err());
+        assert!("2".parse::<Shard>().is_err());
+        assert!("a/8".parse::<Shard>().is_err());
+
+        let shards: Vec<Shard> = (1..=4).map(|index| Shard { index, count: 4 }).collect();
+        let paths: Vec<_> = (0..100).map(|index| format!("src/module_{index}.rs")).collect();
+        let mut sizes = [0; 4];
+        for path in &paths {
+            let owners: Vec<_> =
+                shards.iter().filter(|shard| shard.contains(Path::new(path))).collect();
+            assert_eq!(owners.len(), 1, "{path} must belong to exactly one shard");
+            sizes[usize::try_from(owners[0].index).unwrap() - 1] += 1;
+            assert!(
+                owners[0].contains(Path::new(&format!("./{path}"))),
+                "a leading `./` must not change the shard"
+            );
+        }
+        assert!(sizes.iter().all(|&size| size > 10), "unbalanced shards: {sizes:?}");
+    }
+}
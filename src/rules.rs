@@ -0,0 +1,529 @@
+//! Selection of the rules to report.
+//!
+//! Every rule is listed in the [`RULES`] registry with its categories, so rules can
+//! be selected by code prefix, such as `D4`, or by category, such as
+//! `category:markdown`.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use globset::{GlobBuilder, GlobMatcher};
+
+/// Prefix of a selection entry naming a category instead of a rule code prefix.
+const CATEGORY_PREFIX: &str = "category:";
+
+/// Category of rules, for coarse-grained selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    /// Public items without docs, and the lints that enforce them.
+    MissingDocs,
+    /// Layout of the docs: blank lines, indentation and comment placement.
+    Formatting,
+    /// Wording of the summary: punctuation, capitalization and mood.
+    Grammar,
+    /// Markdown usage: links, code spans, URLs and lists.
+    Markdown,
+    /// Sections that some docs should have, such as `# Cancellation safety`.
+    RustdocSections,
+    /// Rustdoc features and facts about the item, such as intra-doc links, `doc(cfg)`
+    /// attributes and generic parameters.
+    Rustdoc,
+    /// Comments justifying unsafe code, such as `// SAFETY:`.
+    Safety,
+    /// Problems with the source file that keep it from being fully checked, such as
+    /// syntax errors and invalid UTF-8.
+    Source,
+}
+
+/// Implementation of category methods.
+impl Category {
+    /// Every category.
+    pub const ALL: [Self; 8] = [
+        Self::MissingDocs,
+        Self::Formatting,
+        Self::Grammar,
+        Self::Markdown,
+        Self::RustdocSections,
+        Self::Rustdoc,
+        Self::Safety,
+        Self::Source,
+    ];
+
+    /// Return the name of the category, as used in `category:NAME` selections.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::MissingDocs => "missing-docs",
+            Self::Formatting => "formatting",
+            Self::Grammar => "grammar",
+            Self::Markdown => "markdown",
+            Self::RustdocSections => "rustdoc-sections",
+            Self::Rustdoc => "rustdoc",
+            Self::Safety => "safety",
+            Self::Source => "source",
+        }
+    }
+
+    /// Look up a category by its name, accepting `_` in place of `-`.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase().replace('_', "-");
+        Self::ALL.into_iter().find(|category| category.name() == name)
+    }
+}
+
+/// Format a category as its name.
+impl fmt::Display for Category {
+    /// Format the category's name.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Registry entry describing a rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    /// Code of the rule, such as `D400`.
+    pub code: &'static str,
+    /// Short description of what the rule checks.
+    pub summary: &'static str,
+    /// Categories the rule belongs to.
+    pub categories: &'static [Category],
+    /// Source code violating the rule, showing what it reports and how it's fixed.
+    pub example: Option<&'static str>,
+    /// Version of the rule set that added the rule.
+    pub since: u32,
+}
+
+/// Implementation of rule methods.
+impl Rule {
+    /// Return the rule with an example of source code violating it.
+    const fn with_example(self, example: &'static str) -> Self {
+        Self { example: Some(example), ..self }
+    }
+
+    /// Return the rule as added in a version of the rule set after the first.
+    const fn since(self, since: u32) -> Self {
+        Self { since, ..self }
+    }
+}
+
+/// Shorthand for the registry entries.
+const fn rule(code: &'static str, summary: &'static str, categories: &'static [Category]) -> Rule {
+    Rule { code, summary, categories, example: None, since: 1 }
+}
+
+/// Version of the rule set, bumped whenever a release adds rules.
+///
+/// Projects pin a version with `rules-version` so that upgrading doesn't make CI
+/// fail on rules added since; their violations are reported with info severity.
+pub const RULES_VERSION: u32 = 2;
+
+/// Every rule the checker reports.
+pub const RULES: &[Rule] = &[
+    rule("R000", "File should not contain syntax errors", &[Category::Source]).since(2),
+    rule("R001", "File should be valid UTF-8", &[Category::Source]).since(2),
+    rule("D100", "Missing docstring in public module", &[Category::MissingDocs]),
+    rule("D101", "Missing docstring in public class", &[Category::MissingDocs]),
+    rule("D102", "Missing docstring in public method", &[Category::MissingDocs]),
+    rule("D103", "Missing docstring in public function", &[Category::MissingDocs]),
+    rule("D104", "Missing docstring in public package", &[Category::MissingDocs]),
+    rule("D106", "Missing docstring in public nested class", &[Category::MissingDocs]).since(2),
+    rule("R101", "Missing docstring in public type alias", &[Category::MissingDocs]),
+    rule("R102", "Missing docstring in public const/static", &[Category::MissingDocs]),
+    rule("R103", "Missing docstring in public macro", &[Category::MissingDocs]),
+    rule("R104", "Crate root should enable the `missing_docs` lint", &[Category::MissingDocs])
+        .since(2),
+    rule("R105", "Missing docstring in public re-export", &[Category::MissingDocs]).since(2),
+    rule("R106", "Missing docstring in public field", &[Category::MissingDocs]).since(2),
+    rule("R107", "Missing docstring in public trait member", &[Category::MissingDocs]).since(2),
+    rule("R108", "Fields and variants should not be documented by trailing comments", &[
+        Category::MissingDocs,
+        Category::Formatting,
+    ])
+    .with_example("/// A parsed input.\npub struct Input {\n    pub text: String, // The text to parse.\n}\n")
+    .since(2),
+    rule("D201", "No blank lines before docstring", &[Category::Formatting])
+        .with_example("///\n/// Parse the input.\npub fn parse() {}\n"),
+    rule("D202", "No blank lines after docstring", &[Category::Formatting])
+        .with_example("/// Parse the input.\n///\npub fn parse() {}\n"),
+    rule("D205", "Blank line between summary and description", &[Category::Formatting]),
+    rule("D210", "No trailing whitespace in docstring lines", &[Category::Formatting])
+        .with_example("/// Parse the input. \npub fn parse() {}\n")
+        .since(2),
+    rule("D212", "Block comment summary should start on the first line", &[Category::Formatting])
+        .with_example(
+            "/**\n * Parse the input.\n *\n * Stop at the first error.\n */\npub fn parse() {}\n",
+        )
+        .since(2),
+    rule("D213", "Block comment summary should start on the second line", &[Category::Formatting])
+        .with_example(
+            "/** Parse the input.\n *\n * Stop at the first error.\n */\npub fn parse() {}\n",
+        )
+        .since(2),
+    rule("D301", "Consider raw strings for backslashes", &[Category::Formatting]),
+    rule("R201", "First docstring line should not be indented", &[Category::Formatting])
+        .with_example("///  Parse the input.\npub fn parse() {}\n")
+        .since(2),
+    rule("R202", "At most one blank line between summary and description", &[Category::Formatting])
+        .with_example(
+            "/// Parse the input.\n///\n///\n/// Stop at the first error.\npub fn parse() {}\n",
+        )
+        .since(2),
+    rule("R203", "Doc comments should document an item", &[Category::Formatting])
+        .with_example("/// Parse the input.\npub fn parse() {}\n\n/// Print the output.\n")
+        .since(2),
+    rule("R204", "Summary should fit on one line", &[Category::Formatting])
+        .with_example("/// Parse the input and return\n/// the items.\npub fn parse() {}\n")
+        .since(2),
+    rule("D400", "First line should end with period", &[Category::Grammar])
+        .with_example("/// Parse the input\npub fn parse() {}\n"),
+    rule("D401", "First line should be imperative mood", &[Category::Grammar])
+        .with_example("/// Returns the parsed input.\npub fn parse() {}\n"),
+    rule("D402", "First line should not be signature", &[Category::Grammar]),
+    rule("D403", "First word should be capitalized", &[Category::Grammar])
+        .with_example("/// parse the input.\npub fn parse() {}\n"),
+    rule("R409", "Function docs should not start with an article", &[Category::Grammar]).since(2),
+    rule("R410", "Type docs should be noun phrases", &[Category::Grammar]).since(2),
+    rule("R411", "Crate docs should not start with the crate name", &[Category::Grammar]).since(2),
+    rule("R401", "Markdown links with code need backticks", &[Category::Markdown]),
+    rule("R402", "Common types should use inline code", &[Category::Markdown]).with_example(
+        "/// Parse the input into a [Vec].\npub fn parse() -> Vec<u8> {\n    Vec::new()\n}\n",
+    ),
+    rule("R403", "Paths in prose should be intra-doc links", &[
+        Category::Markdown,
+        Category::Rustdoc,
+    ])
+    .since(2),
+    rule("R405", "Code spans and code blocks should not be empty", &[Category::Markdown])
+        .with_example("/// Parse the input ``.\npub fn parse() {}\n")
+        .since(2),
+    rule("R406", "URLs should be autolinks or Markdown links", &[Category::Markdown])
+        .with_example("/// Parse the input, as described in https://example.com.\npub fn parse() {}\n")
+        .since(2),
+    rule("R408", "Bullet lists should use one marker", &[Category::Markdown])
+        .with_example("/// Parse the input.\n///\n/// - Skips blanks.\n/// * Keeps comments.\npub fn parse() {}\n")
+        .since(2),
+    rule("R404", "Feature-gated items should mention their feature", &[Category::Rustdoc]).since(2),
+    rule("R413", "Code spans should not name missing generic parameters", &[Category::Rustdoc])
+        .since(2),
+    rule("R414", "Default trait method docs should mention the default", &[Category::Rustdoc])
+        .since(2),
+    rule("R407", "Long docstrings should use section headings", &[Category::RustdocSections])
+        .since(2),
+    rule("R412", "Public async function docs should document cancellation", &[
+        Category::RustdocSections,
+    ])
+    .since(2),
+    rule("R415", "Functions returning `Result` need an `# Errors` section", &[
+        Category::RustdocSections,
+    ])
+    .with_example("/// Parse the input.\npub fn parse(input: &str) -> Result<u8, Error> {\n    todo!()\n}\n")
+    .since(2),
+    rule("R416", "Functions that can panic need a `# Panics` section", &[
+        Category::RustdocSections,
+    ])
+    .with_example("/// Return the first byte.\npub fn first(bytes: &[u8]) -> u8 {\n    *bytes.first().unwrap()\n}\n")
+    .since(2),
+    rule("R417", "Public items should include a code example", &[Category::RustdocSections])
+        .with_example("/// Return the sum of two numbers.\npub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n")
+        .since(2),
+    rule("R501", "Crate docs should start with a summary sentence", &[Category::Grammar]).since(2),
+    rule("R502", "Crate docs should include a usage example", &[Category::RustdocSections])
+        .since(2),
+    rule("R503", "Crate docs should have a minimum length", &[Category::MissingDocs]).since(2),
+    rule("R601", "Unsafe blocks in public functions need a `// SAFETY:` comment", &[
+        Category::Safety,
+    ])
+    .with_example("/// Return the first byte.\npub fn first(bytes: &[u8]) -> u8 {\n    unsafe { *bytes.as_ptr() }\n}\n")
+    .since(2),
+    rule("R602", "Unsafe functions need a `# Safety` section", &[
+        Category::Safety,
+        Category::RustdocSections,
+    ])
+    .with_example("/// Read the byte at `ptr`.\npub unsafe fn read(ptr: *const u8) -> u8 {\n    *ptr\n}\n")
+    .since(2),
+];
+
+/// Documentation of the rules, with a section per rule or group of related rules.
+const RULE_DOCS: &str = include_str!("../CHECKS.md");
+
+/// Return the URL of the section of the rule documentation describing a rule.
+///
+/// Sections are linked by the anchors GitHub derives from their headings, such as
+/// `#d400-first-line-should-end-with-a-period`. Rules without a section of their own
+/// link to the summary table.
+#[must_use]
+pub fn docs_url(code: &str) -> String {
+    let heading = RULE_DOCS.lines().filter_map(|line| line.strip_prefix("### ")).find(|heading| {
+        heading.split_once(':').is_some_and(|(codes, _)| codes.split(", ").any(|c| c == code))
+    });
+    let anchor = heading.map_or_else(
+        || "summary-table".to_string(),
+        |heading| {
+            heading
+                .to_lowercase()
+                .chars()
+                .filter(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+                .map(|c| if c == ' ' { '-' } else { c })
+                .collect()
+        },
+    );
+    format!("{}/blob/main/CHECKS.md#{anchor}", env!("CARGO_PKG_REPOSITORY"))
+}
+
+/// Look up a rule in the registry by its code.
+#[must_use]
+pub fn find_rule(code: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|rule| rule.code == code)
+}
+
+/// Selection of the rules whose violations are reported, by rule code prefix or `category:NAME`.
+///
+/// A rule matching both lists is decided by the longest matching prefix, with ties
+/// going to `ignore`, so `--ignore D4 --select D401` reports D401 but no other D4xx
+/// rule. Categories are less specific than any prefix.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleSelection {
+    /// Prefixes of the rules to report, such as `D4`, `R401` or `category:markdown`.
+    ///
+    /// Every rule is reported when empty.
+    pub select: Vec<String>,
+    /// Prefixes of the rules not to report.
+    pub ignore: Vec<String>,
+}
+
+/// Implementation of rule selection methods.
+impl RuleSelection {
+    /// Check whether violations of a rule are reported.
+    #[must_use]
+    pub fn allows(&self, rule: &str) -> bool {
+        let selected =
+            if self.select.is_empty() { Some(0) } else { Self::longest_match(&self.select, rule) };
+        let ignored = Self::longest_match(&self.ignore, rule);
+
+        match (selected, ignored) {
+            (Some(selected), Some(ignored)) => selected > ignored,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// Check whether a rule is selected by its full code rather than a prefix.
+    ///
+    /// Selecting an opt-in rule this way enables it.
+    #[must_use]
+    pub fn selects_exactly(&self, rule: &str) -> bool {
+        self.select.iter().any(|prefix| prefix == rule)
+    }
+
+    /// Return the length of the longest prefix matching a rule, `0` for a category.
+    fn longest_match(prefixes: &[String], rule: &str) -> Option<usize> {
+        prefixes
+            .iter()
+            .filter_map(|prefix| match prefix.strip_prefix(CATEGORY_PREFIX) {
+                Some(category) => {
+                    let category = Category::from_name(category)?;
+                    find_rule(rule)?.categories.contains(&category).then_some(0)
+                }
+                None => rule.starts_with(prefix.as_str()).then_some(prefix.len()),
+            })
+            .max()
+    }
+}
+
+/// Rules not to report in the files matching glob patterns, relative to a root.
+///
+/// A pattern without a `/`, such as `build.rs`, matches file names in any
+/// directory. Rules are given by code prefix or `category:NAME`, as for
+/// [`RuleSelection`].
+#[derive(Debug, Clone, Default)]
+pub struct PerFileIgnores {
+    /// Directory the patterns are relative to.
+    root: PathBuf,
+    /// Matchers of the patterns, with the rules to ignore in matching files.
+    entries: Vec<(GlobMatcher, RuleSelection)>,
+}
+
+/// Implementation of per-file ignore methods.
+impl PerFileIgnores {
+    /// Create per-file ignores from patterns relative to `root` and their rules.
+    pub fn new(root: &Path, patterns: &[(String, Vec<String>)]) -> Result<Self, globset::Error> {
+        let root = if root.as_os_str().is_empty() { Path::new(".") } else { root };
+        let entries = patterns
+            .iter()
+            .map(|(pattern, rules)| {
+                let matcher = GlobBuilder::new(pattern.trim_start_matches("./"))
+                    .literal_separator(true)
+                    .build()?
+                    .compile_matcher();
+                Ok((matcher, RuleSelection { select: Vec::new(), ignore: rules.clone() }))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { root: root.canonicalize().unwrap_or_else(|_| root.to_path_buf()), entries })
+    }
+
+    /// Check whether violations of a rule are reported in a file.
+    #[must_use]
+    pub fn allows(&self, path: &Path, rule: &str) -> bool {
+        if self.entries.is_empty() {
+            return true;
+        }
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return true;
+        };
+        self.entries.iter().all(|(matcher, rules)| {
+            let matches = matcher.is_match(relative)
+                || (!matcher.glob().glob().contains('/')
+                    && relative.file_name().is_some_and(|name| matcher.is_match(name)));
+            !matches || rules.allows(rule)
+        })
+    }
+}
+
+/// Parse a rule code prefix or a category selection into its canonical form.
+///
+/// Prefixes are written like `D4` or `r401`, and category selections like `category:markdown`.
+pub fn parse_rule_prefix(value: &str) -> Result<String, String> {
+    if let Some(name) = value.trim().strip_prefix(CATEGORY_PREFIX) {
+        let Some(category) = Category::from_name(name) else {
+            let names: Vec<&str> = Category::ALL.iter().map(|category| category.name()).collect();
+            return Err(format!(
+                "unknown category `{name}`, expected one of: {}",
+                names.join(", ")
+            ));
+        };
+        return Ok(format!("{CATEGORY_PREFIX}{category}"));
+    }
+
+    let prefix = value.trim().to_ascii_uppercase();
+    let mut chars = prefix.chars();
+    if chars.next().is_some_and(|c| c.is_ascii_alphabetic()) && chars.all(|c| c.is_ascii_digit()) {
+        Ok(prefix)
+    } else {
+        Err(format!("`{value}` is not a rule code or prefix, such as `D4` or `R401`"))
+    }
+}
+
+/// Unit tests for rule selection.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a rule selection from prefix lists.
+    fn selection(select: &[&str], ignore: &[&str]) -> RuleSelection {
+        RuleSelection {
+            select: select.iter().map(ToString::to_string).collect(),
+            ignore: ignore.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Test selecting and ignoring rules by prefix.
+    #[test]
+    fn test_allows() {
+        assert!(RuleSelection::default().allows("D400"));
+
+        let rules = selection(&["D4", "R401"], &["D401"]);
+        assert!(rules.allows("D400"));
+        assert!(!rules.allows("D401"));
+        assert!(rules.allows("R401"));
+        assert!(!rules.allows("R402"));
+        assert!(!rules.allows("D100"));
+
+        // The most specific prefix decides
+        let rules = selection(&["D401"], &["D4"]);
+        assert!(rules.allows("D401"));
+        assert!(!rules.allows("D400"));
+        assert!(!selection(&["D4"], &["D4"]).allows("D400"));
+        assert!(!selection(&[], &["R"]).allows("R104"));
+    }
+
+    /// Test selecting and ignoring rules by category.
+    #[test]
+    fn test_allows_category() {
+        let rules = selection(&["category:markdown"], &["R405"]);
+        assert!(rules.allows("R401"));
+        assert!(rules.allows("R403"));
+        assert!(!rules.allows("R405"));
+        assert!(!rules.allows("D400"));
+
+        // Any code prefix is more specific than a category
+        let rules = selection(&["category:grammar"], &["D401"]);
+        assert!(rules.allows("D400"));
+        assert!(!rules.allows("D401"));
+        let rules = selection(&["D401"], &["category:grammar"]);
+        assert!(rules.allows("D401"));
+        assert!(!rules.allows("D400"));
+        assert!(!selection(&[], &["category:missing-docs"]).allows("D103"));
+    }
+
+    /// Test ignoring rules in the files matching patterns.
+    #[test]
+    fn test_per_file_ignores() {
+        let patterns = [
+            ("tests/**".to_string(), vec!["D103".to_string(), "category:grammar".to_string()]),
+            ("build.rs".to_string(), vec!["D1".to_string()]),
+        ];
+        let ignores = PerFileIgnores::new(Path::new("/project"), &patterns).unwrap();
+        let allows = |path: &str, rule| ignores.allows(Path::new(path), rule);
+
+        assert!(!allows("/project/tests/helpers/mod.rs", "D103"));
+        assert!(!allows("/project/tests/cli.rs", "D401"));
+        assert!(allows("/project/tests/cli.rs", "D100"));
+        assert!(allows("/project/src/tests/cli.rs", "D103"));
+        assert!(allows("/elsewhere/tests/cli.rs", "D103"));
+
+        // Patterns without a separator match file names anywhere
+        assert!(!allows("/project/build.rs", "D100"));
+        assert!(!allows("/project/member/build.rs", "D103"));
+        assert!(allows("/project/member/build.rs", "D400"));
+
+        assert!(PerFileIgnores::default().allows(Path::new("tests/cli.rs"), "D103"));
+        assert!(PerFileIgnores::new(Path::new("."), &[("[".to_string(), vec![])]).is_err());
+    }
+
+    /// Test that every rule the checker reports is in the registry.
+    #[test]
+    fn test_registry() {
+        for code in crate::pep257::OPT_IN_RULES.iter().chain(crate::fixer::FIXABLE_RULES) {
+            assert!(find_rule(code).is_some(), "{code} is not registered");
+        }
+        let mut codes: Vec<&str> = RULES.iter().map(|rule| rule.code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), RULES.len());
+        assert!(RULES.iter().all(|rule| !rule.categories.is_empty()));
+        assert!(RULES.iter().all(|rule| (1..=RULES_VERSION).contains(&rule.since)));
+    }
+
+    /// Test linking rules to their documentation.
+    #[test]
+    fn test_docs_url() {
+        let base = "https://github.com/jayvdb/pep257-rs/blob/main/CHECKS.md#";
+        assert_eq!(docs_url("D400"), format!("{base}d400-first-line-should-end-with-a-period"));
+        assert_eq!(
+            docs_url("D402"),
+            format!("{base}d402-first-line-should-not-be-the-functions-signature")
+        );
+        assert_eq!(docs_url("R502"), format!("{base}r501-r502-r503-crate-docs-should-be-complete"));
+        assert!(RULES.iter().all(|rule| !docs_url(rule.code).ends_with("#summary-table")));
+    }
+
+    /// Test parsing rule prefixes from the command line.
+    #[test]
+    fn test_parse_rule_prefix() {
+        assert_eq!(parse_rule_prefix("d4"), Ok("D4".to_string()));
+        assert_eq!(parse_rule_prefix("R401"), Ok("R401".to_string()));
+        assert!(parse_rule_prefix("4").is_err());
+        assert!(parse_rule_prefix("D4x").is_err());
+        assert!(parse_rule_prefix("").is_err());
+        assert_eq!(
+            parse_rule_prefix("category:Missing_Docs"),
+            Ok("category:missing-docs".to_string())
+        );
+        assert!(parse_rule_prefix("category:style").unwrap_err().starts_with("unknown category"));
+    }
+}
@@ -0,0 +1,234 @@
+//! Diagnostics in the JSON format of rustc.
+//!
+//! `rustc --error-format=json` prints one diagnostic per line, and tools built
+//! around cargo, such as editor plugins and bacon, render that format natively:
+//!
+//! ```json
+//! {
+//!   "$message_type": "diagnostic",
+//!   "message": "First line should end with a period",
+//!   "code": { "code": "D400", "explanation": null },
+//!   "level": "error",
+//!   "spans": [
+//!     {
+//!       "file_name": "src/lib.rs",
+//!       "byte_start": 0,
+//!       "byte_end": 12,
+//!       "line_start": 1,
+//!       "line_end": 1,
+//!       "column_start": 1,
+//!       "column_end": 13,
+//!       "is_primary": true,
+//!       "text": [{ "text": "/// Parse it", "highlight_start": 1, "highlight_end": 13 }],
+//!       "label": null,
+//!       "suggested_replacement": null,
+//!       "suggestion_applicability": null,
+//!       "expansion": null
+//!     }
+//!   ],
+//!   "children": [],
+//!   "rendered": "error[D400]: First line should end with a period\n --> src/lib.rs:1:1\n..."
+//! }
+//! ```
+
+use std::fmt::Write as _;
+
+use serde_json::{Value, json};
+
+use crate::{
+    pep257::{Severity, Violation},
+    position::{ColumnUnit, LineIndex, SourcePosition},
+};
+
+/// Return a violation as a diagnostic in the format of `rustc --error-format=json`.
+///
+/// The violation's columns must count UTF-8 bytes. Like rustc, the span counts its
+/// columns in characters, and highlights the violation's region, or the character at
+/// the violation if the region is unknown. Without the file's source, the span has
+/// no text and its columns are left in bytes.
+#[must_use]
+pub fn diagnostic(path: &str, source: Option<&str>, violation: &Violation) -> Value {
+    let level = match violation.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+    };
+    let line = violation.line;
+    let index = source.map(LineIndex::new);
+
+    let (byte_start, byte_end, line_end, column_start, column_end) = match (&index, source) {
+        (Some(index), Some(source)) => {
+            let start = violation
+                .start_byte
+                .filter(|&start| start <= source.len())
+                .unwrap_or_else(|| index.offset(SourcePosition { line, column: violation.column }));
+            let end = violation.end_byte.filter(|&end| (start..=source.len()).contains(&end));
+            let end = end.unwrap_or_else(|| {
+                let width = source[start..]
+                    .chars()
+                    .next()
+                    .filter(|&c| c != '\n' && c != '\r')
+                    .map_or(0, char::len_utf8);
+                start + width
+            });
+            let line_end = index.line_at(end).max(line);
+            let column = |line: usize, offset: usize| {
+                let column = offset.saturating_sub(index.line_range(line).start) + 1;
+                index.convert_column(line, column, ColumnUnit::Utf32)
+            };
+            (start, end, line_end, column(line, start), column(line_end, end))
+        }
+        _ => (
+            violation.start_byte.unwrap_or_default(),
+            violation.end_byte.unwrap_or_default(),
+            violation.end_line.unwrap_or(line),
+            violation.column,
+            violation.end_column.unwrap_or(violation.column),
+        ),
+    };
+
+    let mut rendered = format!(
+        "{level}[{}]: {}\n --> {path}:{line}:{column_start}\n",
+        violation.rule, violation.message
+    );
+    let mut text = Vec::new();
+    if let Some(index) = &index {
+        for span_line in line..=line_end {
+            let line_text = index.line_text(span_line);
+            let highlight_start = if span_line == line { column_start } else { 1 };
+            let highlight_end =
+                if span_line == line_end { column_end } else { line_text.chars().count() + 1 };
+            text.push(json!({
+                "text": line_text,
+                "highlight_start": highlight_start,
+                "highlight_end": highlight_end,
+            }));
+        }
+
+        let gutter = " ".repeat(line.to_string().len());
+        let marker = " ".repeat(column_start.saturating_sub(1));
+        let width = if line_end == line { column_end.saturating_sub(column_start) } else { 1 };
+        let _ = write!(
+            rendered,
+            "{gutter} |\n{line} | {}\n{gutter} | {marker}{}\n\n",
+            index.line_text(line),
+            "^".repeat(width.max(1))
+        );
+    }
+
+    json!({
+        "$message_type": "diagnostic",
+        "message": violation.message,
+        "code": { "code": violation.rule, "explanation": null },
+        "level": level,
+        "spans": [{
+            "file_name": path,
+            "byte_start": byte_start,
+            "byte_end": byte_end,
+            "line_start": line,
+            "line_end": line_end,
+            "column_start": column_start,
+            "column_end": column_end,
+            "is_primary": true,
+            "text": text,
+            "label": null,
+            "suggested_replacement": null,
+            "suggestion_applicability": null,
+            "expansion": null,
+        }],
+        "children": [],
+        "rendered": rendered,
+    })
+}
+
+/// Unit tests for rustc diagnostics.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Return a violation at a position.
+    fn violation(line: usize, column: usize) -> Violation {
+        Violation::new(
+            "D400",
+            "First line should end with a period",
+            SourcePosition { line, column },
+            Severity::Warning,
+        )
+    }
+
+    /// Test the diagnostic of a violation in a known source.
+    #[test]
+    fn test_diagnostic() {
+        let source = "//! Crate.\n\n/// Größe des Puffers\npub const SIZE: usize = 1;\n";
+        // The column of `P` in UTF-8 bytes, after the two-byte `ö` and `ß`
+        let diagnostic = diagnostic("src/lib.rs", Some(source), &violation(3, 17));
+
+        assert_eq!(diagnostic["$message_type"], "diagnostic");
+        assert_eq!(diagnostic["level"], "warning");
+        assert_eq!(diagnostic["code"]["code"], "D400");
+        let span = &diagnostic["spans"][0];
+        assert_eq!(span["file_name"], "src/lib.rs");
+        assert_eq!(&source[28..29], "P");
+        assert_eq!((span["byte_start"].as_u64(), span["byte_end"].as_u64()), (Some(28), Some(29)));
+        assert_eq!((span["line_start"].as_u64(), span["line_end"].as_u64()), (Some(3), Some(3)));
+        assert_eq!(
+            (span["column_start"].as_u64(), span["column_end"].as_u64()),
+            (Some(15), Some(16))
+        );
+        assert_eq!(span["text"][0]["text"], "/// Größe des Puffers");
+        assert_eq!(
+            diagnostic["rendered"],
+            "warning[D400]: First line should end with a period\n --> src/lib.rs:3:15\n  |\n3 | /// Größe des Puffers\n  |               ^\n\n"
+        );
+    }
+
+    /// Test that the span covers the violation's region when it's known.
+    #[test]
+    fn test_diagnostic_range() {
+        let source = "//! Crate.\n\n/// Größe des Puffers\npub fn f() {\n    g();\n}\n";
+        let start = source.find("Größe").unwrap();
+        let word = violation(3, 5).with_byte_range(start..start + "Größe".len());
+        let word = diagnostic("src/lib.rs", Some(source), &word);
+
+        let span = &word["spans"][0];
+        assert_eq!((span["line_start"].as_u64(), span["line_end"].as_u64()), (Some(3), Some(3)));
+        assert_eq!(
+            (span["column_start"].as_u64(), span["column_end"].as_u64()),
+            (Some(5), Some(10))
+        );
+        assert!(word["rendered"].as_str().unwrap().ends_with("  |     ^^^^^\n\n"));
+
+        // A region across lines highlights each of its lines
+        let start = source.find("pub fn").unwrap();
+        let item = violation(4, 1).with_byte_range(start..source.len() - 1);
+        let span = &diagnostic("src/lib.rs", Some(source), &item)["spans"][0];
+        assert_eq!((span["line_start"].as_u64(), span["line_end"].as_u64()), (Some(4), Some(6)));
+        assert_eq!(
+            (span["byte_start"].as_u64(), span["byte_end"].as_u64()),
+            (Some(start as u64), Some(source.len() as u64 - 1))
+        );
+        assert_eq!(span["column_end"], 2);
+        assert_eq!(span["text"].as_array().unwrap().len(), 3);
+        assert_eq!(
+            span["text"][1],
+            json!({ "text": "    g();", "highlight_start": 1, "highlight_end": 9 })
+        );
+    }
+
+    /// Test that a diagnostic without the source has an empty span.
+    #[test]
+    fn test_diagnostic_without_source() {
+        let diagnostic = diagnostic("src/lib.rs", None, &violation(3, 5));
+
+        let span = &diagnostic["spans"][0];
+        assert_eq!(
+            (span["column_start"].as_u64(), span["column_end"].as_u64()),
+            (Some(5), Some(5))
+        );
+        assert_eq!(span["text"], json!([]));
+        assert_eq!(
+            diagnostic["rendered"],
+            "warning[D400]: First line should end with a period\n --> src/lib.rs:3:5\n"
+        );
+    }
+}
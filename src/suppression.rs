@@ -0,0 +1,259 @@
+//! Inline suppression comments.
+//!
+//! A `// pep257: ignore[D400, D401]` comment on the line directly above a docstring,
+//! or above an undocumented item, suppresses those rules for the item, as does a
+//! `// pep257: ignore=D400,D401` comment at the end of one of the item's attribute
+//! lines or of the item's own line. A bare `// pep257: ignore` suppresses every rule.
+//! Suppression comments in the comment header at the top of a file apply to the
+//! file's own module docs and to the crate-level rules.
+
+use std::collections::BTreeMap;
+
+/// Marker that starts a suppression comment.
+const SUPPRESSION_MARKER: &str = "pep257: ignore";
+
+/// Rules suppressed by a single comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Suppression {
+    /// Every rule is suppressed.
+    All,
+    /// Only the listed rule codes are suppressed.
+    Rules(Vec<String>),
+}
+
+/// Implementation of suppression methods.
+impl Suppression {
+    /// Parse a source line holding a suppression comment.
+    pub(crate) fn parse(line: &str) -> Option<Self> {
+        let comment = line.trim().strip_prefix("//")?;
+        if comment.starts_with(['/', '!']) {
+            return None; // Doc comments are part of the documentation
+        }
+
+        let rest = comment.trim_start().strip_prefix(SUPPRESSION_MARKER)?.trim();
+        if rest.is_empty() {
+            return Some(Self::All);
+        }
+        let rules = match rest.strip_prefix('=') {
+            Some(rules) => rules,
+            None => rest.strip_prefix('[')?.strip_suffix(']')?,
+        };
+        Some(Self::Rules(
+            rules.split(',').map(str::trim).filter(|r| !r.is_empty()).map(str::to_string).collect(),
+        ))
+    }
+
+    /// Check whether a rule is suppressed.
+    fn covers(&self, rule: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Rules(rules) => rules.iter().any(|r| r == rule),
+        }
+    }
+
+    /// Parse a suppression comment at the end of a line of code.
+    fn parse_trailing(line: &str) -> Option<Self> {
+        line.match_indices("//").find_map(|(index, _)| Self::parse(&line[index..]))
+    }
+}
+
+/// Kind of a source line, as far as suppressions are concerned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LineKind {
+    /// A plain `//` comment, which may be a suppression.
+    Comment(Option<Suppression>),
+    /// A `///` doc comment.
+    Doc,
+    /// An attribute, which may end with a suppression comment.
+    Attribute(Option<Suppression>),
+    /// Any other line, which may end with a suppression comment.
+    Other(Option<Suppression>),
+}
+
+/// Suppression comments of a source file.
+#[derive(Debug, Default)]
+pub(crate) struct Suppressions {
+    lines: Vec<LineKind>,
+}
+
+/// Implementation of suppression lookup methods.
+impl Suppressions {
+    /// Find the suppression comments of a source file.
+    pub(crate) fn parse(source: &str) -> Self {
+        let lines = source
+            .lines()
+            .map(|line| {
+                let trimmed = line.trim_start();
+                if trimmed.starts_with("///") && !trimmed.starts_with("////") {
+                    LineKind::Doc
+                } else if trimmed.starts_with("//") && !trimmed.starts_with("//!") {
+                    LineKind::Comment(Suppression::parse(line))
+                } else if trimmed.starts_with("#[") {
+                    LineKind::Attribute(Suppression::parse_trailing(line))
+                } else {
+                    LineKind::Other(Suppression::parse_trailing(line))
+                }
+            })
+            .collect();
+        Self { lines }
+    }
+
+    /// Check whether a rule is suppressed for the item whose docs start on a 1-based line.
+    ///
+    /// Any comment in the run of plain comments directly above the line counts, as
+    /// do comments at the end of the item's attributes and of the item's first line.
+    pub(crate) fn suppresses(&self, line: usize, rule: &str) -> bool {
+        let above = self.lines.get(..line.saturating_sub(1)).unwrap_or_default();
+        Self::run_suppresses(&mut above.iter().rev(), rule) || self.item_suppresses(line, rule)
+    }
+
+    /// Check whether a rule is suppressed by a comment between an item's docs and its code.
+    ///
+    /// The comment may be on any line from the docs to the item's first line of code.
+    fn item_suppresses(&self, line: usize, rule: &str) -> bool {
+        let item = self.lines.get(line.saturating_sub(1)..).unwrap_or_default();
+        for kind in item {
+            match kind {
+                LineKind::Doc => {}
+                LineKind::Comment(suppression) | LineKind::Attribute(suppression) => {
+                    if suppression.as_ref().is_some_and(|s| s.covers(rule)) {
+                        return true;
+                    }
+                }
+                LineKind::Other(suppression) => {
+                    return suppression.as_ref().is_some_and(|s| s.covers(rule));
+                }
+            }
+        }
+        false
+    }
+
+    /// Check whether a rule is suppressed by the comment header at the top of the file.
+    pub(crate) fn header_suppresses(&self, rule: &str) -> bool {
+        Self::run_suppresses(&mut self.lines.iter(), rule)
+    }
+
+    /// Check whether a run of comment lines has a suppression covering a rule.
+    fn run_suppresses(lines: &mut dyn Iterator<Item = &LineKind>, rule: &str) -> bool {
+        lines
+            .map_while(|kind| match kind {
+                LineKind::Comment(suppression) => Some(suppression.as_ref()),
+                _ => None,
+            })
+            .flatten()
+            .any(|suppression| suppression.covers(rule))
+    }
+}
+
+/// Format the suppression comment for a set of rules.
+#[must_use]
+pub fn format_comment(rules: &[String]) -> String {
+    format!("// {SUPPRESSION_MARKER}[{}]", rules.join(", "))
+}
+
+/// Insert suppression comments above the given 1-based lines of a source file.
+///
+/// Each comment is indented like the line it's inserted above, and the rules for a
+/// line are sorted and deduplicated. Line endings of the source are preserved.
+#[must_use]
+pub fn insert_comments(source: &str, comments: &[(usize, Vec<String>)]) -> String {
+    let mut by_line: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    for (line, rules) in comments {
+        by_line.entry((*line).max(1)).or_default().extend(rules.iter().cloned());
+    }
+    for rules in by_line.values_mut() {
+        rules.sort();
+        rules.dedup();
+    }
+
+    let newline = if source.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut output = String::with_capacity(source.len());
+    let mut lines = source.split_inclusive('\n').enumerate().peekable();
+
+    for (&line, rules) in &by_line {
+        while let Some((_, text)) = lines.next_if(|&(index, _)| index + 1 < line) {
+            output.push_str(text);
+        }
+
+        let indent = lines.peek().map_or("", |(_, text)| {
+            let trimmed = text.trim_start_matches([' ', '\t']);
+            &text[..text.len() - trimmed.len()]
+        });
+        if lines.peek().is_none() && !output.is_empty() && !output.ends_with('\n') {
+            output.push_str(newline);
+        }
+        output.push_str(indent);
+        output.push_str(&format_comment(rules));
+        output.push_str(newline);
+    }
+    lines.for_each(|(_, text)| output.push_str(text));
+
+    output
+}
+
+/// Unit tests for suppression comments.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test parsing suppression comments.
+    #[test]
+    fn test_parse_suppression() {
+        assert_eq!(Suppression::parse("    // pep257: ignore"), Some(Suppression::All));
+        assert_eq!(
+            Suppression::parse("// pep257: ignore[D400, R405]"),
+            Some(Suppression::Rules(vec!["D400".to_string(), "R405".to_string()]))
+        );
+        assert_eq!(
+            Suppression::parse("// pep257: ignore=D400,D403"),
+            Suppression::parse("// pep257: ignore[D400, D403]")
+        );
+        assert_eq!(Suppression::parse("/// pep257: ignore"), None);
+        assert_eq!(Suppression::parse("// pep257: ignored"), None);
+        assert_eq!(Suppression::parse("// See pep257: ignore"), None);
+    }
+
+    /// Test which lines a suppression comment applies to.
+    #[test]
+    fn test_suppresses() {
+        let source = "// Header.\n// pep257: ignore[R104]\n\n// pep257: ignore[D400]\n// Note.\n/// Summary\nfn f() {}\n";
+        let suppressions = Suppressions::parse(source);
+        assert!(suppressions.suppresses(6, "D400"));
+        assert!(!suppressions.suppresses(6, "D401"));
+        assert!(!suppressions.suppresses(7, "D400"));
+        assert!(suppressions.header_suppresses("R104"));
+        assert!(!suppressions.header_suppresses("D400"));
+    }
+
+    /// Test suppression comments at the end of an item's attribute and code lines.
+    #[test]
+    fn test_suppresses_trailing() {
+        let source = "/// Summary\n#[inline] // pep257: ignore=D400\npub fn f() {} // pep257: ignore=D403\n\nfn g() {} // pep257: ignore\nfn h() {}\n";
+        let suppressions = Suppressions::parse(source);
+        assert!(suppressions.suppresses(1, "D400"));
+        assert!(suppressions.suppresses(1, "D403"));
+        assert!(!suppressions.suppresses(1, "D401"));
+        assert!(suppressions.suppresses(5, "D103"));
+        assert!(!suppressions.suppresses(6, "D103"));
+        assert!(!suppressions.header_suppresses("D400"));
+    }
+
+    /// Test inserting suppression comments with indentation and line endings.
+    #[test]
+    fn test_insert_comments() {
+        let source = "//! Crate.\r\n\r\nmod m {\r\n    /// summary\r\n    fn f() {}\r\n}";
+        let comments = vec![
+            (4, vec!["D400".to_string(), "D403".to_string()]),
+            (1, vec!["R104".to_string()]),
+            (4, vec!["D400".to_string()]),
+        ];
+        assert_eq!(
+            insert_comments(source, &comments),
+            "// pep257: ignore[R104]\r\n//! Crate.\r\n\r\nmod m {\r\n    // pep257: ignore[D400, D403]\r\n    /// summary\r\n    fn f() {}\r\n}"
+        );
+        assert_eq!(
+            insert_comments("", &[(1, vec!["D100".to_string()])]),
+            "// pep257: ignore[D100]\n"
+        );
+    }
+}
@@ -0,0 +1,251 @@
+//! Fixture-based testing of the analyzer.
+//!
+//! A fixture is a Rust source file whose expected violations are written as
+//! comments, in the style of rustc's UI tests:
+//!
+//! ```text
+//! /// calculate the sum
+//! fn add(a: i32, b: i32) -> i32 { //~^ D400 D403
+//!     a + b
+//! }
+//! ```
+//!
+//! `//~ CODE` expects a violation on the comment's own line, and each `^` moves the
+//! expectation one line up. Several annotations may share a line, such as
+//! `//~^^ D205 //~^ D400`. Every reported violation, including warnings, must be
+//! annotated.
+
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+use crate::{
+    analyzer::{AnalyzerOptions, RustDocAnalyzer},
+    parser::ParseError,
+    pep257::Violation,
+};
+
+/// Marker that starts a violation annotation.
+const ANNOTATION_MARKER: &str = "//~";
+
+/// The fixtures of the `test_files` directory by file name.
+///
+/// They're compiled in so an installed binary can check itself with [`self_test`].
+pub const BUNDLED_FIXTURES: &[(&str, &str)] = &[
+    ("all_styles_demo.rs", include_str!("../test_files/all_styles_demo.rs")),
+    ("attribute_order_test.rs", include_str!("../test_files/attribute_order_test.rs")),
+    ("bad_example.rs", include_str!("../test_files/bad_example.rs")),
+    ("blank_line_test.rs", include_str!("../test_files/blank_line_test.rs")),
+    ("calculator.rs", include_str!("../test_files/calculator.rs")),
+    ("dangling_doc_test.rs", include_str!("../test_files/dangling_doc_test.rs")),
+    (
+        "doc_attr_comprehensive_test.rs",
+        include_str!("../test_files/doc_attr_comprehensive_test.rs"),
+    ),
+    ("doc_attr_test.rs", include_str!("../test_files/doc_attr_test.rs")),
+    ("enum_test.rs", include_str!("../test_files/enum_test.rs")),
+    ("good_example.rs", include_str!("../test_files/good_example.rs")),
+    ("helpers.rs", include_str!("../test_files/helpers.rs")),
+    ("macro_test.rs", include_str!("../test_files/macro_test.rs")),
+    ("package_lib_bad.rs", include_str!("../test_files/package_lib_bad.rs")),
+    ("package_lib_good.rs", include_str!("../test_files/package_lib_good.rs")),
+    ("package_main_bad.rs", include_str!("../test_files/package_main_bad.rs")),
+    ("package_main_good.rs", include_str!("../test_files/package_main_good.rs")),
+    ("package_mod_bad.rs", include_str!("../test_files/package_mod_bad.rs")),
+    ("package_mod_good.rs", include_str!("../test_files/package_mod_good.rs")),
+    ("r101_r103_demo.rs", include_str!("../test_files/r101_r103_demo.rs")),
+    ("type_alias_test.rs", include_str!("../test_files/type_alias_test.rs")),
+    ("utils.rs", include_str!("../test_files/utils.rs")),
+    ("validators.rs", include_str!("../test_files/validators.rs")),
+];
+
+/// A violation expected by a fixture annotation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExpectedViolation {
+    /// The 1-based line the violation is expected on.
+    pub line: usize,
+    /// The expected rule code, such as `D400`.
+    pub rule: String,
+}
+
+/// Result of comparing a fixture's annotations with the reported violations.
+#[derive(Debug, Default)]
+pub struct FixtureReport {
+    /// Annotated violations that were not reported.
+    pub missing: Vec<ExpectedViolation>,
+    /// Reported violations that were not annotated.
+    pub unexpected: Vec<Violation>,
+}
+
+/// Implementation of fixture report methods.
+impl FixtureReport {
+    /// Check whether the reported violations matched the annotations exactly.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+/// Format a fixture report for display.
+impl fmt::Display for FixtureReport {
+    /// Format each mismatch on its own line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for expected in &self.missing {
+            writeln!(f, "missing {} at line {}", expected.rule, expected.line)?;
+        }
+        for violation in &self.unexpected {
+            writeln!(f, "unexpected {violation}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse the violation annotations of a fixture.
+#[must_use]
+pub fn parse_annotations(source: &str) -> Vec<ExpectedViolation> {
+    let mut expected = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        for annotation in line.split(ANNOTATION_MARKER).skip(1) {
+            let carets = annotation.chars().take_while(|&c| c == '^').count();
+            let Some(target) = (index + 1).checked_sub(carets).filter(|&line| line > 0) else {
+                continue;
+            };
+            expected.extend(
+                annotation[carets..]
+                    .split_whitespace()
+                    .map(|rule| ExpectedViolation { line: target, rule: rule.to_string() }),
+            );
+        }
+    }
+
+    expected
+}
+
+/// Compare reported violations with the expected ones.
+#[must_use]
+pub fn compare(expected: &[ExpectedViolation], violations: Vec<Violation>) -> FixtureReport {
+    let mut remaining: HashMap<&ExpectedViolation, usize> = HashMap::new();
+    for expectation in expected {
+        *remaining.entry(expectation).or_default() += 1;
+    }
+
+    let mut report = FixtureReport::default();
+    for violation in violations {
+        let key = ExpectedViolation { line: violation.line, rule: violation.rule.clone() };
+        match remaining.get_mut(&key) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => report.unexpected.push(violation),
+        }
+    }
+
+    // Report missing violations in the order they were annotated
+    for expectation in expected {
+        if let Some(count) = remaining.get_mut(expectation)
+            && *count > 0
+        {
+            *count -= 1;
+            report.missing.push(expectation.clone());
+        }
+    }
+
+    report
+}
+
+/// Run the analyzer over a fixture file and compare the result with its annotations.
+pub fn check_fixture<P: AsRef<Path>>(
+    path: P,
+    options: AnalyzerOptions,
+) -> Result<FixtureReport, ParseError> {
+    let source = fs::read_to_string(&path)?;
+    let mut analyzer = RustDocAnalyzer::with_options(options)?;
+    let violations = analyzer.analyze_file(&path)?;
+    Ok(compare(&parse_annotations(&source), violations))
+}
+
+/// Run the analyzer over fixture source code and compare the result with its annotations.
+pub fn check_fixture_source(
+    source: &str,
+    options: AnalyzerOptions,
+) -> Result<FixtureReport, ParseError> {
+    let mut analyzer = RustDocAnalyzer::with_options(options)?;
+    let violations = analyzer.analyze_source(source)?;
+    Ok(compare(&parse_annotations(source), violations))
+}
+
+/// Run the analyzer over the bundled fixtures with the default options.
+///
+/// Returns the report of each fixture by name.
+pub fn self_test() -> Result<Vec<(&'static str, FixtureReport)>, ParseError> {
+    BUNDLED_FIXTURES
+        .iter()
+        .map(|&(name, source)| {
+            Ok((name, check_fixture_source(source, AnalyzerOptions::default())?))
+        })
+        .collect()
+}
+
+/// Assert that a fixture file reports exactly its annotated violations.
+///
+/// # Panics
+///
+/// Panics with the list of mismatches if the fixture doesn't match, or if it can't
+/// be read or parsed.
+pub fn assert_fixture<P: AsRef<Path>>(path: P) {
+    let path = path.as_ref();
+    match check_fixture(path, AnalyzerOptions::default()) {
+        Ok(report) if report.is_ok() => {}
+        Ok(report) => panic!("fixture {} does not match:\n{report}", path.display()),
+        Err(e) => panic!("failed to check fixture {}: {e}", path.display()),
+    }
+}
+
+/// Unit tests for the fixture harness.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test parsing annotations with carets and several codes.
+    #[test]
+    fn test_parse_annotations() {
+        let source =
+            "/// calculate the sum\nfn add() {} //~^ D400 D403\nfn f() {} //~ D103 //~^^ D205\n";
+        let expected = parse_annotations(source);
+        assert_eq!(expected, vec![
+            ExpectedViolation { line: 1, rule: "D400".to_string() },
+            ExpectedViolation { line: 1, rule: "D403".to_string() },
+            ExpectedViolation { line: 3, rule: "D103".to_string() },
+            ExpectedViolation { line: 1, rule: "D205".to_string() },
+        ]);
+    }
+
+    /// Test that mismatches are reported in both directions.
+    #[test]
+    fn test_check_fixture_source() {
+        let source = "/// calculate the sum.\nfn add() {} //~^ D403\n";
+        let report = check_fixture_source(source, AnalyzerOptions::default()).unwrap();
+        assert!(report.is_ok(), "{report}");
+
+        let source = "/// Calculate the sum.\nfn add() {} //~^ D400\npub fn sub() {}\n";
+        let report = check_fixture_source(source, AnalyzerOptions::default()).unwrap();
+        assert_eq!(report.missing, vec![ExpectedViolation { line: 1, rule: "D400".to_string() }]);
+        assert!(report.unexpected.iter().any(|v| v.rule == "D103" && v.line == 3));
+    }
+
+    /// Test that the bundled fixtures are those of `test_files` and all pass.
+    #[test]
+    fn test_self_test() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+        let mut names: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        let bundled: Vec<_> = BUNDLED_FIXTURES.iter().map(|&(name, _)| name).collect();
+        assert_eq!(bundled, names);
+
+        for (name, report) in self_test().unwrap() {
+            assert!(report.is_ok(), "{name}:\n{report}");
+        }
+    }
+}
@@ -5,7 +5,7 @@
 /// Calculate the sum using line comments.
 ///
 /// This is the most common style in Rust code.
-fn add_line_comment(a: i32, b: i32) -> i32 {
+fn add_line_comment(a: i32, b: i32) -> i32 { //~^ D401
     a + b
 }
 
@@ -41,7 +41,7 @@ enum Operation {
 #[doc = ""]
 #[doc = "This struct uses doc attributes for demonstration."]
 struct Calculator {
-    /// Current result
+    /// Current result.
     result: i32,
 }
 
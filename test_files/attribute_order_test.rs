@@ -0,0 +1,46 @@
+//! Test file for docs separated from their item by attributes and comments.
+
+/// Store a value behind blank lines and attributes.
+
+#[derive(Debug)]
+
+#[cfg(test)]
+pub struct Spaced;
+
+/// Store a value with a plain comment before its attributes.
+// Not part of the docs, and doesn't hide them.
+#[derive(Debug, Clone)]
+pub struct Commented;
+
+#[derive(Debug)]
+/// Store a value between attributes.
+///
+/// The description follows the derive.
+#[cfg_attr(test, derive(Clone))]
+pub struct Between;
+
+/** Store a value in a block doc comment. */
+#[derive(Debug)]
+/* Not part of the docs. */
+pub struct Block;
+
+/// Run with attributes and a block comment between the docs and the function.
+#[rustfmt::skip]
+/* Not part of the docs. */
+#[inline]
+pub fn interleaved() {}
+
+//// Four slashes make a plain comment.
+#[derive(Debug)]
+pub struct Undocumented; //~ D101
+
+/// Choose between the variants.
+pub enum Choice {
+    /// Select the first variant.
+    #[default]
+    // Not part of the docs.
+    First,
+    #[allow(unused)]
+    /// Select the second variant.
+    Second,
+}
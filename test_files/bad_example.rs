@@ -1,15 +1,15 @@
 /// calculate the sum of two numbers
-fn add_bad_capitalization(a: i32, b: i32) -> i32 {
+fn add_bad_capitalization(a: i32, b: i32) -> i32 { //~^ D400 D403
     a + b
 }
 
 /// Calculate the sum of two numbers
-fn add_missing_period(a: i32, b: i32) -> i32 {
+fn add_missing_period(a: i32, b: i32) -> i32 { //~^ D400
     a + b
 }
 
 /// this function calculates the difference
-fn subtract_bad_style(a: i32, b: i32) -> i32 {
+fn subtract_bad_style(a: i32, b: i32) -> i32 { //~^ D400 D401 D403
     a - b
 }
 
@@ -19,17 +19,17 @@ fn multiply_missing_docs(a: i32, b: i32) -> i32 {
 
 /// Calculate division
 /// This function divides two numbers
-fn divide_missing_blank_line(a: i32, b: i32) -> f64 {
+fn divide_missing_blank_line(a: i32, b: i32) -> f64 { //~^^ D400 R204
     a as f64 / b as f64
 }
 
 /// gets the absolute value of a number
-fn abs_non_imperative(x: i32) -> i32 {
+fn abs_non_imperative(x: i32) -> i32 { //~^ D400 D401 D403
     if x < 0 { -x } else { x }
 }
 
 /// Calculate the power: pow(base, exponent)
-fn power_has_signature(base: i32, exponent: u32) -> i32 {
+fn power_has_signature(base: i32, exponent: u32) -> i32 { //~^ D400
     base.pow(exponent)
 }
 
@@ -38,7 +38,7 @@ struct UndocumentedStruct {
 }
 
 /// represents a basic counter
-enum UndocumentedEnum {
+enum UndocumentedEnum { //~^ D400 D401 D403
     Zero,
     One,
     Many(i32),
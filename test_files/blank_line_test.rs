@@ -1,72 +1,78 @@
-//! Test file for D201 and D202 checks on various item types.
+//! Test file for D201, D202 and R202 checks on various item types.
 
 ///
 /// This function has a blank line before the docstring.
-pub fn function_with_leading_blank() {}
+pub fn function_with_leading_blank() {} //~^ D201 D205 D401
 
 /// This function has a blank line after the docstring.
-pub fn function_with_trailing_blank() {}
+pub fn function_with_trailing_blank() {} //~^ D401
 
 ///
 /// This struct has a blank line before the docstring.
-pub struct StructWithLeadingBlank {
+pub struct StructWithLeadingBlank { //~^ D201 D205 D401
     x: i32,
 }
 
 /// This struct has a blank line after the docstring.
-pub struct StructWithTrailingBlank {
+pub struct StructWithTrailingBlank { //~^ D401
     y: i32,
 }
 
 ///
 /// This enum has a blank line before the docstring.
-pub enum EnumWithLeadingBlank {
+pub enum EnumWithLeadingBlank { //~^ D201 D205 D401
     Variant1,
     Variant2,
 }
 
 /// This enum has a blank line after the docstring.
-pub enum EnumWithTrailingBlank {
+pub enum EnumWithTrailingBlank { //~^ D401
     Variant1,
     Variant2,
 }
 
 ///
 /// This trait has a blank line before the docstring.
-pub trait TraitWithLeadingBlank {
-    fn method(&self);
+pub trait TraitWithLeadingBlank { //~^ D201 D205 D401
+    fn method(&self); //~ R107
 }
 
 /// This trait has a blank line after the docstring.
-pub trait TraitWithTrailingBlank {
-    fn method(&self);
+pub trait TraitWithTrailingBlank { //~^ D401
+    fn method(&self); //~ R107
 }
 
 ///
 /// This const has a blank line before the docstring.
-pub const CONST_WITH_LEADING_BLANK: i32 = 42;
+pub const CONST_WITH_LEADING_BLANK: i32 = 42; //~^ D201 D205 D401
 
 /// This const has a blank line after the docstring.
-pub const CONST_WITH_TRAILING_BLANK: i32 = 42;
+pub const CONST_WITH_TRAILING_BLANK: i32 = 42; //~^ D401
 
 /// This is properly formatted without blank lines.
-pub fn properly_formatted_function() {}
+pub fn properly_formatted_function() {} //~^ D401
 
 /// This is properly formatted without blank lines.
-pub struct ProperlyFormattedStruct {
+pub struct ProperlyFormattedStruct { //~^ D401
     value: i32,
 }
 
 /// This is properly formatted without blank lines.
-pub enum ProperlyFormattedEnum {
+pub enum ProperlyFormattedEnum { //~^ D401
     One,
     Two,
 }
 
 /// This is properly formatted without blank lines.
-pub trait ProperlyFormattedTrait {
-    fn method(&self);
+pub trait ProperlyFormattedTrait { //~^ D401
+    fn method(&self); //~ R107
 }
 
 /// This is properly formatted without blank lines.
-pub const PROPERLY_FORMATTED_CONST: i32 = 100;
+pub const PROPERLY_FORMATTED_CONST: i32 = 100; //~^ D401
+
+/// Run the function with two blank lines after the summary.
+///
+///
+/// The description should follow a single blank line.
+pub fn function_with_spaced_description() {} //~^^ R202
@@ -0,0 +1,23 @@
+//! Test file for doc comments that no item follows.
+
+/// Store a point in 2D space.
+pub struct Point {
+    /// Horizontal coordinate.
+    pub x: f64,
+    /// Vertical coordinate, documented after the last field.
+} //~^ R203
+
+impl Point {
+    /// Return the distance from the origin.
+    pub fn norm(&self) -> f64 {
+        self.x.abs()
+        /// Documents nothing in the function body.
+    } //~^ R203
+
+    /// Documents nothing at the end of the impl,
+    /// reported on the first line only.
+    #[allow(unused)]
+} //~^^^ R203
+
+/// Describe an item that was removed.
+//~^ R203
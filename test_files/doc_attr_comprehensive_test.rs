@@ -4,22 +4,22 @@ fn good_doc_attr(a: i32, b: i32) -> i32 {
 }
 
 #[doc = "calculate without period"]
-fn bad_doc_attr_no_period(a: i32, b: i32) -> i32 {
+fn bad_doc_attr_no_period(a: i32, b: i32) -> i32 { //~^ D400 D403
     a + b
 }
 
 #[doc = "this is not capitalized."]
-fn bad_doc_attr_not_capitalized(a: i32, b: i32) -> i32 {
+fn bad_doc_attr_not_capitalized(a: i32, b: i32) -> i32 { //~^ D401 D403
     a + b
 }
 
 #[doc = "gets the value from storage"]
-fn bad_doc_attr_non_imperative(a: i32) -> i32 {
+fn bad_doc_attr_non_imperative(a: i32) -> i32 { //~^ D400 D401 D403
     a
 }
 
 /// This is a standard comment for comparison.
-fn standard_comment_good(a: i32) -> i32 {
+fn standard_comment_good(a: i32) -> i32 { //~^ D401
     a
 }
 
@@ -37,7 +37,7 @@ struct GoodDocAttrStruct {
 }
 
 #[doc = "missing period"]
-enum BadDocAttrEnum {
+enum BadDocAttrEnum { //~^ D400 D403
     Variant1,
     Variant2,
 }
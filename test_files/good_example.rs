@@ -27,9 +27,9 @@ fn add(a: i32, b: i32) -> i32 {
 /// This struct holds the coordinates of a point in a two-dimensional
 /// coordinate system.
 struct Point {
-    /// The x-coordinate
+    /// The x-coordinate.
     x: f64,
-    /// The y-coordinate
+    /// The y-coordinate.
     y: f64,
 }
 
@@ -25,7 +25,7 @@ macro_rules! vec_of {
 
 // Missing docstring - should trigger R103
 #[macro_export]
-macro_rules! undocumented_macro {
+macro_rules! undocumented_macro { //~ R103
     () => {
         println!("Hello");
     };
@@ -1,9 +1,9 @@
 // Missing crate-level documentation - should trigger D104
 
 #[cfg(any())]
-pub mod calculator;
+pub mod calculator; //~^^^ D104 //~ D100
 #[cfg(any())]
-pub mod utils;
+pub mod utils; //~ D100
 
 /// Add two numbers.
 pub fn add(a: i32, b: i32) -> i32 {
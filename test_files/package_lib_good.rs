@@ -3,9 +3,9 @@
 //! Provides various calculation and utility functions.
 
 #[cfg(any())]
-pub mod calculator;
+pub mod calculator; //~^^^^^ D401 //~ D100
 #[cfg(any())]
-pub mod utils;
+pub mod utils; //~ D100
 
 /// Add two numbers.
 pub fn add(a: i32, b: i32) -> i32 {
@@ -1,9 +1,9 @@
 // Missing module-level documentation - should trigger D104
 
 #[cfg(any())]
-pub mod helpers;
+pub mod helpers; //~^^^ D104 //~ D100
 #[cfg(any())]
-pub mod validators;
+pub mod validators; //~ D100
 
 /// Helper function.
 pub fn format_message(msg: &str) -> String {
@@ -3,9 +3,9 @@
 //! Contains helper functions used throughout the crate.
 
 #[cfg(any())]
-pub mod helpers;
+pub mod helpers; //~ D100
 #[cfg(any())]
-pub mod validators;
+pub mod validators; //~ D100
 
 /// Helper function.
 pub fn format_message(msg: &str) -> String {
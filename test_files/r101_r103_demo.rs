@@ -1,10 +1,10 @@
 //! Demonstration of R101 (type alias) and R103 (macro) rules.
 
 /// A specialized Result type for this module.
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = std::result::Result<T, Error>; //~^ D401
 
 // Missing docstring - should trigger R101
-pub type BadType = String;
+pub type BadType = String; //~ R101
 
 /// Log an error message with formatting.
 #[macro_export]
@@ -16,11 +16,11 @@ macro_rules! log_error {
 
 // Missing docstring - should trigger R103
 #[macro_export]
-macro_rules! bad_macro {
+macro_rules! bad_macro { //~ R103
     () => {};
 }
 
 /// A simple error type for this module.
-pub struct Error {
+pub struct Error { //~^ D401
     message: String,
 }
@@ -2,13 +2,13 @@
 ///
 /// This type alias simplifies error handling by providing a consistent
 /// Result type throughout the crate.
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = std::result::Result<T, Error>; //~^^^^ D104 //~^ D401
 
 /// A vector of strings.
-pub type StringVec = Vec<String>;
+pub type StringVec = Vec<String>; //~^ D401
 
 // Missing docstring - should trigger R101
-pub type UndocumentedType = i32;
+pub type UndocumentedType = i32; //~ R101
 
 /// Generic callback function type.
 ///
@@ -1,8 +1,12 @@
 //! Integration tests for file collection functionality.
 
-use std::fs;
+use std::{fs, path::Path};
 
-use pep257::file_collector::collect_rust_files_recursive;
+use pep257::file_collector::{
+    CollectOptions, PathStyle, collect_files_recursive, collect_files_with_options,
+    collect_glob_files, collect_rust_files_recursive, glob_base, is_glob, relative_path,
+    styled_path,
+};
 use tempfile::TempDir;
 
 #[test]
@@ -149,3 +153,147 @@ fn test_nested_target_directories() {
     assert!(files.iter().any(|f| f.ends_with("member/target/test.rs")));
     assert!(!files.iter().any(|f| f.to_str().unwrap().contains("target/debug")));
 }
+
+#[test]
+fn test_collect_files_with_extensions() {
+    let test_dir = TempDir::new().unwrap();
+
+    fs::write(test_dir.path().join("lib.rs"), "// rust").unwrap();
+    fs::write(test_dir.path().join("script.py"), "# python").unwrap();
+    fs::write(test_dir.path().join("notes.txt"), "text").unwrap();
+
+    let files = collect_files_recursive(&test_dir.path().to_path_buf(), &["rs", "py"]).unwrap();
+
+    assert_eq!(files.len(), 2);
+    assert!(!files.iter().any(|f| f.file_name().unwrap() == "notes.txt"));
+}
+
+#[test]
+fn test_relative_path() {
+    assert_eq!(relative_path(Path::new("/a/b/c.rs"), Path::new("/a")), Path::new("b/c.rs"));
+    assert_eq!(
+        relative_path(Path::new("/a/b/c.rs"), Path::new("/a/d/e")),
+        Path::new("../../b/c.rs")
+    );
+    assert_eq!(relative_path(Path::new("/a/b"), Path::new("/a/b")), Path::new(""));
+}
+
+#[test]
+fn test_styled_path() {
+    let test_dir = TempDir::new().unwrap();
+    let root = test_dir.path().canonicalize().unwrap();
+    fs::create_dir_all(root.join("repo/.git")).unwrap();
+    fs::create_dir_all(root.join("repo/src")).unwrap();
+    fs::write(root.join("repo/src/lib.rs"), "// lib").unwrap();
+    fs::write(root.join("outside.rs"), "// outside").unwrap();
+
+    let file = root.join("repo/src/../src/lib.rs");
+    assert_eq!(styled_path(&file, PathStyle::Absolute), root.join("repo/src/lib.rs"));
+    assert_eq!(styled_path(&file, PathStyle::FromRoot), Path::new("src/lib.rs"));
+
+    // Files outside a repository and missing files are printed as given
+    let outside = root.join("outside.rs");
+    assert_eq!(styled_path(&outside, PathStyle::FromRoot), outside);
+    let missing = Path::new("missing/file.rs");
+    assert_eq!(styled_path(missing, PathStyle::Absolute), missing);
+}
+
+#[test]
+fn test_ignore_file_precedence() {
+    let test_dir = TempDir::new().unwrap();
+    let root = test_dir.path();
+    fs::create_dir(root.join(".git")).unwrap();
+    for name in ["git.rs", "ignore.rs", "pep257.rs", "custom.rs", "kept.rs"] {
+        fs::write(root.join(name), "// test").unwrap();
+    }
+
+    // Each source ignores its own file and overrides the sources below it
+    fs::write(root.join(".gitignore"), "git.rs\nignore.rs\n").unwrap();
+    fs::write(root.join(".ignore"), "!git.rs\nignore.rs\npep257.rs\n").unwrap();
+    fs::write(root.join(".pep257ignore"), "!ignore.rs\npep257.rs\ncustom.rs\n").unwrap();
+    fs::write(root.join(".docignore"), "!pep257.rs\n").unwrap();
+
+    let names = |files: Vec<std::path::PathBuf>| {
+        let mut names: Vec<String> =
+            files.iter().map(|f| f.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        names.sort();
+        names
+    };
+    let dir = root.to_path_buf();
+    assert_eq!(names(collect_files_recursive(&dir, &["rs"]).unwrap()), vec![
+        "git.rs",
+        "ignore.rs",
+        "kept.rs"
+    ]);
+    let options =
+        CollectOptions { ignore_filenames: vec![".docignore".to_string()], ..Default::default() };
+    assert_eq!(names(collect_files_with_options(&dir, &["rs"], &options).unwrap()), vec![
+        "git.rs",
+        "ignore.rs",
+        "kept.rs",
+        "pep257.rs"
+    ]);
+
+    // Exclude patterns override every ignore file
+    let options = CollectOptions {
+        exclude: vec!["git.rs".to_string(), "/ignore.rs".to_string()],
+        exclude_root: dir.clone(),
+        ..Default::default()
+    };
+    assert_eq!(names(collect_files_with_options(&dir, &["rs"], &options).unwrap()), vec![
+        "kept.rs"
+    ]);
+}
+
+#[test]
+fn test_current_dir_exclude() {
+    // The patterns are relative to the current directory, so the files must be below it
+    let test_dir = TempDir::new_in(env!("CARGO_TARGET_TMPDIR")).unwrap();
+    let dir = test_dir.path().to_path_buf();
+    fs::create_dir_all(dir.join("src/generated")).unwrap();
+    fs::write(dir.join("src/lib.rs"), "// lib").unwrap();
+    fs::write(dir.join("src/generated/api.rs"), "// generated").unwrap();
+
+    let relative = relative_path(&dir, &std::env::current_dir().unwrap());
+    let options = CollectOptions {
+        current_dir_exclude: vec![format!("{}/src/generated/**", relative.display())],
+        ..Default::default()
+    };
+    let files = collect_files_with_options(&dir, &["rs"], &options).unwrap();
+    assert_eq!(files, vec![dir.join("src/lib.rs")]);
+}
+
+#[test]
+fn test_collect_glob_files() {
+    let test_dir = TempDir::new().unwrap();
+    let root = test_dir.path();
+    for path in
+        ["src/lib.rs", "src/api/handlers/users.rs", "src/handlers/mod.rs", "src/handlers/notes.txt"]
+    {
+        fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+        fs::write(root.join(path), "// test").unwrap();
+    }
+
+    let names = |pattern: &str| {
+        let files =
+            collect_glob_files(&root.join(pattern), &["rs"], &CollectOptions::default()).unwrap();
+        let mut names: Vec<String> = files
+            .iter()
+            .map(|f| f.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(names("src/**/handlers/*.rs"), vec![
+        "src/api/handlers/users.rs",
+        "src/handlers/mod.rs"
+    ]);
+    assert_eq!(names("src/*.rs"), vec!["src/lib.rs"]);
+    assert_eq!(names("src/{lib,handlers/mod}.rs"), vec!["src/handlers/mod.rs", "src/lib.rs"]);
+    assert!(names("src/*.txt").is_empty());
+
+    assert!(is_glob(Path::new("src/**/*.rs")));
+    assert!(!is_glob(Path::new("src/lib.rs")));
+    assert_eq!(glob_base(Path::new("src/**/handlers/*.rs")), Path::new("src"));
+    assert_eq!(glob_base(Path::new("*.rs")), Path::new("."));
+}
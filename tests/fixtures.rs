@@ -0,0 +1,24 @@
+//! Integration tests running the analyzer over the annotated files in test_files/.
+
+use std::{fs, path::Path};
+
+use pep257::{analyzer::AnalyzerOptions, testing::check_fixture};
+
+#[test]
+fn test_files_match_annotations() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    let mut failures = Vec::new();
+
+    let mut paths: Vec<_> =
+        fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+    paths.sort();
+
+    for path in paths.iter().filter(|path| path.extension().is_some_and(|ext| ext == "rs")) {
+        let report = check_fixture(path, AnalyzerOptions::default()).unwrap();
+        if !report.is_ok() {
+            failures.push(format!("{}:\n{report}", path.display()));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}